@@ -1,57 +1,641 @@
-use crate::app::state::{AppState, PendingAction, Tab};
-use crate::error::AppError;
+use crate::app::state::{AppState, PendingAction, StatusKind, Tab};
+use crate::broker::{BrokerOperation, ScanCaps};
+use crate::error::{AppError, BrokerError, ErrorCategory};
+use crate::models::{Queue, ScheduledEntry, Task, Worker};
+use crate::utils::formatting::format_interval;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of recent tasks fetched by an incremental refresh
+const RECENT_TASKS_WINDOW: usize = 20;
+
+/// Queue replayed tasks are published to when their original queue is unknown
+const DEFAULT_REPLAY_QUEUE: &str = "celery";
+
+/// Age past which the cleanup action deletes finished task results
+const CLEANUP_RESULT_AGE: Duration = Duration::from_secs(7 * 86_400);
+
+/// Messages listed by the queue peek modal
+pub const QUEUE_PEEK_SIZE: usize = 10;
+
+/// Bytes of stored task metadata shown by the raw details view
+pub const RAW_METADATA_DISPLAY_LIMIT: usize = 64 * 1024;
+
+/// Run a broker call, giving up with `BrokerError::Timeout` after `limit`.
+/// A zero limit waits indefinitely.
+async fn with_timeout<T>(
+    limit: Duration,
+    call: impl Future<Output = Result<T, BrokerError>>,
+) -> Result<T, BrokerError> {
+    if limit.is_zero() {
+        return call.await;
+    }
+    tokio::time::timeout(limit, call)
+        .await
+        .unwrap_or(Err(BrokerError::Timeout))
+}
+
+/// Status message for an action that went through
+fn done(message: String) -> (String, StatusKind) {
+    (message, StatusKind::Info)
+}
+
+/// Status message for an action the broker refused or failed. Validation
+/// errors are reported as the user's to fix, e.g. "Invalid request to retry
+/// task 'x': Task ID cannot be empty".
+fn failed(action: &str, error: &BrokerError) -> (String, StatusKind) {
+    match error {
+        BrokerError::ValidationError(reason) => (
+            format!("Invalid request to {action}: {reason}"),
+            StatusKind::UserError,
+        ),
+        _ => (
+            format!("Failed to {action}: {error}"),
+            StatusKind::of_error(error),
+        ),
+    }
+}
+
+/// Results of the broker calls made by a full refresh
+pub struct RefreshFetch {
+    workers: Result<Vec<Worker>, BrokerError>,
+    tasks: Result<Vec<Task>, BrokerError>,
+    queues: Result<Vec<Queue>, BrokerError>,
+    scheduled: Result<Vec<ScheduledEntry>, BrokerError>,
+    ping: Result<Duration, BrokerError>,
+    scan_caps: ScanCaps,
+    pool_saturation: Option<usize>,
+}
 
 impl AppState {
     /// Refresh all data from the broker
+    ///
+    /// A collection whose fetch times out keeps its previous data and records
+    /// an error, the other collections are still updated.
     pub async fn refresh_data(&mut self) -> Result<(), AppError> {
-        let (workers_result, tasks_result, queues_result) = {
-            let broker = self.broker.lock().await;
+        let fetch = self.begin_refresh();
+        let fetched = fetch.await;
+        self.finish_refresh(fetched)
+    }
+
+    /// Mark a full refresh as running and return the broker calls it makes
+    ///
+    /// The returned future holds only the broker handle, so the UI can keep
+    /// drawing while it runs. Pass its output to `finish_refresh`.
+    pub fn begin_refresh(&mut self) -> impl Future<Output = RefreshFetch> + Send + 'static {
+        self.is_refreshing = true;
+        let broker = Arc::clone(&self.broker);
+        let limit = self.operation_timeout;
+        async move {
+            let broker = broker.lock().await;
 
             // Fetch all data in parallel
+            let (workers, tasks, queues, scheduled, ping) = tokio::join!(
+                with_timeout(limit, broker.get_workers()),
+                with_timeout(limit, broker.get_tasks()),
+                with_timeout(limit, broker.get_queues()),
+                with_timeout(limit, broker.get_scheduled()),
+                with_timeout(limit, broker.ping())
+            );
+            RefreshFetch {
+                workers,
+                tasks,
+                queues,
+                scheduled,
+                ping,
+                scan_caps: broker.scan_caps(),
+                pool_saturation: broker.pool_saturation(),
+            }
+        }
+    }
+
+    /// Apply the results of a full refresh started with `begin_refresh`
+    pub fn finish_refresh(&mut self, fetched: RefreshFetch) -> Result<(), AppError> {
+        self.is_refreshing = false;
+        let RefreshFetch {
+            workers: workers_result,
+            tasks: tasks_result,
+            queues: queues_result,
+            scheduled: scheduled_result,
+            ping: ping_result,
+            scan_caps,
+            pool_saturation,
+        } = fetched;
+        self.scan_caps = scan_caps;
+        self.pool_saturation = pool_saturation;
+
+        // A failed ping only hides the latency, it doesn't fail the refresh
+        self.broker_latency = ping_result.ok();
+
+        let selection = self.selection_keys();
+        let workers = self.collection_update(Tab::Workers, workers_result)?;
+        let tasks = self.collection_update(Tab::Tasks, tasks_result)?;
+        let queues = self.collection_update(Tab::Queues, queues_result)?;
+        // The beat schedule is optional, failing to read it doesn't fail the refresh
+        let scheduled = match scheduled_result {
+            Err(e @ BrokerError::OperationError(_)) => {
+                self.collection_errors.insert(Tab::Scheduled, e.to_string());
+                None
+            }
+            result => self.collection_update(Tab::Scheduled, result)?,
+        };
+        if let Some(workers) = workers {
+            self.workers = workers;
+            self.record_worker_totals(Instant::now());
+        }
+        if let Some(tasks) = tasks {
+            self.replace_tasks(tasks);
+            self.apply_task_name_cache();
+        }
+        if let Some(queues) = queues {
+            self.queues = queues;
+        }
+        if let Some(scheduled) = scheduled {
+            self.scheduled = scheduled;
+        }
+        self.last_full_refresh = Some(Instant::now());
+        self.last_refresh = self.last_full_refresh;
+        self.last_updated_at = Some(self.now());
+
+        self.broker_error = None;
+        self.consecutive_failures = 0;
+        self.refresh_halted = false;
+
+        // Keep the same items selected after data refresh
+        self.restore_selections(selection);
+
+        Ok(())
+    }
+
+    /// Unwrap a fetched collection, turning a timeout into a recorded error so
+    /// the previous data is kept. Other errors fail the refresh.
+    fn collection_update<T>(
+        &mut self,
+        collection: Tab,
+        result: Result<T, BrokerError>,
+    ) -> Result<Option<T>, AppError> {
+        match result {
+            Ok(data) => {
+                self.collection_errors.remove(&collection);
+                Ok(Some(data))
+            }
+            Err(BrokerError::Timeout) => {
+                self.collection_errors.insert(
+                    collection,
+                    format!("timed out after {}ms", self.operation_timeout.as_millis()),
+                );
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Describe what a destructive action would do, without touching the broker
+    ///
+    /// Returns `None` for actions that don't modify broker state.
+    fn dry_run_message(&self, action: &PendingAction) -> Option<String> {
+        match action {
+            PendingAction::PurgeQueue(queue_name) => {
+                let count = self
+                    .queues
+                    .iter()
+                    .find(|q| &q.name == queue_name)
+                    .map(|q| q.length)
+                    .unwrap_or(0);
+                Some(format!(
+                    "DRY RUN: would purge {count} messages from '{queue_name}'"
+                ))
+            }
+            PendingAction::RetryTask(task_id) => {
+                Some(format!("DRY RUN: would retry task '{task_id}'"))
+            }
+            PendingAction::RevokeTask(task_id) => {
+                Some(format!("DRY RUN: would revoke task '{task_id}'"))
+            }
+            PendingAction::RevokeWorkerTasks { worker, task_ids } => Some(format!(
+                "DRY RUN: would revoke {} active tasks of worker '{worker}'",
+                task_ids.len()
+            )),
+            PendingAction::ReplayTask {
+                task_id,
+                args,
+                kwargs,
+            } => Some(format!(
+                "DRY RUN: would replay task '{task_id}' with args {args} and kwargs {kwargs}"
+            )),
+            PendingAction::ClearCompletedResults(older_than) => Some(format!(
+                "DRY RUN: would delete finished task results older than {}",
+                format_interval(*older_than)
+            )),
+            PendingAction::MoveTask {
+                task_id,
+                from_queue,
+                to_queue,
+            } => Some(format!(
+                "DRY RUN: would move task '{task_id}' from queue '{from_queue}' to '{to_queue}'"
+            )),
+            PendingAction::SetRateLimit { task_name, rate } => Some(format!(
+                "DRY RUN: would limit '{task_name}' to {rate} on every worker"
+            )),
+            PendingAction::DumpTaskMetadata(_) | PendingAction::Quit => None,
+        }
+    }
+
+    /// Remember a failed refresh and react to its category: transient errors
+    /// back off automatic refreshes and point at reconnecting, user errors point
+    /// at the settings and fatal ones stop automatic refreshes altogether
+    pub fn record_broker_error(&mut self, error: &AppError) {
+        self.broker_error = Some(error.to_string());
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        self.last_failed_refresh = Some(Instant::now());
+
+        let category = error.category();
+        let hint = match category {
+            ErrorCategory::User => "check the broker URL and settings",
+            ErrorCategory::Transient => "Ctrl-R to reconnect",
+            ErrorCategory::Fatal => {
+                self.refresh_halted = true;
+                "automatic refresh stopped, Ctrl-R to reconnect"
+            }
+        };
+        self.set_status(
+            format!("Broker error: {error} ({hint})"),
+            StatusKind::of_category(category),
+        );
+    }
+
+    /// Rebuild the broker from the original URL and reload all data
+    pub async fn reconnect(&mut self) {
+        self.reconnect_requested = false;
+
+        let (Some(url), Some(connector)) = (self.broker_url.clone(), self.connector.clone()) else {
+            self.set_status_message("Reconnect unavailable: broker URL unknown".to_string());
+            return;
+        };
+
+        match connector.connect(&url).await {
+            Ok(broker) => {
+                // Dropping the old broker tears down its connection pool
+                self.capabilities = broker.capabilities();
+                self.server_version = broker.server_version();
+                *self.broker.lock().await = broker;
+                self.connect_error = None;
+                match self.refresh_data().await {
+                    Ok(()) => self.set_status_message("Reconnected to broker".to_string()),
+                    Err(e) => self.record_broker_error(&e),
+                }
+            }
+            Err(e) => {
+                self.broker_error = Some(e.to_string());
+                if self.connect_error.is_some() {
+                    self.connect_error = Some(e.to_string());
+                }
+                self.set_status(format!("Reconnect failed: {e}"), StatusKind::of_error(&e));
+            }
+        }
+    }
+
+    /// Refresh queue lengths and recent tasks only, merging them into the
+    /// existing data instead of replacing it
+    pub async fn refresh_incremental(&mut self) -> Result<(), AppError> {
+        let limit = self.operation_timeout;
+        let (tasks_result, queues_result) = {
+            let broker = self.broker.lock().await;
+
             tokio::join!(
-                broker.get_workers(),
-                broker.get_tasks(),
-                broker.get_queues()
+                with_timeout(limit, broker.get_recent_tasks(RECENT_TASKS_WINDOW)),
+                with_timeout(limit, broker.get_queues())
             )
         };
 
-        self.workers = workers_result?;
-        self.tasks = tasks_result?;
-        self.queues = queues_result?;
-
-        // Validate selections after data refresh
-        self.validate_selections();
+        let tasks = self.collection_update(Tab::Tasks, tasks_result)?;
+        let queues = self.collection_update(Tab::Queues, queues_result)?;
+        if let Some(tasks) = tasks {
+            self.merge_tasks(tasks);
+            self.apply_task_name_cache();
+        }
+        if let Some(queues) = queues {
+            self.merge_queues(queues);
+        }
+        self.last_refresh = Some(Instant::now());
+        self.last_updated_at = Some(self.now());
+        self.consecutive_failures = 0;
+        self.refresh_halted = false;
 
         Ok(())
     }
 
-    /// Execute the pending action (purge queue, retry task, or revoke task)
+    /// Reload the task shown in the details modal so its status stays live
+    ///
+    /// Only the fields that change while a task runs are updated. Lookup
+    /// failures keep the snapshot taken when the modal was opened.
+    pub async fn refresh_task_details(&mut self) {
+        let Some(task_id) = self.selected_task_details.as_ref().map(|t| t.id.clone()) else {
+            return;
+        };
+
+        let fresh = {
+            let broker = self.broker.lock().await;
+            with_timeout(self.operation_timeout, broker.get_task(&task_id)).await
+        };
+
+        if let (Ok(Some(fresh)), Some(task)) = (fresh, self.selected_task_details.as_mut()) {
+            // The modal may have moved on to another task while fetching
+            if task.id != fresh.id {
+                return;
+            }
+            task.status = fresh.status;
+            task.result = fresh.result;
+            task.result_kind = fresh.result_kind;
+            task.looks_like_error = fresh.looks_like_error;
+            task.result_full_len = fresh.result_full_len;
+            task.traceback = fresh.traceback;
+            task.traceback_full_len = fresh.traceback_full_len;
+            if fresh.worker.is_some() {
+                task.worker = fresh.worker;
+            }
+        }
+    }
+
+    /// Fetch the child task `jump_to_child_task` couldn't find among the
+    /// listed tasks and show it in the details modal
+    pub async fn load_pending_task_jump(&mut self) {
+        let Some(task_id) = self.pending_task_jump.take() else {
+            return;
+        };
+
+        let fetched = {
+            let broker = self.broker.lock().await;
+            with_timeout(self.operation_timeout, broker.get_task(&task_id)).await
+        };
+
+        match fetched {
+            Ok(Some(task)) => self.open_task_details(task),
+            Ok(None) => self.set_status_message(format!("Task '{task_id}' not found")),
+            Err(e) => self.set_status_message(format!("Failed to load task '{task_id}': {e}")),
+        }
+    }
+
+    /// Fetch the next messages of the peeked queue without consuming them
+    ///
+    /// A failed fetch closes the modal and reports the error.
+    pub async fn load_queue_peek(&mut self) {
+        let Some(queue) = self.peeked_queue.clone() else {
+            return;
+        };
+
+        let peeked = {
+            let broker = self.broker.lock().await;
+            with_timeout(
+                self.operation_timeout,
+                broker.peek_queue(&queue, QUEUE_PEEK_SIZE),
+            )
+            .await
+        };
+
+        match peeked {
+            Ok(tasks) => self.queue_peek_messages = tasks,
+            Err(e) => {
+                self.hide_queue_peek();
+                self.set_status_message(format!("Failed to peek queue '{queue}': {e}"));
+            }
+        }
+    }
+
+    /// Fetch the stored metadata of the task in the details modal for the raw view
+    ///
+    /// JSON is pretty-printed and anything else is shown as stored. The text is
+    /// cut to `RAW_METADATA_DISPLAY_LIMIT`. Missing metadata or a failed fetch
+    /// switches back to the parsed fields and reports why.
+    pub async fn load_task_details_raw(&mut self) {
+        let Some(task_id) = self.selected_task_details.as_ref().map(|t| t.id.clone()) else {
+            return;
+        };
+
+        let fetched = {
+            let broker = self.broker.lock().await;
+            with_timeout(
+                self.operation_timeout,
+                broker.get_raw_task_metadata(&task_id),
+            )
+            .await
+        };
+
+        match fetched {
+            Ok(Some(data)) => {
+                let mut text = serde_json::from_str::<serde_json::Value>(&data)
+                    .and_then(|value| serde_json::to_string_pretty(&value))
+                    .unwrap_or(data);
+                self.task_details_raw_len = text.len();
+                if text.len() > RAW_METADATA_DISPLAY_LIMIT {
+                    let mut cut = RAW_METADATA_DISPLAY_LIMIT;
+                    while !text.is_char_boundary(cut) {
+                        cut -= 1;
+                    }
+                    text.truncate(cut);
+                }
+                self.task_details_raw = Some(text);
+            }
+            Ok(None) => {
+                self.toggle_task_details_raw();
+                self.set_status_message(format!("No stored metadata for task '{task_id}'"));
+            }
+            Err(e) => {
+                self.toggle_task_details_raw();
+                self.set_status_message(format!(
+                    "Failed to fetch metadata of task '{task_id}': {e}"
+                ));
+            }
+        }
+    }
+
+    /// Poll the watched task, unpinning it with a notification once it finishes
+    ///
+    /// Lookup failures and tasks without stored state keep the last known status.
+    pub async fn poll_watched_task(&mut self) {
+        let Some(task_id) = self.watched_task.clone() else {
+            return;
+        };
+
+        let fetched = {
+            let broker = self.broker.lock().await;
+            with_timeout(self.operation_timeout, broker.get_task(&task_id)).await
+        };
+        let Ok(Some(fresh)) = fetched else {
+            return;
+        };
+
+        if let Some(task) = self.tasks.iter_mut().find(|t| t.id == task_id) {
+            task.status = fresh.status.clone();
+        }
+
+        if fresh.status.is_finished() {
+            self.stop_watching_task();
+            self.set_status_message(format!(
+                "Watched task '{task_id}' finished: {:?}",
+                fresh.status
+            ));
+        } else {
+            self.watched_task_status = Some(fresh.status);
+        }
+    }
+
+    /// Run a full refresh when one is due, an incremental one otherwise
+    pub async fn refresh_on_tick(&mut self, now: Instant) -> Result<(), AppError> {
+        if self.is_full_refresh_due(now) {
+            self.refresh_data().await
+        } else {
+            self.refresh_incremental().await
+        }
+    }
+
+    /// Execute the pending action (purge queue, retry/revoke/dump task, or quit)
     pub async fn execute_pending_action(&mut self) -> Result<(), AppError> {
         if let Some(action) = self.pending_action.take() {
+            if action.modifies_broker() && !self.require_writable() {
+                self.hide_confirmation_dialog();
+                return Ok(());
+            }
+            if self.dry_run {
+                if let Some(message) = self.dry_run_message(&action) {
+                    self.set_status_message(message);
+                    self.hide_confirmation_dialog();
+                    return Ok(());
+                }
+            }
+
             let message = {
                 let broker = self.broker.lock().await;
 
                 match &action {
                     PendingAction::PurgeQueue(queue_name) => {
-                        match broker.purge_queue(queue_name).await {
+                        Some(match broker.purge_queue(queue_name).await {
                             Ok(count) => {
-                                format!("Purged {count} messages from queue '{queue_name}'")
+                                done(format!("Purged {count} messages from queue '{queue_name}'"))
+                            }
+                            Err(e) => failed(&format!("purge queue '{queue_name}'"), &e),
+                        })
+                    }
+                    PendingAction::RetryTask(task_id) => {
+                        Some(match broker.retry_task(task_id).await {
+                            Ok(_) => done(format!("Task '{task_id}' marked for retry")),
+                            Err(e) => failed(&format!("retry task '{task_id}'"), &e),
+                        })
+                    }
+                    PendingAction::RevokeTask(task_id) => {
+                        Some(match broker.revoke_task(task_id).await {
+                            Ok(_) => done(format!("Task '{task_id}' revoked")),
+                            Err(e) => failed(&format!("revoke task '{task_id}'"), &e),
+                        })
+                    }
+                    PendingAction::RevokeWorkerTasks { worker, task_ids } => {
+                        let mut errors = Vec::new();
+                        for task_id in task_ids {
+                            if let Err(e) = broker.revoke_task(task_id).await {
+                                errors.push((task_id, e));
                             }
-                            Err(e) => format!("Failed to purge queue '{queue_name}': {e}"),
                         }
+                        let revoked = task_ids.len() - errors.len();
+                        Some(match errors.first() {
+                            None => done(format!(
+                                "Revoked {revoked} tasks of worker '{worker}'"
+                            )),
+                            Some((task_id, e)) => (
+                                format!(
+                                    "Revoked {revoked} of {} tasks of worker '{worker}', {} failed, \
+                                     first '{task_id}': {e}",
+                                    task_ids.len(),
+                                    errors.len()
+                                ),
+                                StatusKind::of_error(e),
+                            ),
+                        })
+                    }
+                    PendingAction::DumpTaskMetadata(task_id) => {
+                        Some(match broker.get_raw_task_metadata(task_id).await {
+                            Ok(Some(data)) => {
+                                let path = std::env::temp_dir()
+                                    .join(format!("lazycelery-task-{task_id}.json"));
+                                match std::fs::write(&path, data) {
+                                    Ok(()) => done(format!(
+                                        "Saved metadata of task '{task_id}' to {}",
+                                        path.display()
+                                    )),
+                                    Err(e) => (
+                                        format!("Failed to write {}: {e}", path.display()),
+                                        StatusKind::SystemError,
+                                    ),
+                                }
+                            }
+                            Ok(None) => done(format!("No stored metadata for task '{task_id}'")),
+                            Err(e) => failed(&format!("fetch task '{task_id}'"), &e),
+                        })
+                    }
+                    PendingAction::ReplayTask {
+                        task_id,
+                        args,
+                        kwargs,
+                    } => Some(match self.tasks.iter().find(|t| &t.id == task_id) {
+                        Some(task) => {
+                            let queue = task.queue.as_deref().unwrap_or(DEFAULT_REPLAY_QUEUE);
+                            match broker.replay_task(&task.name, queue, args, kwargs).await {
+                                Ok(new_id) => done(format!(
+                                    "Replayed task '{task_id}' as '{new_id}' on queue '{queue}'"
+                                )),
+                                Err(e) => failed(&format!("replay task '{task_id}'"), &e),
+                            }
+                        }
+                        None => (
+                            format!("Failed to replay task '{task_id}': task is no longer loaded"),
+                            StatusKind::SystemError,
+                        ),
+                    }),
+                    PendingAction::ClearCompletedResults(older_than) => {
+                        let age = format_interval(*older_than);
+                        Some(match broker.clear_completed_results(*older_than).await {
+                            Ok(count) => {
+                                // Drop the deleted results from the task list
+                                self.refresh_requested = true;
+                                done(format!(
+                                    "Deleted {count} finished task results older than {age}"
+                                ))
+                            }
+                            Err(e) => failed("clear task results", &e),
+                        })
+                    }
+                    PendingAction::MoveTask {
+                        task_id,
+                        from_queue,
+                        to_queue,
+                    } => Some(
+                        match broker.move_task(task_id, from_queue, to_queue).await {
+                            Ok(()) => {
+                                self.refresh_requested = true;
+                                done(format!("Moved task '{task_id}' to queue '{to_queue}'"))
+                            }
+                            Err(e) => failed(&format!("move task '{task_id}'"), &e),
+                        },
+                    ),
+                    PendingAction::SetRateLimit { task_name, rate } => {
+                        Some(match broker.set_rate_limit(task_name, rate).await {
+                            Ok(()) => done(format!(
+                                "Asked every worker to limit '{task_name}' to {rate}"
+                            )),
+                            Err(e) => failed(&format!("set the rate limit of '{task_name}'"), &e),
+                        })
+                    }
+                    PendingAction::Quit => {
+                        self.should_quit = true;
+                        None
                     }
-                    PendingAction::RetryTask(task_id) => match broker.retry_task(task_id).await {
-                        Ok(_) => format!("Task '{task_id}' marked for retry"),
-                        Err(e) => format!("Failed to retry task '{task_id}': {e}"),
-                    },
-                    PendingAction::RevokeTask(task_id) => match broker.revoke_task(task_id).await {
-                        Ok(_) => format!("Task '{task_id}' revoked"),
-                        Err(e) => format!("Failed to revoke task '{task_id}': {e}"),
-                    },
                 }
             };
 
-            self.set_status_message(message);
+            if let Some((message, kind)) = message {
+                self.record_audit(&action, &message, kind);
+                self.set_status(message, kind);
+            }
         }
 
         self.hide_confirmation_dialog();
@@ -60,37 +644,127 @@ impl AppState {
 
     /// Initiate queue purge action with confirmation dialog
     pub fn initiate_purge_queue(&mut self) {
-        if !self.queues.is_empty() && self.selected_tab == Tab::Queues {
-            let queue = &self.queues[self.selected_queue];
-            let message = format!(
-                "Are you sure you want to purge all {} messages from queue '{}'?",
-                queue.length, queue.name
-            );
-            self.show_confirmation_dialog(message, PendingAction::PurgeQueue(queue.name.clone()));
+        if !self.queues.is_empty()
+            && self.selected_tab == Tab::Queues
+            && self.require_writable()
+            && self.require_capability(BrokerOperation::Purge)
+        {
+            let Some(queue) = self.get_sorted_queues().get(self.selected_queue).copied() else {
+                return;
+            };
+            let name = queue.name.clone();
+            let action = PendingAction::PurgeQueue(name.clone());
+            if queue.length > self.purge_double_confirm_above {
+                let message = format!(
+                    "Queue '{}' holds {} messages. Type its name to purge all of them.",
+                    name, queue.length
+                );
+                self.show_typed_confirmation_dialog(message, name, action);
+            } else {
+                let message = format!(
+                    "Are you sure you want to purge all {} messages from queue '{}'?",
+                    queue.length, name
+                );
+                self.show_confirmation_dialog(message, action);
+            }
         }
     }
 
     /// Initiate task retry action with confirmation dialog
     pub fn initiate_retry_task(&mut self) {
-        if !self.tasks.is_empty() && self.selected_tab == Tab::Tasks {
-            let filtered_tasks = self.get_filtered_tasks();
-            if self.selected_task < filtered_tasks.len() {
-                let task = filtered_tasks[self.selected_task];
-                let message = format!("Are you sure you want to retry task '{}'?", task.id);
-                self.show_confirmation_dialog(message, PendingAction::RetryTask(task.id.clone()));
+        if !self.tasks.is_empty()
+            && self.selected_tab == Tab::Tasks
+            && self.require_writable()
+            && self.require_capability(BrokerOperation::Retry)
+        {
+            let Some(task_id) = self
+                .get_filtered_tasks()
+                .get(self.selected_task)
+                .map(|t| t.id.clone())
+            else {
+                return;
+            };
+            if self.require_live_task(&task_id) {
+                let message = format!("Are you sure you want to retry task '{task_id}'?");
+                self.show_confirmation_dialog(message, PendingAction::RetryTask(task_id));
             }
         }
     }
 
     /// Initiate task revoke action with confirmation dialog
     pub fn initiate_revoke_task(&mut self) {
-        if !self.tasks.is_empty() && self.selected_tab == Tab::Tasks {
-            let filtered_tasks = self.get_filtered_tasks();
-            if self.selected_task < filtered_tasks.len() {
-                let task = filtered_tasks[self.selected_task];
-                let message = format!("Are you sure you want to revoke task '{}'?", task.id);
-                self.show_confirmation_dialog(message, PendingAction::RevokeTask(task.id.clone()));
+        if !self.tasks.is_empty()
+            && self.selected_tab == Tab::Tasks
+            && self.require_writable()
+            && self.require_capability(BrokerOperation::Revoke)
+        {
+            let Some(task_id) = self
+                .get_filtered_tasks()
+                .get(self.selected_task)
+                .map(|t| t.id.clone())
+            else {
+                return;
+            };
+            if self.require_live_task(&task_id) {
+                let message = format!("Are you sure you want to revoke task '{task_id}'?");
+                self.show_confirmation_dialog(message, PendingAction::RevokeTask(task_id));
             }
         }
     }
+
+    /// Initiate revoking every active task of the selected worker with
+    /// confirmation dialog
+    pub fn initiate_revoke_worker_tasks(&mut self) {
+        if self.selected_tab != Tab::Workers
+            || !self.require_writable()
+            || !self.require_capability(BrokerOperation::Revoke)
+        {
+            return;
+        }
+        let Some((worker, task_ids)) = self
+            .get_filtered_workers()
+            .get(self.selected_worker)
+            .map(|w| (w.hostname.clone(), w.active_tasks.clone()))
+        else {
+            return;
+        };
+        if task_ids.is_empty() {
+            self.set_status_message(format!("Worker '{worker}' has no active tasks to revoke"));
+            return;
+        }
+        let message = format!(
+            "Are you sure you want to revoke all {} active tasks of worker '{worker}'?",
+            task_ids.len()
+        );
+        self.show_confirmation_dialog(
+            message,
+            PendingAction::RevokeWorkerTasks { worker, task_ids },
+        );
+    }
+
+    /// Initiate deleting old finished task results with confirmation dialog
+    pub fn initiate_clear_completed_results(&mut self) {
+        if self.selected_tab == Tab::Tasks && self.require_writable() {
+            let message = format!(
+                "Delete the stored results of all finished tasks older than {}?",
+                format_interval(CLEANUP_RESULT_AGE)
+            );
+            self.show_confirmation_dialog(
+                message,
+                PendingAction::ClearCompletedResults(CLEANUP_RESULT_AGE),
+            );
+        }
+    }
+
+    /// Initiate saving the full, untruncated metadata of the task shown in the details modal
+    pub fn initiate_dump_task_metadata(&mut self) {
+        let Some(task_id) = self.selected_task_details.as_ref().map(|t| t.id.clone()) else {
+            return;
+        };
+        if self.require_live_task(&task_id) {
+            self.hide_task_details();
+            let message = format!("Save the full stored metadata of task '{task_id}' to a file?");
+            self.show_confirmation_dialog(message, PendingAction::DumpTaskMetadata(task_id));
+        }
+    }
 }
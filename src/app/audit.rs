@@ -0,0 +1,121 @@
+use crate::app::state::{AppState, PendingAction, StatusKind};
+use crate::utils::formatting::{format_interval, format_timestamp};
+use chrono::{DateTime, Utc};
+
+/// Entries kept in the audit log, the oldest are dropped first
+pub const AUDIT_LOG_CAPACITY: usize = 500;
+
+/// File the audit log is exported to, in the temp directory
+const AUDIT_LOG_FILE: &str = "lazycelery-audit.log";
+
+/// A confirmed action that ran during the session, for post-incident review
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// What was done, e.g. "purge"
+    pub action: &'static str,
+    /// What it was done to, e.g. the queue name or task id
+    pub target: String,
+    /// Status message the action ended with
+    pub result: String,
+    pub succeeded: bool,
+}
+
+impl AuditEntry {
+    /// The entry on one line, as exported
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} {} {} [{}] {}",
+            format_timestamp(self.timestamp),
+            self.action,
+            self.target,
+            if self.succeeded { "ok" } else { "failed" },
+            self.result
+        )
+    }
+}
+
+impl PendingAction {
+    /// Action name and target recorded in the audit log, `None` for actions
+    /// that aren't worth recording
+    fn audit_subject(&self) -> Option<(&'static str, String)> {
+        Some(match self {
+            PendingAction::PurgeQueue(queue) => ("purge", queue.clone()),
+            PendingAction::RetryTask(task_id) => ("retry", task_id.clone()),
+            PendingAction::RevokeTask(task_id) => ("revoke", task_id.clone()),
+            PendingAction::RevokeWorkerTasks { worker, task_ids } => (
+                "revoke worker tasks",
+                format!("{worker} ({} tasks)", task_ids.len()),
+            ),
+            PendingAction::DumpTaskMetadata(task_id) => ("save metadata", task_id.clone()),
+            PendingAction::ReplayTask { task_id, .. } => ("replay", task_id.clone()),
+            PendingAction::ClearCompletedResults(older_than) => (
+                "clear results",
+                format!("older than {}", format_interval(*older_than)),
+            ),
+            PendingAction::MoveTask {
+                task_id,
+                from_queue,
+                to_queue,
+            } => ("move", format!("{task_id} {from_queue} -> {to_queue}")),
+            PendingAction::SetRateLimit { task_name, rate } => {
+                ("rate limit", format!("{task_name} {rate}"))
+            }
+            PendingAction::Quit => return None,
+        })
+    }
+}
+
+impl AppState {
+    /// Append the outcome of an executed action to the audit log
+    pub(crate) fn record_audit(&mut self, action: &PendingAction, result: &str, kind: StatusKind) {
+        let Some((name, target)) = action.audit_subject() else {
+            return;
+        };
+        if self.audit_log.len() >= AUDIT_LOG_CAPACITY {
+            self.audit_log.remove(0);
+        }
+        self.audit_log.push(AuditEntry {
+            timestamp: self.now(),
+            action: name,
+            target,
+            result: result.to_string(),
+            succeeded: kind == StatusKind::Info,
+        });
+    }
+
+    pub fn toggle_audit_log(&mut self) {
+        self.show_audit_log = !self.show_audit_log;
+        self.audit_log_scroll = 0;
+    }
+
+    /// Scroll the audit log overlay by `delta` entries, newest first
+    pub fn scroll_audit_log(&mut self, delta: isize) {
+        let last = self.audit_log.len().saturating_sub(1);
+        self.audit_log_scroll = self.audit_log_scroll.saturating_add_signed(delta).min(last);
+    }
+
+    /// Write the audit log to a file in the temp directory, oldest entry first
+    pub fn export_audit_log(&mut self) {
+        let path = std::env::temp_dir().join(AUDIT_LOG_FILE);
+        let mut contents: String = self
+            .audit_log
+            .iter()
+            .map(|entry| entry.to_line() + "\n")
+            .collect();
+        if contents.is_empty() {
+            contents = "No actions taken\n".to_string();
+        }
+        match std::fs::write(&path, contents) {
+            Ok(()) => self.set_status_message(format!(
+                "Saved {} audit log entries to {}",
+                self.audit_log.len(),
+                path.display()
+            )),
+            Err(e) => self.set_status(
+                format!("Failed to write {}: {e}", path.display()),
+                StatusKind::SystemError,
+            ),
+        }
+    }
+}
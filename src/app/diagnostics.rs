@@ -0,0 +1,116 @@
+use crate::app::state::{AppState, StatusKind};
+use crate::broker::redis::pool::PoolStats;
+use crate::broker::ScanCap;
+use crate::utils::formatting::broker_location;
+
+/// File the diagnostics key writes to, in the system temp directory
+const DIAGNOSTICS_FILE: &str = "lazycelery-diagnostics.txt";
+
+/// `shown of found`, or `none` when no cap was hit
+fn describe_cap(cap: Option<ScanCap>) -> String {
+    match cap {
+        Some(cap) => format!("showing {} of {}", cap.shown, cap.found),
+        None => "none".to_string(),
+    }
+}
+
+impl AppState {
+    /// Diagnostics for bug reports, with the pool usage read from the broker
+    pub async fn diagnostics(&self) -> String {
+        let pool = self.broker.lock().await.pool_stats().await;
+        self.format_diagnostics(pool)
+    }
+
+    /// Diagnostics for bug reports: the version, where the broker is, what was
+    /// loaded and which limits were hit
+    ///
+    /// The broker URL is reduced to its scheme, host and database, so user
+    /// names, passwords and query parameters never end up in a report.
+    pub fn format_diagnostics(&self, pool: Option<PoolStats>) -> String {
+        let url = self.broker_url.as_deref().unwrap_or_default();
+        let scheme = url.split_once("://").map_or("", |(scheme, _)| scheme);
+        let (host, _) = broker_location(url);
+        let stats = self.cluster_stats();
+
+        let mut lines = vec![
+            format!("lazycelery {}", env!("CARGO_PKG_VERSION")),
+            format!("broker: {scheme}://{host}"),
+            format!("db: {}", self.broker_db().as_deref().unwrap_or("-")),
+            format!(
+                "server version: {}",
+                self.server_version.as_deref().unwrap_or("unknown")
+            ),
+            format!(
+                "connected: {}",
+                if self.is_connected() { "yes" } else { "no" }
+            ),
+            format!(
+                "consecutive refresh failures: {}",
+                self.consecutive_failures
+            ),
+            format!(
+                "broker latency: {}",
+                self.broker_latency
+                    .map_or("-".to_string(), |latency| format!(
+                        "{}ms",
+                        latency.as_millis()
+                    ))
+            ),
+            format!("workers: {} ({} online)", stats.total, stats.online),
+            format!(
+                "tasks: {} ({} archived)",
+                self.tasks.len(),
+                self.archived_tasks.len()
+            ),
+            format!(
+                "queues: {}{}",
+                self.queues.len(),
+                self.queue_scope
+                    .as_ref()
+                    .map_or(String::new(), |scope| format!(" (only {scope})"))
+            ),
+            format!("scheduled: {}", self.scheduled.len()),
+            format!("task scan cap: {}", describe_cap(self.scan_caps.tasks)),
+            format!("worker scan cap: {}", describe_cap(self.scan_caps.workers)),
+            format!("unreadable tasks: {}", self.scan_caps.unreadable_tasks),
+        ];
+        lines.push(match pool {
+            Some(pool) => format!(
+                "pool: {} active, {} open ({} healthy), peak {} of {}",
+                pool.active_connections,
+                pool.total_connections,
+                pool.healthy_connections,
+                pool.peak_connections,
+                pool.max_size
+            ),
+            None => "pool: -".to_string(),
+        });
+        if let Some(size) = self.pool_saturation {
+            lines.push(format!("pool saturated at last refresh: all {size} in use"));
+        }
+
+        let mut report = lines.join("\n");
+        report.push('\n');
+        report
+    }
+
+    /// Ask the main loop to write the diagnostics file
+    pub fn request_diagnostics(&mut self) {
+        self.diagnostics_requested = true;
+    }
+
+    /// Write the diagnostics to a file in the temp directory for attaching to
+    /// bug reports
+    pub async fn save_diagnostics(&mut self) {
+        self.diagnostics_requested = false;
+        let path = std::env::temp_dir().join(DIAGNOSTICS_FILE);
+        let report = self.diagnostics().await;
+        match std::fs::write(&path, report) {
+            Ok(()) => self.set_status_message(format!("Saved diagnostics to {}", path.display())),
+            Err(e) => self.set_status(
+                format!("Failed to write {}: {e}", path.display()),
+                StatusKind::SystemError,
+            ),
+        }
+    }
+}
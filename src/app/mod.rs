@@ -3,12 +3,34 @@
 //! This module is organized into separate concerns:
 //! - `state`: Core application state, navigation, and UI state management
 //! - `actions`: Business logic for broker operations and user actions
+//! - `palette`: Command palette actions and fuzzy matching
+//! - `settings`: Settings the config overlay edits while the app runs
+//! - `diagnostics`: Credential-free report of the connection for bug reports
+//! - `audit`: Log of the actions taken during the session
+//! - `tail`: Tracking of already printed tasks for `lazycelery tail`
 
 mod actions;
+mod audit;
+mod diagnostics;
+mod palette;
+mod settings;
 mod state;
+mod tail;
 
 // Re-export the main types for convenience
-pub use state::{AppState, Tab};
+pub use settings::SettingField;
+pub use state::{AppState, LayoutMode, StatusKind, Tab, TaskTimeRange};
+pub use tail::TaskTail;
+
+// Only referenced through the library API (tests), not by the binary
+#[allow(unused_imports)]
+pub use actions::RefreshFetch;
+#[allow(unused_imports)]
+pub use audit::{AuditEntry, AUDIT_LOG_CAPACITY};
+#[allow(unused_imports)]
+pub use palette::{filter_actions, fuzzy_score, PaletteAction};
+#[allow(unused_imports)]
+pub use state::{parse_replay_input, ClusterStats, PendingAction, QueueSortKey, TaskSortKey};
 
 // Create a type alias for backward compatibility
 pub type App = AppState;
@@ -0,0 +1,295 @@
+use crate::app::state::{AppState, Tab};
+use crate::broker::BrokerOperation;
+
+/// Actions offered by the command palette, each mapped to an existing handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    GoToWorkers,
+    GoToQueues,
+    GoToTasks,
+    GoToScheduled,
+    GoToOverview,
+    Search,
+    Refresh,
+    TogglePause,
+    Reconnect,
+    PurgeQueue,
+    PeekQueue,
+    FilterWorkersByQueue,
+    SortQueues,
+    ShowTaskDetails,
+    RetryTask,
+    RevokeTask,
+    RevokeWorkerTasks,
+    ReplayTask,
+    MoveTask,
+    SetRateLimit,
+    FollowTask,
+    ToggleStuckFilter,
+    FilterTasksByStatus,
+    ChangeTimeRange,
+    ToggleTimestamps,
+    ClearCompletedResults,
+    SaveDiagnostics,
+    EditSettings,
+    ShowAuditLog,
+    Help,
+    Quit,
+}
+
+impl PaletteAction {
+    /// Every action, in the order listed when the palette query is empty
+    pub const ALL: [PaletteAction; 31] = [
+        PaletteAction::GoToWorkers,
+        PaletteAction::GoToQueues,
+        PaletteAction::GoToTasks,
+        PaletteAction::GoToScheduled,
+        PaletteAction::GoToOverview,
+        PaletteAction::Search,
+        PaletteAction::Refresh,
+        PaletteAction::TogglePause,
+        PaletteAction::Reconnect,
+        PaletteAction::PurgeQueue,
+        PaletteAction::PeekQueue,
+        PaletteAction::FilterWorkersByQueue,
+        PaletteAction::SortQueues,
+        PaletteAction::ShowTaskDetails,
+        PaletteAction::RetryTask,
+        PaletteAction::RevokeTask,
+        PaletteAction::RevokeWorkerTasks,
+        PaletteAction::ReplayTask,
+        PaletteAction::MoveTask,
+        PaletteAction::SetRateLimit,
+        PaletteAction::FollowTask,
+        PaletteAction::ToggleStuckFilter,
+        PaletteAction::FilterTasksByStatus,
+        PaletteAction::ChangeTimeRange,
+        PaletteAction::ToggleTimestamps,
+        PaletteAction::ClearCompletedResults,
+        PaletteAction::SaveDiagnostics,
+        PaletteAction::EditSettings,
+        PaletteAction::ShowAuditLog,
+        PaletteAction::Help,
+        PaletteAction::Quit,
+    ];
+
+    /// Name shown in the palette and matched against the query
+    pub fn label(self) -> &'static str {
+        match self {
+            PaletteAction::GoToWorkers => "Go to workers",
+            PaletteAction::GoToQueues => "Go to queues",
+            PaletteAction::GoToTasks => "Go to tasks",
+            PaletteAction::GoToScheduled => "Go to scheduled",
+            PaletteAction::GoToOverview => "Go to overview",
+            PaletteAction::Search => "Search",
+            PaletteAction::Refresh => "Refresh now",
+            PaletteAction::TogglePause => "Pause/resume auto-refresh",
+            PaletteAction::Reconnect => "Reconnect to broker",
+            PaletteAction::PurgeQueue => "Purge selected queue",
+            PaletteAction::PeekQueue => "Peek messages in selected queue",
+            PaletteAction::FilterWorkersByQueue => "Show workers of selected queue",
+            PaletteAction::SortQueues => "Change queue sort order",
+            PaletteAction::ShowTaskDetails => "Show task details",
+            PaletteAction::RetryTask => "Retry selected task",
+            PaletteAction::RevokeTask => "Revoke selected task",
+            PaletteAction::RevokeWorkerTasks => "Revoke all tasks of selected worker",
+            PaletteAction::ReplayTask => "Replay selected task",
+            PaletteAction::MoveTask => "Move selected task to another queue",
+            PaletteAction::SetRateLimit => "Set rate limit of selected task's name",
+            PaletteAction::FollowTask => "Follow selected task",
+            PaletteAction::ToggleStuckFilter => "Toggle stuck tasks filter",
+            PaletteAction::FilterTasksByStatus => "Filter tasks by status",
+            PaletteAction::ChangeTimeRange => "Change task time range",
+            PaletteAction::ToggleTimestamps => "Toggle relative/absolute timestamps",
+            PaletteAction::ClearCompletedResults => "Clean up old task results",
+            PaletteAction::SaveDiagnostics => "Save diagnostics for a bug report",
+            PaletteAction::EditSettings => "View and edit settings",
+            PaletteAction::ShowAuditLog => "Show actions taken this session",
+            PaletteAction::Help => "Help",
+            PaletteAction::Quit => "Quit",
+        }
+    }
+
+    /// Broker operation the action needs, if any
+    pub fn operation(self) -> Option<BrokerOperation> {
+        match self {
+            PaletteAction::PurgeQueue => Some(BrokerOperation::Purge),
+            PaletteAction::PeekQueue => Some(BrokerOperation::Peek),
+            PaletteAction::RetryTask => Some(BrokerOperation::Retry),
+            PaletteAction::RevokeTask | PaletteAction::RevokeWorkerTasks => {
+                Some(BrokerOperation::Revoke)
+            }
+            PaletteAction::ReplayTask => Some(BrokerOperation::Replay),
+            PaletteAction::MoveTask => Some(BrokerOperation::Reroute),
+            PaletteAction::SetRateLimit => Some(BrokerOperation::RateLimit),
+            _ => None,
+        }
+    }
+
+    /// Whether the action changes the broker, hidden in read-only mode
+    pub fn modifies_broker(self) -> bool {
+        matches!(
+            self,
+            PaletteAction::PurgeQueue
+                | PaletteAction::RetryTask
+                | PaletteAction::RevokeTask
+                | PaletteAction::RevokeWorkerTasks
+                | PaletteAction::ReplayTask
+                | PaletteAction::MoveTask
+                | PaletteAction::SetRateLimit
+                | PaletteAction::ClearCompletedResults
+        )
+    }
+
+    /// Key bound to the action outside the palette, if any
+    pub fn key_hint(self) -> &'static str {
+        match self {
+            PaletteAction::Search => "/",
+            PaletteAction::TogglePause => "Space",
+            PaletteAction::Reconnect => "Ctrl-R",
+            PaletteAction::PurgeQueue => "p",
+            PaletteAction::PeekQueue => "Enter",
+            PaletteAction::FilterWorkersByQueue => "w",
+            PaletteAction::SortQueues => "s",
+            PaletteAction::ShowTaskDetails => "Enter",
+            PaletteAction::RetryTask => "r",
+            PaletteAction::RevokeTask | PaletteAction::RevokeWorkerTasks => "x",
+            PaletteAction::ReplayTask => "e",
+            PaletteAction::MoveTask => "m",
+            PaletteAction::SetRateLimit => "l",
+            PaletteAction::FollowTask => "f",
+            PaletteAction::ToggleStuckFilter => "z",
+            PaletteAction::FilterTasksByStatus => "F",
+            PaletteAction::ChangeTimeRange => "T",
+            PaletteAction::ToggleTimestamps => "t",
+            PaletteAction::ClearCompletedResults => "C",
+            PaletteAction::SaveDiagnostics => "D",
+            PaletteAction::EditSettings => "c",
+            PaletteAction::ShowAuditLog => "L",
+            PaletteAction::Help => "?",
+            PaletteAction::Quit => "q",
+            _ => "",
+        }
+    }
+}
+
+/// Score how well `query` fuzzy-matches `candidate`, `None` when it doesn't
+///
+/// The query characters must appear in order, ignoring case. Consecutive
+/// matches and matches at the start of a word score higher, gaps score lower.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = candidate[position..].iter().position(|&c| c == wanted)?;
+        let index = position + offset;
+
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || candidate[index - 1] == ' ' {
+            score += 10;
+        }
+        score -= offset as i64;
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
+
+/// Actions matching `query`, best match first
+///
+/// Ties keep the order of `PaletteAction::ALL`.
+pub fn filter_actions(query: &str) -> Vec<PaletteAction> {
+    let mut scored: Vec<(i64, PaletteAction)> = PaletteAction::ALL
+        .iter()
+        .filter_map(|&action| fuzzy_score(query, action.label()).map(|score| (score, action)))
+        .collect();
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, action)| action).collect()
+}
+
+impl AppState {
+    pub fn open_palette(&mut self) {
+        self.show_palette = true;
+        self.palette_query.clear();
+        self.palette_selected = 0;
+    }
+
+    pub fn close_palette(&mut self) {
+        self.show_palette = false;
+        self.palette_query.clear();
+        self.palette_selected = 0;
+    }
+
+    /// Actions matching the current palette query, without those read-only
+    /// mode disables
+    pub fn palette_matches(&self) -> Vec<PaletteAction> {
+        let mut matches = filter_actions(&self.palette_query);
+        if self.read_only {
+            matches.retain(|action| !action.modifies_broker());
+        }
+        matches
+    }
+
+    /// Move the palette selection by `delta` entries, wrapping around
+    pub fn move_palette_selection(&mut self, delta: isize) {
+        let count = self.palette_matches().len();
+        if count > 0 {
+            self.palette_selected =
+                (self.palette_selected as isize + delta).rem_euclid(count as isize) as usize;
+        }
+    }
+
+    /// Close the palette and run the selected action
+    pub fn submit_palette(&mut self) {
+        let action = self.palette_matches().get(self.palette_selected).copied();
+        self.close_palette();
+        if let Some(action) = action {
+            self.run_palette_action(action);
+        }
+    }
+
+    /// Dispatch `action` to the handler its key binding uses
+    pub fn run_palette_action(&mut self, action: PaletteAction) {
+        match action {
+            PaletteAction::GoToWorkers => self.selected_tab = Tab::Workers,
+            PaletteAction::GoToQueues => self.selected_tab = Tab::Queues,
+            PaletteAction::GoToTasks => self.selected_tab = Tab::Tasks,
+            PaletteAction::GoToScheduled => self.selected_tab = Tab::Scheduled,
+            PaletteAction::GoToOverview => self.selected_tab = Tab::Overview,
+            PaletteAction::Search => self.start_search(),
+            PaletteAction::Refresh => self.request_refresh(),
+            PaletteAction::TogglePause => self.toggle_pause(),
+            PaletteAction::Reconnect => self.request_reconnect(),
+            PaletteAction::PurgeQueue => self.initiate_purge_queue(),
+            PaletteAction::PeekQueue => self.show_queue_peek(),
+            PaletteAction::FilterWorkersByQueue => self.filter_workers_by_selected_queue(),
+            PaletteAction::SortQueues => self.cycle_queue_sort(),
+            PaletteAction::ShowTaskDetails => self.show_task_details(),
+            PaletteAction::RetryTask => self.initiate_retry_task(),
+            PaletteAction::RevokeTask => self.initiate_revoke_task(),
+            PaletteAction::RevokeWorkerTasks => self.initiate_revoke_worker_tasks(),
+            PaletteAction::ReplayTask => self.start_replay_edit(),
+            PaletteAction::MoveTask => self.start_move_edit(),
+            PaletteAction::SetRateLimit => self.start_rate_limit_edit(),
+            PaletteAction::FollowTask => self.toggle_watch_selected_task(),
+            PaletteAction::ToggleStuckFilter => self.toggle_stuck_filter(),
+            PaletteAction::FilterTasksByStatus => self.cycle_task_status_filter(),
+            PaletteAction::ChangeTimeRange => self.cycle_task_time_range(),
+            PaletteAction::ToggleTimestamps => self.toggle_timestamp_mode(),
+            PaletteAction::ClearCompletedResults => self.initiate_clear_completed_results(),
+            PaletteAction::SaveDiagnostics => self.request_diagnostics(),
+            PaletteAction::EditSettings => self.open_config_overlay(),
+            PaletteAction::ShowAuditLog => self.toggle_audit_log(),
+            PaletteAction::Help => self.toggle_help(),
+            PaletteAction::Quit => self.request_quit(),
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::app::state::{AppState, StatusKind};
+use crate::config::Config;
+use std::time::Duration;
+
+/// Settings the config overlay can change while the app runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingField {
+    RefreshInterval,
+}
+
+impl SettingField {
+    /// Every editable setting, in the order the overlay lists them
+    pub const ALL: [SettingField; 1] = [SettingField::RefreshInterval];
+
+    /// Config key of the setting, as written in `config.toml`
+    pub fn key(self) -> &'static str {
+        match self {
+            SettingField::RefreshInterval => "ui.refresh_interval",
+        }
+    }
+
+    /// Current value of the setting in `config`, as typed in the overlay
+    pub fn value(self, config: &Config) -> String {
+        match self {
+            SettingField::RefreshInterval => config.ui.refresh_interval.to_string(),
+        }
+    }
+
+    /// Copy of `config` with the setting changed to `input`, rejected when
+    /// the result doesn't validate
+    pub fn apply(self, config: &Config, input: &str) -> Result<Config, String> {
+        let input = input.trim();
+        let mut edited = config.clone();
+        match self {
+            SettingField::RefreshInterval => {
+                edited.ui.refresh_interval = input
+                    .parse()
+                    .map_err(|_| format!("'{input}' is not a number of milliseconds"))?;
+            }
+        }
+        edited.validate().map_err(|e| e.to_string())?;
+        Ok(edited)
+    }
+}
+
+impl AppState {
+    pub fn open_config_overlay(&mut self) {
+        self.show_config = true;
+        self.config_selected = 0;
+        self.config_input = None;
+    }
+
+    pub fn close_config_overlay(&mut self) {
+        self.show_config = false;
+        self.config_input = None;
+    }
+
+    /// Whether a setting's new value is being typed
+    pub fn is_editing_config(&self) -> bool {
+        self.config_input.is_some()
+    }
+
+    /// Move the overlay selection by `delta` settings, wrapping around
+    pub fn move_config_selection(&mut self, delta: isize) {
+        let count = SettingField::ALL.len() as isize;
+        self.config_selected = (self.config_selected as isize + delta).rem_euclid(count) as usize;
+    }
+
+    /// Setting selected in the overlay
+    pub fn selected_setting(&self) -> SettingField {
+        SettingField::ALL[self.config_selected.min(SettingField::ALL.len() - 1)]
+    }
+
+    /// Start typing a new value for the selected setting, starting from the current one
+    pub fn start_config_edit(&mut self) {
+        self.config_input = Some(self.selected_setting().value(&self.config));
+    }
+
+    pub fn cancel_config_edit(&mut self) {
+        self.config_input = None;
+    }
+
+    /// Validate the typed value and apply it to the running app
+    ///
+    /// An invalid value is reported and kept in the input to be corrected.
+    pub fn submit_config_edit(&mut self) {
+        let Some(input) = self.config_input.clone() else {
+            return;
+        };
+        let field = self.selected_setting();
+        match field.apply(&self.config, &input) {
+            Ok(edited) => {
+                self.config_input = None;
+                self.apply_settings(edited);
+                self.set_status_message(format!(
+                    "Set {} to {}",
+                    field.key(),
+                    field.value(&self.config)
+                ));
+            }
+            Err(e) => self.set_status(
+                format!("Invalid {}: {e}", field.key()),
+                StatusKind::UserError,
+            ),
+        }
+    }
+
+    /// Take over the settings the overlay edits from `config`
+    ///
+    /// A new refresh interval is picked up by the main loop, which rebuilds
+    /// its timer when `refresh_interval_changed` is set.
+    pub fn apply_settings(&mut self, config: Config) {
+        let refresh_interval = Duration::from_millis(config.ui.refresh_interval);
+        if refresh_interval != self.refresh_interval {
+            self.refresh_interval = refresh_interval;
+            self.refresh_interval_changed = true;
+        }
+        self.config = config;
+    }
+
+    /// Take over a config re-read from disk while running, e.g. on SIGHUP
+    ///
+    /// Settings apply right away and the current tab is kept. When the broker
+    /// URL changed a reconnect is requested, and `true` is returned.
+    pub fn reload_config(&mut self, config: &Config) -> bool {
+        let reconnect = self.config.needs_reconnect(config) && self.connector.is_some();
+        let selected_tab = self.selected_tab;
+        let refresh_interval = self.refresh_interval;
+
+        self.apply_config(config);
+        self.selected_tab = selected_tab;
+        if self.refresh_interval != refresh_interval {
+            self.refresh_interval_changed = true;
+        }
+
+        if reconnect {
+            self.broker_url = Some(config.broker.url.clone());
+            self.reconnect_requested = true;
+            self.set_status_message("Reloaded config, reconnecting to the new broker".to_string());
+        } else {
+            self.set_status_message("Reloaded config".to_string());
+        }
+        reconnect
+    }
+
+    /// Write the edited settings to the config file
+    pub fn save_settings(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            self.set_status(
+                "Cannot save settings: config file location unknown".to_string(),
+                StatusKind::SystemError,
+            );
+            return;
+        };
+        match self.config.save_ui_settings(&path) {
+            Ok(()) => self.set_status_message(format!("Saved settings to {}", path.display())),
+            Err(e) => self.set_status(
+                format!("Failed to save settings to {}: {e:#}", path.display()),
+                StatusKind::SystemError,
+            ),
+        }
+    }
+}
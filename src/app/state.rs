@@ -1,13 +1,281 @@
-use crate::broker::Broker;
-use crate::models::{Queue, Task, Worker};
+use crate::app::audit::AuditEntry;
+use crate::broker::disconnected::DisconnectedBroker;
+use crate::broker::redis::database_index;
+use crate::broker::{
+    validate_rate_limit, Broker, BrokerCapabilities, BrokerConnector, BrokerOperation, ScanCaps,
+};
+use crate::config::{Config, TaskColumn, TimestampMode};
+use crate::error::{BrokerError, ErrorCategory};
+use crate::models::task::{group_by_root, sort_by_duration, sort_by_priority};
+use crate::models::{Queue, ScheduledEntry, Task, TaskStatus, Worker, WorkerStatus};
+use crate::ui::keymap::KeyMap;
+use crate::utils::clock::{system_clock, Clock};
+use crate::utils::formatting::broker_location;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+const DEFAULT_STATUS_MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Name shown for tasks whose name the broker couldn't determine
+const UNKNOWN_TASK_NAME: &str = "unknown";
+
+/// Task names remembered before names of tasks no longer listed are dropped
+const MAX_TASK_NAME_CACHE: usize = 10_000;
+
+/// Longest wait between automatic refreshes while the broker keeps failing
+pub const MAX_REFRESH_BACKOFF: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tab {
     Workers,
     Queues,
     Tasks,
+    Scheduled,
+    /// Queues, workers and failures at a glance
+    Overview,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [
+        Tab::Workers,
+        Tab::Queues,
+        Tab::Tasks,
+        Tab::Scheduled,
+        Tab::Overview,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Workers => "Workers",
+            Tab::Queues => "Queues",
+            Tab::Tasks => "Tasks",
+            Tab::Scheduled => "Scheduled",
+        }
+    }
+
+    /// Name of the tab as written in `ui.default_tab` and `--tab`, one of
+    /// `config::TAB_NAMES`
+    pub fn name(self) -> &'static str {
+        match self {
+            Tab::Overview => "overview",
+            Tab::Workers => "workers",
+            Tab::Queues => "queues",
+            Tab::Tasks => "tasks",
+            Tab::Scheduled => "scheduled",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Tab::ALL.into_iter().find(|tab| tab.name() == name)
+    }
+}
+
+/// How the screen is laid out, chosen from the terminal width on every draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutMode {
+    #[default]
+    Standard,
+    /// Stacked panels and fewer columns for narrow terminals
+    Compact,
+}
+
+impl LayoutMode {
+    /// Compact below `compact_width` columns, a zero threshold never compacts
+    pub fn for_width(width: u16, compact_width: u16) -> Self {
+        if width < compact_width {
+            LayoutMode::Compact
+        } else {
+            LayoutMode::Standard
+        }
+    }
+}
+
+/// Order of the queue list, cycled from the Queues tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueueSortKey {
+    /// Alphabetical
+    Name,
+    /// Most messages first
+    #[default]
+    Length,
+    /// Most consumers first
+    Consumers,
+    /// Longest waiting oldest message first, queues with no known age last
+    OldestAge,
+}
+
+impl QueueSortKey {
+    pub fn next(self) -> Self {
+        match self {
+            QueueSortKey::Name => QueueSortKey::Length,
+            QueueSortKey::Length => QueueSortKey::Consumers,
+            QueueSortKey::Consumers => QueueSortKey::OldestAge,
+            QueueSortKey::OldestAge => QueueSortKey::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QueueSortKey::Name => "name",
+            QueueSortKey::Length => "length",
+            QueueSortKey::Consumers => "consumers",
+            QueueSortKey::OldestAge => "oldest message",
+        }
+    }
+
+    /// Sort `queues` by this key, ties are ordered by name
+    pub fn sort(self, queues: &mut [&Queue]) {
+        queues.sort_by(|a, b| {
+            let order = match self {
+                QueueSortKey::Name => std::cmp::Ordering::Equal,
+                QueueSortKey::Length => b.length.cmp(&a.length),
+                QueueSortKey::Consumers => b.consumers.cmp(&a.consumers),
+                QueueSortKey::OldestAge => b.oldest_message_age.cmp(&a.oldest_message_age),
+            };
+            order.then_with(|| a.name.cmp(&b.name))
+        });
+    }
+}
+
+/// Order of the task list, cycled from the Tasks tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskSortKey {
+    /// As the broker lists them
+    #[default]
+    Broker,
+    /// Longest running first, tasks with no known duration last
+    Duration,
+    /// Highest message priority first, tasks without a priority last
+    Priority,
+}
+
+impl TaskSortKey {
+    pub fn next(self) -> Self {
+        match self {
+            TaskSortKey::Broker => TaskSortKey::Duration,
+            TaskSortKey::Duration => TaskSortKey::Priority,
+            TaskSortKey::Priority => TaskSortKey::Broker,
+        }
+    }
+
+    /// Status message announcing the order
+    fn description(self) -> &'static str {
+        match self {
+            TaskSortKey::Broker => "Listing tasks in broker order",
+            TaskSortKey::Duration => "Sorting tasks by duration",
+            TaskSortKey::Priority => "Sorting tasks by priority",
+        }
+    }
+
+    /// Sort `tasks` by this key, keeping the broker order between ties
+    pub fn sort(self, tasks: &mut [&Task]) {
+        match self {
+            TaskSortKey::Broker => {}
+            TaskSortKey::Duration => sort_by_duration(tasks),
+            TaskSortKey::Priority => sort_by_priority(tasks),
+        }
+    }
+}
+
+/// How far back the task list reaches, cycled from the Tasks tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskTimeRange {
+    #[default]
+    All,
+    Last15m,
+    Last1h,
+    Last24h,
+}
+
+impl TaskTimeRange {
+    pub fn next(self) -> Self {
+        match self {
+            TaskTimeRange::All => TaskTimeRange::Last15m,
+            TaskTimeRange::Last15m => TaskTimeRange::Last1h,
+            TaskTimeRange::Last1h => TaskTimeRange::Last24h,
+            TaskTimeRange::Last24h => TaskTimeRange::All,
+        }
+    }
+
+    /// Short form shown in the header, e.g. "last 1h"
+    pub fn label(self) -> &'static str {
+        match self {
+            TaskTimeRange::All => "all time",
+            TaskTimeRange::Last15m => "last 15m",
+            TaskTimeRange::Last1h => "last 1h",
+            TaskTimeRange::Last24h => "last 24h",
+        }
+    }
+
+    /// Oldest task age listed, `None` for no limit
+    pub fn duration(self) -> Option<chrono::Duration> {
+        match self {
+            TaskTimeRange::All => None,
+            TaskTimeRange::Last15m => Some(chrono::Duration::minutes(15)),
+            TaskTimeRange::Last1h => Some(chrono::Duration::hours(1)),
+            TaskTimeRange::Last24h => Some(chrono::Duration::hours(24)),
+        }
+    }
+}
+
+/// Statuses the task status filter steps through, failures first since
+/// they are what the filter is usually wanted for
+const STATUS_FILTER_ORDER: [TaskStatus; 6] = [
+    TaskStatus::Failure,
+    TaskStatus::Active,
+    TaskStatus::Pending,
+    TaskStatus::Retry,
+    TaskStatus::Success,
+    TaskStatus::Revoked,
+];
+
+/// Kind of the status message, the status bar colors errors by who can fix them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusKind {
+    #[default]
+    Info,
+    /// A request the user can correct, such as a malformed task id
+    UserError,
+    /// The broker or the system failed
+    SystemError,
+}
+
+impl StatusKind {
+    /// Kind of the message reporting `error`
+    pub fn of_error(error: &BrokerError) -> Self {
+        if error.is_user_error() {
+            StatusKind::UserError
+        } else {
+            StatusKind::SystemError
+        }
+    }
+
+    /// Kind of the message reporting an error of `category`
+    pub fn of_category(category: ErrorCategory) -> Self {
+        match category {
+            ErrorCategory::User => StatusKind::UserError,
+            ErrorCategory::Transient | ErrorCategory::Fatal => StatusKind::SystemError,
+        }
+    }
+}
+
+/// Totals across every known worker, for the cluster summary line
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ClusterStats {
+    pub processed: u64,
+    pub failed: u64,
+    pub online: usize,
+    pub total: usize,
+    /// Tasks processed per second between the last two worker refreshes
+    pub throughput: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +283,69 @@ pub enum PendingAction {
     PurgeQueue(String),
     RetryTask(String),
     RevokeTask(String),
+    /// Revoke every task a worker was running when the action was confirmed
+    RevokeWorkerTasks {
+        worker: String,
+        task_ids: Vec<String>,
+    },
+    DumpTaskMetadata(String),
+    ReplayTask {
+        task_id: String,
+        args: String,
+        kwargs: String,
+    },
+    ClearCompletedResults(Duration),
+    MoveTask {
+        task_id: String,
+        from_queue: String,
+        to_queue: String,
+    },
+    /// Limit how often every worker runs tasks of `task_name`
+    SetRateLimit {
+        task_name: String,
+        rate: String,
+    },
+    Quit,
+}
+
+impl PendingAction {
+    /// Whether running the action changes broker state, which read-only
+    /// mode forbids
+    pub fn modifies_broker(&self) -> bool {
+        !matches!(
+            self,
+            PendingAction::DumpTaskMetadata(_) | PendingAction::Quit
+        )
+    }
+}
+
+/// Parse the edited replay arguments, `{"args": [...], "kwargs": {...}}`
+///
+/// Missing keys default to no arguments. Returns the compact JSON of the args
+/// and kwargs, or a message describing why the input was rejected.
+pub fn parse_replay_input(input: &str) -> Result<(String, String), String> {
+    let value: Value = serde_json::from_str(input).map_err(|e| format!("invalid JSON: {e}"))?;
+    let Value::Object(mut fields) = value else {
+        return Err("expected an object with \"args\" and \"kwargs\"".to_string());
+    };
+
+    let args = fields
+        .remove("args")
+        .unwrap_or_else(|| Value::Array(Vec::new()));
+    let kwargs = fields
+        .remove("kwargs")
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    if let Some(unknown) = fields.keys().next() {
+        return Err(format!("unexpected key \"{unknown}\""));
+    }
+    if !args.is_array() {
+        return Err("\"args\" must be a JSON array".to_string());
+    }
+    if !kwargs.is_object() {
+        return Err("\"kwargs\" must be a JSON object".to_string());
+    }
+
+    Ok((args.to_string(), kwargs.to_string()))
 }
 
 pub struct AppState {
@@ -22,31 +353,202 @@ pub struct AppState {
     pub workers: Vec<Worker>,
     pub tasks: Vec<Task>,
     pub queues: Vec<Queue>,
+    pub scheduled: Vec<ScheduledEntry>,
+    /// Names seen for task ids, kept across refreshes so a task keeps its name
+    /// once the queue message that carried it is consumed
+    pub task_names: HashMap<String, String>,
+    /// Finished tasks the broker stopped listing, kept visible until evicted.
+    /// The most recently archived task is last.
+    pub archived_tasks: VecDeque<Task>,
+    /// Archived tasks kept before the oldest are evicted, 0 disables archiving
+    pub archive_capacity: usize,
+
+    // Refresh state
+    pub full_refresh_interval: Duration,
+    pub last_full_refresh: Option<Instant>,
+    pub last_refresh: Option<Instant>,
+    /// Wall-clock time of the last successful refresh, shown in the status bar
+    pub last_updated_at: Option<DateTime<Utc>>,
+    pub min_refresh_gap: Duration,
+    /// Configured time between automatic refreshes
+    pub refresh_interval: Duration,
+    /// Refreshes that failed in a row, automatic refreshes back off while nonzero
+    pub consecutive_failures: u32,
+    pub last_failed_refresh: Option<Instant>,
+    /// Automatic refreshes stopped after an error retrying can't fix, until
+    /// a requested refresh or a reconnect succeeds
+    pub refresh_halted: bool,
+    pub broker_latency: Option<Duration>,
+    /// Total processed tasks at the last worker refresh, for throughput
+    pub processed_sample: Option<(u64, Instant)>,
+    pub throughput: Option<f64>,
+    pub broker_error: Option<String>,
+    /// Why the data shown in a tab is stale, set when its last fetch timed out
+    pub collection_errors: HashMap<Tab, String>,
+    /// Parser caps hit by the last full refresh, so cut-off lists are flagged
+    pub scan_caps: ScanCaps,
+    /// Size of the broker connection pool when the last refresh used all of it
+    pub pool_saturation: Option<usize>,
+    /// Operations the connected broker supports, the others are disabled
+    pub capabilities: BrokerCapabilities,
+    /// Version of the connected broker server, unknown when it isn't reported
+    pub server_version: Option<String>,
+    /// Glob limiting which queues are discovered, shown in the Queues tab
+    pub queue_scope: Option<String>,
+    pub operation_timeout: Duration,
+    pub reconnect_requested: bool,
+    /// Why the first connection failed, set until a broker has been connected
+    pub connect_error: Option<String>,
+    /// Set to fetch fresh data right away instead of waiting for the next tick
+    pub refresh_requested: bool,
+    /// Scheduled refreshes are suspended so the view holds still, requested
+    /// ones still run
+    pub paused: bool,
+    /// A full refresh is running, the header shows a spinner meanwhile
+    pub is_refreshing: bool,
+    /// Frame of the refresh spinner, advanced on every tick of a refresh
+    pub spinner_frame: usize,
+    /// Set to write the diagnostics file from the main loop
+    pub diagnostics_requested: bool,
 
     // Navigation state
     pub selected_tab: Tab,
     pub selected_worker: usize,
     pub selected_task: usize,
     pub selected_queue: usize,
+    pub selected_scheduled: usize,
+    /// Order of the queue list, `selected_queue` indexes the sorted list
+    pub queue_sort: QueueSortKey,
+    /// Order of the task list, `selected_task` indexes the sorted list
+    pub task_sort: TaskSortKey,
 
     // UI state
     pub should_quit: bool,
+    pub confirm_quit: bool,
+    pub dry_run: bool,
+    /// Whether moving past either end of a list continues at the other end
+    pub wrap_navigation: bool,
+    /// Show task rows on two lines, with an args preview under the name
+    pub task_rows_multiline: bool,
+    /// Show large counts in lists and summaries as e.g. `15.2k`
+    pub humanize_numbers: bool,
+    /// Refuse every action that changes the broker
+    pub read_only: bool,
     pub show_help: bool,
     pub search_query: String,
     pub is_searching: bool,
+    /// Whether task search also looks inside args and kwargs, which is
+    /// slower on large listings
+    pub search_args: bool,
+    pub show_palette: bool,
+    pub palette_query: String,
+    pub palette_selected: usize,
+    /// Running configuration, shown and partly edited by the config overlay
+    pub config: Config,
+    /// Config file the overlay saves edited settings to, if known
+    pub config_path: Option<PathBuf>,
+    pub show_config: bool,
+    pub config_selected: usize,
+    /// New value being typed for the selected setting
+    pub config_input: Option<String>,
+    /// Set when the refresh interval changed, so the main loop rebuilds its timer
+    pub refresh_interval_changed: bool,
+    /// Actions executed this session, oldest first, capped at `AUDIT_LOG_CAPACITY`
+    pub audit_log: Vec<AuditEntry>,
+    pub show_audit_log: bool,
+    /// Entries of the audit log overlay scrolled past, counting from the newest
+    pub audit_log_scroll: usize,
+    pub worker_queue_filter: Option<String>,
+    /// Only list tasks that have been active for longer than `stuck_task_threshold`
+    pub show_only_stuck: bool,
+    pub stuck_task_threshold: Duration,
+    /// Only list tasks with this status
+    pub task_status_filter: Option<TaskStatus>,
+    /// Only list tasks whose timestamp falls within this range
+    pub task_time_range: TaskTimeRange,
+    pub task_columns: Vec<TaskColumn>,
+    pub compact_width: u16,
+    /// Header title and status bar summary templates, empty keeps the built-in text
+    pub header_format: String,
+    pub status_format: String,
+    pub keymap: KeyMap,
+    pub timestamp_mode: TimestampMode,
+    pub layout_mode: LayoutMode,
+    /// Task pinned to be polled on every refresh until it finishes
+    pub watched_task: Option<String>,
+    pub watched_task_status: Option<TaskStatus>,
+    pub replay_task_id: Option<String>,
+    pub replay_input: String,
+    /// Pending task being moved and the queue it waits in
+    pub move_source: Option<(String, String)>,
+    pub move_input: String,
+    /// Task name a rate limit is being typed for
+    pub rate_limit_task: Option<String>,
+    pub rate_limit_input: String,
 
     // Dialog state
     pub show_confirmation: bool,
     pub confirmation_message: String,
     pub pending_action: Option<PendingAction>,
+    /// Text that has to be typed to confirm, instead of pressing `y`
+    pub confirmation_phrase: Option<String>,
+    pub confirmation_input: String,
+    /// Purging a queue with more messages than this asks for its name
+    pub purge_double_confirm_above: u64,
     pub status_message: String,
+    pub status_kind: StatusKind,
+    pub status_message_set_at: Option<Instant>,
+    pub status_message_timeout: Duration,
 
     // Task details state
     pub show_task_details: bool,
     pub selected_task_details: Option<Task>,
+    pub task_details_scroll: u16,
+    /// Child of the shown task that opening a child jumps to
+    pub task_details_child: usize,
+    /// Child task to fetch from the broker because it isn't among the listed tasks
+    pub pending_task_jump: Option<String>,
+    /// Whether the details modal shows the stored metadata instead of the parsed fields
+    pub show_task_details_raw: bool,
+    /// Stored metadata of the shown task, pretty-printed and cut for display,
+    /// loaded by `load_task_details_raw`
+    pub task_details_raw: Option<String>,
+    /// Size of the stored metadata before it was cut for display
+    pub task_details_raw_len: usize,
+
+    // Queue peek state
+    pub show_queue_peek: bool,
+    pub peeked_queue: Option<String>,
+    pub queue_peek_messages: Vec<Task>,
 
     // Broker
     pub(crate) broker: Arc<Mutex<Box<dyn Broker>>>,
+    pub(crate) broker_url: Option<String>,
+    pub(crate) connector: Option<Arc<dyn BrokerConnector>>,
+    /// Time used for ages, relative timestamps and the stuck task check
+    pub(crate) clock: Arc<dyn Clock>,
+}
+
+/// Index after `index` in a list of `count` items, going back to the top
+/// past the end when `wrap` is set and staying on the last item otherwise
+fn step_next(index: usize, count: usize, wrap: bool) -> usize {
+    match count {
+        0 => index,
+        _ if index + 1 < count => index + 1,
+        _ if wrap => 0,
+        _ => count - 1,
+    }
+}
+
+/// Index before `index` in a list of `count` items, going to the bottom past
+/// the start when `wrap` is set and staying on the first item otherwise
+fn step_previous(index: usize, count: usize, wrap: bool) -> usize {
+    match count {
+        0 => index,
+        _ if index > 0 => (index - 1).min(count - 1),
+        _ if wrap => count - 1,
+        _ => 0,
+    }
 }
 
 impl AppState {
@@ -55,97 +557,524 @@ impl AppState {
             workers: Vec::new(),
             tasks: Vec::new(),
             queues: Vec::new(),
+            scheduled: Vec::new(),
+            task_names: HashMap::new(),
+            archived_tasks: VecDeque::new(),
+            archive_capacity: 50,
+            full_refresh_interval: Duration::ZERO,
+            last_full_refresh: None,
+            last_refresh: None,
+            last_updated_at: None,
+            min_refresh_gap: Duration::ZERO,
+            refresh_interval: Duration::from_secs(1),
+            consecutive_failures: 0,
+            refresh_halted: false,
+            last_failed_refresh: None,
+            broker_latency: None,
+            processed_sample: None,
+            throughput: None,
+            broker_error: None,
+            collection_errors: HashMap::new(),
+            scan_caps: ScanCaps::default(),
+            pool_saturation: None,
+            capabilities: broker.capabilities(),
+            server_version: broker.server_version(),
+            queue_scope: None,
+            operation_timeout: Duration::from_millis(crate::broker::DEFAULT_OPERATION_TIMEOUT_MS),
+            reconnect_requested: false,
+            connect_error: None,
+            refresh_requested: false,
+            paused: false,
+            is_refreshing: false,
+            spinner_frame: 0,
+            diagnostics_requested: false,
             selected_tab: Tab::Workers,
             should_quit: false,
+            confirm_quit: false,
+            dry_run: false,
+            wrap_navigation: true,
+            task_rows_multiline: false,
+            humanize_numbers: true,
+            read_only: false,
             selected_worker: 0,
             selected_task: 0,
             selected_queue: 0,
+            selected_scheduled: 0,
+            queue_sort: QueueSortKey::default(),
+            task_sort: TaskSortKey::default(),
             show_help: false,
             search_query: String::new(),
             is_searching: false,
+            search_args: false,
+            show_palette: false,
+            config: Config::default(),
+            config_path: None,
+            show_config: false,
+            config_selected: 0,
+            config_input: None,
+            refresh_interval_changed: false,
+            audit_log: Vec::new(),
+            show_audit_log: false,
+            audit_log_scroll: 0,
+            palette_query: String::new(),
+            palette_selected: 0,
+            worker_queue_filter: None,
+            show_only_stuck: false,
+            stuck_task_threshold: Duration::from_secs(3_600),
+            task_status_filter: None,
+            task_time_range: TaskTimeRange::default(),
+            task_columns: TaskColumn::DEFAULTS.to_vec(),
+            compact_width: 80,
+            header_format: String::new(),
+            status_format: String::new(),
+            keymap: KeyMap::default(),
+            timestamp_mode: TimestampMode::default(),
+            layout_mode: LayoutMode::Standard,
+            watched_task: None,
+            watched_task_status: None,
+            replay_task_id: None,
+            replay_input: String::new(),
+            move_source: None,
+            move_input: String::new(),
+            rate_limit_task: None,
+            rate_limit_input: String::new(),
             show_confirmation: false,
             confirmation_message: String::new(),
             pending_action: None,
+            confirmation_phrase: None,
+            confirmation_input: String::new(),
+            purge_double_confirm_above: 100,
             status_message: String::new(),
+            status_kind: StatusKind::Info,
+            status_message_set_at: None,
+            status_message_timeout: DEFAULT_STATUS_MESSAGE_TIMEOUT,
             show_task_details: false,
             selected_task_details: None,
+            task_details_scroll: 0,
+            task_details_child: 0,
+            pending_task_jump: None,
+            show_task_details_raw: false,
+            task_details_raw: None,
+            task_details_raw_len: 0,
+            show_queue_peek: false,
+            peeked_queue: None,
+            queue_peek_messages: Vec::new(),
             broker: Arc::new(Mutex::new(broker)),
+            broker_url: None,
+            connector: None,
+            clock: system_clock(),
+        }
+    }
+
+    /// Start without a broker after the first connection to `url` failed,
+    /// showing setup advice until a reconnect succeeds
+    pub fn disconnected(
+        url: String,
+        connector: Arc<dyn BrokerConnector>,
+        error: &BrokerError,
+    ) -> Self {
+        let mut app = Self::new(Box::new(DisconnectedBroker)).with_reconnect(url, connector);
+        app.connect_error = Some(error.to_string());
+        app
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connect_error.is_none()
+    }
+
+    /// Remember where the broker came from so it can be rebuilt on demand
+    pub fn with_reconnect(mut self, url: String, connector: Arc<dyn BrokerConnector>) -> Self {
+        self.broker_url = Some(url);
+        self.connector = Some(connector);
+        self
+    }
+
+    /// Current time according to the app's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Apply user configuration to the application state
+    pub fn apply_config(&mut self, config: &Config) {
+        self.config = config.clone();
+        self.status_message_timeout = Duration::from_secs(config.ui.status_message_timeout);
+        self.confirm_quit = config.ui.confirm_quit;
+        self.dry_run = config.ui.dry_run;
+        self.read_only = config.ui.read_only;
+        self.wrap_navigation = config.ui.wrap_navigation;
+        self.task_rows_multiline = config.ui.task_rows_multiline;
+        self.humanize_numbers = config.ui.humanize_numbers;
+        self.queue_scope =
+            (!config.broker.queue_glob.is_empty()).then(|| config.broker.queue_glob.clone());
+        self.full_refresh_interval = Duration::from_millis(config.ui.full_refresh_interval);
+        self.min_refresh_gap = Duration::from_millis(config.ui.min_refresh_gap);
+        self.refresh_interval = Duration::from_millis(config.ui.refresh_interval);
+        self.operation_timeout = Duration::from_millis(config.broker.operation_timeout);
+        self.compact_width = config.ui.compact_width;
+        self.header_format = config.ui.header_format.clone();
+        self.status_format = config.ui.status_format.clone();
+        self.purge_double_confirm_above = config.ui.purge_double_confirm_above;
+        self.archive_capacity = config.ui.archived_tasks;
+        self.evict_archived_tasks();
+        self.timestamp_mode = config.ui.timestamp_mode;
+        self.stuck_task_threshold = Duration::from_secs(config.ui.stuck_task_threshold);
+        self.selected_tab = Tab::from_name(&config.ui.default_tab).unwrap_or(Tab::Workers);
+
+        let (task_columns, unknown_columns) = config.ui.resolve_task_columns();
+        self.task_columns = task_columns;
+        if !unknown_columns.is_empty() {
+            self.set_status_message(format!(
+                "Ignoring unknown task columns: {}",
+                unknown_columns.join(", ")
+            ));
+        }
+
+        match KeyMap::from_config(&config.keys) {
+            Ok(keymap) => self.keymap = keymap,
+            Err(e) => self.set_status_message(format!("Using default key bindings: {e}")),
+        }
+    }
+
+    /// Whether the next refresh should rescan everything rather than merge
+    /// incremental updates. A zero interval disables incremental refreshes.
+    pub fn is_full_refresh_due(&self, now: Instant) -> bool {
+        match self.last_full_refresh {
+            Some(last) if !self.full_refresh_interval.is_zero() => {
+                now.saturating_duration_since(last) >= self.full_refresh_interval
+            }
+            _ => true,
+        }
+    }
+
+    /// Whether a scheduled refresh should be coalesced with one that completed
+    /// less than `min_refresh_gap` ago, or held back while paused, while the
+    /// broker is failing or halted, or while not connected yet
+    pub fn should_skip_refresh(&self, now: Instant) -> bool {
+        if self.paused || self.refresh_halted || !self.is_connected() {
+            return true;
+        }
+        if let Some(failed) = self
+            .last_failed_refresh
+            .filter(|_| self.consecutive_failures > 0)
+        {
+            if now.saturating_duration_since(failed) < self.next_refresh_interval() {
+                return true;
+            }
+        }
+        match self.last_refresh {
+            Some(last) => now.saturating_duration_since(last) < self.min_refresh_gap,
+            None => false,
+        }
+    }
+
+    /// Time until the next automatic refresh: the configured interval after
+    /// the first failed refresh, doubled for every further one in a row up to
+    /// `MAX_REFRESH_BACKOFF`
+    pub fn next_refresh_interval(&self) -> Duration {
+        let doublings = self.consecutive_failures.saturating_sub(1).min(16);
+        let factor = 2u32.saturating_pow(doublings);
+        self.refresh_interval
+            .saturating_mul(factor)
+            .min(MAX_REFRESH_BACKOFF.max(self.refresh_interval))
+    }
+
+    /// Sample the workers' processed totals taken at `now` and estimate the
+    /// cluster throughput from the previous sample
+    ///
+    /// A total that went down (a worker restarted or left) gives no estimate
+    /// until the next sample.
+    pub fn record_worker_totals(&mut self, now: Instant) {
+        let processed = self.workers.iter().map(|w| w.processed).sum();
+        self.throughput = self.processed_sample.and_then(|(previous, at)| {
+            let elapsed = now.saturating_duration_since(at).as_secs_f64();
+            (elapsed > 0.0 && processed >= previous)
+                .then(|| (processed - previous) as f64 / elapsed)
+        });
+        self.processed_sample = Some((processed, now));
+    }
+
+    /// Totals across all workers, regardless of any filter
+    pub fn cluster_stats(&self) -> ClusterStats {
+        ClusterStats {
+            processed: self.workers.iter().map(|w| w.processed).sum(),
+            failed: self.workers.iter().map(|w| w.failed).sum(),
+            online: self
+                .workers
+                .iter()
+                .filter(|w| w.status == WorkerStatus::Online)
+                .count(),
+            total: self.workers.len(),
+            throughput: self.throughput,
+        }
+    }
+
+    /// Value of a `{name}` variable in the header and status bar formats
+    pub fn format_variable(&self, name: &str) -> Option<String> {
+        let location = || self.broker_url.as_deref().map(broker_location);
+        Some(match name {
+            "broker_host" => location().map(|(host, _)| host).unwrap_or_default(),
+            "db" => self.broker_db().unwrap_or_default(),
+            "refresh_ms" => self.refresh_interval.as_millis().to_string(),
+            "tab" => self.selected_tab.title().to_string(),
+            "selected" => self.selected_item_name().unwrap_or_default(),
+            "workers" => self.workers.len().to_string(),
+            "tasks" => self.tasks.len().to_string(),
+            "queues" => self.queues.len().to_string(),
+            "version" => env!("CARGO_PKG_VERSION").to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Database index of a Redis broker, from the URL it was connected with
+    pub fn broker_db_index(&self) -> Option<i64> {
+        let url = self.broker_url.as_deref()?;
+        url.starts_with("redis://")
+            .then(|| database_index(url).ok())
+            .flatten()
+    }
+
+    /// Redis database index, or the path of other broker URLs such as an AMQP
+    /// virtual host
+    pub(super) fn broker_db(&self) -> Option<String> {
+        match self.broker_db_index() {
+            Some(db) => Some(db.to_string()),
+            None => broker_location(self.broker_url.as_deref()?).1,
+        }
+    }
+
+    /// Name of the row selected in the current tab
+    fn selected_item_name(&self) -> Option<String> {
+        match self.selected_tab {
+            Tab::Overview => None,
+            Tab::Workers => self
+                .get_filtered_workers()
+                .get(self.selected_worker)
+                .map(|w| w.hostname.clone()),
+            Tab::Tasks => self
+                .get_filtered_tasks()
+                .get(self.selected_task)
+                .map(|t| t.id.clone()),
+            Tab::Queues => self
+                .get_sorted_queues()
+                .get(self.selected_queue)
+                .map(|q| q.name.clone()),
+            Tab::Scheduled => self
+                .scheduled
+                .get(self.selected_scheduled)
+                .map(|e| e.name.clone()),
+        }
+    }
+
+    /// Replace the task listing with a full fetch, archiving finished tasks
+    /// the broker no longer lists
+    pub fn replace_tasks(&mut self, tasks: Vec<Task>) {
+        let listed: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        let dropped: Vec<Task> = self
+            .tasks
+            .drain(..)
+            .filter(|t| t.status.is_finished() && !listed.contains(t.id.as_str()))
+            .collect();
+        self.archived_tasks.extend(dropped);
+        self.tasks = tasks;
+        self.unarchive_listed_tasks();
+        self.evict_archived_tasks();
+    }
+
+    // Incremental data merging
+    pub fn merge_tasks(&mut self, updates: Vec<Task>) {
+        let selection = self.selection_keys();
+        merge_by_key(&mut self.tasks, updates, |t| t.id.clone(), true);
+        self.unarchive_listed_tasks();
+        self.restore_selections(selection);
+    }
+
+    /// Drop archived copies of tasks the broker lists again
+    fn unarchive_listed_tasks(&mut self) {
+        if self.archived_tasks.is_empty() {
+            return;
+        }
+        let listed: HashSet<&str> = self.tasks.iter().map(|t| t.id.as_str()).collect();
+        self.archived_tasks
+            .retain(|t| !listed.contains(t.id.as_str()));
+    }
+
+    /// Evict the longest archived tasks beyond `archive_capacity`
+    fn evict_archived_tasks(&mut self) {
+        let excess = self
+            .archived_tasks
+            .len()
+            .saturating_sub(self.archive_capacity);
+        self.archived_tasks.drain(..excess);
+    }
+
+    /// Whether `task_id` is only known from the archive, not from the broker
+    pub fn is_archived(&self, task_id: &str) -> bool {
+        self.archived_tasks.iter().any(|t| t.id == task_id)
+    }
+
+    /// Whether `task_id` is still listed by the broker, reporting it in the
+    /// status bar when it is archived
+    pub fn require_live_task(&mut self, task_id: &str) -> bool {
+        let live = !self.is_archived(task_id);
+        if !live {
+            self.set_status_message(format!(
+                "Task '{task_id}' is archived, the broker no longer lists it"
+            ));
         }
+        live
+    }
+
+    /// Remember the names of the loaded tasks and fill in names the broker
+    /// no longer knows from earlier refreshes
+    pub fn apply_task_name_cache(&mut self) {
+        for task in &mut self.tasks {
+            if task.name == UNKNOWN_TASK_NAME {
+                if let Some(name) = self.task_names.get(&task.id) {
+                    task.name = name.clone();
+                }
+            } else {
+                self.task_names.insert(task.id.clone(), task.name.clone());
+            }
+        }
+
+        if self.task_names.len() > MAX_TASK_NAME_CACHE {
+            let listed: HashSet<&str> = self.tasks.iter().map(|t| t.id.as_str()).collect();
+            self.task_names.retain(|id, _| listed.contains(id.as_str()));
+        }
+    }
+
+    /// Merge a complete queue listing; queues missing from `updates` are dropped
+    pub fn merge_queues(&mut self, updates: Vec<Queue>) {
+        let selection = self.selection_keys();
+        merge_by_key(&mut self.queues, updates, |q| q.name.clone(), false);
+        self.restore_selections(selection);
     }
 
     // Tab navigation
     pub fn next_tab(&mut self) {
         self.selected_tab = match self.selected_tab {
+            Tab::Overview => Tab::Workers,
             Tab::Workers => Tab::Queues,
             Tab::Queues => Tab::Tasks,
-            Tab::Tasks => Tab::Workers,
+            Tab::Tasks => Tab::Scheduled,
+            Tab::Scheduled => Tab::Overview,
         };
     }
 
     pub fn previous_tab(&mut self) {
         self.selected_tab = match self.selected_tab {
-            Tab::Workers => Tab::Tasks,
+            Tab::Overview => Tab::Scheduled,
+            Tab::Workers => Tab::Overview,
             Tab::Queues => Tab::Workers,
             Tab::Tasks => Tab::Queues,
+            Tab::Scheduled => Tab::Tasks,
         };
     }
 
     // Item selection
     pub fn select_next(&mut self) {
+        let wrap = self.wrap_navigation;
         match self.selected_tab {
+            // Nothing to select, the overview only summarizes
+            Tab::Overview => {}
             Tab::Workers => {
-                if !self.workers.is_empty() {
-                    self.selected_worker = (self.selected_worker + 1) % self.workers.len();
-                }
+                let count = self.get_filtered_workers().len();
+                self.selected_worker = step_next(self.selected_worker, count, wrap);
             }
             Tab::Tasks => {
-                let filtered_count = self.get_filtered_tasks().len();
-                if filtered_count > 0 {
-                    self.selected_task = (self.selected_task + 1) % filtered_count;
-                }
+                let count = self.get_filtered_tasks().len();
+                self.selected_task = step_next(self.selected_task, count, wrap);
             }
             Tab::Queues => {
-                if !self.queues.is_empty() {
-                    self.selected_queue = (self.selected_queue + 1) % self.queues.len();
-                }
+                self.selected_queue = step_next(self.selected_queue, self.queues.len(), wrap);
+            }
+            Tab::Scheduled => {
+                self.selected_scheduled =
+                    step_next(self.selected_scheduled, self.scheduled.len(), wrap);
             }
         }
     }
 
     pub fn select_previous(&mut self) {
+        let wrap = self.wrap_navigation;
         match self.selected_tab {
+            Tab::Overview => {}
             Tab::Workers => {
-                if !self.workers.is_empty() {
-                    self.selected_worker = if self.selected_worker == 0 {
-                        self.workers.len() - 1
-                    } else {
-                        self.selected_worker - 1
-                    };
-                }
+                let count = self.get_filtered_workers().len();
+                self.selected_worker = step_previous(self.selected_worker, count, wrap);
             }
             Tab::Tasks => {
-                let filtered_count = self.get_filtered_tasks().len();
-                if filtered_count > 0 {
-                    self.selected_task = if self.selected_task == 0 {
-                        filtered_count - 1
-                    } else {
-                        self.selected_task - 1
-                    };
-                }
+                let count = self.get_filtered_tasks().len();
+                self.selected_task = step_previous(self.selected_task, count, wrap);
             }
             Tab::Queues => {
-                if !self.queues.is_empty() {
-                    self.selected_queue = if self.selected_queue == 0 {
-                        self.queues.len() - 1
-                    } else {
-                        self.selected_queue - 1
-                    };
-                }
+                self.selected_queue = step_previous(self.selected_queue, self.queues.len(), wrap);
+            }
+            Tab::Scheduled => {
+                self.selected_scheduled =
+                    step_previous(self.selected_scheduled, self.scheduled.len(), wrap);
             }
         }
     }
 
     // UI state management
+    /// Quit immediately, or ask for confirmation first when `confirm_quit` is enabled
+    pub fn request_quit(&mut self) {
+        if self.confirm_quit {
+            self.show_confirmation_dialog("Quit lazycelery?".to_string(), PendingAction::Quit);
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    /// Move the refresh spinner on by one frame
+    pub fn advance_spinner(&mut self) {
+        self.spinner_frame = self.spinner_frame.wrapping_add(1);
+    }
+
+    /// Ask the main loop to rebuild the broker connection
+    pub fn request_reconnect(&mut self) {
+        self.reconnect_requested = true;
+    }
+
+    /// Whether actions may change the broker, reporting it in the status bar
+    /// when read-only mode forbids it
+    pub fn require_writable(&mut self) -> bool {
+        if self.read_only {
+            self.set_status_message(
+                "Read-only mode: actions that change the broker are disabled".to_string(),
+            );
+        }
+        !self.read_only
+    }
+
+    /// Whether the broker supports `operation`, reporting it in the status
+    /// bar when it doesn't
+    pub fn require_capability(&mut self, operation: BrokerOperation) -> bool {
+        let supported = self.capabilities.supports(operation);
+        if !supported {
+            self.set_status_message(format!(
+                "{} is not supported by this broker",
+                operation.label()
+            ));
+        }
+        supported
+    }
+
+    /// Suspend or resume scheduled refreshes
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Fetch fresh data right away, or retry connecting when there is no broker yet
+    pub fn request_refresh(&mut self) {
+        if self.is_connected() {
+            self.refresh_requested = true;
+        } else {
+            self.reconnect_requested = true;
+        }
+    }
+
     pub fn toggle_help(&mut self) {
         self.show_help = !self.show_help;
     }
@@ -153,6 +1082,24 @@ impl AppState {
     pub fn start_search(&mut self) {
         self.is_searching = true;
         self.search_query.clear();
+        self.clear_worker_queue_filter();
+    }
+
+    /// Include args and kwargs in the task search or leave them out, keeping
+    /// the selected task selected
+    pub fn toggle_search_args(&mut self) {
+        let keys = self.selection_keys();
+        self.search_args = !self.search_args;
+        self.restore_selections(keys);
+    }
+
+    /// Task fields the search looks in, as shown in the search box
+    pub fn search_fields(&self) -> &'static str {
+        if self.search_args {
+            "name, id, args"
+        } else {
+            "name, id"
+        }
     }
 
     pub fn stop_search(&mut self) {
@@ -164,24 +1111,381 @@ impl AppState {
         }
     }
 
+    /// Switch task times between relative and absolute display
+    pub fn toggle_timestamp_mode(&mut self) {
+        self.timestamp_mode = self.timestamp_mode.toggled();
+    }
+
+    // Task watching
+    /// Pin the selected task to watch its status, or unpin it when already watched
+    pub fn toggle_watch_selected_task(&mut self) {
+        if self.selected_tab != Tab::Tasks {
+            return;
+        }
+        let Some((task_id, status)) = self
+            .get_filtered_tasks()
+            .get(self.selected_task)
+            .map(|task| (task.id.clone(), task.status.clone()))
+        else {
+            return;
+        };
+
+        if self.watched_task.as_deref() == Some(task_id.as_str()) {
+            self.stop_watching_task();
+            self.set_status_message(format!("Stopped watching task '{task_id}'"));
+        } else if self.require_live_task(&task_id) {
+            self.watched_task = Some(task_id);
+            self.watched_task_status = Some(status);
+        }
+    }
+
+    pub fn stop_watching_task(&mut self) {
+        self.watched_task = None;
+        self.watched_task_status = None;
+    }
+
+    // Replay editing
+    pub fn is_editing_replay(&self) -> bool {
+        self.replay_task_id.is_some()
+    }
+
+    /// Start editing the arguments of the selected task to replay it
+    pub fn start_replay_edit(&mut self) {
+        if self.selected_tab != Tab::Tasks
+            || !self.require_writable()
+            || !self.require_capability(BrokerOperation::Replay)
+        {
+            return;
+        }
+        if let Some(task) = self.get_filtered_tasks().get(self.selected_task) {
+            let input = format!(r#"{{"args": {}, "kwargs": {}}}"#, task.args, task.kwargs);
+            self.replay_task_id = Some(task.id.clone());
+            self.replay_input = input;
+        }
+    }
+
+    pub fn cancel_replay_edit(&mut self) {
+        self.replay_task_id = None;
+        self.replay_input.clear();
+    }
+
+    /// Validate the edited arguments and ask for confirmation before replaying.
+    /// Malformed input keeps the editor open and reports the problem.
+    pub fn submit_replay_edit(&mut self) {
+        let Some(task_id) = self.replay_task_id.clone() else {
+            return;
+        };
+
+        match parse_replay_input(&self.replay_input) {
+            Ok((args, kwargs)) => {
+                self.cancel_replay_edit();
+                let message = format!(
+                    "Replay task '{task_id}' as a new task with args {args} and kwargs {kwargs}?"
+                );
+                self.show_confirmation_dialog(
+                    message,
+                    PendingAction::ReplayTask {
+                        task_id,
+                        args,
+                        kwargs,
+                    },
+                );
+            }
+            Err(e) => self.set_status(
+                format!("Invalid replay arguments: {e}"),
+                StatusKind::UserError,
+            ),
+        }
+    }
+
+    // Moving tasks between queues
+    pub fn is_editing_move(&self) -> bool {
+        self.move_source.is_some()
+    }
+
+    /// Start typing the queue to move the selected pending task to
+    pub fn start_move_edit(&mut self) {
+        if self.selected_tab != Tab::Tasks
+            || !self.require_writable()
+            || !self.require_capability(BrokerOperation::Reroute)
+        {
+            return;
+        }
+        let Some(task) = self.get_filtered_tasks().get(self.selected_task).copied() else {
+            return;
+        };
+        match (&task.status, &task.queue) {
+            (TaskStatus::Pending, Some(queue)) => {
+                self.move_source = Some((task.id.clone(), queue.clone()));
+                self.move_input.clear();
+            }
+            _ => self.set_status_message(
+                "Only pending tasks waiting in a known queue can be moved".to_string(),
+            ),
+        }
+    }
+
+    pub fn cancel_move_edit(&mut self) {
+        self.move_source = None;
+        self.move_input.clear();
+    }
+
+    /// Ask for confirmation before moving the task to the typed queue.
+    /// An empty or unchanged queue keeps the editor open and reports the problem.
+    pub fn submit_move_edit(&mut self) {
+        let Some((task_id, from_queue)) = self.move_source.clone() else {
+            return;
+        };
+
+        let to_queue = self.move_input.trim().to_string();
+        if to_queue.is_empty() {
+            self.set_status(
+                "Enter the queue to move the task to".to_string(),
+                StatusKind::UserError,
+            );
+        } else if to_queue == from_queue {
+            self.set_status(
+                format!("Task is already in queue '{from_queue}'"),
+                StatusKind::UserError,
+            );
+        } else {
+            self.cancel_move_edit();
+            let message =
+                format!("Move task '{task_id}' from queue '{from_queue}' to '{to_queue}'?");
+            self.show_confirmation_dialog(
+                message,
+                PendingAction::MoveTask {
+                    task_id,
+                    from_queue,
+                    to_queue,
+                },
+            );
+        }
+    }
+
+    // Task rate limits
+    pub fn is_editing_rate_limit(&self) -> bool {
+        self.rate_limit_task.is_some()
+    }
+
+    /// Start typing a rate limit for the name of the selected task
+    pub fn start_rate_limit_edit(&mut self) {
+        if self.selected_tab != Tab::Tasks
+            || !self.require_writable()
+            || !self.require_capability(BrokerOperation::RateLimit)
+        {
+            return;
+        }
+        if let Some(task) = self.get_filtered_tasks().get(self.selected_task) {
+            self.rate_limit_task = Some(task.name.clone());
+            self.rate_limit_input.clear();
+        }
+    }
+
+    pub fn cancel_rate_limit_edit(&mut self) {
+        self.rate_limit_task = None;
+        self.rate_limit_input.clear();
+    }
+
+    /// Ask for confirmation before sending the typed rate limit to the workers.
+    /// A malformed rate keeps the editor open and reports the problem.
+    pub fn submit_rate_limit_edit(&mut self) {
+        let Some(task_name) = self.rate_limit_task.clone() else {
+            return;
+        };
+
+        let rate = self.rate_limit_input.trim().to_string();
+        match validate_rate_limit(&rate) {
+            Ok(()) => {
+                self.cancel_rate_limit_edit();
+                let message = if rate.parse::<f64>() == Ok(0.0) {
+                    format!("Lift the rate limit of '{task_name}' on every worker?")
+                } else {
+                    format!("Limit '{task_name}' to {rate} on every worker?")
+                };
+                self.show_confirmation_dialog(
+                    message,
+                    PendingAction::SetRateLimit { task_name, rate },
+                );
+            }
+            Err(BrokerError::ValidationError(reason)) => {
+                self.set_status(reason, StatusKind::UserError)
+            }
+            Err(e) => self.set_status(e.to_string(), StatusKind::UserError),
+        }
+    }
+
+    // Queue ordering
+    /// Queues in the order the Queues tab lists them
+    pub fn get_sorted_queues(&self) -> Vec<&Queue> {
+        let mut queues: Vec<&Queue> = self.queues.iter().collect();
+        self.queue_sort.sort(&mut queues);
+        queues
+    }
+
+    /// Switch to the next queue order, keeping the selected queue selected
+    pub fn cycle_queue_sort(&mut self) {
+        if self.selected_tab != Tab::Queues {
+            return;
+        }
+        let keys = self.selection_keys();
+        self.queue_sort = self.queue_sort.next();
+        self.restore_selections(keys);
+        self.set_status_message(format!("Sorting queues by {}", self.queue_sort.label()));
+    }
+
+    // Worker filtering
+    pub fn get_filtered_workers(&self) -> Vec<&Worker> {
+        match &self.worker_queue_filter {
+            Some(queue) => self
+                .workers
+                .iter()
+                .filter(|worker| worker.queues.iter().any(|q| q == queue))
+                .collect(),
+            None => self.workers.iter().collect(),
+        }
+    }
+
+    /// Jump to the Workers tab showing only workers subscribed to the selected queue
+    pub fn filter_workers_by_selected_queue(&mut self) {
+        if self.selected_tab == Tab::Queues {
+            if let Some(queue) = self.get_sorted_queues().get(self.selected_queue) {
+                self.worker_queue_filter = Some(queue.name.clone());
+                self.selected_worker = 0;
+                self.selected_tab = Tab::Workers;
+            }
+        }
+    }
+
+    pub fn clear_worker_queue_filter(&mut self) {
+        if self.worker_queue_filter.take().is_some() {
+            self.selected_worker = 0;
+        }
+    }
+
     // Task filtering
     pub fn get_filtered_tasks(&self) -> Vec<&Task> {
-        if self.search_query.is_empty() {
-            self.tasks.iter().collect()
+        let now = self.now();
+        let oldest = self.task_time_range.duration().map(|range| now - range);
+        // Archived tasks follow the live ones, most recently archived first
+        let tasks = self
+            .tasks
+            .iter()
+            .chain(self.archived_tasks.iter().rev())
+            .filter(|task| !self.show_only_stuck || self.is_task_stuck(task, now))
+            .filter(|task| {
+                self.task_status_filter
+                    .as_ref()
+                    .is_none_or(|status| &task.status == status)
+            })
+            .filter(|task| oldest.is_none_or(|oldest| task.timestamp >= oldest));
+
+        let mut tasks: Vec<&Task> = if self.search_query.is_empty() {
+            tasks.collect()
         } else {
-            self.tasks
-                .iter()
+            let query = self.search_query.to_lowercase();
+            let matches = |field: &str| field.to_lowercase().contains(&query);
+            tasks
                 .filter(|task| {
-                    task.name
-                        .to_lowercase()
-                        .contains(&self.search_query.to_lowercase())
-                        || task
-                            .id
-                            .to_lowercase()
-                            .contains(&self.search_query.to_lowercase())
+                    matches(&task.name)
+                        || matches(&task.id)
+                        || (self.search_args && (matches(&task.args) || matches(&task.kwargs)))
                 })
                 .collect()
+        };
+        self.task_sort.sort(&mut tasks);
+        tasks
+    }
+
+    /// Switch the task list to the next order, keeping the selected task
+    /// selected
+    pub fn cycle_task_sort(&mut self) {
+        if self.selected_tab != Tab::Tasks {
+            return;
         }
+        let keys = self.selection_keys();
+        self.task_sort = self.task_sort.next();
+        self.restore_selections(keys);
+        self.set_status_message(self.task_sort.description().to_string());
+    }
+
+    /// Widen or narrow the time range of the task list, keeping the selected
+    /// task selected when it is still listed
+    pub fn cycle_task_time_range(&mut self) {
+        if self.selected_tab != Tab::Tasks {
+            return;
+        }
+        let keys = self.selection_keys();
+        self.task_time_range = self.task_time_range.next();
+        self.restore_selections(keys);
+        self.set_status_message(format!(
+            "Showing tasks from {}",
+            self.task_time_range.label()
+        ));
+    }
+
+    /// List only tasks of the next status, or of every status again
+    pub fn cycle_task_status_filter(&mut self) {
+        if self.selected_tab != Tab::Tasks {
+            return;
+        }
+        let keys = self.selection_keys();
+        let next = match &self.task_status_filter {
+            None => Some(0),
+            Some(current) => STATUS_FILTER_ORDER
+                .iter()
+                .position(|status| status == current)
+                .map(|index| index + 1)
+                .filter(|&index| index < STATUS_FILTER_ORDER.len()),
+        };
+        self.task_status_filter = next.map(|index| STATUS_FILTER_ORDER[index].clone());
+        self.restore_selections(keys);
+        self.set_status_message(match &self.task_status_filter {
+            Some(status) => format!("Showing {status:?} tasks only"),
+            None => "Showing tasks of every status".to_string(),
+        });
+    }
+
+    pub fn clear_task_status_filter(&mut self) {
+        if self.task_status_filter.take().is_some() {
+            self.selected_task = 0;
+        }
+    }
+
+    /// Whether `task` has been active for longer than the configured threshold
+    pub fn is_task_stuck(&self, task: &Task, now: DateTime<Utc>) -> bool {
+        let threshold =
+            chrono::Duration::from_std(self.stuck_task_threshold).unwrap_or(chrono::Duration::MAX);
+        task.is_stuck(threshold, now)
+    }
+
+    /// Show only stuck tasks, or all tasks again
+    pub fn toggle_stuck_filter(&mut self) {
+        self.show_only_stuck = !self.show_only_stuck;
+        self.selected_task = 0;
+    }
+
+    pub fn clear_stuck_filter(&mut self) {
+        if self.show_only_stuck {
+            self.toggle_stuck_filter();
+        }
+    }
+
+    /// Parent of `task` and the other tasks sharing its workflow root, among the loaded tasks
+    pub fn task_relations(&self, task: &Task) -> (Option<&Task>, Vec<&Task>) {
+        let parent = task
+            .parent_id
+            .as_deref()
+            .and_then(|parent_id| self.tasks.iter().find(|t| t.id == parent_id));
+        let siblings = task
+            .root_id
+            .as_deref()
+            .and_then(|root_id| group_by_root(&self.tasks).remove(root_id))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|t| t.id != task.id)
+            .collect();
+        (parent, siblings)
     }
 
     // Dialog management
@@ -191,49 +1495,340 @@ impl AppState {
         self.show_confirmation = true;
     }
 
+    /// Ask for `phrase` to be typed before running `action`
+    pub fn show_typed_confirmation_dialog(
+        &mut self,
+        message: String,
+        phrase: String,
+        action: PendingAction,
+    ) {
+        self.show_confirmation_dialog(message, action);
+        self.confirmation_phrase = Some(phrase);
+        self.confirmation_input.clear();
+    }
+
     pub fn hide_confirmation_dialog(&mut self) {
         self.show_confirmation = false;
         self.confirmation_message.clear();
         self.pending_action = None;
+        self.confirmation_phrase = None;
+        self.confirmation_input.clear();
+    }
+
+    /// Whether the dialog waits for a typed phrase rather than `y`
+    pub fn is_typing_confirmation(&self) -> bool {
+        self.show_confirmation && self.confirmation_phrase.is_some()
+    }
+
+    /// Whether the typed text matches the phrase the dialog asks for
+    pub fn typed_confirmation_matches(&self) -> bool {
+        self.confirmation_phrase.as_deref() == Some(self.confirmation_input.trim())
+    }
+
+    /// Close a type-to-confirm dialog whose typed text didn't match
+    pub fn reject_typed_confirmation(&mut self) {
+        let phrase = self.confirmation_phrase.clone().unwrap_or_default();
+        self.hide_confirmation_dialog();
+        self.set_status_message(format!(
+            "Typed name doesn't match '{phrase}', nothing was changed"
+        ));
     }
 
     // Status message management
     pub fn set_status_message(&mut self, message: String) {
+        self.set_status(message, StatusKind::Info);
+    }
+
+    /// Show `message` in the status bar, colored by `kind`
+    pub fn set_status(&mut self, message: String, kind: StatusKind) {
         self.status_message = message;
+        self.status_kind = kind;
+        self.status_message_set_at = Some(Instant::now());
     }
 
     pub fn clear_status_message(&mut self) {
         self.status_message.clear();
+        self.status_kind = StatusKind::Info;
+        self.status_message_set_at = None;
+    }
+
+    /// Clear the status message once it has been shown for longer than the
+    /// configured timeout. A zero timeout keeps messages until dismissed.
+    pub fn expire_status_message(&mut self, now: Instant) {
+        if self.status_message_timeout.is_zero() {
+            return;
+        }
+        if let Some(set_at) = self.status_message_set_at {
+            if now.saturating_duration_since(set_at) >= self.status_message_timeout {
+                self.clear_status_message();
+            }
+        }
     }
 
     // Task details management
     pub fn show_task_details(&mut self) {
-        if !self.tasks.is_empty() && self.selected_tab == Tab::Tasks {
+        if self.selected_tab == Tab::Tasks {
             let filtered_tasks = self.get_filtered_tasks();
             if self.selected_task < filtered_tasks.len() {
-                let task = filtered_tasks[self.selected_task];
-                self.selected_task_details = Some(task.clone());
-                self.show_task_details = true;
+                let task = filtered_tasks[self.selected_task].clone();
+                self.open_task_details(task);
             }
         }
     }
 
+    /// Show `task` in the details modal, replacing any task shown before
+    pub fn open_task_details(&mut self, task: Task) {
+        self.selected_task_details = Some(task);
+        self.task_details_scroll = 0;
+        self.task_details_child = 0;
+        self.show_task_details = true;
+        self.hide_task_details_raw();
+    }
+
     pub fn hide_task_details(&mut self) {
         self.show_task_details = false;
         self.selected_task_details = None;
+        self.task_details_scroll = 0;
+        self.task_details_child = 0;
+        self.pending_task_jump = None;
+        self.hide_task_details_raw();
+    }
+
+    /// Switch the details modal between the parsed fields and the stored
+    /// metadata, which is fetched by `load_task_details_raw` on every switch
+    pub fn toggle_task_details_raw(&mut self) {
+        let show = !self.show_task_details_raw;
+        self.hide_task_details_raw();
+        self.show_task_details_raw = show;
+        self.task_details_scroll = 0;
+    }
+
+    fn hide_task_details_raw(&mut self) {
+        self.show_task_details_raw = false;
+        self.task_details_raw = None;
+        self.task_details_raw_len = 0;
+    }
+
+    /// Whether the task shown in the details modal spawned other tasks
+    pub fn task_details_have_children(&self) -> bool {
+        self.selected_task_details
+            .as_ref()
+            .is_some_and(|task| !task.children.is_empty())
+    }
+
+    /// Select the next child of the task shown in the details modal, wrapping around
+    pub fn select_next_child_task(&mut self) {
+        let count = self
+            .selected_task_details
+            .as_ref()
+            .map_or(0, |task| task.children.len());
+        if count > 0 {
+            self.task_details_child = (self.task_details_child + 1) % count;
+        }
+    }
+
+    /// Show the selected child in the details modal
+    ///
+    /// A child among the listed tasks is shown right away and selected in the
+    /// task list. Others are left in `pending_task_jump` for
+    /// `load_pending_task_jump` to fetch from the broker.
+    pub fn jump_to_child_task(&mut self) {
+        let Some(child_id) = self
+            .selected_task_details
+            .as_ref()
+            .and_then(|task| task.children.get(self.task_details_child))
+            .cloned()
+        else {
+            return;
+        };
+
+        match self
+            .tasks
+            .iter()
+            .chain(&self.archived_tasks)
+            .find(|t| t.id == child_id)
+            .cloned()
+        {
+            Some(child) => {
+                if let Some(index) = self
+                    .get_filtered_tasks()
+                    .iter()
+                    .position(|t| t.id == child_id)
+                {
+                    self.selected_task = index;
+                }
+                self.open_task_details(child);
+            }
+            None => self.pending_task_jump = Some(child_id),
+        }
+    }
+
+    /// Open the peek modal for the selected queue, its messages are fetched
+    /// by `load_queue_peek`
+    pub fn show_queue_peek(&mut self) {
+        if self.selected_tab == Tab::Queues && self.require_capability(BrokerOperation::Peek) {
+            if let Some(queue) = self.get_sorted_queues().get(self.selected_queue) {
+                self.peeked_queue = Some(queue.name.clone());
+                self.queue_peek_messages.clear();
+                self.show_queue_peek = true;
+            }
+        }
+    }
+
+    pub fn hide_queue_peek(&mut self) {
+        self.show_queue_peek = false;
+        self.peeked_queue = None;
+        self.queue_peek_messages.clear();
+    }
+
+    /// Scroll the task details by `delta` lines, keeping at least the last of
+    /// `line_count` lines in view
+    pub fn scroll_task_details(&mut self, delta: i32, line_count: usize) {
+        let max_scroll = line_count.saturating_sub(1).min(u16::MAX as usize) as i32;
+        self.task_details_scroll =
+            (self.task_details_scroll as i32 + delta).clamp(0, max_scroll) as u16;
+    }
+
+    /// Identify the selected items so the selection can follow them across data changes
+    pub(crate) fn selection_keys(&self) -> SelectionKeys {
+        let tasks = self.get_filtered_tasks();
+        SelectionKeys {
+            worker: self
+                .get_filtered_workers()
+                .get(self.selected_worker)
+                .map(|w| w.hostname.clone()),
+            task: tasks.get(self.selected_task).map(|t| t.id.clone()),
+            task_neighbors: nearby_indices(self.selected_task, tasks.len())
+                .map(|index| tasks[index].id.clone())
+                .collect(),
+            queue: self
+                .get_sorted_queues()
+                .get(self.selected_queue)
+                .map(|q| q.name.clone()),
+            scheduled: self
+                .scheduled
+                .get(self.selected_scheduled)
+                .map(|e| e.name.clone()),
+        }
+    }
+
+    /// Move selections to where the previously selected items ended up
+    ///
+    /// A task that disappeared hands the selection to its nearest former
+    /// neighbour still listed. Other lists clamp the indices of items that
+    /// disappeared.
+    pub(crate) fn restore_selections(&mut self, keys: SelectionKeys) {
+        if let Some(index) = keys.worker.and_then(|hostname| {
+            self.get_filtered_workers()
+                .iter()
+                .position(|w| w.hostname == hostname)
+        }) {
+            self.selected_worker = index;
+        }
+        let tasks = self.get_filtered_tasks();
+        if let Some(index) = keys
+            .task
+            .iter()
+            .chain(&keys.task_neighbors)
+            .find_map(|id| tasks.iter().position(|t| &t.id == id))
+        {
+            self.selected_task = index;
+        }
+        if let Some(index) = keys
+            .queue
+            .and_then(|name| self.get_sorted_queues().iter().position(|q| q.name == name))
+        {
+            self.selected_queue = index;
+        }
+        if let Some(index) = keys
+            .scheduled
+            .and_then(|name| self.scheduled.iter().position(|e| e.name == name))
+        {
+            self.selected_scheduled = index;
+        }
+        self.validate_selections();
     }
 
     // Data validation after refresh
     pub fn validate_selections(&mut self) {
         // Ensure selection indices are valid
-        if self.selected_worker >= self.workers.len() && !self.workers.is_empty() {
-            self.selected_worker = self.workers.len() - 1;
+        let worker_count = self.get_filtered_workers().len();
+        if self.selected_worker >= worker_count && worker_count > 0 {
+            self.selected_worker = worker_count - 1;
         }
-        if self.selected_task >= self.tasks.len() && !self.tasks.is_empty() {
-            self.selected_task = self.tasks.len() - 1;
+        let task_count = self.get_filtered_tasks().len();
+        if self.selected_task >= task_count && task_count > 0 {
+            self.selected_task = task_count - 1;
         }
         if self.selected_queue >= self.queues.len() && !self.queues.is_empty() {
             self.selected_queue = self.queues.len() - 1;
         }
+        if self.selected_scheduled >= self.scheduled.len() && !self.scheduled.is_empty() {
+            self.selected_scheduled = self.scheduled.len() - 1;
+        }
+    }
+}
+
+/// How many tasks on each side of the selected one are remembered, to fall
+/// back on when it disappears
+const SELECTION_NEIGHBORS: usize = 10;
+
+/// Indices around `index` in a list of `count` items, nearest first and the
+/// following item before the preceding one at the same distance
+fn nearby_indices(index: usize, count: usize) -> impl Iterator<Item = usize> {
+    (1..=SELECTION_NEIGHBORS)
+        .flat_map(move |distance| [index.checked_add(distance), index.checked_sub(distance)])
+        .flatten()
+        .filter(move |&neighbor| neighbor < count)
+}
+
+/// Keys of the items selected in each tab
+pub(crate) struct SelectionKeys {
+    worker: Option<String>,
+    task: Option<String>,
+    /// Ids of the tasks listed around the selected one, nearest first
+    task_neighbors: Vec<String>,
+    queue: Option<String>,
+    scheduled: Option<String>,
+}
+
+/// Merge `updates` into `items` by key
+///
+/// Items with a matching key are replaced in place and new items are appended,
+/// so the order of unchanged items is stable. Items absent from `updates` are
+/// kept when `retain_missing` is set and dropped otherwise.
+fn merge_by_key<T, K, F>(items: &mut Vec<T>, updates: Vec<T>, key: F, retain_missing: bool)
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let mut positions: HashMap<K, usize> = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| (key(item), index))
+        .collect();
+    let mut updated = HashSet::new();
+
+    for update in updates {
+        match positions.get(&key(&update)) {
+            Some(&index) => {
+                items[index] = update;
+                updated.insert(index);
+            }
+            None => {
+                positions.insert(key(&update), items.len());
+                updated.insert(items.len());
+                items.push(update);
+            }
+        }
+    }
+
+    if !retain_missing {
+        let mut index = 0;
+        items.retain(|_| {
+            let keep = updated.contains(&index);
+            index += 1;
+            keep
+        });
     }
 }
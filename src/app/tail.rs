@@ -0,0 +1,38 @@
+use crate::models::Task;
+use std::collections::HashSet;
+
+/// Task ids remembered before ids of tasks no longer listed are dropped
+const MAX_SEEN_TASK_IDS: usize = 100_000;
+
+/// Tracks which tasks `lazycelery tail` has already printed, so each refresh
+/// only emits the tasks that appeared since the previous one
+#[derive(Debug, Default)]
+pub struct TaskTail {
+    seen: HashSet<String>,
+}
+
+impl TaskTail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tasks of a fetched listing whose ids haven't been seen before, in
+    /// listing order
+    ///
+    /// Once more than `MAX_SEEN_TASK_IDS` ids are remembered, ids missing from
+    /// the listing are forgotten and such tasks would be emitted again if
+    /// they reappear.
+    pub fn new_tasks<'a>(&mut self, tasks: &'a [Task]) -> Vec<&'a Task> {
+        let fresh: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| self.seen.insert(task.id.clone()))
+            .collect();
+
+        if self.seen.len() > MAX_SEEN_TASK_IDS {
+            let listed: HashSet<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+            self.seen.retain(|id| listed.contains(id.as_str()));
+        }
+
+        fresh
+    }
+}
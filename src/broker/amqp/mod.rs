@@ -20,9 +20,9 @@ use serde_json::Value;
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
-use crate::broker::Broker;
+use crate::broker::{Broker, BrokerCapabilities};
 use crate::error::BrokerError;
-use crate::models::{Queue, Task, TaskStatus, Worker, WorkerStatus};
+use crate::models::{canonical_node, Queue, Task, TaskStatus, Worker, WorkerStatus};
 
 /// Celery event types we care about
 #[derive(Debug, Clone)]
@@ -121,7 +121,7 @@ impl CeleryEvent {
 
     /// Convert to Worker model
     fn to_worker(&self) -> Option<Worker> {
-        let hostname = self.hostname.clone()?;
+        let hostname = canonical_node(self.hostname.as_deref()?);
         let status = match self.event_type {
             CeleryEventType::WorkerOnline => WorkerStatus::Online,
             CeleryEventType::WorkerOffline => WorkerStatus::Offline,
@@ -132,8 +132,10 @@ impl CeleryEvent {
             hostname,
             status,
             concurrency: 1,
+            pool_type: None,
             queues: vec![],
             active_tasks: vec![],
+            reserved_tasks: vec![],
             processed: 0,
             failed: 0,
         })
@@ -166,7 +168,23 @@ impl CeleryEvent {
             timestamp: DateTime::from_timestamp_millis(self.timestamp as i64)
                 .unwrap_or_else(Utc::now),
             result: self.result.clone(),
+            result_kind: None,
+            looks_like_error: false,
             traceback: self.traceback.clone(),
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: self.retries.unwrap_or(0),
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
         })
     }
 }
@@ -472,6 +490,8 @@ impl Broker for AmqpBroker {
                         name,
                         length: declaration.message_count() as u64,
                         consumers: declaration.consumer_count(),
+                        oldest_message_age: None,
+                        discrepancy: None,
                     });
                 }
                 Err(e) => {
@@ -547,6 +567,20 @@ impl Broker for AmqpBroker {
         Ok(())
     }
 
+    fn capabilities(&self) -> BrokerCapabilities {
+        // There is no way to list or move queued messages, read the beat
+        // schedule, send control commands or publish replayed tasks over
+        // AMQP yet
+        BrokerCapabilities {
+            scheduled: false,
+            peek: false,
+            reroute: false,
+            rate_limit: false,
+            replay: false,
+            ..BrokerCapabilities::ALL
+        }
+    }
+
     async fn purge_queue(&self, queue_name: &str) -> Result<u64, BrokerError> {
         // Purge a queue by redeclaring it with purge option
         let queue = self
@@ -0,0 +1,396 @@
+//! Demo broker serving synthetic data
+//!
+//! Used by `--demo` for screenshots, demos and UI work without a running
+//! broker. Every queue listing, which each refresh makes, advances a small
+//! simulation: tasks are published, picked up by workers and finish, and queue
+//! backlogs drift up and down.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::broker::{validate_rate_limit, Broker};
+use crate::error::BrokerError;
+use crate::models::{Queue, ScheduledEntry, Task, TaskStatus, Worker, WorkerStatus};
+
+/// Queues of the demo cluster
+const QUEUES: [&str; 4] = ["celery", "default", "emails", "reports"];
+
+/// Task names published by the simulation, with the queue each goes to
+const TASK_NAMES: [(&str, &str); 6] = [
+    ("shop.orders.process_order", "celery"),
+    ("shop.orders.send_receipt", "emails"),
+    ("shop.inventory.sync_stock", "default"),
+    ("shop.reports.build_daily_report", "reports"),
+    ("shop.emails.send_newsletter", "emails"),
+    ("shop.payments.capture_payment", "celery"),
+];
+
+/// Tasks kept by the simulation, the oldest finished ones are dropped first
+const MAX_DEMO_TASKS: usize = 60;
+
+/// Broker of a made-up cluster whose data changes a little on every refresh
+pub struct DemoBroker {
+    state: Mutex<DemoState>,
+}
+
+struct DemoState {
+    rng: u64,
+    next_task: u64,
+    workers: Vec<Worker>,
+    tasks: Vec<Task>,
+    /// Messages waiting in each queue beyond the listed pending tasks
+    backlog: [u64; QUEUES.len()],
+}
+
+impl Default for DemoBroker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoBroker {
+    pub fn new() -> Self {
+        let workers = [
+            (
+                "demo-web-1",
+                WorkerStatus::Online,
+                &["celery", "default"][..],
+            ),
+            (
+                "demo-web-2",
+                WorkerStatus::Online,
+                &["celery", "emails"][..],
+            ),
+            ("demo-batch-1", WorkerStatus::Online, &["reports"][..]),
+            ("demo-batch-2", WorkerStatus::Offline, &["reports"][..]),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, (hostname, status, queues))| Worker {
+            hostname: hostname.to_string(),
+            status,
+            concurrency: 4,
+            pool_type: Some("prefork".to_string()),
+            queues: queues.iter().map(|q| q.to_string()).collect(),
+            active_tasks: Vec::new(),
+            reserved_tasks: Vec::new(),
+            processed: 1_000 + 250 * i as u64,
+            failed: 3 * i as u64,
+        })
+        .collect();
+
+        let mut state = DemoState {
+            rng: 0x5eed_cafe,
+            next_task: 1,
+            workers,
+            tasks: Vec::new(),
+            backlog: [12, 3, 7, 1],
+        };
+        // Start with some history rather than an empty cluster
+        for _ in 0..8 {
+            state.step();
+        }
+
+        Self {
+            state: Mutex::new(state),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, DemoState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl DemoState {
+    /// Next pseudo-random number below `bound`, deterministic so demos repeat
+    fn random(&mut self, bound: u64) -> u64 {
+        self.rng = self
+            .rng
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.rng >> 33) % bound.max(1)
+    }
+
+    /// Advance the simulation by one refresh
+    fn step(&mut self) {
+        // Running tasks finish, a few of them with an error
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].status != TaskStatus::Active || self.random(3) == 0 {
+                continue;
+            }
+            let failed = self.random(10) == 0;
+            let task = &mut self.tasks[i];
+            task.timestamp = Utc::now();
+            if failed {
+                task.status = TaskStatus::Failure;
+                task.result = Some("ConnectionError('upstream timed out')".to_string());
+                task.traceback = Some(
+                    "Traceback (most recent call last):\n  File \"shop/tasks.py\", line 42, in run\nConnectionError: upstream timed out"
+                        .to_string(),
+                );
+            } else {
+                task.status = TaskStatus::Success;
+                task.result = Some(format!("{{\"ok\": true, \"items\": {}}}", i % 7 + 1));
+            }
+            let (id, worker) = (task.id.clone(), task.worker.clone());
+            if let Some(worker) = self
+                .workers
+                .iter_mut()
+                .find(|w| Some(&w.hostname) == worker.as_ref())
+            {
+                worker.active_tasks.retain(|active| *active != id);
+                if failed {
+                    worker.failed += 1;
+                } else {
+                    worker.processed += 1;
+                }
+            }
+        }
+
+        // Idle online workers pick up pending tasks from their queues
+        for i in 0..self.tasks.len() {
+            if self.tasks[i].status != TaskStatus::Pending || self.random(2) == 0 {
+                continue;
+            }
+            let queue = self.tasks[i].queue.clone();
+            let Some(worker) = self.workers.iter_mut().find(|w| {
+                w.status == WorkerStatus::Online
+                    && (w.active_tasks.len() as u32) < w.concurrency
+                    && queue.as_ref().is_some_and(|q| w.queues.contains(q))
+            }) else {
+                continue;
+            };
+            let task = &mut self.tasks[i];
+            task.status = TaskStatus::Active;
+            task.worker = Some(worker.hostname.clone());
+            task.timestamp = Utc::now();
+            worker.active_tasks.push(task.id.clone());
+        }
+
+        // New tasks are published
+        for _ in 0..=self.random(3) {
+            let (name, queue) = TASK_NAMES[self.random(TASK_NAMES.len() as u64) as usize];
+            let mut task = Task::new(format!("demo-{:06}", self.next_task), name.to_string());
+            task.args = format!("[{}]", 1_000 + self.next_task);
+            task.queue = Some(queue.to_string());
+            self.next_task += 1;
+            self.tasks.push(task);
+        }
+
+        // Queue backlogs drift up and down
+        for i in 0..QUEUES.len() {
+            let delta = self.random(5) as i64 - 2;
+            self.backlog[i] = self.backlog[i].saturating_add_signed(delta);
+        }
+
+        // Forget the oldest finished tasks
+        while self.tasks.len() > MAX_DEMO_TASKS {
+            let Some(oldest) = self
+                .tasks
+                .iter()
+                .position(|t| !matches!(t.status, TaskStatus::Pending | TaskStatus::Active))
+            else {
+                break;
+            };
+            self.tasks.remove(oldest);
+        }
+    }
+
+    fn pending_in<'a>(&'a self, queue: &'a str) -> impl Iterator<Item = &'a Task> + 'a {
+        self.tasks
+            .iter()
+            .filter(move |t| t.status == TaskStatus::Pending && t.queue.as_deref() == Some(queue))
+    }
+
+    fn queues(&self) -> Vec<Queue> {
+        QUEUES
+            .iter()
+            .zip(self.backlog)
+            .map(|(name, backlog)| {
+                let length = self.pending_in(name).count() as u64 + backlog;
+                Queue {
+                    name: name.to_string(),
+                    length,
+                    consumers: self
+                        .workers
+                        .iter()
+                        .filter(|w| {
+                            w.status == WorkerStatus::Online && w.queues.iter().any(|q| q == name)
+                        })
+                        .count() as u32,
+                    oldest_message_age: (length > 0)
+                        .then(|| Duration::from_secs(5 * length + backlog)),
+                    discrepancy: None,
+                }
+            })
+            .collect()
+    }
+
+    /// Take `task_id` off the active tasks of every worker
+    fn release(&mut self, task_id: &str) {
+        for worker in &mut self.workers {
+            worker.active_tasks.retain(|active| active != task_id);
+        }
+    }
+
+    fn task_mut(&mut self, task_id: &str) -> Result<&mut Task, BrokerError> {
+        self.tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| BrokerError::OperationError(format!("Task {task_id} not found")))
+    }
+}
+
+#[async_trait]
+impl Broker for DemoBroker {
+    async fn connect(_url: &str) -> Result<Self, BrokerError> {
+        Ok(Self::new())
+    }
+
+    async fn get_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        Ok(self.lock().workers.clone())
+    }
+
+    async fn get_tasks(&self) -> Result<Vec<Task>, BrokerError> {
+        let mut tasks = self.lock().tasks.clone();
+        tasks.reverse();
+        Ok(tasks)
+    }
+
+    async fn get_queues(&self) -> Result<Vec<Queue>, BrokerError> {
+        let mut state = self.lock();
+        state.step();
+        Ok(state.queues())
+    }
+
+    async fn get_scheduled(&self) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        let now = Utc::now();
+        Ok(vec![
+            ScheduledEntry {
+                name: "sync-stock".to_string(),
+                task: "shop.inventory.sync_stock".to_string(),
+                schedule: "every 5m".to_string(),
+                next_run: Some(now + chrono::Duration::seconds(170)),
+            },
+            ScheduledEntry {
+                name: "daily-report".to_string(),
+                task: "shop.reports.build_daily_report".to_string(),
+                schedule: "crontab 0 6 * * *".to_string(),
+                next_run: Some(now + chrono::Duration::hours(9)),
+            },
+        ])
+    }
+
+    async fn ping(&self) -> Result<Duration, BrokerError> {
+        let millis = 2 + self.lock().random(6);
+        Ok(Duration::from_millis(millis))
+    }
+
+    async fn retry_task(&self, task_id: &str) -> Result<(), BrokerError> {
+        let mut state = self.lock();
+        let task = state.task_mut(task_id)?;
+        task.status = TaskStatus::Pending;
+        task.retries += 1;
+        task.worker = None;
+        task.result = None;
+        task.traceback = None;
+        state.release(task_id);
+        Ok(())
+    }
+
+    async fn revoke_task(&self, task_id: &str) -> Result<(), BrokerError> {
+        let mut state = self.lock();
+        state.task_mut(task_id)?.status = TaskStatus::Revoked;
+        state.release(task_id);
+        Ok(())
+    }
+
+    async fn purge_queue(&self, queue_name: &str) -> Result<u64, BrokerError> {
+        let mut state = self.lock();
+        let index = QUEUES
+            .iter()
+            .position(|q| *q == queue_name)
+            .ok_or_else(|| BrokerError::OperationError(format!("Unknown queue {queue_name}")))?;
+        let purged = state.pending_in(queue_name).count() as u64 + state.backlog[index];
+        state.tasks.retain(|t| {
+            !(t.status == TaskStatus::Pending && t.queue.as_deref() == Some(queue_name))
+        });
+        state.backlog[index] = 0;
+        Ok(purged)
+    }
+
+    async fn get_task(&self, task_id: &str) -> Result<Option<Task>, BrokerError> {
+        Ok(self.lock().tasks.iter().find(|t| t.id == task_id).cloned())
+    }
+
+    async fn peek_queue(&self, queue: &str, n: usize) -> Result<Vec<Task>, BrokerError> {
+        Ok(self.lock().pending_in(queue).take(n).cloned().collect())
+    }
+
+    async fn move_task(
+        &self,
+        task_id: &str,
+        from_queue: &str,
+        to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        let mut state = self.lock();
+        let task = state.task_mut(task_id)?;
+        if task.status != TaskStatus::Pending || task.queue.as_deref() != Some(from_queue) {
+            return Err(BrokerError::OperationError(format!(
+                "Task {task_id} is not waiting in queue '{from_queue}'"
+            )));
+        }
+        task.queue = Some(to_queue.to_string());
+        Ok(())
+    }
+
+    async fn set_rate_limit(&self, task_name: &str, rate: &str) -> Result<(), BrokerError> {
+        validate_rate_limit(rate)?;
+        let state = self.lock();
+        if !state.tasks.iter().any(|t| t.name == task_name) {
+            return Err(BrokerError::OperationError(format!(
+                "No worker knows task '{task_name}'"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn replay_task(
+        &self,
+        task_name: &str,
+        queue: &str,
+        args: &str,
+        kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        let mut state = self.lock();
+        let id = format!("demo-{:06}", state.next_task);
+        state.next_task += 1;
+        let mut task = Task::new(id.clone(), task_name.to_string());
+        task.args = args.to_string();
+        task.kwargs = kwargs.to_string();
+        task.queue = Some(queue.to_string());
+        state.tasks.push(task);
+        Ok(id)
+    }
+
+    async fn clear_completed_results(&self, older_than: Duration) -> Result<u64, BrokerError> {
+        let cutoff = Utc::now() - chrono::Duration::from_std(older_than).unwrap_or_default();
+        let mut state = self.lock();
+        let before = state.tasks.len();
+        state.tasks.retain(|t| {
+            !(matches!(t.status, TaskStatus::Success | TaskStatus::Failure) && t.timestamp < cutoff)
+        });
+        Ok((before - state.tasks.len()) as u64)
+    }
+
+    async fn get_raw_task_metadata(&self, task_id: &str) -> Result<Option<String>, BrokerError> {
+        let state = self.lock();
+        let task = state.tasks.iter().find(|t| t.id == task_id);
+        task.map(serde_json::to_string_pretty)
+            .transpose()
+            .map_err(|e| BrokerError::OperationError(e.to_string()))
+    }
+}
@@ -0,0 +1,61 @@
+//! Placeholder broker used until the first connection succeeds
+//!
+//! Lets the TUI start when the broker can't be reached, showing setup advice
+//! until a reconnect replaces it with a real broker.
+
+use async_trait::async_trait;
+
+use crate::broker::{Broker, BrokerCapabilities};
+use crate::error::BrokerError;
+use crate::models::{Queue, Task, Worker};
+
+/// Broker that fails every call with a connection error
+pub struct DisconnectedBroker;
+
+fn not_connected() -> BrokerError {
+    BrokerError::ConnectionError("not connected to a broker".to_string())
+}
+
+#[async_trait]
+impl Broker for DisconnectedBroker {
+    async fn connect(_url: &str) -> Result<Self, BrokerError> {
+        Ok(Self)
+    }
+
+    async fn get_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        Err(not_connected())
+    }
+
+    async fn get_tasks(&self) -> Result<Vec<Task>, BrokerError> {
+        Err(not_connected())
+    }
+
+    async fn get_queues(&self) -> Result<Vec<Queue>, BrokerError> {
+        Err(not_connected())
+    }
+
+    async fn retry_task(&self, _task_id: &str) -> Result<(), BrokerError> {
+        Err(not_connected())
+    }
+
+    async fn revoke_task(&self, _task_id: &str) -> Result<(), BrokerError> {
+        Err(not_connected())
+    }
+
+    async fn purge_queue(&self, _queue_name: &str) -> Result<u64, BrokerError> {
+        Err(not_connected())
+    }
+
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities {
+            retry: false,
+            revoke: false,
+            purge: false,
+            scheduled: false,
+            peek: false,
+            reroute: false,
+            rate_limit: false,
+            replay: false,
+        }
+    }
+}
@@ -1,11 +1,233 @@
 pub mod amqp;
+pub mod demo;
+pub mod disconnected;
+pub mod discovery;
+pub mod registry;
 pub mod result_backend;
+pub mod tunnel;
 
 pub mod redis;
 
+use self::redis::pool::PoolStats;
+use crate::config::{BrokerConfig, Config, ParserLimits};
 use crate::error::BrokerError;
-use crate::models::{Queue, Task, Worker};
+use crate::models::{Queue, ScheduledEntry, Task, Worker};
+use crate::utils::clock::{system_clock, Clock};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default maximum stored size of task results and tracebacks, in bytes
+pub const DEFAULT_MAX_RESULT_LENGTH: usize = 16 * 1024;
+
+/// Default maximum stored size of task args and kwargs, in bytes
+pub const DEFAULT_MAX_ARGS_LENGTH: usize = 4 * 1024;
+
+/// Default time allowed for a single broker call during a refresh, in milliseconds
+pub const DEFAULT_OPERATION_TIMEOUT_MS: u64 = 5_000;
+
+/// Default number of connections in each Redis connection pool
+pub const DEFAULT_POOL_SIZE: usize = 10;
+
+/// Default time to wait for workers to answer inspect requests, in
+/// milliseconds. Inspecting is off unless configured, it broadcasts to every
+/// worker on each refresh.
+pub const DEFAULT_INSPECT_TIMEOUT_MS: u64 = 0;
+
+/// Tunables applied by broker implementations while parsing Celery data
+#[derive(Debug, Clone)]
+pub struct BrokerOptions {
+    /// Maximum stored size of task results and tracebacks in bytes, 0 disables truncation
+    pub max_result_length: usize,
+    /// Maximum stored size of task args and kwargs in bytes, 0 disables truncation
+    pub max_args_length: usize,
+    /// Where task metadata is stored when it differs from the broker URL
+    pub result_backend_url: Option<String>,
+    /// Caps on how much data a single refresh reads
+    pub limits: ParserLimits,
+    /// Connections in each connection pool
+    pub pool_size: usize,
+    /// How long to wait for workers to answer inspect requests, zero skips them
+    pub inspect_timeout: Duration,
+    /// Time used for messages without a timestamp and for message ages
+    pub clock: Arc<dyn Clock>,
+    /// Store queue lengths in the broker on each refresh
+    pub queue_history: bool,
+    /// Only discover queues whose names match this glob, e.g. `tenantA.*`
+    pub queue_glob: Option<String>,
+    /// Follow the Celery event stream for task states and worker heartbeats
+    pub events: bool,
+    /// How long a revoke stays in effect, zero keeps revokes forever
+    pub revoke_ttl: Duration,
+    /// Never write to the broker while reading it, which skips inspect
+    /// requests and queue history
+    pub read_only: bool,
+}
+
+impl Default for BrokerOptions {
+    fn default() -> Self {
+        Self {
+            max_result_length: DEFAULT_MAX_RESULT_LENGTH,
+            max_args_length: DEFAULT_MAX_ARGS_LENGTH,
+            result_backend_url: None,
+            limits: ParserLimits::default(),
+            pool_size: DEFAULT_POOL_SIZE,
+            inspect_timeout: Duration::from_millis(DEFAULT_INSPECT_TIMEOUT_MS),
+            clock: system_clock(),
+            queue_history: false,
+            queue_glob: None,
+            events: false,
+            revoke_ttl: Duration::ZERO,
+            read_only: false,
+        }
+    }
+}
+
+impl From<&BrokerConfig> for BrokerOptions {
+    fn from(config: &BrokerConfig) -> Self {
+        Self {
+            max_result_length: config.max_result_length,
+            max_args_length: config.max_args_length,
+            result_backend_url: None,
+            limits: config.limits,
+            pool_size: config.pool_size,
+            inspect_timeout: Duration::from_millis(config.inspect_timeout),
+            clock: system_clock(),
+            queue_history: config.queue_history,
+            queue_glob: (!config.queue_glob.is_empty()).then(|| config.queue_glob.clone()),
+            events: config.events,
+            revoke_ttl: Duration::from_secs(config.revoke_ttl),
+            read_only: false,
+        }
+    }
+}
+
+/// A parser cap that was hit: `found` items existed but only `shown` were read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanCap {
+    pub shown: usize,
+    pub found: usize,
+}
+
+impl ScanCap {
+    /// The cap hit when reading at most `limit` of `found` items, if any
+    pub fn check(found: usize, limit: usize) -> Option<Self> {
+        (found > limit).then_some(Self {
+            shown: limit,
+            found,
+        })
+    }
+
+    /// Add up the caps of several reads feeding the same list
+    pub fn merge(a: Option<Self>, b: Option<Self>) -> Option<Self> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(Self {
+                shown: a.shown + b.shown,
+                found: a.found + b.found,
+            }),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Caps hit by the latest full fetch of each collection, and what it had to
+/// skip as unreadable
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanCaps {
+    pub tasks: Option<ScanCap>,
+    pub workers: Option<ScanCap>,
+    /// Task metadata values and messages that couldn't be parsed
+    pub unreadable_tasks: usize,
+}
+
+/// Operations that a broker may not support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrokerOperation {
+    Retry,
+    Revoke,
+    Purge,
+    Scheduled,
+    Peek,
+    Reroute,
+    RateLimit,
+    Replay,
+}
+
+impl BrokerOperation {
+    /// Name used in status messages
+    pub fn label(self) -> &'static str {
+        match self {
+            BrokerOperation::Retry => "Retrying tasks",
+            BrokerOperation::Revoke => "Revoking tasks",
+            BrokerOperation::Purge => "Purging queues",
+            BrokerOperation::Scheduled => "Reading the beat schedule",
+            BrokerOperation::Peek => "Peeking at queues",
+            BrokerOperation::Reroute => "Moving tasks between queues",
+            BrokerOperation::RateLimit => "Setting task rate limits",
+            BrokerOperation::Replay => "Replaying tasks",
+        }
+    }
+}
+
+/// Which optional operations a broker supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BrokerCapabilities {
+    pub retry: bool,
+    pub revoke: bool,
+    pub purge: bool,
+    pub scheduled: bool,
+    pub peek: bool,
+    pub reroute: bool,
+    pub rate_limit: bool,
+    pub replay: bool,
+}
+
+impl BrokerCapabilities {
+    /// Every operation supported
+    pub const ALL: Self = Self {
+        retry: true,
+        revoke: true,
+        purge: true,
+        scheduled: true,
+        peek: true,
+        reroute: true,
+        rate_limit: true,
+        replay: true,
+    };
+
+    pub fn supports(&self, operation: BrokerOperation) -> bool {
+        match operation {
+            BrokerOperation::Retry => self.retry,
+            BrokerOperation::Revoke => self.revoke,
+            BrokerOperation::Purge => self.purge,
+            BrokerOperation::Scheduled => self.scheduled,
+            BrokerOperation::Peek => self.peek,
+            BrokerOperation::Reroute => self.reroute,
+            BrokerOperation::RateLimit => self.rate_limit,
+            BrokerOperation::Replay => self.replay,
+        }
+    }
+}
+
+impl Default for BrokerCapabilities {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl From<&Config> for BrokerOptions {
+    fn from(config: &Config) -> Self {
+        Self {
+            result_backend_url: config
+                .result_backend
+                .as_ref()
+                .map(|backend| backend.url.clone()),
+            read_only: config.ui.read_only,
+            ..Self::from(&config.broker)
+        }
+    }
+}
 
 #[async_trait]
 #[allow(dead_code)]
@@ -20,15 +242,224 @@ pub trait Broker: Send + Sync {
     async fn retry_task(&self, task_id: &str) -> Result<(), BrokerError>;
     async fn revoke_task(&self, task_id: &str) -> Result<(), BrokerError>;
     async fn purge_queue(&self, queue_name: &str) -> Result<u64, BrokerError>;
+
+    /// Fetch a single task by id
+    ///
+    /// Brokers without a direct lookup fall back to the full task scan.
+    async fn get_task(&self, task_id: &str) -> Result<Option<Task>, BrokerError> {
+        let tasks = self.get_tasks().await?;
+        Ok(tasks.into_iter().find(|task| task.id == task_id))
+    }
+
+    /// Fetch a cheap window of at most `limit` recently seen tasks
+    ///
+    /// Used between full refreshes. Brokers without a cheaper source fall back
+    /// to the full task scan.
+    async fn get_recent_tasks(&self, limit: usize) -> Result<Vec<Task>, BrokerError> {
+        let mut tasks = self.get_tasks().await?;
+        tasks.truncate(limit);
+        Ok(tasks)
+    }
+
+    /// Fetch the upcoming periodic tasks of the celery beat schedule
+    ///
+    /// Brokers that can't see the schedule report none.
+    async fn get_scheduled(&self) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        Ok(Vec::new())
+    }
+
+    /// Measure the broker round-trip latency
+    ///
+    /// Brokers without a dedicated ping command time a queue listing instead.
+    async fn ping(&self) -> Result<Duration, BrokerError> {
+        let start = Instant::now();
+        self.get_queues().await?;
+        Ok(start.elapsed())
+    }
+
+    /// Optional operations this broker supports, the UI disables the others
+    fn capabilities(&self) -> BrokerCapabilities {
+        BrokerCapabilities::ALL
+    }
+
+    /// Caps hit by the latest `get_tasks` and `get_workers` calls
+    fn scan_caps(&self) -> ScanCaps {
+        ScanCaps::default()
+    }
+
+    /// Size of a connection pool that had every connection in use since the
+    /// previous call, for brokers that pool connections
+    fn pool_saturation(&self) -> Option<usize> {
+        None
+    }
+
+    /// Version of the broker server, when it reported one on connect
+    fn server_version(&self) -> Option<String> {
+        None
+    }
+
+    /// Current usage of the connection pool, for brokers that pool connections
+    async fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+
+    /// Delete stored results of tasks that finished more than `older_than` ago,
+    /// returning how many were removed
+    async fn clear_completed_results(&self, _older_than: Duration) -> Result<u64, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Publish a new message for `task_name` on `queue` with the given JSON
+    /// `args` and `kwargs`, returning the id of the new task
+    async fn replay_task(
+        &self,
+        _task_name: &str,
+        _queue: &str,
+        _args: &str,
+        _kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Fetch the raw, untruncated metadata stored for a task
+    async fn get_raw_task_metadata(&self, _task_id: &str) -> Result<Option<String>, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Read up to `n` messages waiting in `queue` without consuming them,
+    /// as Pending tasks
+    async fn peek_queue(&self, _queue: &str, _n: usize) -> Result<Vec<Task>, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Lengths of `queue` sampled on refreshes since `since`, oldest first
+    ///
+    /// Only brokers that keep queue history, with `queue_history` enabled,
+    /// have samples to return.
+    async fn get_queue_history(
+        &self,
+        _queue: &str,
+        _since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Move the waiting message of `task_id` from `from_queue` to `to_queue`
+    async fn move_task(
+        &self,
+        _task_id: &str,
+        _from_queue: &str,
+        _to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Ask the workers themselves for their active and reserved tasks, pool
+    /// size and task totals, over Celery's remote control
+    ///
+    /// Only workers that answer in time are listed. Fails with
+    /// `BrokerError::Timeout` when none do.
+    async fn inspect_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+
+    /// Ask every worker to run at most `rate` of `task_name`, e.g. "10/s" or
+    /// "100/m", over Celery's remote control
+    ///
+    /// A rate of "0" lifts the limit. Workers don't confirm the change.
+    async fn set_rate_limit(&self, _task_name: &str, _rate: &str) -> Result<(), BrokerError> {
+        Err(BrokerError::NotImplemented)
+    }
+}
+
+/// Check a Celery rate limit: a number of tasks, optionally followed by `/s`,
+/// `/m` or `/h`, per second when the unit is left out
+pub fn validate_rate_limit(rate: &str) -> Result<(), BrokerError> {
+    let invalid = || {
+        BrokerError::ValidationError(format!(
+            "Invalid rate limit '{rate}', expected e.g. 10/s, 100/m or 1000/h"
+        ))
+    };
+    let (count, unit) = rate.split_once('/').unwrap_or((rate, "s"));
+    if !matches!(unit, "s" | "m" | "h") {
+        return Err(invalid());
+    }
+    // Digits with at most one decimal point, which rules out signs, "inf"
+    // and exponents that Celery's float parsing would otherwise accept
+    let mut parts = count.splitn(2, '.');
+    let whole = parts.next().unwrap_or_default();
+    let fraction = parts.next().unwrap_or_default();
+    let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Opens broker connections, used to rebuild the broker after the connection is lost
+#[async_trait]
+pub trait BrokerConnector: Send + Sync {
+    async fn connect(&self, url: &str) -> Result<Box<dyn Broker>, BrokerError>;
+}
+
+/// Connects through `create_broker_with_options`
+pub struct DefaultBrokerConnector {
+    options: BrokerOptions,
+}
+
+impl DefaultBrokerConnector {
+    pub fn new(options: BrokerOptions) -> Self {
+        Self { options }
+    }
+}
+
+#[async_trait]
+impl BrokerConnector for DefaultBrokerConnector {
+    async fn connect(&self, url: &str) -> Result<Box<dyn Broker>, BrokerError> {
+        create_broker_with_options(url, self.options.clone()).await
+    }
 }
 
 /// Create a broker based on the URL scheme
 pub async fn create_broker(url: &str) -> Result<Box<dyn Broker>, BrokerError> {
-    if url.starts_with("redis://") {
-        Ok(Box::new(redis::RedisBroker::connect(url).await?))
-    } else if url.starts_with("amqp://") || url.starts_with("rabbitmq://") {
-        Ok(Box::new(amqp::AmqpBroker::connect(url).await?))
-    } else {
-        Err(BrokerError::InvalidUrl(url.to_string()))
+    create_broker_with_options(url, BrokerOptions::default()).await
+}
+
+/// Create a broker based on the URL scheme, applying the given parsing options
+///
+/// The implementation is picked from `BrokerRegistry::builtin`.
+pub async fn create_broker_with_options(
+    url: &str,
+    options: BrokerOptions,
+) -> Result<Box<dyn Broker>, BrokerError> {
+    registry::BrokerRegistry::builtin()
+        .connect(url, options)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limits_in_celery_format_are_accepted() {
+        for rate in ["10/s", "100/m", "1000/h", "5", "0", "0.5/s", "12.25/m"] {
+            assert!(validate_rate_limit(rate).is_ok(), "{rate}");
+        }
+    }
+
+    #[test]
+    fn test_malformed_rate_limits_are_rejected() {
+        for rate in [
+            "", "/s", "10/", "10/d", "10/sec", "ten/s", "-1/s", "+1/s", "1e3/s", "inf", "1.2.3/s",
+            ".5/s", "10 /s", "10/s/m",
+        ] {
+            let error = validate_rate_limit(rate).unwrap_err();
+            assert!(error.is_user_error(), "{rate}");
+            assert_eq!(
+                error.to_string(),
+                format!("Validation error: Invalid rate limit '{rate}', expected e.g. 10/s, 100/m or 1000/h")
+            );
+        }
     }
 }
@@ -1,45 +1,146 @@
 use crate::broker::redis::operations::TaskOperations;
-use crate::broker::redis::pool::ConnectionPool;
-use crate::broker::redis::protocol::ProtocolParser;
+use crate::broker::redis::pool::{ConnectionLease, ConnectionPool, PoolStats};
+use crate::broker::redis::protocol::{EventMonitor, ProtocolParser, QueueParser, WorkerParser};
+use crate::broker::{validate_rate_limit, BrokerOptions, ScanCaps};
 use crate::error::BrokerError;
-use crate::models::{Queue, Task, Worker};
-use std::sync::Arc;
+use crate::models::{Queue, ScheduledEntry, Task, Worker};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, instrument, warn};
 
 /// BrokerFacade provides a clean, high-level interface for Redis broker operations.
 /// It encapsulates connection management, error handling, and operation complexity.
 pub struct BrokerFacade {
     pool: Arc<ConnectionPool>,
+    /// Pool holding task metadata, shares `pool` unless a result backend URL is set
+    backend: Arc<ConnectionPool>,
+    options: BrokerOptions,
+    /// Database the pools select, kombu prefixes broadcast channels with it
+    db: i64,
+    /// Caps hit by the latest worker and task scans
+    scan_caps: Mutex<ScanCaps>,
+    /// Version reported by `INFO server` on connect, unknown when INFO is refused
+    server_version: Option<String>,
+    /// Celery events followed in the background, when enabled
+    events: Option<EventMonitor>,
+    /// Nodes that answered the latest inspect, the next one stops waiting once
+    /// as many have answered
+    inspected_nodes: AtomicUsize,
+    /// Workers consuming each queue according to the latest inspect, empty
+    /// when inspect is disabled or failed
+    queue_consumers: Mutex<HashMap<String, u32>>,
 }
 
 impl BrokerFacade {
-    pub async fn new(url: &str) -> Result<Self, BrokerError> {
+    pub async fn new(url: &str, options: BrokerOptions) -> Result<Self, BrokerError> {
         info!(
             "Creating new Redis broker facade for URL: {}",
             url.split('@').next_back().unwrap_or("hidden")
         );
 
-        let pool = ConnectionPool::new(url, Some(10)).await.map_err(|e| {
-            error!("Failed to create connection pool: {}", e);
-            e
-        })?;
+        let db = super::database_index(url)?;
+        let pool = ConnectionPool::new(url, Some(options.pool_size))
+            .await
+            .map_err(|e| {
+                error!("Failed to create connection pool: {}", e);
+                e
+            })?;
+
+        let pool = Arc::new(pool);
+        let backend = match options.result_backend_url.as_deref() {
+            Some(backend_url) if backend_url != url => {
+                info!(
+                    "Using separate result backend at: {}",
+                    backend_url.split('@').next_back().unwrap_or("hidden")
+                );
+                let backend = ConnectionPool::new(backend_url, Some(options.pool_size))
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to create result backend connection pool: {}", e);
+                        e
+                    })?;
+                Arc::new(backend)
+            }
+            _ => Arc::clone(&pool),
+        };
+
+        let server_version = Self::read_server_version(&pool).await;
+        let events = options.events.then(|| EventMonitor::spawn(url, db));
 
         info!("Redis broker facade created successfully");
 
         Ok(Self {
-            pool: Arc::new(pool),
+            pool,
+            backend,
+            options,
+            db,
+            scan_caps: Mutex::new(ScanCaps::default()),
+            server_version,
+            events,
+            inspected_nodes: AtomicUsize::new(0),
+            queue_consumers: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Ask the server for its version
+    ///
+    /// Servers whose ACLs deny INFO still work for everything else, so a
+    /// refused or unreadable reply leaves the version unknown instead of
+    /// failing the connection.
+    async fn read_server_version(pool: &ConnectionPool) -> Option<String> {
+        let mut conn = pool.get_connection().await.ok()?;
+        match redis::cmd("INFO")
+            .arg("server")
+            .query_async::<_, String>(&mut *conn)
+            .await
+        {
+            Ok(info) => {
+                let version = super::parse_server_version(&info);
+                debug!("Redis server version: {:?}", version);
+                version
+            }
+            Err(e) => {
+                warn!("Could not read the Redis server version: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Version of the Redis server, if it could be read on connect
+    pub fn server_version(&self) -> Option<&str> {
+        self.server_version.as_deref()
+    }
+
     /// Get all workers with comprehensive error handling and logging
+    ///
+    /// Workers that answer an inspect request are listed with what they
+    /// report, the rest as found in task metadata. With events enabled,
+    /// heartbeats decide which workers are online.
     #[instrument(skip(self), name = "get_workers")]
     pub async fn get_workers(&self) -> Result<Vec<Worker>, BrokerError> {
         debug!("Fetching workers from Redis");
 
-        let connection = self.get_pooled_connection("get_workers").await?;
+        let (parsed, inspected) = tokio::join!(
+            ProtocolParser::parse_workers(&self.pool, &self.backend, &self.options),
+            self.try_inspect_workers()
+        );
 
-        match ProtocolParser::parse_workers(&connection).await {
-            Ok(workers) => {
+        match parsed {
+            Ok(parsed) => {
+                if parsed.malformed > 0 {
+                    warn!("Skipped {} unreadable task results", parsed.malformed);
+                }
+                self.lock_scan_caps().workers = parsed.capped;
+                let mut workers = match inspected {
+                    Some(inspected) => WorkerParser::merge_inspected(inspected, parsed.items),
+                    None => parsed.items,
+                };
+                if let Some(events) = &self.events {
+                    events.overlay_workers(&mut workers, self.options.clock.now());
+                }
                 info!("Successfully retrieved {} workers", workers.len());
                 debug!(
                     "Workers: {:?}",
@@ -54,15 +155,78 @@ impl BrokerFacade {
         }
     }
 
+    /// Ask the workers for their state over the Celery remote control
+    #[instrument(skip(self), name = "inspect_workers")]
+    pub async fn inspect_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        debug!("Inspecting workers on database {}", self.db);
+
+        let connection = self.get_pooled_connection("inspect_workers").await?;
+        let expected_nodes = self.inspected_nodes.load(Ordering::Relaxed);
+
+        let now = self.options.clock.now();
+        match ProtocolParser::inspect_workers(
+            &connection,
+            self.db,
+            &self.options,
+            expected_nodes,
+            now,
+        )
+        .await
+        {
+            Ok((workers, nodes)) => {
+                self.inspected_nodes.store(nodes, Ordering::Relaxed);
+                *self.lock_queue_consumers() = QueueParser::consumer_counts(&workers);
+                Ok(workers)
+            }
+            Err(e) => {
+                debug!("Worker inspect failed: {}", e);
+                self.inspected_nodes.store(0, Ordering::Relaxed);
+                self.lock_queue_consumers().clear();
+                Err(self.add_operation_context(e, "inspect_workers"))
+            }
+        }
+    }
+
+    /// Inspected workers for `get_workers`, or `None` to fall back to task
+    /// metadata alone when inspect is disabled or nobody answered
+    ///
+    /// Inspect publishes to the broker, so it is skipped in read-only mode.
+    async fn try_inspect_workers(&self) -> Option<Vec<Worker>> {
+        if self.options.inspect_timeout.is_zero() || self.options.read_only {
+            return None;
+        }
+        self.inspect_workers().await.ok()
+    }
+
     /// Get all tasks with comprehensive error handling and logging
+    ///
+    /// Pending and running tasks whose revoke is in effect are listed as
+    /// revoked when revokes expire. With events enabled, task states reported
+    /// by events take precedence.
     #[instrument(skip(self), name = "get_tasks")]
     pub async fn get_tasks(&self) -> Result<Vec<Task>, BrokerError> {
         debug!("Fetching tasks from Redis");
 
-        let connection = self.get_pooled_connection("get_tasks").await?;
-
-        match ProtocolParser::parse_tasks(&connection).await {
-            Ok(tasks) => {
+        match ProtocolParser::parse_tasks(&self.pool, &self.backend, &self.options).await {
+            Ok(parsed) => {
+                if let Some(cap) = parsed.capped {
+                    warn!("Task scan capped at {} of {} items", cap.shown, cap.found);
+                }
+                if parsed.malformed > 0 {
+                    warn!("Skipped {} unreadable tasks", parsed.malformed);
+                }
+                {
+                    let mut scan_caps = self.lock_scan_caps();
+                    scan_caps.tasks = parsed.capped;
+                    scan_caps.unreadable_tasks = parsed.malformed;
+                }
+                let mut tasks = parsed.items;
+                if !self.options.revoke_ttl.is_zero() {
+                    self.apply_active_revokes(&mut tasks).await;
+                }
+                if let Some(events) = &self.events {
+                    events.overlay_tasks(&mut tasks, &self.options);
+                }
                 info!("Successfully retrieved {} tasks", tasks.len());
                 debug!(
                     "Task statuses: {:?}",
@@ -77,6 +241,78 @@ impl BrokerFacade {
         }
     }
 
+    /// Mark the tasks whose revoke hasn't expired yet as revoked
+    ///
+    /// Revokes only add to what the scan found, so failing to read them is
+    /// logged rather than failing the refresh.
+    async fn apply_active_revokes(&self, tasks: &mut [Task]) {
+        let revoked = match self.get_pooled_connection("active_revokes").await {
+            Ok(connection) => {
+                TaskOperations::active_revokes(&connection, self.options.clock.now()).await
+            }
+            Err(e) => Err(e),
+        };
+        match revoked {
+            Ok(revoked) => TaskOperations::apply_revokes(tasks, &revoked),
+            Err(e) => warn!("Failed to read active revokes: {}", e),
+        }
+    }
+
+    /// Get a single task from its stored metadata, without scanning other keys
+    #[instrument(skip(self), fields(task_id = %task_id), name = "get_task")]
+    pub async fn get_task(&self, task_id: &str) -> Result<Option<Task>, BrokerError> {
+        debug!("Fetching task {} from Redis", task_id);
+
+        let connection = self.get_backend_connection("get_task").await?;
+
+        let data = TaskOperations::get_raw_task_metadata(&connection, task_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch task {}: {}", task_id, e);
+                self.add_operation_context(e, "get_task")
+            })?;
+
+        data.map(|data| ProtocolParser::parse_task(task_id, &data, &self.options))
+            .transpose()
+            .map_err(|e| self.add_operation_context(e, "get_task"))
+    }
+
+    /// Get the pending tasks waiting in the queues, without scanning task metadata
+    #[instrument(skip(self), name = "get_recent_tasks")]
+    pub async fn get_recent_tasks(&self, limit: usize) -> Result<Vec<Task>, BrokerError> {
+        debug!("Fetching up to {} recent tasks from Redis", limit);
+
+        match ProtocolParser::parse_recent_tasks(&self.pool, &self.options, limit).await {
+            Ok(tasks) => {
+                debug!("Retrieved {} recent tasks", tasks.len());
+                Ok(tasks)
+            }
+            Err(e) => {
+                error!("Failed to parse recent tasks: {}", e);
+                Err(self.add_operation_context(e, "get_recent_tasks"))
+            }
+        }
+    }
+
+    /// Get the celery beat schedule kept by the redisbeat scheduler
+    #[instrument(skip(self), name = "get_scheduled")]
+    pub async fn get_scheduled(&self) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        debug!("Fetching beat schedule from Redis");
+
+        let connection = self.get_pooled_connection("get_scheduled").await?;
+
+        match ProtocolParser::parse_schedule(&connection).await {
+            Ok(entries) => {
+                info!("Successfully retrieved {} scheduled entries", entries.len());
+                Ok(entries)
+            }
+            Err(e) => {
+                error!("Failed to parse beat schedule: {}", e);
+                Err(self.add_operation_context(e, "get_scheduled"))
+            }
+        }
+    }
+
     /// Get all queues with comprehensive error handling and logging
     #[instrument(skip(self), name = "get_queues")]
     pub async fn get_queues(&self) -> Result<Vec<Queue>, BrokerError> {
@@ -84,13 +320,24 @@ impl BrokerFacade {
 
         let connection = self.get_pooled_connection("get_queues").await?;
 
-        match ProtocolParser::parse_queues(&connection).await {
+        let now = self.options.clock.now();
+        let glob = self.options.queue_glob.as_deref();
+        let consumers = self.lock_queue_consumers().clone();
+        match ProtocolParser::parse_queues(&connection, now, glob, &consumers).await {
             Ok(queues) => {
                 info!("Successfully retrieved {} queues", queues.len());
                 debug!(
                     "Queue names: {:?}",
                     queues.iter().map(|q| &q.name).collect::<Vec<_>>()
                 );
+                if self.options.queue_history && !self.options.read_only {
+                    // History is a convenience, a failed write must not hide the queues
+                    if let Err(e) =
+                        ProtocolParser::record_queue_history(&connection, &queues, now).await
+                    {
+                        warn!("Failed to record queue history: {}", e);
+                    }
+                }
                 Ok(queues)
             }
             Err(e) => {
@@ -100,6 +347,29 @@ impl BrokerFacade {
         }
     }
 
+    /// Length samples of a queue recorded since `since`, oldest first
+    #[instrument(skip(self), fields(queue_name = %queue_name), name = "get_queue_history")]
+    pub async fn get_queue_history(
+        &self,
+        queue_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        debug!("Reading history of queue: {}", queue_name);
+
+        let connection = self.get_pooled_connection("get_queue_history").await?;
+
+        match TaskOperations::get_queue_history(&connection, queue_name, since).await {
+            Ok(samples) => {
+                debug!("Read {} samples of queue: {}", samples.len(), queue_name);
+                Ok(samples)
+            }
+            Err(e) => {
+                error!("Failed to read history of queue {}: {}", queue_name, e);
+                Err(self.add_operation_context(e, "get_queue_history"))
+            }
+        }
+    }
+
     /// Retry a task with validation and comprehensive error handling
     #[instrument(skip(self), fields(task_id = %task_id), name = "retry_task")]
     pub async fn retry_task(&self, task_id: &str) -> Result<(), BrokerError> {
@@ -112,7 +382,7 @@ impl BrokerFacade {
             ));
         }
 
-        let connection = self.get_pooled_connection("retry_task").await?;
+        let connection = self.get_backend_connection("retry_task").await?;
 
         match TaskOperations::retry_task(&connection, task_id).await {
             Ok(()) => {
@@ -127,6 +397,9 @@ impl BrokerFacade {
     }
 
     /// Revoke a task with validation and comprehensive error handling
+    ///
+    /// Expiring revokes leave the task's metadata alone, so the task is listed
+    /// as revoked only while its revoke is in effect.
     #[instrument(skip(self), fields(task_id = %task_id), name = "revoke_task")]
     pub async fn revoke_task(&self, task_id: &str) -> Result<(), BrokerError> {
         info!("Revoking task: {}", task_id);
@@ -138,9 +411,25 @@ impl BrokerFacade {
             ));
         }
 
-        let connection = self.get_pooled_connection("revoke_task").await?;
+        // The backend may share the broker's pool, so the broker lease is
+        // given back before the metadata is updated
+        let ttl = self.options.revoke_ttl;
+        let revoked = {
+            let connection = self.get_pooled_connection("revoke_task").await?;
+            let now = self.options.clock.now();
+            TaskOperations::revoke_task(&connection, task_id, ttl, now).await
+        };
+        // An expiring revoke must not outlive its TTL in the stored status
+        let result = match revoked {
+            Ok(()) if !ttl.is_zero() => Ok(()),
+            Ok(()) => {
+                let backend = self.get_backend_connection("revoke_task").await?;
+                TaskOperations::mark_task_revoked(&backend, task_id).await
+            }
+            Err(e) => Err(e),
+        };
 
-        match TaskOperations::revoke_task(&connection, task_id).await {
+        match result {
             Ok(()) => {
                 info!("Successfully revoked task: {}", task_id);
                 Ok(())
@@ -181,12 +470,169 @@ impl BrokerFacade {
         }
     }
 
-    /// Perform health check on the connection pool
+    /// Read the next messages of a queue without consuming them
+    #[instrument(skip(self), fields(queue_name = %queue_name), name = "peek_queue")]
+    pub async fn peek_queue(&self, queue_name: &str, n: usize) -> Result<Vec<Task>, BrokerError> {
+        debug!("Peeking {} messages from queue: {}", n, queue_name);
+
+        let connection = self.get_pooled_connection("peek_queue").await?;
+
+        let now = self.options.clock.now();
+        match TaskOperations::peek_queue(&connection, queue_name, n, now).await {
+            Ok(tasks) => {
+                debug!("Peeked {} messages from queue: {}", tasks.len(), queue_name);
+                Ok(tasks)
+            }
+            Err(e) => {
+                error!("Failed to peek queue {}: {}", queue_name, e);
+                Err(self.add_operation_context(e, "peek_queue"))
+            }
+        }
+    }
+
+    /// Move a waiting task message to another queue
+    #[instrument(skip(self), fields(task_id = %task_id, from = %from_queue, to = %to_queue), name = "move_task")]
+    pub async fn move_task(
+        &self,
+        task_id: &str,
+        from_queue: &str,
+        to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        info!(
+            "Moving task {} from queue {} to queue {}",
+            task_id, from_queue, to_queue
+        );
+
+        let connection = self.get_pooled_connection("move_task").await?;
+
+        match TaskOperations::move_task(&connection, task_id, from_queue, to_queue).await {
+            Ok(()) => {
+                info!("Moved task {} to queue {}", task_id, to_queue);
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to move task {}: {}", task_id, e);
+                Err(self.add_operation_context(e, "move_task"))
+            }
+        }
+    }
+
+    /// Broadcast a rate limit for `task_name` to every worker
+    #[instrument(skip(self), fields(task_name = %task_name, rate = %rate), name = "set_rate_limit")]
+    pub async fn set_rate_limit(&self, task_name: &str, rate: &str) -> Result<(), BrokerError> {
+        validate_rate_limit(rate)?;
+        if task_name.trim().is_empty() {
+            return Err(BrokerError::ValidationError(
+                "Task name cannot be empty".to_string(),
+            ));
+        }
+        info!("Setting rate limit of {} to {}", task_name, rate);
+
+        let connection = self.get_pooled_connection("set_rate_limit").await?;
+
+        ProtocolParser::set_rate_limit(&connection, self.db, task_name, rate)
+            .await
+            .map_err(|e| {
+                error!("Failed to set rate limit of {}: {}", task_name, e);
+                self.add_operation_context(e, "set_rate_limit")
+            })
+    }
+
+    /// Delete finished task results older than `older_than` from the result backend
+    #[instrument(skip(self), name = "clear_completed_results")]
+    pub async fn clear_completed_results(&self, older_than: Duration) -> Result<u64, BrokerError> {
+        info!("Clearing finished task results older than {:?}", older_than);
+
+        let connection = self
+            .get_backend_connection("clear_completed_results")
+            .await?;
+
+        let now = self.options.clock.now();
+        match TaskOperations::clear_completed_results(&connection, older_than, now).await {
+            Ok(cleared) => {
+                info!("Cleared {} finished task results", cleared);
+                Ok(cleared)
+            }
+            Err(e) => {
+                error!("Failed to clear finished task results: {}", e);
+                Err(self.add_operation_context(e, "clear_completed_results"))
+            }
+        }
+    }
+
+    /// Publish a fresh copy of a task with new arguments
+    #[instrument(skip(self, args, kwargs), fields(task_name = %task_name, queue = %queue), name = "replay_task")]
+    pub async fn replay_task(
+        &self,
+        task_name: &str,
+        queue: &str,
+        args: &str,
+        kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        info!("Replaying task {} on queue {}", task_name, queue);
+
+        let connection = self.get_pooled_connection("replay_task").await?;
+
+        match TaskOperations::replay_task(&connection, task_name, queue, args, kwargs).await {
+            Ok(task_id) => {
+                info!("Published replay of {} as task {}", task_name, task_id);
+                Ok(task_id)
+            }
+            Err(e) => {
+                error!("Failed to replay task {}: {}", task_name, e);
+                Err(self.add_operation_context(e, "replay_task"))
+            }
+        }
+    }
+
+    /// Fetch the raw stored metadata for a task without any truncation
+    #[instrument(skip(self), fields(task_id = %task_id), name = "get_raw_task_metadata")]
+    pub async fn get_raw_task_metadata(
+        &self,
+        task_id: &str,
+    ) -> Result<Option<String>, BrokerError> {
+        debug!("Fetching raw metadata for task: {}", task_id);
+
+        let connection = self.get_backend_connection("get_raw_task_metadata").await?;
+
+        TaskOperations::get_raw_task_metadata(&connection, task_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch metadata for task {}: {}", task_id, e);
+                self.add_operation_context(e, "get_raw_task_metadata")
+            })
+    }
+
+    /// Time a PING round trip on a pooled connection
+    #[instrument(skip(self), name = "ping")]
+    pub async fn ping(&self) -> Result<Duration, BrokerError> {
+        let mut connection = self.get_pooled_connection("ping").await?;
+
+        let start = Instant::now();
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut connection)
+            .await
+            .map_err(|e| {
+                warn!("PING failed: {}", e);
+                self.add_operation_context(BrokerError::ConnectionError(e.to_string()), "ping")
+            })?;
+        let latency = start.elapsed();
+
+        debug!("PING round trip took {:?}", latency);
+        Ok(latency)
+    }
+
+    /// Perform health check on the connection pools
     #[instrument(skip(self), name = "health_check")]
     pub async fn health_check(&self) -> Result<(), BrokerError> {
         debug!("Performing health check on connection pool");
 
-        match self.pool.health_check().await {
+        let mut result = self.pool.health_check().await;
+        if result.is_ok() && !Arc::ptr_eq(&self.pool, &self.backend) {
+            result = self.backend.health_check().await;
+        }
+
+        match result {
             Ok(()) => {
                 debug!("Health check passed");
                 Ok(())
@@ -198,23 +644,31 @@ impl BrokerFacade {
         }
     }
 
-    /// Get statistics about the connection pool
+    /// Get statistics about the broker connection pool since the previous call
     #[allow(dead_code)]
     pub async fn get_pool_stats(&self) -> PoolStats {
-        // This is a simplified implementation - in a real scenario,
-        // we'd track more detailed statistics
-        PoolStats {
-            active_connections: 1,  // Simplified
-            total_connections: 1,   // Simplified
-            healthy_connections: 1, // Simplified
+        self.pool.stats().await
+    }
+
+    /// Usage of the broker connection pool, leaving saturation tracking alone
+    pub async fn pool_snapshot(&self) -> PoolStats {
+        self.pool.snapshot().await
+    }
+
+    /// Size of the pool that ran out of connections since the previous call
+    ///
+    /// A separate result backend pool counts too, as task parsing draws
+    /// connections from both.
+    pub fn take_pool_saturation(&self) -> Option<usize> {
+        let mut saturated = self.pool.take_saturation().then_some(self.pool.max_size());
+        if !Arc::ptr_eq(&self.pool, &self.backend) && self.backend.take_saturation() {
+            saturated = saturated.or(Some(self.backend.max_size()));
         }
+        saturated
     }
 
     /// Internal method to get a connection from the pool with context
-    async fn get_pooled_connection(
-        &self,
-        operation: &str,
-    ) -> Result<redis::aio::MultiplexedConnection, BrokerError> {
+    async fn get_pooled_connection(&self, operation: &str) -> Result<ConnectionLease, BrokerError> {
         debug!("Getting pooled connection for operation: {}", operation);
 
         self.pool.get_connection().await.map_err(|e| {
@@ -223,6 +677,41 @@ impl BrokerFacade {
         })
     }
 
+    /// Caps hit by the latest `get_tasks` and `get_workers` calls
+    pub fn scan_caps(&self) -> ScanCaps {
+        *self.lock_scan_caps()
+    }
+
+    fn lock_scan_caps(&self) -> std::sync::MutexGuard<'_, ScanCaps> {
+        // The caps are plain data, a panic elsewhere can't leave them inconsistent
+        self.scan_caps
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn lock_queue_consumers(&self) -> std::sync::MutexGuard<'_, HashMap<String, u32>> {
+        // Plain counts, replaced wholesale by each inspect
+        self.queue_consumers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Internal method to get a result backend connection with context
+    async fn get_backend_connection(
+        &self,
+        operation: &str,
+    ) -> Result<ConnectionLease, BrokerError> {
+        debug!(
+            "Getting result backend connection for operation: {}",
+            operation
+        );
+
+        self.backend.get_connection().await.map_err(|e| {
+            error!("Failed to get backend connection for {}: {}", operation, e);
+            self.add_operation_context(e, operation)
+        })
+    }
+
     /// Add contextual information to errors for better debugging
     fn add_operation_context(&self, error: BrokerError, operation: &str) -> BrokerError {
         match error {
@@ -237,15 +726,6 @@ impl BrokerFacade {
     }
 }
 
-/// Statistics about the connection pool
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-pub struct PoolStats {
-    pub active_connections: usize,
-    pub total_connections: usize,
-    pub healthy_connections: usize,
-}
-
 impl Drop for BrokerFacade {
     fn drop(&mut self) {
         debug!("BrokerFacade being dropped");
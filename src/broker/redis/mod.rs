@@ -3,10 +3,14 @@ pub mod operations;
 pub mod pool;
 pub mod protocol;
 
-use crate::broker::Broker;
+use crate::broker::{Broker, BrokerOptions, ScanCaps};
 use crate::error::BrokerError;
-use crate::models::{Queue, Task, Worker};
+use crate::models::{Queue, ScheduledEntry, Task, Worker};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use pool::PoolStats;
+use redis::IntoConnectionInfo;
+use std::time::Duration;
 use tracing::{debug, info};
 
 // Re-export for backward compatibility
@@ -17,24 +21,54 @@ pub struct RedisBroker {
     facade: BrokerFacade,
 }
 
-#[async_trait]
-impl Broker for RedisBroker {
-    async fn connect(url: &str) -> Result<Self, BrokerError> {
+impl RedisBroker {
+    /// Connect to Redis, applying the given parsing options
+    pub async fn connect_with_options(
+        url: &str,
+        options: BrokerOptions,
+    ) -> Result<Self, BrokerError> {
         info!("Connecting to Redis broker using facade pattern");
         debug!(
             "Redis URL: {}",
             url.split('@').next_back().unwrap_or("hidden")
         );
 
-        let facade = BrokerFacade::new(url).await?;
+        let db = database_index(url)?;
+        let facade = BrokerFacade::new(url, options).await?;
 
         // Perform initial health check
         facade.health_check().await?;
 
-        info!("Redis broker connected successfully");
+        info!("Redis broker connected successfully to database {db}");
 
         Ok(Self { facade })
     }
+}
+
+/// Database index a Redis URL selects, from its path like `redis://host:6379/5`
+///
+/// Every connection of the broker selects this database, so queues, workers
+/// and task results of other databases are never seen. URLs without a path
+/// use database 0.
+pub fn database_index(url: &str) -> Result<i64, BrokerError> {
+    url.into_connection_info()
+        .map(|info| info.redis.db)
+        .map_err(|e| BrokerError::InvalidUrl(format!("Invalid Redis URL: {e}")))
+}
+
+/// `redis_version` from the reply to `INFO server`, if present
+pub fn parse_server_version(info: &str) -> Option<String> {
+    info.lines()
+        .find_map(|line| line.trim().strip_prefix("redis_version:"))
+        .map(str::to_string)
+        .filter(|version| !version.is_empty())
+}
+
+#[async_trait]
+impl Broker for RedisBroker {
+    async fn connect(url: &str) -> Result<Self, BrokerError> {
+        Self::connect_with_options(url, BrokerOptions::default()).await
+    }
 
     async fn get_workers(&self) -> Result<Vec<Worker>, BrokerError> {
         self.facade.get_workers().await
@@ -44,10 +78,26 @@ impl Broker for RedisBroker {
         self.facade.get_tasks().await
     }
 
+    async fn inspect_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        self.facade.inspect_workers().await
+    }
+
+    async fn get_task(&self, task_id: &str) -> Result<Option<Task>, BrokerError> {
+        self.facade.get_task(task_id).await
+    }
+
+    async fn get_recent_tasks(&self, limit: usize) -> Result<Vec<Task>, BrokerError> {
+        self.facade.get_recent_tasks(limit).await
+    }
+
     async fn get_queues(&self) -> Result<Vec<Queue>, BrokerError> {
         self.facade.get_queues().await
     }
 
+    async fn get_scheduled(&self) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        self.facade.get_scheduled().await
+    }
+
     async fn retry_task(&self, task_id: &str) -> Result<(), BrokerError> {
         self.facade.retry_task(task_id).await
     }
@@ -59,4 +109,69 @@ impl Broker for RedisBroker {
     async fn purge_queue(&self, queue_name: &str) -> Result<u64, BrokerError> {
         self.facade.purge_queue(queue_name).await
     }
+
+    fn scan_caps(&self) -> ScanCaps {
+        self.facade.scan_caps()
+    }
+
+    fn pool_saturation(&self) -> Option<usize> {
+        self.facade.take_pool_saturation()
+    }
+
+    fn server_version(&self) -> Option<String> {
+        self.facade.server_version().map(str::to_string)
+    }
+
+    async fn pool_stats(&self) -> Option<PoolStats> {
+        Some(self.facade.pool_snapshot().await)
+    }
+
+    async fn clear_completed_results(&self, older_than: Duration) -> Result<u64, BrokerError> {
+        self.facade.clear_completed_results(older_than).await
+    }
+
+    async fn replay_task(
+        &self,
+        task_name: &str,
+        queue: &str,
+        args: &str,
+        kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        self.facade
+            .replay_task(task_name, queue, args, kwargs)
+            .await
+    }
+
+    async fn ping(&self) -> Result<Duration, BrokerError> {
+        self.facade.ping().await
+    }
+
+    async fn get_raw_task_metadata(&self, task_id: &str) -> Result<Option<String>, BrokerError> {
+        self.facade.get_raw_task_metadata(task_id).await
+    }
+
+    async fn peek_queue(&self, queue: &str, n: usize) -> Result<Vec<Task>, BrokerError> {
+        self.facade.peek_queue(queue, n).await
+    }
+
+    async fn get_queue_history(
+        &self,
+        queue: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        self.facade.get_queue_history(queue, since).await
+    }
+
+    async fn move_task(
+        &self,
+        task_id: &str,
+        from_queue: &str,
+        to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        self.facade.move_task(task_id, from_queue, to_queue).await
+    }
+
+    async fn set_rate_limit(&self, task_name: &str, rate: &str) -> Result<(), BrokerError> {
+        self.facade.set_rate_limit(task_name, rate).await
+    }
 }
@@ -1,7 +1,56 @@
+use crate::broker::redis::protocol::{ProtocolParser, QueueKind, QueueStore};
 use crate::error::BrokerError;
+use crate::models::{Task, TaskStatus};
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Celery states after which a task's result is never updated again
+const TERMINAL_STATES: [&str; 3] = ["SUCCESS", "FAILURE", "REVOKED"];
+
+/// Set of revoked task ids
+const REVOKED_SET: &str = "revoked";
+
+/// Sorted set of the task ids with expiring revokes, scored by the Unix
+/// timestamp each revoke expires at
+const EXPIRING_REVOKES: &str = "lazycelery:revokes";
+
+/// Drop the revokes that expired by `ARGV[2]` from the revoked set, then
+/// revoke `ARGV[1]` until `ARGV[3]`, or for good when it is empty
+const REVOKE_SCRIPT: &str = r"
+local expired = redis.call('ZRANGEBYSCORE', KEYS[2], '-inf', ARGV[2])
+for _, id in ipairs(expired) do
+    redis.call('SREM', KEYS[1], id)
+end
+redis.call('ZREMRANGEBYSCORE', KEYS[2], '-inf', ARGV[2])
+redis.call('SADD', KEYS[1], ARGV[1])
+if ARGV[3] == '' then
+    redis.call('ZREM', KEYS[2], ARGV[1])
+else
+    redis.call('ZADD', KEYS[2], ARGV[3], ARGV[1])
+end
+return 1
+";
+
+/// How many result keys are read with one MGET, or deleted with one DEL, when
+/// clearing completed results
+const CLEAR_BATCH_SIZE: usize = 500;
+
+/// Take a message off one list and push its rerouted copy onto another in a
+/// single step, so a failure in between can't lose it. Returns 0 without
+/// pushing when the message is no longer in the source list.
+const MOVE_MESSAGE_SCRIPT: &str = r"
+if redis.call('LREM', KEYS[1], 1, ARGV[1]) == 0 then
+    return 0
+end
+redis.call('LPUSH', KEYS[2], ARGV[2])
+return 1
+";
 
 /// Input validation utilities for Redis operations
 mod validation {
@@ -217,37 +266,387 @@ impl TaskOperations {
         Ok(())
     }
 
+    /// Revoke a task on the broker, leaving its metadata to `mark_task_revoked`
+    ///
+    /// The task is added to the `revoked` set. With a non-zero `ttl` the
+    /// revoke expires after `ttl`, like Celery workers forget revokes after a
+    /// while: the task is also added to a sorted set scored by the time it
+    /// expires as seen from `now`, and revokes that are over are taken out of
+    /// both sets.
     pub async fn revoke_task(
         connection: &MultiplexedConnection,
         task_id: &str,
+        ttl: Duration,
+        now: DateTime<Utc>,
     ) -> Result<(), BrokerError> {
         // Validate input
         validation::validate_task_id(task_id)?;
 
         let mut conn = connection.clone();
 
-        // Add task to Celery's revoked tasks set
-        let revoked_key = validation::sanitize_redis_key("revoked")?;
-        conn.sadd::<_, _, ()>(&revoked_key, task_id)
+        let revoked_key = validation::sanitize_redis_key(REVOKED_SET)?;
+        let expires_at = Self::revoke_expiry(ttl, now)
+            .map(|expires_at| expires_at.to_string())
+            .unwrap_or_default();
+        redis::Script::new(REVOKE_SCRIPT)
+            .key(&revoked_key)
+            .key(EXPIRING_REVOKES)
+            .arg(task_id)
+            .arg(now.timestamp())
+            .arg(expires_at)
+            .invoke_async::<_, ()>(&mut conn)
             .await
             .map_err(|e| BrokerError::OperationError(e.to_string()))?;
 
-        // Update task metadata if it exists
+        // Note: In a real implementation with active workers, the workers would
+        // check the revoked set and terminate any running tasks with this ID
+
+        Ok(())
+    }
+
+    /// Set the status in a task's metadata to revoked, if it has metadata
+    pub async fn mark_task_revoked(
+        backend: &MultiplexedConnection,
+        task_id: &str,
+    ) -> Result<(), BrokerError> {
+        // Validate input
+        validation::validate_task_id(task_id)?;
+
+        let mut backend_conn = backend.clone();
+
         let task_key = validation::sanitize_redis_key(&format!("celery-task-meta-{task_id}"))?;
-        if let Ok(Some(task_data)) = conn.get::<_, Option<String>>(&task_key).await {
+        if let Ok(Some(task_data)) = backend_conn.get::<_, Option<String>>(&task_key).await {
             if let Ok(mut task_json) = serde_json::from_str::<Value>(&task_data) {
                 // Update status to revoked
                 task_json["status"] = Value::String("REVOKED".to_string());
 
                 if let Ok(updated_data) = serde_json::to_string(&task_json) {
-                    let _: Result<(), _> = conn.set(&task_key, updated_data).await;
+                    let _: Result<(), _> = backend_conn.set(&task_key, updated_data).await;
                 }
             }
         }
 
-        // Note: In a real implementation with active workers, the workers would
-        // check the revoked set and terminate any running tasks with this ID
+        Ok(())
+    }
+
+    /// Unix timestamp a revoke made at `now` expires at, `None` for revokes
+    /// that never expire
+    pub fn revoke_expiry(ttl: Duration, now: DateTime<Utc>) -> Option<i64> {
+        if ttl.is_zero() {
+            return None;
+        }
+        // Expiry is kept in whole seconds, a shorter TTL still needs one
+        Some(
+            now.timestamp()
+                .saturating_add_unsigned(ttl.as_secs().max(1)),
+        )
+    }
+
+    /// Ids of the tasks whose revoke is still in effect at `now`
+    ///
+    /// Revokes that expired but weren't dropped by a later revoke yet are
+    /// left out, the listing never writes.
+    pub async fn active_revokes(
+        connection: &MultiplexedConnection,
+        now: DateTime<Utc>,
+    ) -> Result<HashSet<String>, BrokerError> {
+        let mut conn = connection.clone();
+        let (revoked, expired): (HashSet<String>, Vec<String>) = redis::pipe()
+            .smembers(REVOKED_SET)
+            .zrangebyscore(EXPIRING_REVOKES, "-inf", now.timestamp())
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| BrokerError::OperationError(format!("Failed to read revokes: {e}")))?;
+        Ok(Self::unexpired_revokes(revoked, expired))
+    }
+
+    /// `revoked` without the ids in `expired`
+    pub fn unexpired_revokes(
+        mut revoked: HashSet<String>,
+        expired: Vec<String>,
+    ) -> HashSet<String> {
+        for id in expired {
+            revoked.remove(&id);
+        }
+        revoked
+    }
+
+    /// Mark unfinished tasks among `tasks` revoked when their id is in `revoked`
+    pub fn apply_revokes(tasks: &mut [Task], revoked: &HashSet<String>) {
+        for task in tasks {
+            if !task.status.is_finished() && revoked.contains(&task.id) {
+                task.status = TaskStatus::Revoked;
+            }
+        }
+    }
+
+    pub async fn get_raw_task_metadata(
+        connection: &MultiplexedConnection,
+        task_id: &str,
+    ) -> Result<Option<String>, BrokerError> {
+        // Validate input
+        validation::validate_task_id(task_id)?;
+
+        let mut conn = connection.clone();
+
+        let task_key = validation::sanitize_redis_key(&format!("celery-task-meta-{task_id}"))?;
+        conn.get(&task_key)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))
+    }
+
+    /// Push a new Celery message for `task_name` onto `queue`, returning its id
+    ///
+    /// `args` and `kwargs` must be a JSON array and a JSON object.
+    pub async fn replay_task(
+        connection: &MultiplexedConnection,
+        task_name: &str,
+        queue: &str,
+        args: &str,
+        kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        // Validate input
+        validation::validate_queue_name(queue)?;
+        let sanitized_queue = validation::sanitize_redis_key(queue)?;
+
+        let args: Value = serde_json::from_str(args)
+            .map_err(|e| BrokerError::ValidationError(format!("Invalid args JSON: {e}")))?;
+        let kwargs: Value = serde_json::from_str(kwargs)
+            .map_err(|e| BrokerError::ValidationError(format!("Invalid kwargs JSON: {e}")))?;
+        if !args.is_array() || !kwargs.is_object() {
+            return Err(BrokerError::ValidationError(
+                "Args must be a JSON array and kwargs a JSON object".to_string(),
+            ));
+        }
+
+        let task_id = Uuid::new_v4().to_string();
+        let message = Self::build_task_message(&task_id, task_name, queue, &args, &kwargs);
+
+        let mut conn = connection.clone();
+        conn.lpush::<_, _, ()>(&sanitized_queue, message.to_string())
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+
+        Ok(task_id)
+    }
+
+    /// Build a Celery protocol v2 message as kombu stores it in a Redis queue
+    pub fn build_task_message(
+        task_id: &str,
+        task_name: &str,
+        queue: &str,
+        args: &Value,
+        kwargs: &Value,
+    ) -> Value {
+        let body = json!([
+            args,
+            kwargs,
+            {"callbacks": null, "errbacks": null, "chain": null, "chord": null}
+        ]);
+
+        json!({
+            "body": base64::engine::general_purpose::STANDARD.encode(body.to_string()),
+            "content-encoding": "utf-8",
+            "content-type": "application/json",
+            "headers": {
+                "lang": "py",
+                "task": task_name,
+                "id": task_id,
+                "root_id": task_id,
+                "parent_id": null,
+                "group": null,
+                "retries": 0,
+                "eta": null,
+                "expires": null,
+                "argsrepr": args.to_string(),
+                "kwargsrepr": kwargs.to_string(),
+                "origin": "lazycelery",
+            },
+            "properties": {
+                "correlation_id": task_id,
+                "reply_to": "",
+                "delivery_mode": 2,
+                "delivery_info": {"exchange": "", "routing_key": queue},
+                "priority": 0,
+                "body_encoding": "base64",
+                "delivery_tag": Uuid::new_v4().to_string(),
+            },
+        })
+    }
+
+    /// Delete the metadata of finished tasks whose `date_done` is more than
+    /// `older_than` before `now`, returning how many results were removed
+    ///
+    /// Results without a readable `date_done` are kept.
+    pub async fn clear_completed_results(
+        connection: &MultiplexedConnection,
+        older_than: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<u64, BrokerError> {
+        let older_than = chrono::Duration::from_std(older_than)
+            .map_err(|e| BrokerError::ValidationError(format!("Invalid age: {e}")))?;
+        let cutoff = now - older_than;
+
+        let mut conn = connection.clone();
+
+        let task_keys: Vec<String> = {
+            let mut iter = conn
+                .scan_match::<_, String>("celery-task-meta-*")
+                .await
+                .map_err(|e| {
+                    BrokerError::OperationError(format!("Failed to scan task metadata keys: {e}"))
+                })?;
+            let mut keys = Vec::new();
+            while let Some(key) = iter.next_item().await {
+                keys.push(key);
+            }
+            keys
+        };
+
+        let mut expired = Vec::new();
+        for batch in task_keys.chunks(CLEAR_BATCH_SIZE) {
+            let values: Vec<Option<String>> = redis::cmd("MGET")
+                .arg(batch)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+
+            for (key, data) in batch.iter().zip(values) {
+                if Self::is_expired_result(data.as_deref(), cutoff) {
+                    expired.push(key.clone());
+                }
+            }
+        }
+
+        let mut removed = 0;
+        for batch in expired.chunks(CLEAR_BATCH_SIZE) {
+            removed += conn
+                .del::<_, u64>(batch)
+                .await
+                .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+        }
+        Ok(removed)
+    }
+
+    /// Whether a stored result is finished with a `date_done` before `cutoff`
+    fn is_expired_result(data: Option<&str>, cutoff: DateTime<Utc>) -> bool {
+        let Some(task_json) = data.and_then(|d| serde_json::from_str::<Value>(d).ok()) else {
+            return false;
+        };
+
+        let finished = task_json
+            .get("status")
+            .and_then(|s| s.as_str())
+            .is_some_and(|status| TERMINAL_STATES.contains(&status));
+        let done_at = task_json
+            .get("date_done")
+            .and_then(|d| d.as_str())
+            .and_then(Self::parse_date_done);
+
+        finished && done_at.is_some_and(|done_at| done_at < cutoff)
+    }
+
+    /// Parse a Celery `date_done`, which older versions store without an offset
+    fn parse_date_done(value: &str) -> Option<DateTime<Utc>> {
+        value.parse::<DateTime<Utc>>().ok().or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                .ok()
+                .map(|naive| naive.and_utc())
+        })
+    }
+
+    /// Read up to `n` messages from the head of a queue without consuming them
+    pub async fn peek_queue(
+        connection: &MultiplexedConnection,
+        queue_name: &str,
+        n: usize,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Task>, BrokerError> {
+        validation::validate_queue_name(queue_name)?;
+        let sanitized_queue = validation::sanitize_redis_key(queue_name)?;
+
+        ProtocolParser::peek_queue(connection, &sanitized_queue, n, now).await
+    }
+
+    /// Read the length samples of a queue taken since `since`
+    pub async fn get_queue_history(
+        connection: &MultiplexedConnection,
+        queue_name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        validation::validate_queue_name(queue_name)?;
+        let sanitized_queue = validation::sanitize_redis_key(queue_name)?;
+
+        ProtocolParser::read_queue_history(connection, &sanitized_queue, since).await
+    }
+
+    /// Move the message of `task_id` waiting in `from_queue` to `to_queue`
+    ///
+    /// The whole source queue is scanned for the message. Its routing key is
+    /// updated to the new queue before it is pushed there.
+    pub async fn move_task(
+        connection: &MultiplexedConnection,
+        task_id: &str,
+        from_queue: &str,
+        to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        // Validate input
+        validation::validate_task_id(task_id)?;
+        validation::validate_queue_name(from_queue)?;
+        validation::validate_queue_name(to_queue)?;
+        if from_queue == to_queue {
+            return Err(BrokerError::ValidationError(format!(
+                "Task {task_id} is already in queue '{to_queue}'"
+            )));
+        }
+        let sanitized_from = validation::sanitize_redis_key(from_queue)?;
+        let sanitized_to = validation::sanitize_redis_key(to_queue)?;
+
+        let mut conn = connection.clone();
+
+        for queue in [&sanitized_from, &sanitized_to] {
+            if QueueStore::kind(&mut conn, queue).await? == QueueKind::Stream {
+                return Err(BrokerError::OperationError(format!(
+                    "Queue '{queue}' is a Redis stream, tasks can only be moved between lists"
+                )));
+            }
+        }
+
+        let messages: Vec<String> = conn
+            .lrange(&sanitized_from, 0, -1)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+        let Some((raw, mut message)) = messages.into_iter().find_map(|raw| {
+            let message: Value = serde_json::from_str(&raw).ok()?;
+            let id = message.pointer("/headers/id").and_then(|id| id.as_str());
+            (id == Some(task_id)).then_some((raw, message))
+        }) else {
+            return Err(BrokerError::OperationError(format!(
+                "Task {task_id} is not waiting in queue '{from_queue}'"
+            )));
+        };
+
+        if let Some(delivery_info) = message
+            .pointer_mut("/properties/delivery_info")
+            .and_then(|d| d.as_object_mut())
+        {
+            delivery_info.insert("routing_key".to_string(), json!(to_queue));
+        }
 
+        let moved: u64 = redis::Script::new(MOVE_MESSAGE_SCRIPT)
+            .key(&sanitized_from)
+            .key(&sanitized_to)
+            .arg(&raw)
+            .arg(message.to_string())
+            .invoke_async(&mut conn)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+        // A worker may have taken the message since it was read
+        if moved == 0 {
+            return Err(BrokerError::OperationError(format!(
+                "Task {task_id} left queue '{from_queue}' before it could be moved"
+            )));
+        }
         Ok(())
     }
 
@@ -262,13 +661,9 @@ impl TaskOperations {
         let mut conn = connection.clone();
 
         // Get current queue length for reporting
-        let queue_length: u64 = conn
-            .llen(&sanitized_queue)
-            .await
-            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+        let queue_length = QueueStore::length(&mut conn, &sanitized_queue).await?;
 
-        // Delete all messages from the queue (Redis LIST)
-        // Using DEL command to completely remove the list
+        // Delete all messages from the queue, DEL removes lists and streams alike
         let deleted: u64 = conn
             .del(&sanitized_queue)
             .await
@@ -282,3 +677,80 @@ impl TaskOperations {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task(id: &str, status: TaskStatus) -> Task {
+        let mut task = ProtocolParser::parse_task(
+            id,
+            &json!({"status": "PENDING", "result": null}).to_string(),
+            &crate::broker::BrokerOptions::default(),
+        )
+        .unwrap();
+        task.status = status;
+        task
+    }
+
+    #[test]
+    fn test_revoke_expiry_follows_the_ttl() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            TaskOperations::revoke_expiry(Duration::from_secs(3600), now),
+            Some(now.timestamp() + 3600)
+        );
+        // A revoke lasts at least a second
+        assert_eq!(
+            TaskOperations::revoke_expiry(Duration::from_millis(10), now),
+            Some(now.timestamp() + 1)
+        );
+        // Without a TTL revokes go to the `revoked` set as before
+        assert_eq!(TaskOperations::revoke_expiry(Duration::ZERO, now), None);
+    }
+
+    #[test]
+    fn test_only_old_finished_results_are_expired() {
+        let cutoff = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let result = |status: &str, date_done: &str| {
+            json!({"status": status, "date_done": date_done}).to_string()
+        };
+
+        let old = result("SUCCESS", "2024-04-01T12:00:00.000000");
+        assert!(TaskOperations::is_expired_result(Some(&old), cutoff));
+        let recent = result("FAILURE", "2024-05-01T13:00:00+00:00");
+        assert!(!TaskOperations::is_expired_result(Some(&recent), cutoff));
+        let running = result("STARTED", "2024-04-01T12:00:00+00:00");
+        assert!(!TaskOperations::is_expired_result(Some(&running), cutoff));
+        // Keys deleted between the scan and the read come back empty
+        assert!(!TaskOperations::is_expired_result(None, cutoff));
+        assert!(!TaskOperations::is_expired_result(Some("not json"), cutoff));
+    }
+
+    #[test]
+    fn test_expired_revokes_are_not_active() {
+        let revoked: HashSet<String> = ["kept", "expired"].map(String::from).into();
+
+        let active = TaskOperations::unexpired_revokes(revoked, vec!["expired".to_string()]);
+        assert_eq!(active, ["kept".to_string()].into());
+    }
+
+    #[test]
+    fn test_revokes_only_mark_unfinished_tasks() {
+        let revoked: HashSet<String> = ["pending", "done"].map(String::from).into();
+        let mut tasks = vec![
+            task("pending", TaskStatus::Pending),
+            task("done", TaskStatus::Success),
+            task("other", TaskStatus::Active),
+        ];
+
+        TaskOperations::apply_revokes(&mut tasks, &revoked);
+        let statuses: Vec<TaskStatus> = tasks.into_iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses,
+            [TaskStatus::Revoked, TaskStatus::Success, TaskStatus::Active]
+        );
+    }
+}
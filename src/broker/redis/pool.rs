@@ -1,12 +1,14 @@
+use crate::broker::DEFAULT_POOL_SIZE;
 use crate::error::BrokerError;
-use redis::aio::MultiplexedConnection;
-use redis::Client;
-use std::sync::Arc;
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::{Client, Cmd, Pipeline, RedisFuture, Value};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard};
 use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, Semaphore};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::time::sleep;
 
-const DEFAULT_POOL_SIZE: usize = 10;
 #[allow(dead_code)]
 const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
@@ -29,10 +31,6 @@ impl PooledConnection {
         }
     }
 
-    pub fn mark_used(&mut self) {
-        self.last_used = Instant::now();
-    }
-
     pub async fn health_check(&mut self) -> bool {
         // Simple ping to check if connection is alive
         let mut conn = self.connection.clone();
@@ -54,11 +52,167 @@ impl PooledConnection {
     }
 }
 
+/// Counts the connections a pool has lent out, to tell when it runs out
+#[derive(Debug, Default)]
+pub struct PoolUsage {
+    in_use: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl PoolUsage {
+    /// Count one more connection as lent out until the returned slot is dropped
+    pub fn checkout(self: &Arc<Self>) -> UsageSlot {
+        let in_use = self.in_use.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_use, Ordering::SeqCst);
+        UsageSlot {
+            usage: Arc::clone(self),
+        }
+    }
+
+    /// Connections lent out right now
+    pub fn in_use(&self) -> usize {
+        self.in_use.load(Ordering::SeqCst)
+    }
+
+    /// Most connections lent out at once since the last `take_peak`
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+
+    /// Most connections lent out at once since the last call, which starts a
+    /// new measurement from the current count
+    pub fn take_peak(&self) -> usize {
+        self.peak.swap(self.in_use(), Ordering::SeqCst)
+    }
+}
+
+/// A connection counted as in use by `PoolUsage`, released on drop
+#[derive(Debug)]
+pub struct UsageSlot {
+    usage: Arc<PoolUsage>,
+}
+
+impl Drop for UsageSlot {
+    fn drop(&mut self) {
+        self.usage.in_use.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A connection lent out by a `ConnectionPool`, counted as in use until dropped
+///
+/// The lease holds one of the pool's permits, so at most `max_size` leases
+/// exist at once. Dropping it hands the connection back for reuse, unless a
+/// command found the connection broken.
+pub struct ConnectionLease {
+    connection: MultiplexedConnection,
+    idle: Arc<StdMutex<Vec<PooledConnection>>>,
+    broken: bool,
+    _permit: OwnedSemaphorePermit,
+    _slot: UsageSlot,
+}
+
+impl ConnectionLease {
+    /// Remember errors that leave the connection unusable, so it isn't reused
+    fn check<T>(&mut self, result: &redis::RedisResult<T>) {
+        if let Err(e) = result {
+            if e.is_io_error() || e.is_connection_dropped() {
+                self.broken = true;
+            }
+        }
+    }
+}
+
+impl Drop for ConnectionLease {
+    fn drop(&mut self) {
+        if self.broken {
+            return;
+        }
+        let mut idle = lock_idle(&self.idle);
+        idle.push(PooledConnection::new(self.connection.clone()));
+    }
+}
+
+impl Deref for ConnectionLease {
+    type Target = MultiplexedConnection;
+
+    fn deref(&self) -> &Self::Target {
+        &self.connection
+    }
+}
+
+impl DerefMut for ConnectionLease {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.connection
+    }
+}
+
+impl ConnectionLike for ConnectionLease {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        Box::pin(async move {
+            let result = self.connection.req_packed_command(cmd).await;
+            self.check(&result);
+            result
+        })
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        Box::pin(async move {
+            let result = self
+                .connection
+                .req_packed_commands(cmd, offset, count)
+                .await;
+            self.check(&result);
+            result
+        })
+    }
+
+    fn get_db(&self) -> i64 {
+        self.connection.get_db()
+    }
+}
+
+/// How busy a connection pool is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Connections lent out right now
+    pub active_connections: usize,
+    /// Most connections lent out at once since the previous stats
+    pub peak_connections: usize,
+    /// Open connections kept for reuse
+    pub total_connections: usize,
+    pub healthy_connections: usize,
+    /// Connections dialed since the pool was created, reused ones counted once
+    pub opened_connections: usize,
+    pub max_size: usize,
+}
+
+impl PoolStats {
+    /// Whether every connection was in use at some point since the previous stats
+    #[allow(dead_code)]
+    pub fn is_saturated(&self) -> bool {
+        self.peak_connections >= self.max_size
+    }
+}
+
+/// The idle connections, a panic while holding the lock can't leave the list
+/// inconsistent
+fn lock_idle(idle: &StdMutex<Vec<PooledConnection>>) -> MutexGuard<'_, Vec<PooledConnection>> {
+    idle.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 pub struct ConnectionPool {
     client: Client,
-    connections: Arc<Mutex<Vec<PooledConnection>>>,
+    /// Connections not lent out, most recently returned last
+    idle: Arc<StdMutex<Vec<PooledConnection>>>,
     semaphore: Arc<Semaphore>,
     max_size: usize,
+    usage: Arc<PoolUsage>,
+    opened: AtomicUsize,
 }
 
 impl ConnectionPool {
@@ -66,19 +220,19 @@ impl ConnectionPool {
         let client = Client::open(url)
             .map_err(|e| BrokerError::InvalidUrl(format!("Invalid Redis URL: {e}")))?;
 
-        let max_size = max_size.unwrap_or(DEFAULT_POOL_SIZE);
-        let connections = Arc::new(Mutex::new(Vec::with_capacity(max_size)));
-        let semaphore = Arc::new(Semaphore::new(max_size));
-
+        let max_size = max_size.unwrap_or(DEFAULT_POOL_SIZE).max(1);
         let pool = Self {
             client,
-            connections,
-            semaphore,
+            idle: Arc::new(StdMutex::new(Vec::with_capacity(max_size))),
+            semaphore: Arc::new(Semaphore::new(max_size)),
             max_size,
+            usage: Arc::new(PoolUsage::default()),
+            opened: AtomicUsize::new(0),
         };
 
-        // Pre-populate pool with one connection to test connectivity
-        pool.create_connection().await?;
+        // Open the first connection up front to test connectivity, and keep it
+        let first = pool.create_connection().await?;
+        lock_idle(&pool.idle).push(first);
 
         Ok(pool)
     }
@@ -92,65 +246,47 @@ impl ConnectionPool {
                 BrokerError::ConnectionError(format!("Failed to create connection: {e}"))
             })?;
 
+        self.opened.fetch_add(1, Ordering::SeqCst);
         Ok(PooledConnection::new(connection))
     }
 
-    pub async fn get_connection(&self) -> Result<MultiplexedConnection, BrokerError> {
-        // Acquire semaphore permit first to limit concurrent connections
-        let _permit = self
+    /// Lend out a connection, waiting while `max_size` are already lent out
+    ///
+    /// Idle connections are reused before new ones are opened. Callers must
+    /// not wait for a second lease while holding one, or they can wait forever
+    /// on a pool they exhausted themselves.
+    pub async fn get_connection(&self) -> Result<ConnectionLease, BrokerError> {
+        let permit = self
             .semaphore
             .clone()
             .acquire_owned()
             .await
             .map_err(|_| BrokerError::ConnectionError("Pool semaphore error".to_string()))?;
+        let connection = self.checkout_connection().await?;
+        Ok(ConnectionLease {
+            connection,
+            idle: Arc::clone(&self.idle),
+            broken: false,
+            _permit: permit,
+            _slot: self.usage.checkout(),
+        })
+    }
 
-        // Try to get an existing healthy connection
-        let mut connections = self.connections.lock().await;
-
-        // Look for a healthy connection
-        if let Some(index) = connections.iter().position(|conn| conn.is_healthy) {
-            let mut pooled_conn = connections.remove(index);
-            pooled_conn.mark_used();
-
-            // Quick health check for connections that haven't been used recently
-            if pooled_conn.last_used.elapsed() > HEALTH_CHECK_INTERVAL
-                && !pooled_conn.health_check().await
-            {
-                // Connection is unhealthy, create a new one
-                drop(connections); // Release lock before creating new connection
-                return self
-                    .create_connection_with_retry()
-                    .await
-                    .map(|conn| conn.connection);
+    /// An idle connection that still works, or a new one
+    async fn checkout_connection(&self) -> Result<MultiplexedConnection, BrokerError> {
+        loop {
+            // The lock is released before any await
+            let Some(mut pooled) = lock_idle(&self.idle).pop() else {
+                break;
+            };
+            // Connections idle for a while may have been closed by the server
+            if pooled.last_used.elapsed() <= HEALTH_CHECK_INTERVAL || pooled.health_check().await {
+                return Ok(pooled.connection);
             }
-
-            let connection = pooled_conn.connection.clone();
-            connections.push(pooled_conn); // Return to pool
-            return Ok(connection);
-        }
-
-        // No healthy connections available, create new one if under max size
-        if connections.len() < self.max_size {
-            drop(connections); // Release lock before creating new connection
-            return self
-                .create_connection_with_retry()
-                .await
-                .map(|conn| conn.connection);
-        }
-
-        // Pool is full, return the oldest connection
-        if let Some(mut pooled_conn) = connections.pop() {
-            pooled_conn.mark_used();
-            let connection = pooled_conn.connection.clone();
-            connections.push(pooled_conn);
-            Ok(connection)
-        } else {
-            // Should not happen, but fallback to creating new connection
-            drop(connections);
-            self.create_connection_with_retry()
-                .await
-                .map(|conn| conn.connection)
         }
+        self.create_connection_with_retry()
+            .await
+            .map(|pooled| pooled.connection)
     }
 
     async fn create_connection_with_retry(&self) -> Result<PooledConnection, BrokerError> {
@@ -174,42 +310,91 @@ impl ConnectionPool {
         ))
     }
 
-    #[allow(dead_code)]
-    pub async fn return_connection(&self, connection: MultiplexedConnection) {
-        let mut connections = self.connections.lock().await;
-        if connections.len() < self.max_size {
-            connections.push(PooledConnection::new(connection));
+    /// Maximum number of connections this pool will hand out concurrently
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    fn stats_with_peak(&self, peak_connections: usize) -> PoolStats {
+        let idle = lock_idle(&self.idle);
+        PoolStats {
+            active_connections: self.usage.in_use(),
+            peak_connections,
+            total_connections: idle.len(),
+            healthy_connections: idle.iter().filter(|c| c.is_healthy).count(),
+            opened_connections: self.opened.load(Ordering::SeqCst),
+            max_size: self.max_size,
         }
-        // If pool is full, just drop the connection
     }
 
-    pub async fn health_check(&self) -> Result<(), BrokerError> {
-        let mut connections = self.connections.lock().await;
+    /// Connection use since the previous call, see `PoolStats::is_saturated`
+    pub async fn stats(&self) -> PoolStats {
+        self.stats_with_peak(self.usage.take_peak())
+    }
 
-        // Check health of all pooled connections
-        for conn in connections.iter_mut() {
-            if !conn.health_check().await {
-                // Mark as unhealthy - it will be replaced on next use
-                conn.is_healthy = false;
-            }
-        }
+    /// Connection use so far, without starting a new measurement like `stats`
+    pub async fn snapshot(&self) -> PoolStats {
+        self.stats_with_peak(self.usage.peak())
+    }
 
-        // Remove unhealthy connections
-        connections.retain(|conn| conn.is_healthy);
+    /// Whether every connection was in use at some point since the previous
+    /// check, which starts a new measurement
+    pub fn take_saturation(&self) -> bool {
+        self.usage.take_peak() >= self.max_size
+    }
 
+    /// Ping the idle connections and drop those that don't answer
+    pub async fn health_check(&self) -> Result<(), BrokerError> {
+        let mut checked = std::mem::take(&mut *lock_idle(&self.idle));
+        for conn in checked.iter_mut() {
+            conn.health_check().await;
+        }
+        checked.retain(|conn| conn.is_healthy);
+        // Connections returned while checking stay too
+        lock_idle(&self.idle).extend(checked);
         Ok(())
     }
 
     #[allow(dead_code)]
     pub async fn close(&self) {
-        let mut connections = self.connections.lock().await;
-        connections.clear();
+        lock_idle(&self.idle).clear();
     }
 }
 
-impl Drop for ConnectionPool {
-    fn drop(&mut self) {
-        // Note: Cannot run async code in Drop, so connections will be cleaned up
-        // when they go out of scope. For proper cleanup, call close() explicitly.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_counts_checked_out_slots() {
+        let usage = Arc::new(PoolUsage::default());
+        let first = usage.checkout();
+        let second = usage.checkout();
+        assert_eq!(usage.in_use(), 2);
+
+        drop(first);
+        assert_eq!(usage.in_use(), 1);
+        // The peak is kept until it is read
+        assert_eq!(usage.take_peak(), 2);
+        assert_eq!(usage.take_peak(), 1);
+
+        drop(second);
+        assert_eq!(usage.in_use(), 0);
+        assert_eq!(usage.take_peak(), 1);
+        assert_eq!(usage.take_peak(), 0);
+    }
+
+    #[test]
+    fn test_saturation_needs_every_connection() {
+        let stats = |peak_connections| PoolStats {
+            active_connections: 0,
+            peak_connections,
+            total_connections: 3,
+            healthy_connections: 3,
+            opened_connections: 3,
+            max_size: 3,
+        };
+        assert!(!stats(2).is_saturated());
+        assert!(stats(3).is_saturated());
     }
 }
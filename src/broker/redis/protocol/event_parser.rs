@@ -0,0 +1,756 @@
+//! Celery events over Redis
+//!
+//! Workers started with `-E` publish task state changes and heartbeats to the
+//! `celeryev` fanout exchange, the stream Flower consumes. Kombu maps it to
+//! Redis pub/sub channels named after the routing key, like
+//! `/0.celeryev/task.succeeded`. Events are folded into an `EventState` as
+//! they arrive, and each refresh lays that state over what scanning found.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use super::{QueueParser, TaskParser};
+use crate::broker::BrokerOptions;
+use crate::models::{
+    canonical_hostname, canonical_node, find_worker, Task, TaskStatus, Worker, WorkerStatus,
+};
+
+/// Fanout exchange workers publish events to
+const EVENT_EXCHANGE: &str = "celeryev";
+
+/// Tasks remembered from events, the least recently updated are dropped first
+const MAX_EVENT_TASKS: usize = 10_000;
+
+/// Heartbeat interval in seconds assumed when a worker doesn't send one
+const DEFAULT_HEARTBEAT_FREQ: f64 = 2.0;
+
+/// Missed heartbeat intervals after which a worker counts as gone, as in Celery
+const HEARTBEAT_EXPIRE_WINDOW: f64 = 2.0;
+
+/// Wait before subscribing again after the event connection drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// A task state change reported by a worker or client
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskEvent {
+    pub id: String,
+    /// `None` for events that don't change the state, e.g. `task-rejected`
+    pub status: Option<TaskStatus>,
+    pub name: Option<String>,
+    pub args: Option<String>,
+    pub kwargs: Option<String>,
+    /// Canonical node name of the worker that sent the event
+    pub worker: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    /// Result of a succeeded task, or the exception of a failed one
+    pub result: Option<String>,
+    pub traceback: Option<String>,
+    pub runtime: Option<Duration>,
+    pub retries: Option<u32>,
+    pub queue: Option<String>,
+}
+
+/// A worker announcing itself, its heartbeat or its shutdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkerEvent {
+    /// Canonical node name of the worker
+    pub hostname: String,
+    /// `false` for `worker-offline`
+    pub online: bool,
+    /// When the worker sent the event, by its own clock
+    pub timestamp: DateTime<Utc>,
+    /// When the event arrived, by the local clock, which heartbeats are
+    /// judged by since worker clocks may be off. The send time until the
+    /// event is stamped on receipt.
+    pub received_at: DateTime<Utc>,
+    /// Seconds between heartbeats
+    pub freq: f64,
+    /// Tasks executing when the heartbeat was sent
+    pub active: Option<u32>,
+    pub processed: Option<u64>,
+}
+
+impl WorkerEvent {
+    /// Whether the worker was online at `now`, i.e. it didn't go offline and
+    /// its last heartbeat arrived recently enough
+    pub fn is_alive(&self, now: DateTime<Utc>) -> bool {
+        let window = Duration::try_from_secs_f64(self.freq * HEARTBEAT_EXPIRE_WINDOW)
+            .unwrap_or(Duration::ZERO);
+        self.online
+            && chrono::Duration::from_std(window)
+                .is_ok_and(|window| now - self.received_at <= window)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CeleryEvent {
+    Task(TaskEvent),
+    Worker(WorkerEvent),
+}
+
+impl CeleryEvent {
+    /// The event as received at `at` by the local clock
+    pub fn received(mut self, at: DateTime<Utc>) -> Self {
+        if let CeleryEvent::Worker(event) = &mut self {
+            event.received_at = at;
+        }
+        self
+    }
+}
+
+/// Parses events published on the `celeryev` exchange
+pub struct EventParser;
+
+impl EventParser {
+    /// Pattern matching the pub/sub channels of every event on database `db`
+    pub fn channel_pattern(db: i64) -> String {
+        format!("/{db}.{EVENT_EXCHANGE}/*")
+    }
+
+    /// Event carried by a published kombu message, or by a bare event body
+    ///
+    /// Events lazycelery has no use for, such as `worker-online` without a
+    /// hostname, and malformed messages yield `None`.
+    pub fn parse_message(message: &str) -> Option<CeleryEvent> {
+        let envelope: Value = serde_json::from_str(message).ok()?;
+        let event = match envelope.get("body") {
+            Some(body) => Self::decode_body(&envelope, body.as_str()?)?,
+            None => envelope,
+        };
+        Self::parse_event(&event)
+    }
+
+    fn decode_body(envelope: &Value, body: &str) -> Option<Value> {
+        let base64 = envelope
+            .get("properties")
+            .and_then(|p| p.get("body_encoding"))
+            .and_then(Value::as_str)
+            == Some("base64");
+        if base64 {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .ok()?;
+            serde_json::from_slice(&bytes).ok()
+        } else {
+            serde_json::from_str(body).ok()
+        }
+    }
+
+    /// Event from its decoded body, e.g. `{"type": "task-succeeded", ...}`
+    pub fn parse_event(event: &Value) -> Option<CeleryEvent> {
+        let kind = event.get("type")?.as_str()?;
+        let timestamp = event
+            .get("timestamp")
+            .and_then(Value::as_f64)
+            .and_then(|secs| DateTime::from_timestamp_micros((secs * 1e6) as i64))
+            .unwrap_or_else(Utc::now);
+        let hostname = Self::string_field(event, "hostname");
+
+        if let Some(state) = kind.strip_prefix("worker-") {
+            let online = match state {
+                "online" | "heartbeat" => true,
+                "offline" => false,
+                _ => return None,
+            };
+            return Some(CeleryEvent::Worker(WorkerEvent {
+                hostname: canonical_node(&hostname?),
+                online,
+                timestamp,
+                received_at: timestamp,
+                freq: event
+                    .get("freq")
+                    .and_then(Value::as_f64)
+                    .filter(|freq| *freq > 0.0)
+                    .unwrap_or(DEFAULT_HEARTBEAT_FREQ),
+                active: event
+                    .get("active")
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u32::try_from(v).ok()),
+                processed: event.get("processed").and_then(Value::as_u64),
+            }));
+        }
+
+        let state = kind.strip_prefix("task-")?;
+        let status = match state {
+            "sent" | "received" => Some(TaskStatus::Pending),
+            "started" => Some(TaskStatus::Active),
+            "succeeded" => Some(TaskStatus::Success),
+            "failed" => Some(TaskStatus::Failure),
+            "retried" => Some(TaskStatus::Retry),
+            "revoked" => Some(TaskStatus::Revoked),
+            "rejected" => None,
+            _ => return None,
+        };
+        let result = match status {
+            Some(TaskStatus::Failure) | Some(TaskStatus::Retry) => {
+                Self::string_field(event, "exception")
+            }
+            _ => Self::string_field(event, "result"),
+        };
+
+        Some(CeleryEvent::Task(TaskEvent {
+            id: Self::string_field(event, "uuid")?,
+            status,
+            name: Self::string_field(event, "name"),
+            args: Self::string_field(event, "args"),
+            kwargs: Self::string_field(event, "kwargs"),
+            worker: hostname.as_deref().map(canonical_node),
+            timestamp,
+            result,
+            traceback: Self::string_field(event, "traceback"),
+            runtime: event
+                .get("runtime")
+                .and_then(Value::as_f64)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+            retries: event
+                .get("retries")
+                .and_then(Value::as_u64)
+                .and_then(|v| u32::try_from(v).ok()),
+            queue: Self::string_field(event, "queue")
+                .or_else(|| Self::string_field(event, "routing_key")),
+        }))
+    }
+
+    fn string_field(value: &Value, key: &str) -> Option<String> {
+        value
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    }
+}
+
+/// What the events received so far say about tasks and workers
+#[derive(Debug, Default)]
+pub struct EventState {
+    /// Tasks as the events describe them, by id
+    tasks: HashMap<String, Task>,
+    /// Ids of `tasks` by the time they were last updated, oldest first
+    task_order: BTreeSet<(DateTime<Utc>, String)>,
+    /// Latest event of each worker, by canonical node name
+    workers: HashMap<String, WorkerEvent>,
+}
+
+impl EventState {
+    pub fn apply(&mut self, event: CeleryEvent) {
+        match event {
+            CeleryEvent::Task(event) => self.apply_task(event),
+            CeleryEvent::Worker(event) => {
+                self.workers.insert(event.hostname.clone(), event);
+            }
+        }
+    }
+
+    fn apply_task(&mut self, event: TaskEvent) {
+        if !self.tasks.contains_key(&event.id) && self.tasks.len() >= MAX_EVENT_TASKS {
+            if let Some((_, oldest)) = self.task_order.pop_first() {
+                self.tasks.remove(&oldest);
+            }
+        }
+
+        let task = self.tasks.entry(event.id.clone()).or_insert_with(|| Task {
+            timestamp: event.timestamp,
+            ..Task::new(event.id.clone(), String::new())
+        });
+        self.task_order.remove(&(task.timestamp, task.id.clone()));
+
+        // A late `task-received` doesn't undo the `task-started` before it
+        if let Some(status) = event.status {
+            if event.timestamp >= task.timestamp || task.status == TaskStatus::Pending {
+                task.status = status;
+            }
+        }
+        task.timestamp = task.timestamp.max(event.timestamp);
+        if let Some(name) = event.name {
+            task.name = name;
+        }
+        if let Some(args) = event.args {
+            task.args = args;
+        }
+        if let Some(kwargs) = event.kwargs {
+            task.kwargs = kwargs;
+        }
+        task.worker = event.worker.or(task.worker.take());
+        task.result = event.result.or(task.result.take());
+        task.looks_like_error = task.status == TaskStatus::Failure;
+        task.traceback = event.traceback.or(task.traceback.take());
+        task.duration = event.runtime.or(task.duration);
+        task.retries = event.retries.unwrap_or(task.retries);
+        task.queue = event.queue.or(task.queue.take());
+        self.task_order.insert((task.timestamp, task.id.clone()));
+    }
+
+    /// Lay the task states events reported over `tasks`
+    ///
+    /// Events win over scanned states, except that a task scanning found
+    /// finished isn't moved back to an unfinished state by an event that
+    /// arrived out of order. Tasks only known from events are added once an
+    /// event named them, the most recently updated first and no more than
+    /// `options.limits.max_task_results`, leaving out those sent to queues
+    /// outside `options.queue_glob`. What events add is truncated like
+    /// scanned tasks are.
+    pub fn overlay_tasks(&self, tasks: &mut Vec<Task>, options: &BrokerOptions) {
+        let mut seen = HashSet::new();
+        for task in tasks.iter_mut() {
+            let Some(event) = self.tasks.get(&task.id) else {
+                continue;
+            };
+            seen.insert(task.id.clone());
+            if !task.status.is_finished() || event.status.is_finished() {
+                task.status = event.status.clone();
+            }
+            if task.worker.is_none() {
+                task.worker = event.worker.clone();
+            }
+            if task.result.is_none() {
+                (task.result, task.result_full_len) =
+                    Self::limit(event.result.clone(), options.max_result_length);
+                task.looks_like_error |= event.looks_like_error;
+            }
+            if task.traceback.is_none() {
+                (task.traceback, task.traceback_full_len) =
+                    Self::limit(event.traceback.clone(), options.max_result_length);
+            }
+            task.duration = task.duration.or(event.duration);
+            task.retries = task.retries.max(event.retries);
+        }
+
+        let in_scope = |task: &Task| match (&options.queue_glob, &task.queue) {
+            (Some(glob), Some(queue)) => QueueParser::glob_matches(glob, queue),
+            _ => true,
+        };
+        let added: Vec<Task> = self
+            .task_order
+            .iter()
+            .rev()
+            .filter_map(|(_, id)| self.tasks.get(id))
+            .filter(|event| !event.name.is_empty() && !seen.contains(&event.id))
+            .filter(|event| in_scope(event))
+            .take(options.limits.max_task_results)
+            .map(|event| Self::truncated(event.clone(), options))
+            .collect();
+        tasks.extend(added);
+    }
+
+    /// `task` with its arguments, result and traceback cut to the configured
+    /// lengths
+    fn truncated(mut task: Task, options: &BrokerOptions) -> Task {
+        (task.args, task.args_full_len) =
+            TaskParser::limit_length(task.args, options.max_args_length);
+        (task.kwargs, task.kwargs_full_len) =
+            TaskParser::limit_length(task.kwargs, options.max_args_length);
+        (task.result, task.result_full_len) = Self::limit(task.result, options.max_result_length);
+        (task.traceback, task.traceback_full_len) =
+            Self::limit(task.traceback, options.max_result_length);
+        task
+    }
+
+    fn limit(value: Option<String>, max_len: usize) -> (Option<String>, Option<usize>) {
+        match value {
+            Some(value) => {
+                let (value, full_len) = TaskParser::limit_length(value, max_len);
+                (Some(value), full_len)
+            }
+            None => (None, None),
+        }
+    }
+
+    /// Lay the worker states events reported over `workers`
+    ///
+    /// A worker with a recent heartbeat is online, one that announced its
+    /// shutdown offline, and processed counts from heartbeats are kept when
+    /// higher. A worker listed by its bare host takes the node name when
+    /// events know of exactly one node on that host. Workers only known from
+    /// events are added.
+    pub fn overlay_workers(&self, workers: &mut Vec<Worker>, now: DateTime<Utc>) {
+        for event in self.workers.values() {
+            let alive = event.is_alive(now);
+            let host = canonical_hostname(&event.hostname);
+            let only_node_on_host = self
+                .workers
+                .keys()
+                .filter(|node| canonical_hostname(node) == host)
+                .count()
+                == 1;
+            let idx = find_worker(workers, &event.hostname).or_else(|| {
+                only_node_on_host
+                    .then(|| workers.iter().position(|w| w.hostname == host))
+                    .flatten()
+            });
+            let worker = match idx {
+                Some(idx) => &mut workers[idx],
+                None => {
+                    workers.push(Worker::new(event.hostname.clone()));
+                    workers.last_mut().expect("worker was just added")
+                }
+            };
+            worker.hostname = event.hostname.clone();
+            if alive {
+                worker.status = WorkerStatus::Online;
+            } else if !event.online {
+                worker.status = WorkerStatus::Offline;
+            }
+            if let Some(processed) = event.processed {
+                worker.processed = worker.processed.max(processed);
+            }
+        }
+    }
+}
+
+/// Follows the event stream in the background while events are enabled
+pub struct EventMonitor {
+    state: Arc<Mutex<EventState>>,
+    listener: JoinHandle<()>,
+}
+
+impl EventMonitor {
+    /// Subscribe to the events of database `db` at `url`, resubscribing after
+    /// the connection drops
+    pub fn spawn(url: &str, db: i64) -> Self {
+        let state = Arc::new(Mutex::new(EventState::default()));
+        let listener = tokio::spawn(Self::listen(url.to_string(), db, Arc::clone(&state)));
+        Self { state, listener }
+    }
+
+    async fn listen(url: String, db: i64, state: Arc<Mutex<EventState>>) {
+        let pattern = EventParser::channel_pattern(db);
+        loop {
+            match Self::follow(&url, &pattern, &state).await {
+                Ok(()) => debug!("Celery event subscription closed"),
+                Err(e) => warn!("Celery event subscription failed: {}", e),
+            }
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    }
+
+    async fn follow(
+        url: &str,
+        pattern: &str,
+        state: &Mutex<EventState>,
+    ) -> ::redis::RedisResult<()> {
+        use futures::StreamExt;
+
+        let client = ::redis::Client::open(url)?;
+        let mut pubsub = client.get_async_pubsub().await?;
+        pubsub.psubscribe(pattern).await?;
+        info!("Following Celery events on {}", pattern);
+
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let Ok(payload) = message.get_payload::<String>() else {
+                continue;
+            };
+            if let Some(event) = EventParser::parse_message(&payload) {
+                Self::lock(state).apply(event.received(Utc::now()));
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(state: &Mutex<EventState>) -> std::sync::MutexGuard<'_, EventState> {
+        state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn overlay_tasks(&self, tasks: &mut Vec<Task>, options: &BrokerOptions) {
+        Self::lock(&self.state).overlay_tasks(tasks, options);
+    }
+
+    pub fn overlay_workers(&self, workers: &mut Vec<Worker>, now: DateTime<Utc>) {
+        Self::lock(&self.state).overlay_workers(workers, now);
+    }
+}
+
+impl Drop for EventMonitor {
+    fn drop(&mut self) {
+        self.listener.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Event wrapped the way kombu publishes it on a fanout channel
+    fn published(event: Value) -> String {
+        json!({
+            "body": base64::engine::general_purpose::STANDARD.encode(event.to_string()),
+            "content-encoding": "utf-8",
+            "content-type": "application/json",
+            "headers": {"hostname": "celery@worker-1"},
+            "properties": {
+                "delivery_mode": 1,
+                "delivery_info": {"exchange": "celeryev", "routing_key": "task.succeeded"},
+                "priority": 0,
+                "body_encoding": "base64",
+                "delivery_tag": "b7c4c0a5-59f5-4a4b-9f49-4f2b5e3b3f11",
+            },
+        })
+        .to_string()
+    }
+
+    fn at(secs: f64) -> DateTime<Utc> {
+        DateTime::from_timestamp_micros((secs * 1e6) as i64).unwrap()
+    }
+
+    fn listed_task(id: &str, status: TaskStatus) -> Task {
+        let mut state = EventState::default();
+        state.apply(CeleryEvent::Task(TaskEvent {
+            id: id.to_string(),
+            status: Some(status),
+            name: Some("tasks.add".to_string()),
+            args: None,
+            kwargs: None,
+            worker: None,
+            timestamp: at(1_700_000_000.0),
+            result: None,
+            traceback: None,
+            runtime: None,
+            retries: None,
+            queue: None,
+        }));
+        let mut tasks = Vec::new();
+        state.overlay_tasks(&mut tasks, &BrokerOptions::default());
+        tasks.remove(0)
+    }
+
+    #[test]
+    fn test_task_succeeded_event_updates_the_task() {
+        let message = published(json!({
+            "type": "task-succeeded",
+            "uuid": "2f7b3b5e-6f0e-4d0c-8f5e-6a1d2c3b4a59",
+            "result": "42",
+            "runtime": 0.125,
+            "hostname": "celery@Worker-1",
+            "timestamp": 1_700_000_010.5,
+            "clock": 17,
+            "pid": 4242,
+            "utcoffset": 0,
+        }));
+        let Some(CeleryEvent::Task(event)) = EventParser::parse_message(&message) else {
+            panic!("not a task event");
+        };
+        assert_eq!(event.id, "2f7b3b5e-6f0e-4d0c-8f5e-6a1d2c3b4a59");
+        assert_eq!(event.status, Some(TaskStatus::Success));
+        assert_eq!(event.result.as_deref(), Some("42"));
+        assert_eq!(event.runtime, Some(Duration::from_millis(125)));
+        assert_eq!(event.worker.as_deref(), Some("celery@worker-1"));
+        assert_eq!(event.timestamp, at(1_700_000_010.5));
+
+        // Scanning still saw the task started, the event finishes it
+        let mut tasks = vec![listed_task(&event.id, TaskStatus::Active)];
+        let mut state = EventState::default();
+        state.apply(CeleryEvent::Task(event));
+        state.overlay_tasks(&mut tasks, &BrokerOptions::default());
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::Success);
+        assert_eq!(tasks[0].result.as_deref(), Some("42"));
+        assert_eq!(tasks[0].duration, Some(Duration::from_millis(125)));
+        assert_eq!(tasks[0].worker.as_deref(), Some("celery@worker-1"));
+    }
+
+    #[test]
+    fn test_task_events_fold_into_one_task() {
+        let mut state = EventState::default();
+        for event in [
+            json!({"type": "task-received", "uuid": "t1", "name": "billing.charge",
+                   "args": "(7,)", "kwargs": "{}", "hostname": "celery@w1",
+                   "timestamp": 100.0}),
+            json!({"type": "task-started", "uuid": "t1", "hostname": "celery@w1",
+                   "timestamp": 101.0}),
+            json!({"type": "task-failed", "uuid": "t1", "exception": "ValueError('card declined')",
+                   "traceback": "Traceback ...", "hostname": "celery@w1", "timestamp": 102.0}),
+            // Delivered late, but doesn't undo the failure
+            json!({"type": "task-received", "uuid": "t1", "hostname": "celery@w1",
+                   "timestamp": 100.0}),
+            // Events without a task id are dropped
+            json!({"type": "task-started", "hostname": "celery@w1", "timestamp": 101.0}),
+        ] {
+            if let Some(event) = EventParser::parse_message(&event.to_string()) {
+                state.apply(event);
+            }
+        }
+
+        let mut tasks = Vec::new();
+        state.overlay_tasks(&mut tasks, &BrokerOptions::default());
+        assert_eq!(tasks.len(), 1);
+        let task = &tasks[0];
+        assert_eq!(task.name, "billing.charge");
+        assert_eq!(task.args, "(7,)");
+        assert_eq!(task.status, TaskStatus::Failure);
+        assert_eq!(task.result.as_deref(), Some("ValueError('card declined')"));
+        assert!(task.looks_like_error);
+        assert_eq!(task.traceback.as_deref(), Some("Traceback ..."));
+        assert_eq!(task.timestamp, at(102.0));
+    }
+
+    #[test]
+    fn test_finished_tasks_stay_finished() {
+        let mut state = EventState::default();
+        state.apply(
+            EventParser::parse_message(
+                &json!({"type": "task-started", "uuid": "t1", "timestamp": 50.0}).to_string(),
+            )
+            .unwrap(),
+        );
+        let mut tasks = vec![listed_task("t1", TaskStatus::Success)];
+        state.overlay_tasks(&mut tasks, &BrokerOptions::default());
+        assert_eq!(tasks[0].status, TaskStatus::Success);
+
+        // Tasks whose name no event carried aren't added on their own
+        let mut tasks = Vec::new();
+        state.overlay_tasks(&mut tasks, &BrokerOptions::default());
+        assert!(tasks.is_empty());
+    }
+
+    fn sent(id: &str, queue: &str, args: &str, secs: f64) -> CeleryEvent {
+        let event = json!({"type": "task-sent", "uuid": id, "name": "tasks.add",
+                           "args": args, "queue": queue, "timestamp": secs});
+        EventParser::parse_message(&event.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_tasks_added_from_events_follow_the_scan_limits() {
+        let mut state = EventState::default();
+        state.apply(sent("a-old", "tenantA.celery", "(1,)", 10.0));
+        state.apply(sent("a-new", "tenantA.celery", "(2,)", 20.0));
+        state.apply(sent("b", "tenantB.celery", "(3,)", 30.0));
+        state.apply(sent(
+            "a-long",
+            "tenantA.celery",
+            &format!("({})", "9".repeat(50)),
+            15.0,
+        ));
+
+        let mut options = BrokerOptions {
+            queue_glob: Some("tenantA.*".to_string()),
+            max_args_length: 16,
+            ..BrokerOptions::default()
+        };
+        options.limits.max_task_results = 2;
+
+        let mut tasks = Vec::new();
+        state.overlay_tasks(&mut tasks, &options);
+        let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["a-new", "a-long"]);
+        assert_eq!(tasks[1].args_full_len, Some(52));
+        assert!(tasks[1].args.contains("truncated"));
+    }
+
+    #[test]
+    fn test_least_recently_updated_task_is_evicted() {
+        let mut state = EventState::default();
+        state.apply(sent("first", "celery", "()", 1.0));
+        state.apply(sent("second", "celery", "()", 2.0));
+        // An update makes the first task the most recent one
+        let started = json!({"type": "task-started", "uuid": "first", "timestamp": 5.0});
+        state.apply(EventParser::parse_message(&started.to_string()).unwrap());
+        for i in 0..MAX_EVENT_TASKS - 2 {
+            state.apply(sent(&format!("filler-{i}"), "celery", "()", 3.0));
+        }
+        assert_eq!(state.tasks.len(), MAX_EVENT_TASKS);
+
+        state.apply(sent("newest", "celery", "()", 6.0));
+        assert_eq!(state.tasks.len(), MAX_EVENT_TASKS);
+        assert_eq!(state.task_order.len(), MAX_EVENT_TASKS);
+        assert!(!state.tasks.contains_key("second"));
+        assert!(state.tasks.contains_key("first"));
+        assert!(state.tasks.contains_key("newest"));
+    }
+
+    #[test]
+    fn test_worker_heartbeat_marks_the_worker_online() {
+        let message = published(json!({
+            "type": "worker-heartbeat",
+            "hostname": "celery@worker-2",
+            "timestamp": 1_700_000_000.0,
+            "freq": 2.0,
+            "active": 3,
+            "processed": 1250,
+            "loadavg": [0.5, 0.4, 0.3],
+            "sw_ident": "py-celery",
+            "sw_ver": "5.3.6",
+            "sw_sys": "Linux",
+            "utcoffset": 0,
+            "pid": 99,
+            "clock": 311,
+        }));
+        let Some(CeleryEvent::Worker(event)) = EventParser::parse_message(&message) else {
+            panic!("not a worker event");
+        };
+        assert_eq!(event.hostname, "celery@worker-2");
+        assert!(event.online);
+        assert_eq!(event.active, Some(3));
+        assert_eq!(event.processed, Some(1250));
+
+        let mut state = EventState::default();
+        state.apply(CeleryEvent::Worker(event));
+
+        // Scanning found the worker offline, the heartbeat says otherwise.
+        // It's the only node on its host, so the host takes the node name.
+        let mut listed = Worker::new("worker-2".to_string());
+        listed.processed = 1000;
+        let mut workers = vec![listed];
+        state.overlay_workers(&mut workers, at(1_700_000_003.0));
+        assert_eq!(workers.len(), 1);
+        assert_eq!(workers[0].hostname, "celery@worker-2");
+        assert_eq!(workers[0].status, WorkerStatus::Online);
+        assert_eq!(workers[0].processed, 1250);
+
+        // Two missed heartbeats later it's no longer vouched for
+        let mut workers = vec![Worker::new("worker-2".to_string())];
+        state.overlay_workers(&mut workers, at(1_700_000_005.0));
+        assert_eq!(workers[0].status, WorkerStatus::Offline);
+    }
+
+    #[test]
+    fn test_heartbeats_are_judged_by_when_they_arrived() {
+        // The worker's clock is an hour behind ours
+        let message = json!({
+            "type": "worker-heartbeat",
+            "hostname": "celery@worker-3",
+            "timestamp": 1_699_996_400.0,
+            "freq": 2.0,
+        });
+        let event = EventParser::parse_message(&message.to_string())
+            .unwrap()
+            .received(at(1_700_000_000.0));
+
+        let mut state = EventState::default();
+        state.apply(event);
+        let mut workers = Vec::new();
+        state.overlay_workers(&mut workers, at(1_700_000_001.0));
+        assert_eq!(workers[0].status, WorkerStatus::Online);
+
+        let mut workers = Vec::new();
+        state.overlay_workers(&mut workers, at(1_700_000_005.0));
+        assert_eq!(workers[0].status, WorkerStatus::Offline);
+    }
+
+    #[test]
+    fn test_worker_offline_event_and_unknown_events() {
+        let mut state = EventState::default();
+        let offline = json!({"type": "worker-offline", "hostname": "celery@w3", "timestamp": 10.0});
+        state.apply(EventParser::parse_message(&offline.to_string()).unwrap());
+
+        let mut workers = vec![Worker::new("w3".to_string())];
+        workers[0].status = WorkerStatus::Online;
+        state.overlay_workers(&mut workers, at(11.0));
+        assert_eq!(workers[0].status, WorkerStatus::Offline);
+
+        for message in [
+            json!({"type": "worker-online", "timestamp": 10.0}).to_string(),
+            json!({"type": "custom-event", "uuid": "t1"}).to_string(),
+            "not json".to_string(),
+        ] {
+            assert_eq!(EventParser::parse_message(&message), None, "{message}");
+        }
+        assert_eq!(EventParser::channel_pattern(3), "/3.celeryev/*");
+    }
+}
@@ -0,0 +1,534 @@
+//! Celery remote control over Redis
+//!
+//! Workers listen for control commands on the `celery.pidbox` fanout exchange,
+//! which kombu maps to a Redis pub/sub channel. An `inspect` command names a
+//! reply exchange and routing key; each worker answers by pushing a message to
+//! the queues bound to that key, so replies are collected from a temporary
+//! queue bound for the duration of the call. Commands that change workers,
+//! such as `rate_limit`, are sent without asking for replies.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::error::BrokerError;
+use crate::models::{canonical_node, merge_workers_by_host, Worker, WorkerStatus};
+
+/// Fanout exchange workers receive control commands on
+const CONTROL_EXCHANGE: &str = "celery.pidbox";
+
+/// Direct exchange workers publish control replies to
+const REPLY_EXCHANGE: &str = "reply.celery.pidbox";
+
+/// Separator kombu puts between the fields of an exchange binding
+const BINDING_SEPARATOR: &str = "\x06\x16";
+
+/// How often the reply queue is polled while waiting for replies
+const REPLY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Inspect commands sent to build the worker list
+const INSPECT_METHODS: [InspectMethod; 4] = [
+    InspectMethod::Active,
+    InspectMethod::Reserved,
+    InspectMethod::Stats,
+    InspectMethod::ActiveQueues,
+];
+
+/// Inspect commands lazycelery understands the replies of
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InspectMethod {
+    /// Tasks currently executing
+    Active,
+    /// Tasks prefetched and waiting for a free process
+    Reserved,
+    /// Pool size and task totals
+    Stats,
+    /// Queues the worker consumes from
+    ActiveQueues,
+}
+
+impl InspectMethod {
+    pub fn name(self) -> &'static str {
+        match self {
+            InspectMethod::Active => "active",
+            InspectMethod::Reserved => "reserved",
+            InspectMethod::Stats => "stats",
+            InspectMethod::ActiveQueues => "active_queues",
+        }
+    }
+}
+
+/// Sends inspect commands and turns the replies into workers
+pub struct WorkerInspector;
+
+impl WorkerInspector {
+    /// Ask every worker for its active and reserved tasks, its stats and the
+    /// queues it consumes, waiting up to `timeout` for replies
+    ///
+    /// Workers don't say how many of them there are, so the wait only ends
+    /// early once `expected_nodes` nodes, e.g. as many as replied last time,
+    /// have answered every command. Returns the workers and how many nodes
+    /// replied.
+    ///
+    /// `db` is the Redis database of the connection, kombu prefixes fanout
+    /// channels with it. Commands expire `timeout` after `now`. Fails with
+    /// `BrokerError::Timeout` when no worker replies.
+    pub async fn inspect_workers(
+        connection: &MultiplexedConnection,
+        db: i64,
+        timeout: Duration,
+        expected_nodes: usize,
+        now: DateTime<Utc>,
+    ) -> Result<(Vec<Worker>, usize), BrokerError> {
+        let mut conn = connection.clone();
+        let oid = Uuid::new_v4().to_string();
+        let reply_queue = format!("{oid}.{REPLY_EXCHANGE}");
+        let binding_key = format!("_kombu.binding.{REPLY_EXCHANGE}");
+        let binding = [oid.as_str(), "", reply_queue.as_str()].join(BINDING_SEPARATOR);
+
+        conn.sadd::<_, _, ()>(&binding_key, &binding)
+            .await
+            .map_err(|e| BrokerError::OperationError(format!("Failed to bind reply queue: {e}")))?;
+
+        let result = Self::broadcast_and_collect(
+            &mut conn,
+            db,
+            &oid,
+            &reply_queue,
+            timeout,
+            expected_nodes,
+            now,
+        )
+        .await;
+
+        // Best effort cleanup, a leftover binding only costs workers a lookup
+        let _: Result<(), _> = conn.srem(&binding_key, &binding).await;
+        let _: Result<(), _> = conn.del(&reply_queue).await;
+
+        let replies = result?;
+        if replies.is_empty() {
+            return Err(BrokerError::Timeout);
+        }
+        let nodes = Self::replying_nodes(&replies).len();
+        Ok((Self::parse_replies(&replies), nodes))
+    }
+
+    /// Node names found in reply bodies like `{"celery@host": [...]}`
+    fn replying_nodes(replies: &[(InspectMethod, Value)]) -> HashSet<String> {
+        replies
+            .iter()
+            .filter_map(|(_, body)| body.as_object())
+            .flat_map(|nodes| nodes.keys().cloned())
+            .collect()
+    }
+
+    /// Whether `expected_nodes` nodes answered every inspect command
+    fn all_answered(replies: &[(InspectMethod, Value)], expected_nodes: usize) -> bool {
+        expected_nodes > 0
+            && INSPECT_METHODS.iter().all(|method| {
+                let answered: Vec<(InspectMethod, Value)> = replies
+                    .iter()
+                    .filter(|(m, _)| m == method)
+                    .cloned()
+                    .collect();
+                Self::replying_nodes(&answered).len() >= expected_nodes
+            })
+    }
+
+    async fn broadcast_and_collect(
+        conn: &mut MultiplexedConnection,
+        db: i64,
+        oid: &str,
+        reply_queue: &str,
+        timeout: Duration,
+        expected_nodes: usize,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<(InspectMethod, Value)>, BrokerError> {
+        let channel = format!("/{db}.{CONTROL_EXCHANGE}");
+        let mut tickets = HashMap::new();
+        for method in INSPECT_METHODS {
+            let ticket = Uuid::new_v4().to_string();
+            let message = Self::build_command(method, &ticket, oid, timeout, now);
+            conn.publish::<_, _, ()>(&channel, message.to_string())
+                .await
+                .map_err(|e| {
+                    BrokerError::OperationError(format!(
+                        "Failed to send inspect {}: {e}",
+                        method.name()
+                    ))
+                })?;
+            tickets.insert(ticket, method);
+        }
+
+        let deadline = Instant::now() + timeout;
+        let mut replies = Vec::new();
+        loop {
+            let message: Option<String> = conn.rpop(reply_queue, None).await.map_err(|e| {
+                BrokerError::OperationError(format!("Failed to read inspect replies: {e}"))
+            })?;
+            match message {
+                Some(message) => {
+                    if let Some((ticket, body)) = Self::parse_reply(&message) {
+                        if let Some(method) = tickets.get(&ticket) {
+                            replies.push((*method, body));
+                        }
+                    }
+                    if Self::all_answered(&replies, expected_nodes) {
+                        break;
+                    }
+                }
+                None if Instant::now() >= deadline => break,
+                None => tokio::time::sleep(REPLY_POLL_INTERVAL).await,
+            }
+        }
+        Ok(replies)
+    }
+
+    /// Kombu message carrying a broadcast inspect command, expiring `timeout`
+    /// after `now`
+    pub fn build_command(
+        method: InspectMethod,
+        ticket: &str,
+        oid: &str,
+        timeout: Duration,
+        now: DateTime<Utc>,
+    ) -> Value {
+        let body = json!({
+            "method": method.name(),
+            "arguments": {},
+            "destination": null,
+            "pattern": null,
+            "matcher": null,
+            "reply_to": {"exchange": REPLY_EXCHANGE, "routing_key": oid},
+            "ticket": ticket,
+        });
+        let expires = now.timestamp_millis() as f64 / 1000.0 + timeout.as_secs_f64();
+        Self::control_message(&body, json!({"clock": 1, "expires": expires}))
+    }
+
+    /// Ask every worker to limit how often it runs `task_name`, without
+    /// waiting for replies
+    pub async fn set_rate_limit(
+        connection: &MultiplexedConnection,
+        db: i64,
+        task_name: &str,
+        rate: &str,
+    ) -> Result<(), BrokerError> {
+        let mut conn = connection.clone();
+        let channel = format!("/{db}.{CONTROL_EXCHANGE}");
+        let message = Self::build_rate_limit_command(task_name, rate);
+        conn.publish::<_, _, ()>(&channel, message.to_string())
+            .await
+            .map_err(|e| BrokerError::OperationError(format!("Failed to send rate_limit: {e}")))
+    }
+
+    /// Kombu message carrying a broadcast `rate_limit` command
+    pub fn build_rate_limit_command(task_name: &str, rate: &str) -> Value {
+        let body = json!({
+            "method": "rate_limit",
+            "arguments": {"task_name": task_name, "rate_limit": rate},
+            "destination": null,
+            "pattern": null,
+            "matcher": null,
+        });
+        Self::control_message(&body, json!({"clock": 1}))
+    }
+
+    /// Envelope kombu wraps a control command `body` in
+    fn control_message(body: &Value, headers: Value) -> Value {
+        json!({
+            "body": base64::engine::general_purpose::STANDARD.encode(body.to_string()),
+            "content-encoding": "utf-8",
+            "content-type": "application/json",
+            "headers": headers,
+            "properties": {
+                "delivery_mode": 2,
+                "delivery_info": {"exchange": CONTROL_EXCHANGE, "routing_key": ""},
+                "priority": 0,
+                "body_encoding": "base64",
+                "delivery_tag": Uuid::new_v4().to_string(),
+            },
+        })
+    }
+
+    /// Ticket and decoded body of a reply message
+    pub fn parse_reply(message: &str) -> Option<(String, Value)> {
+        let envelope: Value = serde_json::from_str(message).ok()?;
+        let ticket = envelope
+            .get("headers")?
+            .get("ticket")?
+            .as_str()?
+            .to_string();
+        let body = envelope.get("body")?.as_str()?;
+        let base64 = envelope
+            .get("properties")
+            .and_then(|p| p.get("body_encoding"))
+            .and_then(Value::as_str)
+            == Some("base64");
+        let body = if base64 {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(body)
+                .ok()?;
+            serde_json::from_slice(&bytes).ok()?
+        } else {
+            serde_json::from_str(body).ok()?
+        };
+        Some((ticket, body))
+    }
+
+    /// Build one worker per replying node from reply bodies like
+    /// `{"celery@host": [...]}`
+    ///
+    /// Concurrency comes from `stats` (`pool.max-concurrency`), the pool type
+    /// from its `pool.implementation` and processed counts from its per task
+    /// `total`, queues from the names in `active_queues`. Replies that aren't
+    /// in the expected
+    /// shape, such as errors for unknown commands, are skipped.
+    pub fn parse_replies(replies: &[(InspectMethod, Value)]) -> Vec<Worker> {
+        let mut workers: BTreeMap<String, Worker> = BTreeMap::new();
+
+        for (method, body) in replies {
+            let Some(nodes) = body.as_object() else {
+                continue;
+            };
+            for (node, reply) in nodes {
+                let worker = workers.entry(node.clone()).or_insert_with(|| Worker {
+                    hostname: canonical_node(node),
+                    status: WorkerStatus::Online,
+                    concurrency: 0,
+                    pool_type: None,
+                    queues: Vec::new(),
+                    active_tasks: Vec::new(),
+                    reserved_tasks: Vec::new(),
+                    processed: 0,
+                    failed: 0,
+                });
+                match method {
+                    InspectMethod::Active => worker.active_tasks = Self::task_ids(reply),
+                    InspectMethod::Reserved => worker.reserved_tasks = Self::task_ids(reply),
+                    InspectMethod::Stats => {
+                        if let Some(concurrency) = reply
+                            .pointer("/pool/max-concurrency")
+                            .and_then(Value::as_u64)
+                        {
+                            worker.concurrency = concurrency as u32;
+                        }
+                        if let Some(implementation) = reply
+                            .pointer("/pool/implementation")
+                            .and_then(Value::as_str)
+                        {
+                            worker.pool_type = Some(Self::pool_type(implementation));
+                        }
+                        if let Some(total) = reply.get("total").and_then(Value::as_object) {
+                            worker.processed = total.values().filter_map(Value::as_u64).sum();
+                        }
+                    }
+                    InspectMethod::ActiveQueues => {
+                        worker.queues = reply
+                            .as_array()
+                            .map(|queues| {
+                                queues
+                                    .iter()
+                                    .filter_map(|queue| queue.get("name").and_then(Value::as_str))
+                                    .map(str::to_string)
+                                    .collect()
+                            })
+                            .unwrap_or_default();
+                    }
+                }
+            }
+        }
+
+        merge_workers_by_host(workers.into_values().collect())
+    }
+
+    /// Short name of a pool class path, `prefork` for
+    /// `celery.concurrency.prefork:TaskPool`
+    fn pool_type(implementation: &str) -> String {
+        let module = implementation.split(':').next().unwrap_or(implementation);
+        module.rsplit('.').next().unwrap_or(module).to_string()
+    }
+
+    /// Ids of the task requests listed in an `active` or `reserved` reply
+    fn task_ids(reply: &Value) -> Vec<String> {
+        reply
+            .as_array()
+            .map(|requests| {
+                requests
+                    .iter()
+                    .filter_map(|request| request.get("id").and_then(Value::as_str))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn reply_message(ticket: &str, body: &Value) -> String {
+        json!({
+            "body": base64::engine::general_purpose::STANDARD.encode(body.to_string()),
+            "content-encoding": "utf-8",
+            "content-type": "application/json",
+            "headers": {"ticket": ticket, "clock": 3},
+            "properties": {"body_encoding": "base64", "delivery_tag": "tag"},
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_command_names_reply_queue_and_ticket() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let message = WorkerInspector::build_command(
+            InspectMethod::Reserved,
+            "t-1",
+            "oid-1",
+            Duration::from_millis(1500),
+            now,
+        );
+        assert_eq!(message["headers"]["expires"], 1_714_564_801.5);
+        assert_eq!(
+            message["properties"]["delivery_info"]["exchange"],
+            "celery.pidbox"
+        );
+
+        let encoded = message["body"].as_str().unwrap();
+        let body: Value = serde_json::from_slice(
+            &base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["method"], "reserved");
+        assert_eq!(body["ticket"], "t-1");
+        assert_eq!(body["reply_to"]["exchange"], "reply.celery.pidbox");
+        assert_eq!(body["reply_to"]["routing_key"], "oid-1");
+    }
+
+    #[test]
+    fn test_rate_limit_command_names_task_and_rate() {
+        let message = WorkerInspector::build_rate_limit_command("tasks.send_email", "10/m");
+        assert_eq!(
+            message["properties"]["delivery_info"]["exchange"],
+            "celery.pidbox"
+        );
+        assert_eq!(message["properties"]["body_encoding"], "base64");
+        assert!(message["headers"].get("expires").is_none());
+
+        let encoded = message["body"].as_str().unwrap();
+        let body: Value = serde_json::from_slice(
+            &base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["method"], "rate_limit");
+        assert_eq!(
+            body["arguments"],
+            json!({"task_name": "tasks.send_email", "rate_limit": "10/m"})
+        );
+        // Broadcast to every worker, nobody is asked to reply
+        assert_eq!(body["destination"], Value::Null);
+        assert!(body.get("reply_to").is_none());
+        assert!(body.get("ticket").is_none());
+    }
+
+    #[test]
+    fn test_parse_reply_decodes_body() {
+        let body = json!({"celery@host-1": [{"id": "task-1"}]});
+        let (ticket, decoded) = WorkerInspector::parse_reply(&reply_message("t-1", &body)).unwrap();
+        assert_eq!(ticket, "t-1");
+        assert_eq!(decoded, body);
+
+        assert!(WorkerInspector::parse_reply("not json").is_none());
+    }
+
+    #[test]
+    fn test_known_nodes_answering_every_command_ends_the_wait() {
+        let node = |name: &str| json!({ name: [] });
+        let mut replies = vec![
+            (InspectMethod::Active, node("celery@host-1")),
+            (InspectMethod::Active, node("celery@host-2")),
+            (InspectMethod::Reserved, node("celery@host-1")),
+            (InspectMethod::Reserved, node("celery@host-2")),
+            (InspectMethod::Stats, node("celery@host-1")),
+            (InspectMethod::ActiveQueues, node("celery@host-1")),
+            (InspectMethod::ActiveQueues, node("celery@host-2")),
+        ];
+        assert!(!WorkerInspector::all_answered(&replies, 2));
+        assert!(WorkerInspector::all_answered(&replies, 1));
+
+        replies.push((InspectMethod::Stats, node("celery@host-2")));
+        assert!(WorkerInspector::all_answered(&replies, 2));
+        // Without a previous count the timeout is waited out
+        assert!(!WorkerInspector::all_answered(&replies, 0));
+        assert_eq!(WorkerInspector::replying_nodes(&replies).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_replies_builds_workers() {
+        let replies = vec![
+            (
+                InspectMethod::Active,
+                json!({
+                    "celery@host-1": [{"id": "task-1", "name": "tasks.add"}],
+                    "celery@host-2": [],
+                }),
+            ),
+            (
+                InspectMethod::Reserved,
+                json!({"celery@host-1": [{"id": "task-2"}, {"id": "task-3"}]}),
+            ),
+            (
+                InspectMethod::Stats,
+                json!({
+                    "celery@host-1": {
+                        "pool": {
+                            "max-concurrency": 4,
+                            "implementation": "celery.concurrency.prefork:TaskPool",
+                        },
+                        "total": {"tasks.add": 10, "tasks.mul": 5},
+                    },
+                    "celery@host-2": {"error": "unknown command"},
+                }),
+            ),
+            (
+                InspectMethod::ActiveQueues,
+                json!({
+                    "celery@host-1": [{"name": "celery"}, {"name": "reports"}],
+                    "celery@host-2": {"error": "unknown command"},
+                }),
+            ),
+        ];
+
+        let workers = WorkerInspector::parse_replies(&replies);
+        assert_eq!(workers.len(), 2);
+
+        let first = &workers[0];
+        assert_eq!(first.hostname, "celery@host-1");
+        assert_eq!(first.status, WorkerStatus::Online);
+        assert_eq!(first.active_tasks, vec!["task-1"]);
+        assert_eq!(first.reserved_tasks, vec!["task-2", "task-3"]);
+        assert_eq!(first.concurrency, 4);
+        assert_eq!(first.pool_type.as_deref(), Some("prefork"));
+        assert_eq!(first.processed, 15);
+        assert_eq!(first.queues, vec!["celery", "reports"]);
+
+        let second = &workers[1];
+        assert_eq!(second.hostname, "celery@host-2");
+        assert!(second.active_tasks.is_empty());
+        assert_eq!(second.concurrency, 0);
+        assert_eq!(second.pool_type, None);
+        assert!(second.queues.is_empty());
+    }
+}
@@ -3,39 +3,203 @@
 //! This module contains parsers for different Celery protocol data types.
 //! Each parser is responsible for parsing a specific type of data from Redis.
 
+mod event_parser;
+mod inspect;
+mod queue_history;
 mod queue_parser;
+mod queue_store;
+mod schedule_parser;
 mod task_parser;
 mod worker_parser;
 
+pub use event_parser::EventMonitor;
+pub use inspect::WorkerInspector;
+pub use queue_history::QueueHistory;
 pub use queue_parser::QueueParser;
+pub use queue_store::{QueueKind, QueueStore};
+pub use schedule_parser::ScheduleParser;
 pub use task_parser::TaskParser;
 pub use worker_parser::WorkerParser;
 
 // Re-export the main ProtocolParser for backward compatibility
+use crate::broker::redis::pool::ConnectionPool;
+use crate::broker::{BrokerOptions, ScanCap};
 use crate::error::BrokerError;
-use crate::models::{Queue, Task, Worker};
+use crate::models::{Queue, ScheduledEntry, Task, Worker};
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
 use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use std::collections::HashMap;
+
+/// Items read by a parser, with the cap that cut the read short if one was hit
+#[derive(Debug)]
+pub struct Parsed<T> {
+    pub items: Vec<T>,
+    pub capped: Option<ScanCap>,
+    /// Keys and messages skipped because they couldn't be parsed
+    pub malformed: usize,
+}
 
 /// Main protocol parser that delegates to specialized parsers
 pub struct ProtocolParser;
 
 impl ProtocolParser {
-    /// Parse workers from Redis connection
+    /// Parse workers from queue messages and the task metadata in the
+    /// result backend
     pub async fn parse_workers(
+        pool: &ConnectionPool,
+        backend: &ConnectionPool,
+        options: &BrokerOptions,
+    ) -> Result<Parsed<Worker>, BrokerError> {
+        WorkerParser::parse_workers(pool, backend, &options.limits).await
+    }
+
+    /// Ask workers for their state over the Celery remote control, and how
+    /// many nodes replied
+    pub async fn inspect_workers(
         connection: &MultiplexedConnection,
-    ) -> Result<Vec<Worker>, BrokerError> {
-        WorkerParser::parse_workers(connection).await
+        db: i64,
+        options: &BrokerOptions,
+        expected_nodes: usize,
+        now: DateTime<Utc>,
+    ) -> Result<(Vec<Worker>, usize), BrokerError> {
+        WorkerInspector::inspect_workers(
+            connection,
+            db,
+            options.inspect_timeout,
+            expected_nodes,
+            now,
+        )
+        .await
     }
 
-    /// Parse tasks from Redis connection
-    pub async fn parse_tasks(connection: &MultiplexedConnection) -> Result<Vec<Task>, BrokerError> {
-        TaskParser::parse_tasks(connection).await
+    /// Limit how often workers run `task_name` over the Celery remote control
+    pub async fn set_rate_limit(
+        connection: &MultiplexedConnection,
+        db: i64,
+        task_name: &str,
+        rate: &str,
+    ) -> Result<(), BrokerError> {
+        WorkerInspector::set_rate_limit(connection, db, task_name, rate).await
+    }
+
+    /// Parse tasks from broker messages and result backend metadata
+    pub async fn parse_tasks(
+        pool: &ConnectionPool,
+        backend: &ConnectionPool,
+        options: &BrokerOptions,
+    ) -> Result<Parsed<Task>, BrokerError> {
+        TaskParser::parse_tasks(pool, backend, options).await
     }
 
-    /// Parse queues from Redis connection
+    /// Parse the stored metadata of a single task
+    pub fn parse_task(
+        task_id: &str,
+        data: &str,
+        options: &BrokerOptions,
+    ) -> Result<Task, BrokerError> {
+        TaskParser::parse_task(task_id, data, options)
+    }
+
+    /// Parse at most `limit` pending tasks from queue messages
+    pub async fn parse_recent_tasks(
+        pool: &ConnectionPool,
+        options: &BrokerOptions,
+        limit: usize,
+    ) -> Result<Vec<Task>, BrokerError> {
+        TaskParser::parse_recent_tasks(pool, options, limit).await
+    }
+
+    /// Parse up to `n` messages waiting in `queue` without consuming them
+    pub async fn peek_queue(
+        connection: &MultiplexedConnection,
+        queue: &str,
+        n: usize,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Task>, BrokerError> {
+        TaskParser::peek_queue(&mut connection.clone(), queue, n, now).await
+    }
+
+    /// Parse queues from Redis connection, measuring message ages up to `now`,
+    /// keeping to queues matching `glob` when set and taking consumer counts
+    /// from `consumers`
     pub async fn parse_queues(
         connection: &MultiplexedConnection,
+        now: DateTime<Utc>,
+        glob: Option<&str>,
+        consumers: &HashMap<String, u32>,
     ) -> Result<Vec<Queue>, BrokerError> {
-        QueueParser::parse_queues(connection).await
+        QueueParser::parse_queues(connection, now, glob, consumers).await
     }
+
+    /// Store the current queue lengths as samples taken at `now`
+    pub async fn record_queue_history(
+        connection: &MultiplexedConnection,
+        queues: &[Queue],
+        now: DateTime<Utc>,
+    ) -> Result<(), BrokerError> {
+        QueueHistory::record(&mut connection.clone(), queues, now).await
+    }
+
+    /// Read the length samples of `queue` taken since `since`
+    pub async fn read_queue_history(
+        connection: &MultiplexedConnection,
+        queue: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        QueueHistory::read(&mut connection.clone(), queue, since).await
+    }
+
+    /// Parse the celery beat schedule kept by the redisbeat scheduler
+    pub async fn parse_schedule(
+        connection: &MultiplexedConnection,
+    ) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        ScheduleParser::parse_schedule(connection).await
+    }
+}
+
+/// Every key matching `pattern`, read with SCAN rather than KEYS so large
+/// keyspaces don't block the server
+pub(crate) async fn scan_keys(
+    conn: &mut MultiplexedConnection,
+    pattern: &str,
+) -> Result<Vec<String>, BrokerError> {
+    let mut iter = conn
+        .scan_match::<_, String>(pattern)
+        .await
+        .map_err(|e| BrokerError::OperationError(format!("Failed to scan {pattern} keys: {e}")))?;
+    let mut keys = Vec::new();
+    while let Some(key) = iter.next_item().await {
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+/// Fetch the string values of `keys` with at most `concurrency` GETs in flight
+///
+/// Each request draws its own connection from the pool, so no more than the
+/// pool's size are in flight whatever `concurrency` asks for. Results are
+/// returned in the same order as `keys`; keys that are missing or unreadable
+/// yield `None`.
+async fn fetch_values_concurrently(
+    pool: &ConnectionPool,
+    keys: &[String],
+    concurrency: usize,
+) -> Vec<Option<String>> {
+    let mut fetched: Vec<(usize, Option<String>)> = stream::iter(keys.iter().cloned().enumerate())
+        .map(|(index, key)| async move {
+            let value = match pool.get_connection().await {
+                Ok(mut conn) => conn.get::<_, Option<String>>(&key).await.ok().flatten(),
+                Err(_) => None,
+            };
+            (index, value)
+        })
+        .buffer_unordered(concurrency.clamp(1, pool.max_size()))
+        .collect()
+        .await;
+
+    // buffer_unordered yields in completion order, restore key order explicitly
+    fetched.sort_by_key(|(index, _)| *index);
+    fetched.into_iter().map(|(_, value)| value).collect()
 }
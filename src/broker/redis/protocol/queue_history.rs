@@ -0,0 +1,107 @@
+//! Queue length samples kept in Redis by lazycelery
+//!
+//! When `queue_history` is enabled, every queue refresh adds the length of each
+//! queue to a sorted set `lazycelery:qlen:<queue>`, scored by the sample time in
+//! Unix milliseconds. Samples older than `QUEUE_HISTORY_RETENTION` are trimmed
+//! on each write, so a restarted UI can recover recent history without the
+//! sets growing forever.
+
+use crate::error::BrokerError;
+use crate::models::Queue;
+use chrono::{DateTime, Utc};
+use redis::aio::MultiplexedConnection;
+use std::time::Duration;
+
+/// Prefix of the sorted sets holding queue length samples
+pub const QUEUE_HISTORY_PREFIX: &str = "lazycelery:qlen:";
+
+/// How long samples are kept
+pub const QUEUE_HISTORY_RETENTION: Duration = Duration::from_secs(7 * 86_400);
+
+/// Reads and writes queue length samples
+pub struct QueueHistory;
+
+impl QueueHistory {
+    /// Sorted set holding the samples of `queue`
+    pub fn key(queue: &str) -> String {
+        format!("{QUEUE_HISTORY_PREFIX}{queue}")
+    }
+
+    /// Record the current length of each of `queues` as sampled at `now`,
+    /// dropping samples that fell out of the retention window
+    pub async fn record(
+        conn: &mut MultiplexedConnection,
+        queues: &[Queue],
+        now: DateTime<Utc>,
+    ) -> Result<(), BrokerError> {
+        let millis = now.timestamp_millis();
+        let oldest = millis - QUEUE_HISTORY_RETENTION.as_millis() as i64;
+
+        let mut pipe = redis::pipe();
+        for queue in queues {
+            let key = Self::key(&queue.name);
+            // Members must be unique per sample, so the time is part of them
+            pipe.zadd(&key, format!("{millis}:{}", queue.length), millis)
+                .ignore()
+                .cmd("ZREMRANGEBYSCORE")
+                .arg(&key)
+                .arg("-inf")
+                .arg(format!("({oldest}"))
+                .ignore()
+                .expire(&key, QUEUE_HISTORY_RETENTION.as_secs() as i64)
+                .ignore();
+        }
+        pipe.query_async::<_, ()>(conn).await.map_err(|e| {
+            BrokerError::OperationError(format!("Failed to record queue history: {e}"))
+        })
+    }
+
+    /// Samples of `queue` taken at or after `since`, oldest first
+    pub async fn read(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, u64)>, BrokerError> {
+        let members: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(Self::key(queue))
+            .arg(since.timestamp_millis())
+            .arg("+inf")
+            .query_async(conn)
+            .await
+            .map_err(|e| {
+                BrokerError::OperationError(format!("Failed to read history of {queue}: {e}"))
+            })?;
+
+        Ok(members
+            .iter()
+            .filter_map(|member| Self::parse_sample(member))
+            .collect())
+    }
+
+    /// Time and length of a `<millis>:<length>` member
+    fn parse_sample(member: &str) -> Option<(DateTime<Utc>, u64)> {
+        let (millis, length) = member.split_once(':')?;
+        let time = DateTime::from_timestamp_millis(millis.parse().ok()?)?;
+        Some((time, length.parse().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_is_namespaced() {
+        assert_eq!(QueueHistory::key("celery"), "lazycelery:qlen:celery");
+    }
+
+    #[test]
+    fn test_parse_sample() {
+        let (time, length) = QueueHistory::parse_sample("1714564800000:42").unwrap();
+        assert_eq!(time.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        assert_eq!(length, 42);
+
+        assert_eq!(QueueHistory::parse_sample("garbage"), None);
+        assert_eq!(QueueHistory::parse_sample("1714564800000:-1"), None);
+    }
+}
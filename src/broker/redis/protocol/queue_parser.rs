@@ -2,13 +2,20 @@
 //!
 //! This module handles parsing queue information from Redis data structures.
 //! It discovers queues from kombu bindings and checks standard queue names
-//! to provide information about queue status and message counts.
+//! to provide information about queue status and message counts. Queues may be
+//! Redis lists or streams. A glob such as `tenantA.*` limits discovery to the
+//! queues of one tenant. Bound queues that look misconfigured are flagged with
+//! a `QueueDiscrepancy`. Consumer counts come from the queues inspected
+//! workers report consuming from.
 
+use super::{scan_keys, QueueStore};
 use crate::error::BrokerError;
-use crate::models::Queue;
+use crate::models::{Queue, QueueDiscrepancy, Worker};
+use chrono::{DateTime, Utc};
 use redis::aio::MultiplexedConnection;
-use redis::AsyncCommands;
-use std::collections::HashSet;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 /// Parser for queue-related data from Redis
 pub struct QueueParser;
@@ -18,42 +25,85 @@ impl QueueParser {
     ///
     /// Discovers active queues from kombu bindings and standard queue names,
     /// then checks their length and consumer information to build a comprehensive
-    /// view of the queue system.
+    /// view of the queue system. Message ages are measured up to `now`. With a
+    /// `glob`, only queues whose names match it are discovered. Bound queues
+    /// are listed even when empty if something about them looks wrong.
+    /// `consumers` maps queue names to the number of workers consuming them,
+    /// see `consumer_counts`.
     pub async fn parse_queues(
         connection: &MultiplexedConnection,
+        now: DateTime<Utc>,
+        glob: Option<&str>,
+        consumers: &HashMap<String, u32>,
     ) -> Result<Vec<Queue>, BrokerError> {
         let mut conn = connection.clone();
         let mut queues = Vec::new();
         let mut discovered_queues = HashSet::new();
+        let mut bound_queues = HashSet::new();
 
-        // First, discover queues from kombu bindings
-        let binding_keys: Vec<String> = conn.keys("_kombu.binding.*").await.unwrap_or_default();
+        // First, discover queues from kombu bindings, letting Redis apply the glob
+        let pattern = format!("_kombu.binding.{}", glob.unwrap_or("*"));
+        let binding_keys = scan_keys(&mut conn, &pattern).await.unwrap_or_default();
 
         for binding_key in binding_keys {
             if let Some(queue_name) = binding_key.strip_prefix("_kombu.binding.") {
                 discovered_queues.insert(queue_name.to_string());
+                bound_queues.insert(queue_name.to_string());
             }
         }
 
         // Also check for common queue names
         let common_queues = vec!["celery", "default", "priority", "high", "low"];
         for queue_name in common_queues {
-            discovered_queues.insert(queue_name.to_string());
+            if glob.is_none_or(|glob| Self::glob_matches(glob, queue_name)) {
+                discovered_queues.insert(queue_name.to_string());
+            }
         }
 
         // Check each discovered queue
-        for queue_name in discovered_queues {
-            let length: u64 = conn.llen(&queue_name).await.unwrap_or(0);
+        let discovered_queues: Vec<String> = discovered_queues.into_iter().collect();
+        let types_and_lengths =
+            QueueStore::types_and_lengths(&mut conn, &discovered_queues).await?;
+
+        for (queue_name, (key_type, length)) in discovered_queues.into_iter().zip(types_and_lengths)
+        {
+            let consumers = consumers.get(&queue_name).copied().unwrap_or(0);
+            let discrepancy = QueueDiscrepancy::detect(
+                bound_queues.contains(&queue_name),
+                &key_type,
+                length,
+                consumers,
+            );
 
-            // Only include queues that exist (have been used) or are standard
-            if length > 0 || ["celery", "default"].contains(&queue_name.as_str()) {
-                // Estimate consumers from worker data (simplified)
-                let consumers = if length > 0 { 1 } else { 0 }; // Simplified consumer count
+            // Only include queues that exist (have been used), are standard or
+            // need a closer look
+            if length > 0
+                || ["celery", "default"].contains(&queue_name.as_str())
+                || discrepancy.is_some()
+            {
+                // Streams record when each entry was added, which stands in
+                // for messages without timing information
+                let oldest_message_age = if length > 0 {
+                    QueueStore::oldest(&mut conn, &queue_name)
+                        .await
+                        .ok()
+                        .flatten()
+                        .and_then(|(message, added_millis)| {
+                            Self::message_age(&message, now).or_else(|| {
+                                let added = DateTime::from_timestamp_millis(added_millis?)?;
+                                Some((now - added).to_std().unwrap_or(Duration::ZERO))
+                            })
+                        })
+                } else {
+                    None
+                };
 
                 queues.push(Queue {
                     name: queue_name,
                     length,
                     consumers,
+                    oldest_message_age,
+                    discrepancy,
                 });
             }
         }
@@ -63,4 +113,220 @@ impl QueueParser {
 
         Ok(queues)
     }
+
+    /// Number of workers consuming each queue, from the queues `workers`
+    /// reported in their inspect replies
+    pub fn consumer_counts(workers: &[Worker]) -> HashMap<String, u32> {
+        let mut counts = HashMap::new();
+        for worker in workers {
+            let queues: HashSet<&String> = worker.queues.iter().collect();
+            for queue in queues {
+                *counts.entry(queue.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Whether `name` matches `glob`, like the patterns of Redis SCAN MATCH
+    ///
+    /// `*` matches any run of characters, `?` any single character and
+    /// `[abc]`, `[a-z]` or `[^abc]` one character of a class. `\` makes the
+    /// next character literal.
+    pub fn glob_matches(glob: &str, name: &str) -> bool {
+        let glob: Vec<char> = glob.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        let (mut g, mut n) = (0, 0);
+        // Where the last `*` was seen and how much of `name` it has taken so far
+        let mut star: Option<(usize, usize)> = None;
+
+        while n < name.len() {
+            if glob.get(g) == Some(&'*') {
+                star = Some((g, n));
+                g += 1;
+                continue;
+            }
+            match Self::match_char(&glob, g, name[n]) {
+                Some(next) => {
+                    g = next;
+                    n += 1;
+                }
+                None => match star {
+                    // Let the last `*` take one more character and retry
+                    Some((star_g, star_n)) => {
+                        star = Some((star_g, star_n + 1));
+                        g = star_g + 1;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        glob[g..].iter().all(|&c| c == '*')
+    }
+
+    /// Match `c` against the pattern element starting at `glob[g]`, returning
+    /// where the next element starts when it matches
+    ///
+    /// A `[` without a closing `]` is taken literally.
+    fn match_char(glob: &[char], g: usize, c: char) -> Option<usize> {
+        match *glob.get(g)? {
+            '?' => Some(g + 1),
+            '\\' if g + 1 < glob.len() => (glob[g + 1] == c).then_some(g + 2),
+            '[' => match Self::match_class(glob, g + 1, c) {
+                Some((matched, next)) => matched.then_some(next),
+                None => (c == '[').then_some(g + 1),
+            },
+            literal => (literal == c).then_some(g + 1),
+        }
+    }
+
+    /// Match `c` against the class whose body starts at `glob[start]`,
+    /// returning whether it matched and where the class ends, or `None` when
+    /// the class is never closed
+    fn match_class(glob: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+        let mut i = start;
+        let negated = glob.get(i) == Some(&'^');
+        if negated {
+            i += 1;
+        }
+
+        let mut matched = false;
+        loop {
+            match *glob.get(i)? {
+                ']' => return Some((matched != negated, i + 1)),
+                '\\' => {
+                    matched |= *glob.get(i + 1)? == c;
+                    i += 2;
+                }
+                low => match (glob.get(i + 1), glob.get(i + 2)) {
+                    (Some('-'), Some(&high)) if high != ']' => {
+                        // Redis accepts ranges written either way round
+                        let (low, high) = if low <= high {
+                            (low, high)
+                        } else {
+                            (high, low)
+                        };
+                        matched |= (low..=high).contains(&c);
+                        i += 3;
+                    }
+                    _ => {
+                        matched |= low == c;
+                        i += 1;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Work out how long a queued message has been waiting
+    ///
+    /// Uses the enqueue timestamp from the message properties or headers when the
+    /// producer set one, otherwise how far past its `eta` the message is. Opaque
+    /// messages and messages without timing information yield `None`.
+    pub fn message_age(message: &str, now: DateTime<Utc>) -> Option<Duration> {
+        let message: Value = serde_json::from_str(message).ok()?;
+
+        let enqueued_at = ["properties", "headers"]
+            .iter()
+            .filter_map(|section| message.get(section)?.get("timestamp")?.as_f64())
+            .find_map(|secs| DateTime::from_timestamp_millis((secs * 1000.0) as i64))
+            .or_else(|| {
+                message
+                    .get("headers")?
+                    .get("eta")?
+                    .as_str()?
+                    .parse::<DateTime<Utc>>()
+                    .ok()
+            })?;
+
+        // Messages scheduled for the future haven't started waiting yet
+        Some((now - enqueued_at).to_std().unwrap_or(Duration::ZERO))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_age_from_timestamp_property() {
+        let now = Utc::now();
+        let sent = now.timestamp() as f64 - 90.0;
+        let message = json!({"properties": {"timestamp": sent}, "headers": {}}).to_string();
+
+        let age = QueueParser::message_age(&message, now).unwrap();
+        assert!(age >= Duration::from_secs(89) && age <= Duration::from_secs(91));
+    }
+
+    #[test]
+    fn test_message_age_from_eta() {
+        let now = Utc::now();
+        let eta = (now - chrono::Duration::seconds(30)).to_rfc3339();
+        let message = json!({"headers": {"eta": eta}}).to_string();
+        assert!(QueueParser::message_age(&message, now).unwrap() >= Duration::from_secs(29));
+
+        let future = (now + chrono::Duration::seconds(30)).to_rfc3339();
+        let message = json!({"headers": {"eta": future}}).to_string();
+        assert_eq!(
+            QueueParser::message_age(&message, now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(QueueParser::glob_matches("*", "celery"));
+        assert!(QueueParser::glob_matches("tenantA.*", "tenantA.celery"));
+        assert!(!QueueParser::glob_matches("tenantA.*", "tenantB.celery"));
+        assert!(!QueueParser::glob_matches("tenantA.*", "celery"));
+        assert!(QueueParser::glob_matches("*.celery", "tenantB.celery"));
+        assert!(QueueParser::glob_matches(
+            "tenant?.*.high",
+            "tenantA.x.y.high"
+        ));
+        assert!(!QueueParser::glob_matches(
+            "tenant?.celery",
+            "tenantAB.celery"
+        ));
+        assert!(QueueParser::glob_matches("celery", "celery"));
+        assert!(!QueueParser::glob_matches("celery", "celery2"));
+    }
+
+    #[test]
+    fn test_glob_matches_classes_and_escapes() {
+        assert!(QueueParser::glob_matches("tenant[AB].*", "tenantB.celery"));
+        assert!(!QueueParser::glob_matches("tenant[AB].*", "tenantC.celery"));
+        assert!(QueueParser::glob_matches("q[0-9]", "q7"));
+        assert!(!QueueParser::glob_matches("q[0-9]", "qx"));
+        assert!(QueueParser::glob_matches("q[^0-9]", "qx"));
+        assert!(!QueueParser::glob_matches("q[^0-9]", "q7"));
+        assert!(QueueParser::glob_matches("*[-_]high", "reports_high"));
+        // Escaped wildcards and unclosed classes match themselves
+        assert!(QueueParser::glob_matches("what\\?", "what?"));
+        assert!(!QueueParser::glob_matches("what\\?", "whats"));
+        assert!(QueueParser::glob_matches("odd[name", "odd[name"));
+    }
+
+    #[test]
+    fn test_consumer_counts_count_each_worker_once_per_queue() {
+        let worker = |hostname: &str, queues: &[&str]| Worker {
+            queues: queues.iter().map(|q| q.to_string()).collect(),
+            ..Worker::new(hostname.to_string())
+        };
+        let counts = QueueParser::consumer_counts(&[
+            worker("w1@host", &["celery", "reports", "celery"]),
+            worker("w2@host", &["celery"]),
+        ]);
+        assert_eq!(counts.get("celery"), Some(&2));
+        assert_eq!(counts.get("reports"), Some(&1));
+        assert_eq!(counts.get("default"), None);
+    }
+
+    #[test]
+    fn test_message_age_of_opaque_message_is_none() {
+        let now = Utc::now();
+        assert_eq!(QueueParser::message_age("not json at all", now), None);
+        assert_eq!(QueueParser::message_age(r#"{"headers": {}}"#, now), None);
+    }
 }
@@ -0,0 +1,243 @@
+//! Queue storage for Redis Celery protocol
+//!
+//! Kombu keeps queue messages in Redis LISTs, but newer transports and some
+//! custom setups append them to Redis Streams instead. This module detects how
+//! a queue is stored and reads its length and messages either way.
+
+use crate::error::BrokerError;
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+
+/// Stream entry fields holding the message, in the order they are tried
+const STREAM_MESSAGE_FIELDS: [&str; 4] = ["payload", "message", "body", "data"];
+
+/// How the messages of a queue are stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueKind {
+    List,
+    Stream,
+}
+
+/// Reads queues regardless of whether they are lists or streams
+pub struct QueueStore;
+
+impl QueueStore {
+    /// Redis type of the `queue` key as reported by `TYPE`, `none` when it
+    /// doesn't exist
+    pub async fn key_type(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+    ) -> Result<String, BrokerError> {
+        redis::cmd("TYPE")
+            .arg(queue)
+            .query_async(conn)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))
+    }
+
+    /// Detect how `queue` is stored, keys that don't exist count as lists
+    pub async fn kind(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+    ) -> Result<QueueKind, BrokerError> {
+        Ok(match Self::key_type(conn, queue).await?.as_str() {
+            "stream" => QueueKind::Stream,
+            _ => QueueKind::List,
+        })
+    }
+
+    /// Number of messages waiting in `queue`
+    pub async fn length(conn: &mut MultiplexedConnection, queue: &str) -> Result<u64, BrokerError> {
+        let length = match Self::kind(conn, queue).await? {
+            QueueKind::List => conn.llen(queue).await,
+            QueueKind::Stream => redis::cmd("XLEN").arg(queue).query_async(conn).await,
+        };
+        length.map_err(|e| BrokerError::OperationError(e.to_string()))
+    }
+
+    /// Redis type and length of each of `queues`, in order
+    ///
+    /// The types are read in one pipeline and the lengths in another, keys
+    /// that are neither lists nor streams have length 0.
+    pub async fn types_and_lengths(
+        conn: &mut MultiplexedConnection,
+        queues: &[String],
+    ) -> Result<Vec<(String, u64)>, BrokerError> {
+        if queues.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pipe = redis::pipe();
+        for queue in queues {
+            pipe.cmd("TYPE").arg(queue);
+        }
+        let types: Vec<String> = pipe
+            .query_async(conn)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+
+        let mut pipe = redis::pipe();
+        for (queue, key_type) in queues.iter().zip(&types) {
+            match key_type.as_str() {
+                "list" => pipe.cmd("LLEN").arg(queue),
+                "stream" => pipe.cmd("XLEN").arg(queue),
+                _ => continue,
+            };
+        }
+        let mut lengths = if pipe.cmd_iter().next().is_some() {
+            pipe.query_async::<_, Vec<u64>>(conn)
+                .await
+                .map_err(|e| BrokerError::OperationError(e.to_string()))?
+                .into_iter()
+        } else {
+            Vec::new().into_iter()
+        };
+
+        Ok(types
+            .into_iter()
+            .map(|key_type| {
+                let length = match key_type.as_str() {
+                    "list" | "stream" => lengths.next().unwrap_or(0),
+                    _ => 0,
+                };
+                (key_type, length)
+            })
+            .collect())
+    }
+
+    /// Read up to `count` messages from the head of `queue` without removing
+    /// them, the next one to be consumed first
+    ///
+    /// Kombu pushes on the left and pops from the right, so the head of a list
+    /// is its tail. The head of a stream is its oldest entry.
+    pub async fn head(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+        count: usize,
+    ) -> Result<Vec<String>, BrokerError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        match Self::kind(conn, queue).await? {
+            QueueKind::List => conn
+                .lrange::<_, Vec<String>>(queue, -(count as isize), -1)
+                .await
+                .map(|mut messages| {
+                    messages.reverse();
+                    messages
+                })
+                .map_err(|e| BrokerError::OperationError(e.to_string())),
+            QueueKind::Stream => Ok(Self::read_stream(conn, queue, count)
+                .await?
+                .into_iter()
+                .map(|(_, message)| message)
+                .collect()),
+        }
+    }
+
+    /// The message that has been waiting longest in `queue`, with the time a
+    /// stream recorded it in milliseconds since the epoch
+    ///
+    /// Kombu pushes on the left and pops from the right, so the oldest message
+    /// of a list sits at its tail.
+    pub async fn oldest(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+    ) -> Result<Option<(String, Option<i64>)>, BrokerError> {
+        match Self::kind(conn, queue).await? {
+            QueueKind::List => conn
+                .lindex::<_, Option<String>>(queue, -1)
+                .await
+                .map(|message| message.map(|message| (message, None)))
+                .map_err(|e| BrokerError::OperationError(e.to_string())),
+            QueueKind::Stream => Ok(Self::read_stream(conn, queue, 1)
+                .await?
+                .into_iter()
+                .next()
+                .map(|(id, message)| (message, Self::entry_millis(&id)))),
+        }
+    }
+
+    /// Read up to `count` entries from the start of a stream as `(id, message)`
+    ///
+    /// Entries without a message field are skipped.
+    async fn read_stream(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+        count: usize,
+    ) -> Result<Vec<(String, String)>, BrokerError> {
+        let entries: Vec<(String, Vec<(String, String)>)> = redis::cmd("XRANGE")
+            .arg(queue)
+            .arg("-")
+            .arg("+")
+            .arg("COUNT")
+            .arg(count)
+            .query_async(conn)
+            .await
+            .map_err(|e| BrokerError::OperationError(e.to_string()))?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|(id, fields)| Some((id, Self::entry_message(fields)?)))
+            .collect())
+    }
+
+    /// Pick the message out of a stream entry's fields, a lone field is taken
+    /// whatever its name
+    fn entry_message(mut fields: Vec<(String, String)>) -> Option<String> {
+        if fields.len() == 1 {
+            return fields.pop().map(|(_, value)| value);
+        }
+        STREAM_MESSAGE_FIELDS.iter().find_map(|name| {
+            fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value.clone())
+        })
+    }
+
+    /// Milliseconds since the epoch encoded in a stream entry id like `1700000000000-0`
+    fn entry_millis(id: &str) -> Option<i64> {
+        id.split_once('-')
+            .map_or(id, |(millis, _)| millis)
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_entry_message_picks_known_field() {
+        assert_eq!(
+            QueueStore::entry_message(fields(&[("anything", "{}")])),
+            Some("{}".to_string())
+        );
+        assert_eq!(
+            QueueStore::entry_message(fields(&[("kind", "task"), ("body", "{\"a\":1}")])),
+            Some("{\"a\":1}".to_string())
+        );
+        assert_eq!(
+            QueueStore::entry_message(fields(&[("kind", "task"), ("other", "x")])),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entry_millis() {
+        assert_eq!(
+            QueueStore::entry_millis("1700000000000-3"),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(QueueStore::entry_millis("garbage"), None);
+    }
+}
@@ -0,0 +1,212 @@
+//! Beat schedule parser for Redis
+//!
+//! The redisbeat scheduler keeps the celery beat schedule in a sorted set. Each
+//! member is a jsonpickle-encoded `ScheduleEntry` and its score is the unix
+//! time of the next run.
+
+use crate::error::BrokerError;
+use crate::models::ScheduledEntry;
+use crate::utils::formatting::format_interval;
+use chrono::DateTime;
+use redis::aio::MultiplexedConnection;
+use redis::AsyncCommands;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Sorted set the redisbeat scheduler stores its entries in
+const REDISBEAT_SCHEDULE_KEY: &str = "celery:beat:order_tasks";
+
+/// Parser for celery beat schedule entries
+pub struct ScheduleParser;
+
+impl ScheduleParser {
+    /// Read the beat schedule, ordered by next run time
+    ///
+    /// A missing schedule key yields an empty list.
+    pub async fn parse_schedule(
+        connection: &MultiplexedConnection,
+    ) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        let mut conn = connection.clone();
+        let members: Vec<(String, f64)> = conn
+            .zrange_withscores(REDISBEAT_SCHEDULE_KEY, 0, -1)
+            .await
+            .map_err(|e| {
+                BrokerError::OperationError(format!("Failed to read beat schedule: {e}"))
+            })?;
+
+        Ok(members
+            .iter()
+            .filter_map(|(payload, score)| Self::parse_entry(payload, *score))
+            .collect())
+    }
+
+    /// Parse one jsonpickle-encoded schedule entry, `next_run` being its score
+    pub fn parse_entry(payload: &str, next_run: f64) -> Option<ScheduledEntry> {
+        let entry: Value = serde_json::from_str(payload).ok()?;
+        let name = entry.get("name")?.as_str()?.to_string();
+        let task = entry
+            .get("task")
+            .and_then(Value::as_str)
+            .unwrap_or(&name)
+            .to_string();
+        let schedule = entry
+            .get("schedule")
+            .map(Self::describe_schedule)
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Some(ScheduledEntry {
+            name,
+            task,
+            schedule,
+            next_run: DateTime::from_timestamp_millis((next_run * 1000.0) as i64),
+        })
+    }
+
+    /// Describe an encoded `celery.schedules` object for display
+    pub fn describe_schedule(schedule: &Value) -> String {
+        let kind = schedule
+            .get("py/object")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+
+        match kind {
+            "celery.schedules.schedule" => schedule
+                .get("run_every")
+                .and_then(Self::timedelta_seconds)
+                .map(|seconds| format!("every {}", format_interval(Duration::from_secs(seconds))))
+                .unwrap_or_else(|| "interval".to_string()),
+            "celery.schedules.crontab" => {
+                let field = |name: &str| {
+                    schedule
+                        .get(name)
+                        .and_then(Value::as_str)
+                        .unwrap_or("*")
+                        .to_string()
+                };
+                format!(
+                    "crontab {} {} {} {} {}",
+                    field("_orig_minute"),
+                    field("_orig_hour"),
+                    field("_orig_day_of_month"),
+                    field("_orig_month_of_year"),
+                    field("_orig_day_of_week"),
+                )
+            }
+            "celery.schedules.solar" => match schedule.get("event").and_then(Value::as_str) {
+                Some(event) => format!("solar {event}"),
+                None => "solar".to_string(),
+            },
+            "" => "unknown".to_string(),
+            other => other.rsplit('.').next().unwrap_or(other).to_string(),
+        }
+    }
+
+    /// Total seconds of a jsonpickle-encoded `datetime.timedelta`
+    ///
+    /// The arguments are `(days, seconds, microseconds)`, stored under
+    /// `py/reduce` by current jsonpickle and `__reduce__` by older versions.
+    fn timedelta_seconds(value: &Value) -> Option<u64> {
+        let reduce = value.get("py/reduce").or_else(|| value.get("__reduce__"))?;
+        let args = reduce.get(1)?;
+        let args = args.get("py/tuple").unwrap_or(args).as_array()?;
+        let days = args.first().and_then(Value::as_f64).unwrap_or(0.0);
+        let seconds = args.get(1).and_then(Value::as_f64).unwrap_or(0.0);
+        Some((days * 86_400.0 + seconds) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_interval_entry() {
+        let payload = json!({
+            "py/object": "celery.beat.ScheduleEntry",
+            "name": "add-every-10s",
+            "task": "tasks.add",
+            "schedule": {
+                "py/object": "celery.schedules.schedule",
+                "run_every": {
+                    "py/reduce": [{"py/type": "datetime.timedelta"}, {"py/tuple": [0, 10, 0]}]
+                },
+                "relative": false
+            },
+            "args": {"py/tuple": [1, 2]},
+            "kwargs": {},
+            "options": {}
+        })
+        .to_string();
+
+        let entry = ScheduleParser::parse_entry(&payload, 1_700_000_000.0).unwrap();
+        assert_eq!(entry.name, "add-every-10s");
+        assert_eq!(entry.task, "tasks.add");
+        assert_eq!(entry.schedule, "every 10s");
+        assert_eq!(entry.next_run.unwrap().timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_parse_crontab_entry() {
+        let payload = json!({
+            "name": "nightly-report",
+            "task": "reports.nightly",
+            "schedule": {
+                "py/object": "celery.schedules.crontab",
+                "_orig_minute": "30",
+                "_orig_hour": "2",
+                "_orig_day_of_week": "*",
+                "_orig_day_of_month": "*",
+                "_orig_month_of_year": "*"
+            }
+        })
+        .to_string();
+
+        let entry = ScheduleParser::parse_entry(&payload, 1_700_000_000.0).unwrap();
+        assert_eq!(entry.schedule, "crontab 30 2 * * *");
+    }
+
+    #[test]
+    fn test_describe_legacy_timedelta_and_units() {
+        let schedule = |reduce_args: Value| {
+            json!({
+                "py/object": "celery.schedules.schedule",
+                "run_every": {"__reduce__": [{"py/type": "datetime.timedelta"}, reduce_args]}
+            })
+        };
+
+        assert_eq!(
+            ScheduleParser::describe_schedule(&schedule(json!([0, 300, 0]))),
+            "every 5m"
+        );
+        assert_eq!(
+            ScheduleParser::describe_schedule(&schedule(json!([0, 7200, 0]))),
+            "every 2h"
+        );
+        assert_eq!(
+            ScheduleParser::describe_schedule(&schedule(json!([1, 0, 0]))),
+            "every 1d"
+        );
+        assert_eq!(
+            ScheduleParser::describe_schedule(&schedule(json!([0, 90, 0]))),
+            "every 90s"
+        );
+    }
+
+    #[test]
+    fn test_describe_other_schedules() {
+        let solar = json!({"py/object": "celery.schedules.solar", "event": "sunrise"});
+        assert_eq!(ScheduleParser::describe_schedule(&solar), "solar sunrise");
+
+        let custom = json!({"py/object": "myapp.schedules.business_hours"});
+        assert_eq!(ScheduleParser::describe_schedule(&custom), "business_hours");
+
+        assert_eq!(ScheduleParser::describe_schedule(&json!({})), "unknown");
+    }
+
+    #[test]
+    fn test_parse_entry_rejects_malformed_payloads() {
+        assert!(ScheduleParser::parse_entry("not json", 0.0).is_none());
+        assert!(ScheduleParser::parse_entry(r#"{"task": "tasks.add"}"#, 0.0).is_none());
+    }
+}
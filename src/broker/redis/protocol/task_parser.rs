@@ -4,62 +4,265 @@
 //! It extracts task metadata, status, and combines information from both
 //! completed tasks (metadata) and pending tasks (queue messages).
 
+use super::{fetch_values_concurrently, scan_keys, Parsed, QueueStore};
+use crate::broker::redis::pool::ConnectionPool;
+use crate::broker::{BrokerOptions, ScanCap};
 use crate::error::BrokerError;
-use crate::models::{Task, TaskStatus};
+use crate::models::{looks_like_exception, ResultKind, Task, TaskStatus};
 use base64::Engine;
 use chrono::{DateTime, Utc};
 use redis::aio::MultiplexedConnection;
 use redis::AsyncCommands;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::io::Read;
 
 // Configuration constants for task parsing
-const MAX_TASK_RESULTS: usize = 100;
-const MAX_QUEUE_MESSAGES: usize = 100;
-const MAX_PENDING_TASKS: usize = 20;
+const MAX_UNACKED_TASKS: usize = 100;
+
+/// Kombu's hash of delivered but not yet acknowledged messages, keyed by delivery tag
+const UNACKED_KEY: &str = "unacked";
+/// Kombu's sorted set of delivery tags scored by delivery time
+const UNACKED_INDEX_KEY: &str = "unacked_index";
+
+/// Tasks gathered from result metadata and broker messages, one per id in the
+/// order they were first seen
+///
+/// Result metadata is authoritative for how a task went (status, result,
+/// traceback, completion time), the message for what was sent (name,
+/// arguments, queue, origin). A task found in both ends up with the best fields
+/// of each, whichever source was read first.
+#[derive(Debug, Default)]
+struct TaskSet {
+    tasks: Vec<Task>,
+    /// Position of each id in `tasks`, and whether its metadata was read
+    index: HashMap<String, (usize, bool)>,
+    /// Metadata values and messages skipped because they couldn't be parsed
+    malformed: usize,
+}
+
+impl TaskSet {
+    /// Add a task read from its `celery-task-meta-*` result
+    fn add_metadata(&mut self, task: Task) {
+        match self.index.get_mut(&task.id) {
+            Some((position, has_metadata)) => {
+                let existing = &mut self.tasks[*position];
+                if *has_metadata {
+                    return;
+                }
+                *has_metadata = true;
+                let message = std::mem::replace(existing, task);
+                Self::merge_message(existing, message);
+            }
+            None => self.push(task, true),
+        }
+    }
+
+    /// Add a task read from a queued or unacknowledged message
+    fn add_message(&mut self, task: Task) {
+        match self.index.get(&task.id) {
+            Some(&(position, _)) => Self::merge_message(&mut self.tasks[position], task),
+            None => self.push(task, false),
+        }
+    }
+
+    fn push(&mut self, task: Task, has_metadata: bool) {
+        self.index
+            .insert(task.id.clone(), (self.tasks.len(), has_metadata));
+        self.tasks.push(task);
+    }
+
+    /// Fill `task` in with what a message for the same id says
+    ///
+    /// The message wins for the fields it carries. A message a worker is
+    /// holding turns a task still reported pending into an active one.
+    fn merge_message(task: &mut Task, message: Task) {
+        task.name = message.name;
+        task.args = message.args;
+        task.kwargs = message.kwargs;
+        task.args_full_len = message.args_full_len;
+        task.kwargs_full_len = message.kwargs_full_len;
+        task.queue = message.queue.or(task.queue.take());
+        task.worker = message.worker.or(task.worker.take());
+        task.eta = message.eta.or(task.eta);
+        task.root_id = message.root_id.or(task.root_id.take());
+        task.parent_id = message.parent_id.or(task.parent_id.take());
+        task.group_id = message.group_id.or(task.group_id.take());
+        task.retries = task.retries.max(message.retries);
+        task.max_retries = message.max_retries.or(task.max_retries);
+        task.priority = message.priority.or(task.priority);
+        if task.status == TaskStatus::Pending && message.status == TaskStatus::Active {
+            task.status = TaskStatus::Active;
+        }
+    }
+
+    /// Note a metadata value or message that couldn't be parsed
+    fn skip_malformed(&mut self) {
+        self.malformed += 1;
+    }
+
+    fn into_tasks(self) -> Vec<Task> {
+        self.tasks
+    }
+}
 
 /// Parser for task-related data from Redis
 pub struct TaskParser;
 
 impl TaskParser {
-    /// Parse tasks from Redis connection
+    /// Parse tasks using connections from the broker and result backend pools
+    ///
+    /// Combines information from task metadata (completed tasks), unacknowledged
+    /// messages (in-progress tasks) and queue messages (pending tasks) to provide a
+    /// comprehensive view of all tasks. Messages are read from the broker `pool`
+    /// and metadata from the `backend` pool, which may be the same pool. Metadata
+    /// is fetched concurrently, bounded by the backend pool size. Reads stop at
+    /// `options.limits`, and the returned cap says whether any was hit.
+    pub async fn parse_tasks(
+        pool: &ConnectionPool,
+        backend: &ConnectionPool,
+        options: &BrokerOptions,
+    ) -> Result<Parsed<Task>, BrokerError> {
+        Self::parse_tasks_with_concurrency(pool, backend, options, backend.max_size()).await
+    }
+
+    /// Parse tasks with at most `concurrency` metadata requests in flight
     ///
-    /// Combines information from task metadata (completed tasks) and queue messages
-    /// (pending tasks) to provide a comprehensive view of all tasks.
-    pub async fn parse_tasks(connection: &MultiplexedConnection) -> Result<Vec<Task>, BrokerError> {
-        let mut conn = connection.clone();
-        let mut tasks = Vec::new();
+    /// A concurrency of 1 fetches metadata serially. The resulting task order
+    /// does not depend on the concurrency level.
+    pub async fn parse_tasks_with_concurrency(
+        pool: &ConnectionPool,
+        backend: &ConnectionPool,
+        options: &BrokerOptions,
+        concurrency: usize,
+    ) -> Result<Parsed<Task>, BrokerError> {
+        let mut tasks = TaskSet::default();
+        let limits = &options.limits;
+        let now = options.clock.now();
 
-        // First, get task names from pending queue messages
-        let task_names = Self::get_queue_messages(&mut conn).await?;
+        // First, get task names from pending queue messages. No lease is held
+        // while the metadata is fetched, which draws connections of its own.
+        let task_names = {
+            let mut conn = pool.get_connection().await?;
+            Self::get_queue_messages(&mut conn, limits.max_queue_messages).await?
+        };
 
         // Get task results from metadata keys
-        Self::parse_task_metadata(&mut conn, &mut tasks, &task_names).await?;
+        let metadata_cap =
+            Self::parse_task_metadata(backend, options, concurrency, &mut tasks, &task_names)
+                .await?;
+
+        let mut conn = pool.get_connection().await?;
+
+        // Add tasks reserved by workers but not yet acknowledged
+        Self::add_unacked_tasks(&mut conn, &mut tasks, now, options.max_args_length).await;
 
         // Add pending tasks from queues that might not have metadata yet
-        Self::add_pending_tasks_from_queues(&mut conn, &mut tasks).await?;
+        let pending_cap = Self::add_pending_tasks_from_queues(
+            &mut conn,
+            &mut tasks,
+            limits.max_pending_tasks,
+            now,
+            options.max_args_length,
+        )
+        .await?;
+
+        let malformed = tasks.malformed;
+        Ok(Parsed {
+            items: tasks.into_tasks(),
+            capped: ScanCap::merge(metadata_cap, pending_cap),
+            malformed,
+        })
+    }
+
+    /// Parse at most `limit` pending tasks from queue messages
+    ///
+    /// Skips the expensive metadata key scan, so completed tasks are only
+    /// picked up by `parse_tasks`.
+    pub async fn parse_recent_tasks(
+        pool: &ConnectionPool,
+        options: &BrokerOptions,
+        limit: usize,
+    ) -> Result<Vec<Task>, BrokerError> {
+        let mut conn = pool.get_connection().await?;
+        let mut tasks = TaskSet::default();
+        let now = options.clock.now();
+
+        Self::add_unacked_tasks(&mut conn, &mut tasks, now, options.max_args_length).await;
+        Self::add_pending_tasks_from_queues(
+            &mut conn,
+            &mut tasks,
+            options.limits.max_pending_tasks,
+            now,
+            options.max_args_length,
+        )
+        .await?;
+        let mut tasks = tasks.into_tasks();
+        tasks.truncate(limit);
 
         Ok(tasks)
     }
 
+    /// Parse the stored `celery-task-meta-<id>` value of a single task
+    pub fn parse_task(
+        task_id: &str,
+        data: &str,
+        options: &BrokerOptions,
+    ) -> Result<Task, BrokerError> {
+        let task_data: Value = serde_json::from_str(data).map_err(|e| {
+            BrokerError::OperationError(format!("Malformed metadata for task {task_id}: {e}"))
+        })?;
+        Self::extract_task_from_metadata(
+            &format!("celery-task-meta-{task_id}"),
+            &task_data,
+            &HashMap::new(),
+            options,
+        )
+    }
+
+    /// Parse up to `n` messages from the head of `queue` without removing them
+    ///
+    /// Messages that aren't Celery task messages are skipped, so fewer than
+    /// `n` tasks may be returned. Tasks are stamped with `now`. Only a few
+    /// messages are read, so their arguments are kept whole.
+    pub async fn peek_queue(
+        conn: &mut MultiplexedConnection,
+        queue: &str,
+        n: usize,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Task>, BrokerError> {
+        let messages = QueueStore::head(conn, queue, n).await.map_err(|e| {
+            BrokerError::OperationError(format!("Failed to read queue {queue}: {e}"))
+        })?;
+
+        Ok(messages
+            .iter()
+            .filter_map(|message| serde_json::from_str::<Value>(message).ok())
+            .filter_map(|message| {
+                Self::parse_task_message(&message, Some(queue), now, 0)
+                    .ok()
+                    .flatten()
+            })
+            .collect())
+    }
+
     /// Extract task names and IDs from queue messages
     ///
     /// Scans common queues to build a mapping of task IDs to task names,
     /// which helps identify task types for completed tasks that may not
-    /// have this information in their metadata.
+    /// have this information in their metadata. At most `limit` messages are
+    /// read from each queue.
     async fn get_queue_messages(
         conn: &mut MultiplexedConnection,
+        limit: usize,
     ) -> Result<HashMap<String, String>, BrokerError> {
         let mut task_names: HashMap<String, String> = HashMap::new();
         let queue_names = vec!["celery", "default", "priority"];
 
         for queue_name in &queue_names {
-            match conn.llen::<_, u64>(queue_name).await {
-                Ok(queue_length) if queue_length > 0 => {
-                    match conn
-                        .lrange::<_, Vec<String>>(queue_name, 0, MAX_QUEUE_MESSAGES as isize)
-                        .await
-                    {
+            match QueueStore::length(conn, queue_name).await {
+                Ok(queue_length) if queue_length > 0 && limit > 0 => {
+                    match QueueStore::head(conn, queue_name, limit).await {
                         Ok(messages) => {
                             for message in &messages {
                                 if let Ok(task_message) = serde_json::from_str::<Value>(message) {
@@ -94,109 +297,258 @@ impl TaskParser {
     /// Parse task metadata from Redis keys
     ///
     /// Processes completed task metadata stored in Redis to extract task
-    /// information including status, results, and execution details.
+    /// information including status, results, and execution details. Only the
+    /// first `options.limits.max_task_results` keys are read.
     async fn parse_task_metadata(
-        conn: &mut MultiplexedConnection,
-        tasks: &mut Vec<Task>,
+        pool: &ConnectionPool,
+        options: &BrokerOptions,
+        concurrency: usize,
+        tasks: &mut TaskSet,
         task_names: &HashMap<String, String>,
-    ) -> Result<(), BrokerError> {
-        let task_keys: Vec<String> = conn.keys("celery-task-meta-*").await.map_err(|e| {
-            BrokerError::OperationError(format!("Failed to get task metadata keys: {e}"))
-        })?;
+    ) -> Result<Option<ScanCap>, BrokerError> {
+        let mut task_keys = {
+            let mut conn = pool.get_connection().await?;
+            scan_keys(&mut conn, "celery-task-meta-*").await?
+        };
+        let limit = options.limits.max_task_results;
+        let capped = ScanCap::check(task_keys.len(), limit);
+        task_keys.truncate(limit);
 
-        for key in task_keys.iter().take(MAX_TASK_RESULTS) {
-            match conn.get::<_, String>(key).await {
-                Ok(data) => {
-                    match serde_json::from_str::<Value>(&data) {
-                        Ok(task_data) => {
-                            match Self::extract_task_from_metadata(key, &task_data, task_names) {
-                                Ok(task) => tasks.push(task),
-                                Err(_) => {
-                                    // Skip malformed task metadata - continue processing
-                                    continue;
-                                }
-                            }
-                        }
-                        Err(_) => {
-                            // Skip malformed JSON - continue processing
-                            continue;
-                        }
+        let values = fetch_values_concurrently(pool, &task_keys, concurrency).await;
+
+        for (key, value) in task_keys.iter().zip(values) {
+            // Skip inaccessible keys - continue processing
+            let Some(data) = value else {
+                continue;
+            };
+
+            match serde_json::from_str::<Value>(&data) {
+                Ok(task_data) => {
+                    match Self::extract_task_from_metadata(key, &task_data, task_names, options) {
+                        Ok(task) => tasks.add_metadata(task),
+                        // Skip malformed task metadata - continue processing
+                        Err(_) => tasks.skip_malformed(),
                     }
                 }
-                Err(_) => {
-                    // Skip inaccessible keys - continue processing
-                    continue;
-                }
+                // Skip malformed JSON - continue processing
+                Err(_) => tasks.skip_malformed(),
             }
         }
 
-        Ok(())
+        Ok(capped)
     }
 
     /// Extract task information from metadata
     ///
     /// Converts raw task metadata into a Task struct with all relevant
-    /// information including status, results, and timing data.
+    /// information including status, results, and timing data. Oversized
+    /// results and tracebacks are truncated to `options.max_result_length`,
+    /// args and kwargs to `options.max_args_length`.
     fn extract_task_from_metadata(
         key: &str,
         task_data: &Value,
         task_names: &HashMap<String, String>,
+        options: &BrokerOptions,
     ) -> Result<Task, BrokerError> {
         let task_id = key
             .strip_prefix("celery-task-meta-")
             .unwrap_or("unknown")
             .to_string();
 
-        let timestamp = Self::parse_timestamp(task_data);
+        let timestamp = Self::parse_timestamp(task_data, options.clock.now());
         let task_name = Self::get_task_name(&task_id, task_data, task_names);
         let status = Self::parse_task_status(task_data);
 
-        Ok(Task {
-            id: task_id,
-            name: task_name,
-            args: task_data
+        let (result, result_full_len) = task_data
+            .get("result")
+            .filter(|r| !r.is_null())
+            .map(|r| Self::limit_length(ResultKind::render(r), options.max_result_length))
+            .unzip();
+        let result_kind = task_data
+            .get("result")
+            .filter(|r| !r.is_null())
+            .map(ResultKind::classify);
+        let looks_like_error = task_data.get("result").is_some_and(looks_like_exception);
+        let (traceback, traceback_full_len) = task_data
+            .get("traceback")
+            .and_then(|t| t.as_str())
+            .map(|s| Self::limit_length(s.to_string(), options.max_result_length))
+            .unzip();
+        let (args, args_full_len) = Self::limit_length(
+            task_data
                 .get("args")
                 .map(|a| a.to_string())
                 .unwrap_or_else(|| "[]".to_string()),
-            kwargs: task_data
+            options.max_args_length,
+        );
+        let (kwargs, kwargs_full_len) = Self::limit_length(
+            task_data
                 .get("kwargs")
                 .map(|k| k.to_string())
                 .unwrap_or_else(|| "{}".to_string()),
+            options.max_args_length,
+        );
+
+        Ok(Task {
+            id: task_id,
+            name: task_name,
+            args,
+            kwargs,
             status,
-            worker: None, // Task metadata doesn't contain worker hostname
+            worker: Self::worker_field(task_data),
             timestamp,
-            result: task_data.get("result").and_then(|r| {
-                if r.is_null() {
-                    None
-                } else {
-                    Some(r.to_string())
-                }
-            }),
-            traceback: task_data
-                .get("traceback")
-                .and_then(|t| t.as_str())
-                .map(|s| s.to_string()),
+            result,
+            result_kind,
+            looks_like_error,
+            traceback,
+            result_full_len: result_full_len.flatten(),
+            traceback_full_len: traceback_full_len.flatten(),
+            args_full_len,
+            kwargs_full_len,
+            eta: None,
+            root_id: Self::string_field(task_data, "root_id"),
+            parent_id: Self::string_field(task_data, "parent_id"),
+            group_id: Self::string_field(task_data, "group_id"),
+            queue: Self::routing_key(Some(task_data)),
+            retries: Self::u32_field(task_data, "retries").unwrap_or(0),
+            max_retries: Self::u32_field(task_data, "max_retries"),
+            priority: None,
+            children: Self::children_field(task_data),
+            duration: Self::duration_field(task_data),
         })
     }
 
+    /// Read an optional non-negative integer field, treating JSON null as absent
+    fn u32_field(value: &Value, key: &str) -> Option<u32> {
+        value
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u32::try_from(v).ok())
+    }
+
+    /// Priority from the message `properties`, values outside 0-255 are ignored
+    fn priority_field(properties: Option<&Value>) -> Option<u8> {
+        properties?
+            .get("priority")
+            .and_then(|v| v.as_u64())
+            .and_then(|v| u8::try_from(v).ok())
+    }
+
+    /// Read the ids listed in `children`
+    ///
+    /// Celery stores each child as a serialized result tuple such as
+    /// `[["child-id", null], null]`, so the first id found in each entry is taken.
+    fn children_field(value: &Value) -> Vec<String> {
+        value
+            .get("children")
+            .and_then(Value::as_array)
+            .map(|children| children.iter().filter_map(Self::result_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Id of a serialized result, a plain id or the first id nested in a tuple
+    fn result_id(value: &Value) -> Option<String> {
+        match value {
+            Value::String(id) => Some(id.clone()),
+            Value::Array(items) => items.first().and_then(Self::result_id),
+            Value::Object(_) => Self::string_field(value, "id"),
+            _ => None,
+        }
+    }
+
+    /// Read `delivery_info.routing_key` of message properties or task metadata
+    fn routing_key(value: Option<&Value>) -> Option<String> {
+        value
+            .and_then(|v| v.get("delivery_info"))
+            .and_then(|d| Self::string_field(d, "routing_key"))
+    }
+
+    /// How long the task ran
+    ///
+    /// Taken from `runtime` in seconds when present, otherwise from
+    /// `date_done - date_started`. Missing, negative or unparseable values
+    /// leave the duration unknown.
+    fn duration_field(value: &Value) -> Option<std::time::Duration> {
+        if let Some(runtime) = value.get("runtime").and_then(Value::as_f64) {
+            return std::time::Duration::try_from_secs_f64(runtime).ok();
+        }
+        let started = Self::date_field(value, "date_started")?;
+        let done = Self::date_field(value, "date_done")?;
+        (done - started).to_std().ok()
+    }
+
+    /// Read a date field, either RFC 3339 or the naive ISO 8601 Celery writes
+    /// for UTC times
+    fn date_field(value: &Value, key: &str) -> Option<DateTime<Utc>> {
+        let text = value.get(key)?.as_str()?;
+        text.parse::<DateTime<Utc>>().ok().or_else(|| {
+            text.parse::<chrono::NaiveDateTime>()
+                .ok()
+                .map(|naive| naive.and_utc())
+        })
+    }
+
+    /// Read an optional string field, treating JSON null as absent
+    fn string_field(value: &Value, key: &str) -> Option<String> {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+
+    /// Hostname of the worker that ran the task, when the metadata records it
+    ///
+    /// Extended results store it as `worker`, some setups as `hostname`, and
+    /// with `task_track_started` a started task's result carries `hostname`.
+    fn worker_field(task_data: &Value) -> Option<String> {
+        Self::string_field(task_data, "worker")
+            .or_else(|| Self::string_field(task_data, "hostname"))
+            .or_else(|| {
+                task_data
+                    .get("result")
+                    .and_then(|result| Self::string_field(result, "hostname"))
+            })
+            .filter(|worker| !worker.is_empty())
+    }
+
+    /// Truncate a value to at most `max_len` bytes
+    ///
+    /// Cuts on a character boundary and appends a marker with the original size,
+    /// which is also returned. A `max_len` of 0 disables truncation.
+    pub(super) fn limit_length(value: String, max_len: usize) -> (String, Option<usize>) {
+        if max_len == 0 || value.len() <= max_len {
+            return (value, None);
+        }
+
+        let full_len = value.len();
+        let mut cut = max_len;
+        while !value.is_char_boundary(cut) {
+            cut -= 1;
+        }
+
+        (
+            format!("{}… (truncated, {full_len} bytes total)", &value[..cut]),
+            Some(full_len),
+        )
+    }
+
     /// Parse timestamp from task data
     ///
     /// Extracts and parses the completion timestamp from task metadata,
-    /// using the current time as fallback if parsing fails.
-    fn parse_timestamp(task_data: &Value) -> DateTime<Utc> {
-        if let Some(date_done) = task_data.get("date_done").and_then(|d| d.as_str()) {
-            date_done
-                .parse::<DateTime<Utc>>()
-                .unwrap_or_else(|_| Utc::now())
-        } else {
-            Utc::now()
-        }
+    /// using `now` as fallback if parsing fails.
+    fn parse_timestamp(task_data: &Value, now: DateTime<Utc>) -> DateTime<Utc> {
+        task_data
+            .get("date_done")
+            .and_then(|d| d.as_str())
+            .and_then(|date_done| date_done.parse::<DateTime<Utc>>().ok())
+            .unwrap_or(now)
     }
 
     /// Get task name from various sources
     ///
     /// Attempts to determine the task name from the task names mapping
-    /// (from queue messages) or task metadata, with fallback to "unknown".
+    /// (from queue messages) or the `task` or `name` field of the metadata,
+    /// with fallback to "unknown".
     fn get_task_name(
         task_id: &str,
         task_data: &Value,
@@ -205,12 +557,8 @@ impl TaskParser {
         task_names
             .get(task_id)
             .cloned()
-            .or_else(|| {
-                task_data
-                    .get("task")
-                    .and_then(|t| t.as_str())
-                    .map(|s| s.to_string())
-            })
+            .or_else(|| Self::string_field(task_data, "task"))
+            .or_else(|| Self::string_field(task_data, "name"))
             .unwrap_or_else(|| "unknown".to_string())
     }
 
@@ -231,29 +579,44 @@ impl TaskParser {
 
     /// Add pending tasks from queue messages
     ///
-    /// Scans queues for pending tasks that may not have metadata yet
-    /// and adds them to the task list with PENDING status.
+    /// Scans queues for pending tasks, adding the ones without metadata yet
+    /// with PENDING status and merging the others. At most `limit`
+    /// messages are read from each queue, the returned cap covers the queues
+    /// holding more. Arguments are truncated to `max_args_len` bytes.
     async fn add_pending_tasks_from_queues(
         conn: &mut MultiplexedConnection,
-        tasks: &mut Vec<Task>,
-    ) -> Result<(), BrokerError> {
+        tasks: &mut TaskSet,
+        limit: usize,
+        now: DateTime<Utc>,
+        max_args_len: usize,
+    ) -> Result<Option<ScanCap>, BrokerError> {
         let queue_names = vec!["celery", "default", "priority"];
+        let mut capped = None;
 
         for queue_name in &queue_names {
-            match conn.llen::<_, u64>(queue_name).await {
+            match QueueStore::length(conn, queue_name).await {
                 Ok(queue_length) if queue_length > 0 => {
-                    match conn
-                        .lrange::<_, Vec<String>>(queue_name, 0, MAX_PENDING_TASKS as isize)
-                        .await
-                    {
+                    capped = ScanCap::merge(capped, ScanCap::check(queue_length as usize, limit));
+                    if limit == 0 {
+                        continue;
+                    }
+                    match QueueStore::head(conn, queue_name, limit).await {
                         Ok(messages) => {
                             for message in &messages {
-                                if let Ok(task_message) = serde_json::from_str::<Value>(message) {
-                                    match Self::parse_task_message(&task_message, tasks) {
-                                        Ok(Some(task)) => tasks.push(task),
-                                        Ok(None) => continue, // Task already exists or invalid
-                                        Err(_) => continue,   // Skip malformed message
-                                    }
+                                let Ok(task_message) = serde_json::from_str::<Value>(message)
+                                else {
+                                    tasks.skip_malformed();
+                                    continue;
+                                };
+                                match Self::parse_task_message(
+                                    &task_message,
+                                    Some(queue_name),
+                                    now,
+                                    max_args_len,
+                                ) {
+                                    Ok(Some(task)) => tasks.add_message(task),
+                                    Ok(None) => continue, // Not a task message
+                                    Err(_) => tasks.skip_malformed(),
                                 }
                             }
                         }
@@ -270,38 +633,143 @@ impl TaskParser {
             }
         }
 
-        Ok(())
+        Ok(capped)
+    }
+
+    /// Add in-progress tasks from kombu's unacknowledged messages
+    ///
+    /// Workers hold reserved messages in the `unacked` hash until they finish.
+    /// Tasks already known from metadata are upgraded to Active; the keys are
+    /// optional and missing or unreadable ones are skipped.
+    async fn add_unacked_tasks(
+        conn: &mut MultiplexedConnection,
+        tasks: &mut TaskSet,
+        now: DateTime<Utc>,
+        max_args_len: usize,
+    ) {
+        let Ok(entries) = conn
+            .hgetall::<_, HashMap<String, String>>(UNACKED_KEY)
+            .await
+        else {
+            return;
+        };
+        if entries.is_empty() {
+            return;
+        }
+
+        let delivered_at: HashMap<String, f64> = conn
+            .zrange_withscores::<_, Vec<(String, f64)>>(UNACKED_INDEX_KEY, 0, -1)
+            .await
+            .map(|scores| scores.into_iter().collect())
+            .unwrap_or_default();
+
+        for (delivery_tag, payload) in entries.iter().take(MAX_UNACKED_TASKS) {
+            let Some(task) = Self::parse_unacked_entry(
+                payload,
+                delivered_at.get(delivery_tag).copied(),
+                now,
+                max_args_len,
+            ) else {
+                // Skip malformed entries
+                tasks.skip_malformed();
+                continue;
+            };
+
+            tasks.add_message(task);
+        }
+    }
+
+    /// Parse an `unacked` hash value into an Active task
+    ///
+    /// Kombu stores `[message, exchange, routing_key]`; the message has the same
+    /// shape as a queued one. The worker is taken from the `origin` header, and
+    /// `now` stands in for an unknown delivery time.
+    fn parse_unacked_entry(
+        payload: &str,
+        delivered_at: Option<f64>,
+        now: DateTime<Utc>,
+        max_args_len: usize,
+    ) -> Option<Task> {
+        let entry: Value = serde_json::from_str(payload).ok()?;
+        let message = match entry.get(0) {
+            Some(message) => message,
+            None => &entry,
+        };
+
+        let routing_key = entry
+            .get(2)
+            .and_then(|r| r.as_str())
+            .filter(|r| !r.is_empty());
+        let mut task = Self::parse_task_message(message, routing_key, now, max_args_len).ok()??;
+        task.status = TaskStatus::Active;
+        task.worker = message
+            .get("headers")
+            .and_then(|h| h.get("origin"))
+            .and_then(|o| o.as_str())
+            .map(|o| o.to_string());
+        if let Some(timestamp) =
+            delivered_at.and_then(|secs| DateTime::from_timestamp_millis((secs * 1000.0) as i64))
+        {
+            task.timestamp = timestamp;
+        }
+
+        Some(task)
     }
 
     /// Parse task from queue message
     ///
-    /// Extracts task information from a queue message, checking if the task
-    /// already exists to avoid duplicates.
+    /// Extracts task information from a queue message, messages without a task
+    /// id and name yield `None`. `queue` is the queue the message was read
+    /// from, the routing key of the message is used when it is unknown.
+    /// Messages carry no time, so the task is stamped with `now`. Arguments
+    /// are truncated to `max_args_len` bytes.
     fn parse_task_message(
         task_message: &Value,
-        existing_tasks: &[Task],
+        queue: Option<&str>,
+        now: DateTime<Utc>,
+        max_args_len: usize,
     ) -> Result<Option<Task>, BrokerError> {
         if let Some(headers) = task_message.get("headers") {
             if let (Some(task_id), Some(task_name)) = (
                 headers.get("id").and_then(|id| id.as_str()),
                 headers.get("task").and_then(|task| task.as_str()),
             ) {
-                // Only add if not already in our task list
-                if !existing_tasks.iter().any(|t| t.id == task_id) {
-                    let (args, kwargs) = Self::decode_task_body(task_message);
-
-                    return Ok(Some(Task {
-                        id: task_id.to_string(),
-                        name: task_name.to_string(),
-                        args,
-                        kwargs,
-                        status: TaskStatus::Pending,
-                        worker: None,
-                        timestamp: Utc::now(),
-                        result: None,
-                        traceback: None,
-                    }));
-                }
+                let ((args, args_full_len), (kwargs, kwargs_full_len)) =
+                    Self::decode_task_body(task_message, max_args_len);
+                let eta = headers
+                    .get("eta")
+                    .and_then(|e| e.as_str())
+                    .and_then(|e| e.parse::<DateTime<Utc>>().ok());
+
+                return Ok(Some(Task {
+                    id: task_id.to_string(),
+                    name: task_name.to_string(),
+                    args,
+                    kwargs,
+                    status: TaskStatus::Pending,
+                    worker: None,
+                    timestamp: now,
+                    result: None,
+                    result_kind: None,
+                    looks_like_error: false,
+                    traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len,
+                    kwargs_full_len,
+                    eta,
+                    root_id: Self::string_field(headers, "root_id"),
+                    parent_id: Self::string_field(headers, "parent_id"),
+                    group_id: Self::string_field(headers, "group"),
+                    queue: queue
+                        .map(str::to_string)
+                        .or_else(|| Self::routing_key(task_message.get("properties"))),
+                    retries: Self::u32_field(headers, "retries").unwrap_or(0),
+                    max_retries: Self::u32_field(headers, "max_retries"),
+                    priority: Self::priority_field(task_message.get("properties")),
+                    children: Vec::new(),
+                    duration: None,
+                }));
             }
         }
 
@@ -310,27 +778,769 @@ impl TaskParser {
 
     /// Decode base64-encoded task body
     ///
-    /// Attempts to decode the task body from base64 and extract
-    /// arguments and keyword arguments from the Celery message format.
-    fn decode_task_body(task_message: &Value) -> (String, String) {
+    /// Attempts to decode the task body from base64, decompress it when the
+    /// message declares a compression, and extract arguments and keyword
+    /// arguments from the Celery message format. Each is truncated to
+    /// `max_len` bytes with `limit_length`, and returned with its original
+    /// size when it was.
+    fn decode_task_body(
+        task_message: &Value,
+        max_len: usize,
+    ) -> ((String, Option<usize>), (String, Option<usize>)) {
         if let Some(body) = task_message.get("body").and_then(|b| b.as_str()) {
             if let Ok(decoded) = base64::engine::general_purpose::STANDARD.decode(body) {
-                if let Ok(body_str) = String::from_utf8(decoded) {
-                    if let Ok(body_json) = serde_json::from_str::<Value>(&body_str) {
-                        let args = body_json
-                            .get(0)
-                            .map(|a| a.to_string())
-                            .unwrap_or_else(|| "[]".to_string());
-                        let kwargs = body_json
-                            .get(1)
-                            .map(|k| k.to_string())
-                            .unwrap_or_else(|| "{}".to_string());
-                        return (args, kwargs);
+                if let Some(decompressed) = Self::decompress_body(task_message, decoded) {
+                    if let Ok(body_str) = String::from_utf8(decompressed) {
+                        if let Ok(body_json) = serde_json::from_str::<Value>(&body_str) {
+                            let args = body_json
+                                .get(0)
+                                .map(|a| a.to_string())
+                                .unwrap_or_else(|| "[]".to_string());
+                            let kwargs = body_json
+                                .get(1)
+                                .map(|k| k.to_string())
+                                .unwrap_or_else(|| "{}".to_string());
+                            return (
+                                Self::limit_length(args, max_len),
+                                Self::limit_length(kwargs, max_len),
+                            );
+                        }
                     }
                 }
             }
         }
 
-        ("[]".to_string(), "{}".to_string())
+        (("[]".to_string(), None), ("{}".to_string(), None))
+    }
+
+    /// Decompress a decoded task body according to the message's compression
+    ///
+    /// Kombu records the compression in the `compression` header as a MIME type,
+    /// while some producers set `content-encoding` instead. Uncompressed bodies
+    /// are returned as-is; unsupported encodings and corrupt data yield `None`.
+    fn decompress_body(task_message: &Value, body: Vec<u8>) -> Option<Vec<u8>> {
+        let compression = task_message
+            .get("headers")
+            .and_then(|h| h.get("compression"))
+            .or_else(|| task_message.get("content-encoding"))
+            .and_then(|c| c.as_str());
+
+        let mut decompressed = Vec::new();
+        match compression {
+            None | Some("utf-8") | Some("binary") => return Some(body),
+            Some("gzip") | Some("application/x-gzip") => {
+                flate2::read::GzDecoder::new(body.as_slice())
+                    .read_to_end(&mut decompressed)
+                    .ok()?;
+            }
+            Some("zlib") | Some("application/x-zlib") => {
+                flate2::read::ZlibDecoder::new(body.as_slice())
+                    .read_to_end(&mut decompressed)
+                    .ok()?;
+            }
+            Some("bzip2") | Some("application/x-bz2") => {
+                bzip2::read::BzDecoder::new(body.as_slice())
+                    .read_to_end(&mut decompressed)
+                    .ok()?;
+            }
+            Some(_) => return None,
+        }
+
+        Some(decompressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_duration_from_runtime() {
+        let data = json!({
+            "status": "SUCCESS",
+            "result": 3,
+            "date_done": "2024-01-15T14:30:45Z",
+            "date_started": "2024-01-15T14:30:00Z",
+            "runtime": 1.5,
+        });
+        let task =
+            TaskParser::parse_task("t-1", &data.to_string(), &BrokerOptions::default()).unwrap();
+        // runtime wins over the start and completion times
+        assert_eq!(task.duration, Some(std::time::Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_duration_from_start_and_done() {
+        let data = json!({
+            "status": "SUCCESS",
+            "date_started": "2024-01-15T14:30:00.250000",
+            "date_done": "2024-01-15T14:30:45.750000",
+        });
+        let task =
+            TaskParser::parse_task("t-1", &data.to_string(), &BrokerOptions::default()).unwrap();
+        assert_eq!(
+            task.duration,
+            Some(std::time::Duration::from_millis(45_500))
+        );
+    }
+
+    #[test]
+    fn test_duration_unknown_without_timing() {
+        let options = BrokerOptions::default();
+        for data in [
+            json!({"status": "SUCCESS", "date_done": "2024-01-15T14:30:45Z"}),
+            json!({"status": "SUCCESS", "runtime": -2.0}),
+            json!({
+                "status": "SUCCESS",
+                "date_started": "2024-01-15T14:31:00Z",
+                "date_done": "2024-01-15T14:30:45Z",
+            }),
+        ] {
+            let task = TaskParser::parse_task("t-1", &data.to_string(), &options).unwrap();
+            assert_eq!(task.duration, None, "{data}");
+        }
+    }
+
+    #[test]
+    fn test_oversized_result_is_truncated() {
+        let options = BrokerOptions {
+            max_result_length: 64,
+            ..BrokerOptions::default()
+        };
+        let big_result = "x".repeat(10_000);
+        let task_data = json!({
+            "status": "SUCCESS",
+            "result": big_result,
+            "traceback": null,
+            "date_done": "2024-01-15T14:30:45Z",
+        });
+
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-big-result",
+            &task_data,
+            &HashMap::new(),
+            &options,
+        )
+        .unwrap();
+
+        // String results are kept without their JSON quotes
+        let full_len = big_result.len();
+        let result = task.result.unwrap();
+        assert!(result.starts_with(&"x".repeat(64)));
+        assert!(result.ends_with(&format!("… (truncated, {full_len} bytes total)")));
+        assert_eq!(task.result_full_len, Some(full_len));
+        assert_eq!(task.traceback_full_len, None);
+    }
+
+    #[test]
+    fn test_small_result_is_untouched() {
+        let task_data = json!({
+            "status": "FAILURE",
+            "result": {"exc_type": "ValueError"},
+            "traceback": "Traceback (most recent call last):\nValueError",
+        });
+
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-small",
+            &task_data,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            task.result.as_deref(),
+            Some("{\n  \"exc_type\": \"ValueError\"\n}")
+        );
+        assert_eq!(task.result_kind, Some(ResultKind::Object { keys: 1 }));
+        assert_eq!(task.result_full_len, None);
+        assert_eq!(task.traceback_full_len, None);
+    }
+
+    #[test]
+    fn test_successful_task_with_exception_result_is_flagged() {
+        let task_data = json!({
+            "status": "SUCCESS",
+            "result": {"exc_type": "TimeoutError", "exc_message": ["gave up"]},
+        });
+
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-masked",
+            &task_data,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+
+        // The status is kept as stored
+        assert_eq!(task.status, TaskStatus::Success);
+        assert!(task.looks_like_error);
+    }
+
+    #[test]
+    fn test_truncation_respects_char_boundaries() {
+        let (value, full_len) = TaskParser::limit_length("ééé".to_string(), 3);
+        assert!(value.starts_with("é…"));
+        assert_eq!(full_len, Some(6));
+
+        let (value, full_len) = TaskParser::limit_length("abc".to_string(), 0);
+        assert_eq!(value, "abc");
+        assert_eq!(full_len, None);
+    }
+
+    fn encoded_message(body: &[u8], headers: Value) -> Value {
+        json!({
+            "body": base64::engine::general_purpose::STANDARD.encode(body),
+            "headers": headers,
+        })
+    }
+
+    #[test]
+    fn test_gzip_body_is_decompressed() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let payload = json!([[1, "two"], {"retries": 3}, {}]).to_string();
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(payload.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let message = encoded_message(
+            &compressed,
+            json!({"id": "gz-1", "task": "tasks.add", "compression": "application/x-gzip"}),
+        );
+
+        let ((args, _), (kwargs, _)) = TaskParser::decode_task_body(&message, 0);
+        assert_eq!(args, r#"[1,"two"]"#);
+        assert_eq!(kwargs, r#"{"retries":3}"#);
+    }
+
+    #[test]
+    fn test_bzip2_content_encoding_is_decompressed() {
+        use bzip2::write::BzEncoder;
+        use bzip2::Compression;
+        use std::io::Write;
+
+        let payload = json!([["a"], {"b": 1}, {}]).to_string();
+        let mut encoder = BzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(payload.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut message = encoded_message(&compressed, json!({"id": "bz-1"}));
+        message["content-encoding"] = json!("bzip2");
+
+        let ((args, _), (kwargs, _)) = TaskParser::decode_task_body(&message, 0);
+        assert_eq!(args, r#"["a"]"#);
+        assert_eq!(kwargs, r#"{"b":1}"#);
+    }
+
+    #[test]
+    fn test_unsupported_compression_falls_back_to_defaults() {
+        let message = encoded_message(
+            b"not really compressed",
+            json!({"id": "x-1", "compression": "application/x-lzma"}),
+        );
+
+        let ((args, _), (kwargs, _)) = TaskParser::decode_task_body(&message, 0);
+        assert_eq!(args, "[]");
+        assert_eq!(kwargs, "{}");
+    }
+
+    #[test]
+    fn test_oversized_message_args_are_truncated() {
+        let big_arg = "y".repeat(5_000);
+        let payload = json!([[big_arg], {"small": 1}, {}]).to_string();
+        let message = encoded_message(
+            payload.as_bytes(),
+            json!({"id": "big-args", "task": "tasks.ingest"}),
+        );
+
+        let ((args, args_full_len), (kwargs, kwargs_full_len)) =
+            TaskParser::decode_task_body(&message, 100);
+        let full_len = json!([big_arg]).to_string().len();
+        assert!(args.starts_with(&format!("[\"{}", "y".repeat(98))));
+        assert!(args.ends_with(&format!("… (truncated, {full_len} bytes total)")));
+        assert_eq!(args_full_len, Some(full_len));
+        assert_eq!(kwargs, r#"{"small":1}"#);
+        assert_eq!(kwargs_full_len, None);
+
+        // The sizes end up on the task, 0 keeps the arguments whole
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 100)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.args_full_len, Some(full_len));
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.args.len(), full_len);
+        assert_eq!(task.args_full_len, None);
+    }
+
+    #[test]
+    fn test_oversized_metadata_kwargs_are_truncated() {
+        let options = BrokerOptions {
+            max_args_length: 32,
+            ..BrokerOptions::default()
+        };
+        let task_data = json!({
+            "status": "SUCCESS",
+            "args": [1, 2],
+            "kwargs": {"payload": "z".repeat(1_000)},
+        });
+
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-big-kwargs",
+            &task_data,
+            &HashMap::new(),
+            &options,
+        )
+        .unwrap();
+
+        let full_len = task_data["kwargs"].to_string().len();
+        assert_eq!(task.args, "[1,2]");
+        assert_eq!(task.args_full_len, None);
+        assert!(task
+            .kwargs
+            .ends_with(&format!("… (truncated, {full_len} bytes total)")));
+        assert_eq!(task.kwargs_full_len, Some(full_len));
+    }
+
+    #[test]
+    fn test_workflow_ids_are_read_from_headers() {
+        let message = json!({
+            "body": base64::engine::general_purpose::STANDARD.encode("[[], {}, {}]"),
+            "headers": {
+                "id": "child-1",
+                "task": "tasks.step",
+                "root_id": "root-1",
+                "parent_id": "parent-1",
+                "group": "group-1",
+            },
+        });
+
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.root_id.as_deref(), Some("root-1"));
+        assert_eq!(task.parent_id.as_deref(), Some("parent-1"));
+        assert_eq!(task.group_id.as_deref(), Some("group-1"));
+    }
+
+    #[test]
+    fn test_retries_are_read_from_headers() {
+        let message = json!({
+            "headers": {
+                "id": "flaky-1",
+                "task": "tasks.flaky",
+                "retries": 2,
+                "max_retries": 3,
+            },
+        });
+
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.retries, 2);
+        assert_eq!(task.max_retries, Some(3));
+        assert!(!task.retries_exhausted());
+
+        let message = json!({"headers": {"id": "fresh-1", "task": "tasks.fresh"}});
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.retries, 0);
+        assert_eq!(task.max_retries, None);
+    }
+
+    #[test]
+    fn test_task_name_falls_back_to_metadata_name() {
+        let names = HashMap::from([("queued".to_string(), "tasks.queued".to_string())]);
+
+        let from_queue = json!({"task": "tasks.other"});
+        assert_eq!(
+            TaskParser::get_task_name("queued", &from_queue, &names),
+            "tasks.queued"
+        );
+
+        let extended = json!({"name": "tasks.extended", "task": null});
+        assert_eq!(
+            TaskParser::get_task_name("done", &extended, &names),
+            "tasks.extended"
+        );
+
+        assert_eq!(
+            TaskParser::get_task_name("done", &json!({}), &names),
+            "unknown"
+        );
+    }
+
+    #[test]
+    fn test_retries_are_read_from_metadata() {
+        let task_data = json!({
+            "status": "FAILURE",
+            "result": {"exc_type": "ConnectionError"},
+            "retries": 3,
+            "max_retries": 3,
+        });
+
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-flaky-2",
+            &task_data,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(task.retries, 3);
+        assert_eq!(task.max_retries, Some(3));
+        assert!(task.retries_exhausted());
+    }
+
+    #[test]
+    fn test_missing_workflow_ids_are_none() {
+        let message = json!({
+            "headers": {
+                "id": "solo-1",
+                "task": "tasks.solo",
+                "root_id": null,
+            },
+        });
+
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.root_id, None);
+        assert_eq!(task.parent_id, None);
+        assert_eq!(task.group_id, None);
+    }
+
+    #[test]
+    fn test_missing_timestamps_come_from_the_clock() {
+        use crate::utils::clock::Clock;
+        use std::sync::Arc;
+
+        #[derive(Debug)]
+        struct StoppedClock(DateTime<Utc>);
+
+        impl Clock for StoppedClock {
+            fn now(&self) -> DateTime<Utc> {
+                self.0
+            }
+        }
+
+        let now = "2024-05-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let options = BrokerOptions {
+            clock: Arc::new(StoppedClock(now)),
+            ..BrokerOptions::default()
+        };
+        let task = TaskParser::parse_task("t-1", r#"{"status": "STARTED"}"#, &options).unwrap();
+        assert_eq!(task.timestamp, now);
+
+        let done = json!({"status": "SUCCESS", "date_done": "2024-04-30T08:00:00+00:00"});
+        let task = TaskParser::parse_task("t-2", &done.to_string(), &options).unwrap();
+        assert_eq!(task.timestamp.to_rfc3339(), "2024-04-30T08:00:00+00:00");
+
+        let message = json!({"headers": {"id": "q-1", "task": "tasks.add"}});
+        let task = TaskParser::parse_task_message(&message, None, now, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.timestamp, now);
+    }
+
+    #[test]
+    fn test_worker_is_read_from_metadata() {
+        let extended = json!({
+            "status": "SUCCESS",
+            "result": 4,
+            "name": "tasks.add",
+            "worker": "celery@worker-1",
+        });
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-extended",
+            &extended,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(task.worker.as_deref(), Some("celery@worker-1"));
+
+        let hostname = json!({"status": "SUCCESS", "hostname": "celery@worker-2"});
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-hostname",
+            &hostname,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(task.worker.as_deref(), Some("celery@worker-2"));
+
+        // What task_track_started stores for a running task
+        let started = json!({
+            "status": "STARTED",
+            "result": {"pid": 4242, "hostname": "celery@worker-3"},
+        });
+        let task = TaskParser::extract_task_from_metadata(
+            "celery-task-meta-started",
+            &started,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(task.worker.as_deref(), Some("celery@worker-3"));
+    }
+
+    #[test]
+    fn test_missing_worker_stays_none() {
+        for task_data in [
+            json!({"status": "SUCCESS", "result": {"hostname_count": 2}}),
+            json!({"status": "SUCCESS", "result": "celery@worker-1"}),
+            json!({"status": "FAILURE", "worker": ""}),
+            json!({"status": "SUCCESS", "worker": null}),
+        ] {
+            let task = TaskParser::extract_task_from_metadata(
+                "celery-task-meta-no-worker",
+                &task_data,
+                &HashMap::new(),
+                &BrokerOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(task.worker, None, "{task_data}");
+        }
+    }
+
+    #[test]
+    fn test_unacked_entry_becomes_active_task() {
+        let message = json!({
+            "body": base64::engine::general_purpose::STANDARD.encode("[[1], {}, {}]"),
+            "headers": {
+                "id": "in-flight-1",
+                "task": "tasks.long_running",
+                "origin": "gen42@worker-host",
+            },
+        });
+        let payload = json!([message, "", "celery"]).to_string();
+
+        let task = TaskParser::parse_unacked_entry(&payload, Some(1_700_000_000.0), Utc::now(), 0)
+            .unwrap();
+        assert_eq!(task.id, "in-flight-1");
+        assert_eq!(task.status, TaskStatus::Active);
+        assert_eq!(task.worker.as_deref(), Some("gen42@worker-host"));
+        assert_eq!(task.args, "[1]");
+        assert_eq!(task.queue.as_deref(), Some("celery"));
+        assert_eq!(task.timestamp.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn test_pending_task_records_its_queue() {
+        let message = json!({
+            "headers": {"id": "urgent-1", "task": "tasks.urgent"},
+            "properties": {"delivery_info": {"routing_key": "celery"}},
+        });
+
+        // The queue the message was read from wins over its routing key
+        let task = TaskParser::parse_task_message(&message, Some("priority"), Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.queue.as_deref(), Some("priority"));
+
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.queue.as_deref(), Some("celery"));
+
+        let message = json!({"headers": {"id": "plain-1", "task": "tasks.plain"}});
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.queue, None);
+    }
+
+    #[test]
+    fn test_priority_is_read_from_properties() {
+        let message = json!({
+            "headers": {"id": "urgent-2", "task": "tasks.urgent"},
+            "properties": {"priority": 7, "delivery_info": {"routing_key": "celery"}},
+        });
+        let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(task.priority, Some(7));
+
+        for properties in [
+            json!({}),
+            json!({"priority": null}),
+            json!({"priority": 300}),
+        ] {
+            let message = json!({
+                "headers": {"id": "plain-2", "task": "tasks.plain"},
+                "properties": properties,
+            });
+            let task = TaskParser::parse_task_message(&message, None, Utc::now(), 0)
+                .unwrap()
+                .unwrap();
+            assert_eq!(task.priority, None);
+        }
+    }
+
+    #[test]
+    fn test_metadata_queue_comes_from_delivery_info() {
+        let parse = |task_data: Value| {
+            TaskParser::extract_task_from_metadata(
+                "celery-task-meta-done-1",
+                &task_data,
+                &HashMap::new(),
+                &BrokerOptions::default(),
+            )
+            .unwrap()
+        };
+
+        let routed = parse(json!({
+            "status": "SUCCESS",
+            "delivery_info": {"exchange": "", "routing_key": "priority"},
+        }));
+        assert_eq!(routed.queue.as_deref(), Some("priority"));
+
+        assert_eq!(parse(json!({"status": "SUCCESS"})).queue, None);
+    }
+
+    fn metadata_task(task_id: &str, task_data: Value) -> Task {
+        TaskParser::extract_task_from_metadata(
+            &format!("celery-task-meta-{task_id}"),
+            &task_data,
+            &HashMap::new(),
+            &BrokerOptions::default(),
+        )
+        .unwrap()
+    }
+
+    fn queued_task(task_id: &str, queue: &str) -> Task {
+        let message = json!({
+            "body": base64::engine::general_purpose::STANDARD.encode("[[1, 2], {\"force\": true}, {}]"),
+            "headers": {
+                "id": task_id,
+                "task": "reports.build",
+                "origin": "gen7@producer-host",
+                "root_id": "root-1",
+            },
+        });
+        let mut task = TaskParser::parse_task_message(&message, Some(queue), Utc::now(), 0)
+            .unwrap()
+            .unwrap();
+        task.worker = Some("gen7@producer-host".to_string());
+        task
+    }
+
+    #[test]
+    fn test_children_are_read_from_metadata() {
+        let task = metadata_task(
+            "parent-1",
+            json!({
+                "status": "SUCCESS",
+                "children": [
+                    [["child-1", null], null],
+                    [["child-2", ["parent-1", null]], null],
+                    "child-3",
+                    {"id": "child-4"},
+                    null,
+                ],
+            }),
+        );
+        assert_eq!(
+            task.children,
+            vec!["child-1", "child-2", "child-3", "child-4"]
+        );
+
+        let task = metadata_task("leaf-1", json!({"status": "SUCCESS", "children": []}));
+        assert!(task.children.is_empty());
+        let task = metadata_task("old-1", json!({"status": "SUCCESS"}));
+        assert!(task.children.is_empty());
+
+        // Messages don't list children, merging keeps those of the metadata
+        let mut tasks = TaskSet::default();
+        tasks.add_message(queued_task("parent-1", "celery"));
+        tasks.add_metadata(metadata_task(
+            "parent-1",
+            json!({"status": "SUCCESS", "children": [[["child-1", null], null]]}),
+        ));
+        assert_eq!(tasks.into_tasks()[0].children, vec!["child-1"]);
+    }
+
+    #[test]
+    fn test_task_in_metadata_and_queue_is_merged() {
+        let metadata = metadata_task(
+            "both-1",
+            json!({
+                "status": "SUCCESS",
+                "result": 42,
+                "date_done": "2024-05-01T12:00:00+00:00",
+            }),
+        );
+
+        let mut tasks = TaskSet::default();
+        tasks.add_metadata(metadata);
+        tasks.add_message(queued_task("both-1", "reports"));
+        tasks.add_message(queued_task("queued-only", "reports"));
+        let tasks = tasks.into_tasks();
+
+        let ids: Vec<&str> = tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["both-1", "queued-only"]);
+
+        // Metadata decides how the task went, the message what was sent
+        let merged = &tasks[0];
+        assert_eq!(merged.status, TaskStatus::Success);
+        assert_eq!(merged.result.as_deref(), Some("42"));
+        assert_eq!(merged.timestamp.to_rfc3339(), "2024-05-01T12:00:00+00:00");
+        assert_eq!(merged.name, "reports.build");
+        assert_eq!(merged.args, "[1,2]");
+        assert_eq!(merged.kwargs, r#"{"force":true}"#);
+        assert_eq!(merged.queue.as_deref(), Some("reports"));
+        assert_eq!(merged.worker.as_deref(), Some("gen7@producer-host"));
+        assert_eq!(merged.root_id.as_deref(), Some("root-1"));
+    }
+
+    #[test]
+    fn test_merge_does_not_depend_on_source_order() {
+        let metadata =
+            || metadata_task("both-1", json!({"status": "FAILURE", "traceback": "boom"}));
+
+        let mut metadata_first = TaskSet::default();
+        metadata_first.add_metadata(metadata());
+        metadata_first.add_message(queued_task("both-1", "celery"));
+
+        let mut message_first = TaskSet::default();
+        message_first.add_message(queued_task("both-1", "celery"));
+        message_first.add_metadata(metadata());
+
+        for tasks in [metadata_first.into_tasks(), message_first.into_tasks()] {
+            assert_eq!(tasks.len(), 1);
+            assert_eq!(tasks[0].status, TaskStatus::Failure);
+            assert_eq!(tasks[0].traceback.as_deref(), Some("boom"));
+            assert_eq!(tasks[0].args, "[1,2]");
+            assert_eq!(tasks[0].queue.as_deref(), Some("celery"));
+        }
+    }
+
+    #[test]
+    fn test_unacked_message_marks_pending_metadata_active() {
+        let mut tasks = TaskSet::default();
+        tasks.add_metadata(metadata_task("running-1", json!({"status": "PENDING"})));
+        let mut unacked = queued_task("running-1", "celery");
+        unacked.status = TaskStatus::Active;
+        tasks.add_message(unacked);
+        // The same message still listed in the queue doesn't undo that
+        tasks.add_message(queued_task("running-1", "celery"));
+
+        let tasks = tasks.into_tasks();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, TaskStatus::Active);
+    }
+
+    #[test]
+    fn test_malformed_unacked_entry_is_skipped() {
+        assert!(TaskParser::parse_unacked_entry("not json", None, Utc::now(), 0).is_none());
+        assert!(
+            TaskParser::parse_unacked_entry(r#"[{"headers": {}}]"#, None, Utc::now(), 0).is_none()
+        );
     }
 }
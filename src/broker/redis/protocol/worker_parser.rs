@@ -4,34 +4,65 @@
 //! It extracts worker statistics, status, and queue assignments from task metadata
 //! and queue messages.
 
+use super::{fetch_values_concurrently, scan_keys, Parsed, QueueStore};
+use crate::broker::redis::pool::ConnectionPool;
+use crate::broker::ScanCap;
+use crate::config::ParserLimits;
 use crate::error::BrokerError;
-use crate::models::{Worker, WorkerStatus};
+use crate::models::{
+    canonical_hostname, canonical_node, find_worker, merge_workers_by_host, Worker, WorkerStatus,
+};
 use redis::aio::MultiplexedConnection;
-use redis::AsyncCommands;
 use serde_json::Value;
 use std::collections::HashMap;
 
 // Configuration constants for worker parsing
-const MAX_TASK_METADATA_KEYS: usize = 500;
 const DEFAULT_WORKER_CONCURRENCY: u32 = 16;
 
+/// Host that activity is credited to when the result doesn't say which worker ran it
+const GENERIC_WORKER: &str = "celery-worker";
+
+/// Host made up when there is Celery activity but no worker could be named
+const DETECTED_WORKER: &str = "detected-worker";
+
+/// Processed count, failed count and queues collected for each hostname
+type WorkerStats = HashMap<String, (u64, u64, Vec<String>)>;
+
+/// What a pass over the task results in the result backend turned up
+struct MetadataScan {
+    /// Results found, including any beyond the cap
+    found: usize,
+    capped: Option<ScanCap>,
+    /// Results that weren't valid JSON
+    malformed: usize,
+}
+
 /// Parser for worker-related data from Redis
 pub struct WorkerParser;
 
 impl WorkerParser {
-    /// Parse workers from Redis connection
+    /// Parse workers using connections from the pools
     ///
     /// Extracts worker information from task metadata and queue messages to build
     /// a comprehensive view of active workers, their status, and statistics.
+    /// Queue messages are read from `pool` and task results from `backend`.
+    /// Statistics come from at most `limits.max_task_metadata_keys` results,
+    /// the returned cap says whether more existed.
     pub async fn parse_workers(
-        connection: &MultiplexedConnection,
-    ) -> Result<Vec<Worker>, BrokerError> {
-        let mut conn = connection.clone();
-        let mut worker_stats: HashMap<String, (u64, u64, Vec<String>)> = HashMap::new();
+        pool: &ConnectionPool,
+        backend: &ConnectionPool,
+        limits: &ParserLimits,
+    ) -> Result<Parsed<Worker>, BrokerError> {
+        let mut worker_stats: WorkerStats = HashMap::new();
         let active_workers: HashMap<String, Vec<String>> = HashMap::new();
 
-        // Get task metadata and extract worker information
-        Self::get_task_metadata(&mut conn, &mut worker_stats).await?;
+        // Get task metadata from the result backend and extract worker information
+        let metadata =
+            Self::get_task_metadata(backend, limits.max_task_metadata_keys, &mut worker_stats)
+                .await?;
+
+        // Taken after the metadata fetch, which needs connections of its own
+        let mut conn = pool.get_connection().await?;
 
         // Extract worker info from queue messages
         Self::extract_worker_info_from_queues(&mut conn, &mut worker_stats).await?;
@@ -40,9 +71,13 @@ impl WorkerParser {
         let mut workers = Self::build_worker_list(worker_stats, active_workers);
 
         // Handle case where no workers are detected
-        Self::ensure_default_worker_if_needed(&mut conn, &mut workers).await?;
+        Self::ensure_default_worker_if_needed(&mut conn, &mut workers, metadata.found).await;
 
-        Ok(workers)
+        Ok(Parsed {
+            items: workers,
+            capped: metadata.capped,
+            malformed: metadata.malformed,
+        })
     }
 
     /// Extract worker statistics from task metadata
@@ -50,54 +85,67 @@ impl WorkerParser {
     /// Processes completed task metadata to extract worker performance statistics
     /// including processed and failed task counts.
     async fn get_task_metadata(
-        conn: &mut MultiplexedConnection,
-        worker_stats: &mut HashMap<String, (u64, u64, Vec<String>)>,
-    ) -> Result<(), BrokerError> {
-        let task_keys: Vec<String> = conn.keys("celery-task-meta-*").await.map_err(|e| {
-            BrokerError::OperationError(format!("Failed to get task metadata keys: {e}"))
-        })?;
-
-        for key in task_keys.iter().take(MAX_TASK_METADATA_KEYS) {
-            match conn.get::<_, String>(key).await {
-                Ok(data) => {
-                    match serde_json::from_str::<Value>(&data) {
-                        Ok(task_data) => {
-                            let status = task_data
-                                .get("status")
-                                .and_then(|s| s.as_str())
-                                .unwrap_or("UNKNOWN");
-
-                            // For completed tasks, we don't have hostname in metadata
-                            // So we'll create a generic worker based on activity
-                            let hostname = "celery-worker".to_string();
-                            let (processed, failed, queues) =
-                                worker_stats.entry(hostname).or_insert((0, 0, Vec::new()));
-
-                            match status {
-                                "SUCCESS" => *processed += 1,
-                                "FAILURE" => *failed += 1,
-                                _ => {}
-                            }
+        pool: &ConnectionPool,
+        limit: usize,
+        worker_stats: &mut WorkerStats,
+    ) -> Result<MetadataScan, BrokerError> {
+        let mut task_keys = {
+            let mut conn = pool.get_connection().await?;
+            scan_keys(&mut conn, "celery-task-meta-*").await?
+        };
+        let found = task_keys.len();
+        let capped = ScanCap::check(found, limit);
+        task_keys.truncate(limit);
 
-                            // Add default queue
-                            if !queues.contains(&"celery".to_string()) {
-                                queues.push("celery".to_string());
-                            }
-                        }
-                        Err(_) => {
-                            // Skip malformed task data - log error but continue processing
-                            continue;
-                        }
+        let values = fetch_values_concurrently(pool, &task_keys, pool.max_size()).await;
+        let mut malformed = 0;
+
+        for value in values {
+            // Skip inaccessible keys - continue processing other tasks
+            let Some(data) = value else {
+                continue;
+            };
+
+            match serde_json::from_str::<Value>(&data) {
+                Ok(task_data) => {
+                    let status = task_data
+                        .get("status")
+                        .and_then(|s| s.as_str())
+                        .unwrap_or("UNKNOWN");
+
+                    // Only extended results record the worker node, credit the
+                    // rest to a generic worker
+                    let hostname = task_data
+                        .get("worker")
+                        .and_then(|worker| worker.as_str())
+                        .map(canonical_node)
+                        .unwrap_or_else(|| GENERIC_WORKER.to_string());
+                    let (processed, failed, queues) =
+                        worker_stats.entry(hostname).or_insert((0, 0, Vec::new()));
+
+                    match status {
+                        "SUCCESS" => *processed += 1,
+                        "FAILURE" => *failed += 1,
+                        _ => {}
+                    }
+
+                    // Add default queue
+                    if !queues.contains(&"celery".to_string()) {
+                        queues.push("celery".to_string());
                     }
                 }
                 Err(_) => {
-                    // Skip inaccessible keys - continue processing other tasks
-                    continue;
+                    // Skip malformed task data - count it but continue processing
+                    malformed += 1;
                 }
             }
         }
 
-        Ok(())
+        Ok(MetadataScan {
+            found,
+            capped,
+            malformed,
+        })
     }
 
     /// Extract worker information from queue messages
@@ -106,14 +154,14 @@ impl WorkerParser {
     /// associated queue assignments.
     async fn extract_worker_info_from_queues(
         conn: &mut MultiplexedConnection,
-        worker_stats: &mut HashMap<String, (u64, u64, Vec<String>)>,
+        worker_stats: &mut WorkerStats,
     ) -> Result<(), BrokerError> {
         let queue_names = vec!["celery", "default", "priority"];
 
         for queue_name in queue_names {
-            match conn.llen::<_, u64>(queue_name).await {
+            match QueueStore::length(conn, queue_name).await {
                 Ok(queue_length) if queue_length > 0 => {
-                    match conn.lrange::<_, Vec<String>>(queue_name, 0, 5).await {
+                    match QueueStore::head(conn, queue_name, 6).await {
                         Ok(messages) => {
                             for message in &messages {
                                 if let Ok(task_message) = serde_json::from_str::<Value>(message) {
@@ -155,27 +203,56 @@ impl WorkerParser {
             .get("headers")
             .and_then(|headers| headers.get("origin"))
             .and_then(|origin| origin.as_str())
-            .map(|origin| {
-                // Extract hostname from origin like "gen447152@archflowx13"
-                if let Some(at_pos) = origin.find('@') {
-                    origin[at_pos + 1..].to_string()
-                } else {
-                    origin.to_string()
-                }
-            })
+            .map(Self::hostname_of)
+    }
+
+    /// Hostname part of a message origin like "gen447152@archflowx13", in its
+    /// canonical form
+    ///
+    /// Origins name the producing process rather than a worker node, so only
+    /// the host is kept.
+    pub(super) fn hostname_of(node: &str) -> String {
+        canonical_hostname(node)
     }
 
     /// Build the final worker list from collected statistics
     ///
     /// Converts raw worker statistics into Worker structs with appropriate
-    /// status determination and queue assignments.
+    /// status determination and queue assignments. Real hostnames take
+    /// precedence over the generic worker: activity credited to it goes to the
+    /// only real worker when there is one, and is left out when it can't be
+    /// told apart between several. A host seen in message origins is combined
+    /// with the worker node on it as `merge_workers_by_host` does. Workers are
+    /// sorted by hostname and their
+    /// queues sorted and deduplicated, so refreshes don't reshuffle them.
     fn build_worker_list(
-        worker_stats: HashMap<String, (u64, u64, Vec<String>)>,
+        mut worker_stats: WorkerStats,
         active_workers: HashMap<String, Vec<String>>,
     ) -> Vec<Worker> {
+        if let Some(generic) = worker_stats.remove(GENERIC_WORKER) {
+            match worker_stats.len() {
+                0 => {
+                    worker_stats.insert(GENERIC_WORKER.to_string(), generic);
+                }
+                1 => {
+                    let (processed, failed, queues) = worker_stats
+                        .values_mut()
+                        .next()
+                        .expect("one worker is known");
+                    *processed += generic.0;
+                    *failed += generic.1;
+                    queues.extend(generic.2);
+                }
+                _ => {}
+            }
+        }
+
         let mut workers = Vec::new();
 
-        for (hostname, (processed, failed, queues)) in worker_stats {
+        for (hostname, (processed, failed, mut queues)) in worker_stats {
+            queues.sort();
+            queues.dedup();
+
             let active_tasks = active_workers.get(&hostname).cloned().unwrap_or_default();
 
             // Determine worker status - if we have recent task data, assume online
@@ -189,17 +266,67 @@ impl WorkerParser {
                 hostname,
                 status,
                 concurrency: DEFAULT_WORKER_CONCURRENCY,
+                pool_type: None,
                 queues: if queues.is_empty() {
                     vec!["celery".to_string()]
                 } else {
                     queues
                 },
                 active_tasks,
+                reserved_tasks: Vec::new(),
                 processed,
                 failed,
             });
         }
 
+        // Origins only name hosts, fold them into the node running there
+        let mut workers = merge_workers_by_host(workers);
+        workers.sort_by(|a, b| a.hostname.cmp(&b.hostname));
+        workers
+    }
+
+    /// Combine workers that answered an inspect request with those found in
+    /// task metadata
+    ///
+    /// Inspected workers are authoritative for status, concurrency, task
+    /// lists and the queues they reported; failure counts, and queues when a
+    /// worker didn't report any, still come from the metadata. Metadata is
+    /// matched to inspected nodes as `find_worker` does. Workers only seen in
+    /// metadata are kept as offline, except the placeholder hosts used when no
+    /// worker could be named.
+    pub fn merge_inspected(inspected: Vec<Worker>, parsed: Vec<Worker>) -> Vec<Worker> {
+        let mut workers = inspected;
+        let inspected_count = workers.len();
+        let mut known_queues: Vec<Vec<String>> = vec![Vec::new(); inspected_count];
+
+        for mut known in parsed
+            .into_iter()
+            .filter(|w| w.hostname != GENERIC_WORKER && w.hostname != DETECTED_WORKER)
+        {
+            match find_worker(&workers[..inspected_count], &known.hostname) {
+                Some(idx) => {
+                    workers[idx].failed += known.failed;
+                    known_queues[idx].append(&mut known.queues);
+                }
+                None => {
+                    known.status = WorkerStatus::Offline;
+                    workers.push(known);
+                }
+            }
+        }
+
+        for (worker, mut queues) in workers.iter_mut().zip(known_queues) {
+            if worker.queues.is_empty() {
+                queues.sort();
+                queues.dedup();
+                worker.queues = if queues.is_empty() {
+                    vec!["celery".to_string()]
+                } else {
+                    queues
+                };
+            }
+        }
+        workers.sort_by(|a, b| a.hostname.cmp(&b.hostname));
         workers
     }
 
@@ -207,35 +334,153 @@ impl WorkerParser {
     ///
     /// Creates a default worker when no specific workers are found but
     /// there is evidence of Celery activity (pending tasks or completed tasks).
+    /// `task_count` is how many task results the result backend holds.
     async fn ensure_default_worker_if_needed(
         conn: &mut MultiplexedConnection,
         workers: &mut Vec<Worker>,
-    ) -> Result<(), BrokerError> {
+        task_count: usize,
+    ) {
         if workers.is_empty() {
-            let celery_queue_len: u64 = conn.llen("celery").await.unwrap_or(0);
-            let task_keys: Vec<String> = conn.keys("celery-task-meta-*").await.map_err(|e| {
-                BrokerError::OperationError(format!("Failed to check for task metadata keys: {e}"))
-            })?;
-            let task_count = task_keys.len();
+            let celery_queue_len = QueueStore::length(conn, "celery").await.unwrap_or(0);
 
             if celery_queue_len > 0 || task_count > 0 {
                 // There is activity, so assume a worker exists
                 workers.push(Worker {
-                    hostname: "detected-worker".to_string(),
+                    hostname: DETECTED_WORKER.to_string(),
                     status: if celery_queue_len > 0 {
                         WorkerStatus::Offline
                     } else {
                         WorkerStatus::Online
                     },
                     concurrency: DEFAULT_WORKER_CONCURRENCY,
+                    pool_type: None,
                     queues: vec!["celery".to_string()],
                     active_tasks: vec![],
+                    reserved_tasks: vec![],
                     processed: task_count as u64,
                     failed: 0,
                 });
             }
         }
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(entries: &[(&str, u64, u64, &[&str])]) -> WorkerStats {
+        entries
+            .iter()
+            .map(|(host, processed, failed, queues)| {
+                let queues = queues.iter().map(|q| q.to_string()).collect();
+                (host.to_string(), (*processed, *failed, queues))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_worker_queues_are_sorted_and_deduplicated() {
+        let workers = WorkerParser::build_worker_list(
+            stats(&[
+                (
+                    "worker-b",
+                    1,
+                    0,
+                    &["priority", "celery", "priority", "default"],
+                ),
+                ("worker-a", 0, 0, &[]),
+            ]),
+            HashMap::new(),
+        );
+
+        let hostnames: Vec<&str> = workers.iter().map(|w| w.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["worker-a", "worker-b"]);
+        assert_eq!(workers[0].queues, vec!["celery"]);
+        assert_eq!(workers[1].queues, vec!["celery", "default", "priority"]);
+    }
+
+    #[test]
+    fn test_generic_worker_only_without_real_hostnames() {
+        let workers = WorkerParser::build_worker_list(
+            stats(&[(GENERIC_WORKER, 5, 1, &["celery"])]),
+            HashMap::new(),
+        );
+        assert_eq!(workers.len(), 1);
+        assert_eq!(workers[0].hostname, GENERIC_WORKER);
+        assert_eq!(workers[0].processed, 5);
+    }
+
+    #[test]
+    fn test_real_hostnames_take_precedence_over_generic_worker() {
+        // A single real worker is credited with the unattributed activity
+        let workers = WorkerParser::build_worker_list(
+            stats(&[
+                (GENERIC_WORKER, 5, 1, &["celery"]),
+                ("archflowx13", 0, 0, &["default"]),
+            ]),
+            HashMap::new(),
+        );
+        assert_eq!(workers.len(), 1);
+        assert_eq!(workers[0].hostname, "archflowx13");
+        assert_eq!((workers[0].processed, workers[0].failed), (5, 1));
+        assert_eq!(workers[0].status, WorkerStatus::Online);
+        assert_eq!(workers[0].queues, vec!["celery", "default"]);
+
+        // Several real workers can't be told apart, so it is left out
+        let workers = WorkerParser::build_worker_list(
+            stats(&[
+                (GENERIC_WORKER, 5, 1, &["celery"]),
+                ("host-1", 2, 0, &["default"]),
+                ("host-2", 0, 0, &["priority"]),
+            ]),
+            HashMap::new(),
+        );
+        let hostnames: Vec<&str> = workers.iter().map(|w| w.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["host-1", "host-2"]);
+        assert_eq!(workers[0].processed, 2);
+    }
+
+    #[test]
+    fn test_inspected_workers_take_precedence() {
+        let parsed = WorkerParser::build_worker_list(
+            stats(&[
+                ("host-1", 3, 2, &["default"]),
+                ("host-2", 1, 0, &["priority"]),
+            ]),
+            HashMap::new(),
+        );
+        let mut inspected = Worker::new("host-1".to_string());
+        inspected.status = WorkerStatus::Online;
+        inspected.concurrency = 4;
+        inspected.reserved_tasks = vec!["task-1".to_string()];
+        inspected.processed = 10;
+        let mut detected = Worker::new(DETECTED_WORKER.to_string());
+        detected.status = WorkerStatus::Online;
+
+        let mut parsed_with_placeholder = parsed;
+        parsed_with_placeholder.push(detected);
+        let workers = WorkerParser::merge_inspected(vec![inspected], parsed_with_placeholder);
+
+        let hostnames: Vec<&str> = workers.iter().map(|w| w.hostname.as_str()).collect();
+        assert_eq!(hostnames, vec!["host-1", "host-2"]);
+        assert_eq!(workers[0].concurrency, 4);
+        assert_eq!(workers[0].reserved_tasks, vec!["task-1"]);
+        assert_eq!((workers[0].processed, workers[0].failed), (10, 2));
+        assert_eq!(workers[0].queues, vec!["default"]);
+        assert_eq!(workers[1].status, WorkerStatus::Offline);
+    }
+
+    #[test]
+    fn test_hostname_of_node_name() {
+        assert_eq!(
+            WorkerParser::hostname_of("gen447152@archflowx13"),
+            "archflowx13"
+        );
+        assert_eq!(WorkerParser::hostname_of("archflowx13"), "archflowx13");
+        assert_eq!(
+            WorkerParser::hostname_of("celery@Worker-Prod-1"),
+            "worker-prod-1"
+        );
     }
 }
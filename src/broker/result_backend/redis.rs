@@ -8,7 +8,7 @@ use tracing::{debug, error};
 
 use crate::broker::ResultBackend;
 use crate::error::BrokerError;
-use crate::models::{Task, TaskStatus};
+use crate::models::{looks_like_exception, ResultKind, Task, TaskStatus};
 
 /// Redis result backend
 pub struct RedisResultBackend {
@@ -52,7 +52,10 @@ impl RedisResultBackend {
             _ => TaskStatus::Pending,
         };
 
-        let result = json.get("result").and_then(|v| v.as_str()).map(String::from);
+        let result_value = json.get("result").filter(|v| !v.is_null());
+        let result = result_value.map(ResultKind::render);
+        let result_kind = result_value.map(ResultKind::classify);
+        let looks_like_error = result_value.is_some_and(looks_like_exception);
         let traceback = json.get("traceback").and_then(|v| v.as_str()).map(String::from);
 
         let name = json
@@ -91,7 +94,23 @@ impl RedisResultBackend {
             worker,
             timestamp,
             result,
+            result_kind,
+            looks_like_error,
             traceback,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
         })
     }
 }
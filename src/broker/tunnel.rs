@@ -0,0 +1,183 @@
+//! SSH tunnels to brokers only reachable through a bastion host
+//!
+//! The tunnel is an `ssh -N -L` child process forwarding a local port to the
+//! broker. Connections then go to `127.0.0.1` on that port, and dropping the
+//! tunnel stops the process.
+
+use std::net::TcpListener;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::time::Instant;
+
+use crate::config::SshTunnelConfig;
+use crate::error::BrokerError;
+
+/// How long ssh gets to log in and start forwarding
+const TUNNEL_READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How often the forwarded port is probed while ssh starts
+const TUNNEL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A running SSH tunnel, closed when dropped
+pub struct SshTunnel {
+    child: Child,
+    url: String,
+}
+
+impl SshTunnel {
+    /// Forward a free local port through `config.host` to the broker of
+    /// `broker_url`, waiting until the port accepts connections
+    pub async fn open(config: &SshTunnelConfig, broker_url: &str) -> Result<Self, BrokerError> {
+        let remote = remote_address(config, broker_url)?;
+        let local_port = free_local_port()?;
+
+        let mut child = Command::new("ssh")
+            .args(ssh_args(config, &remote, local_port))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| BrokerError::ConnectionError(format!("Failed to run ssh: {e}")))?;
+
+        let deadline = Instant::now() + TUNNEL_READY_TIMEOUT;
+        loop {
+            if let Some(status) = child
+                .try_wait()
+                .map_err(|e| BrokerError::ConnectionError(format!("Failed to watch ssh: {e}")))?
+            {
+                let mut stderr = String::new();
+                if let Some(mut pipe) = child.stderr.take() {
+                    let _ = pipe.read_to_string(&mut stderr).await;
+                }
+                return Err(BrokerError::ConnectionError(format!(
+                    "SSH tunnel through {} exited ({status}): {}",
+                    config.host,
+                    stderr.trim()
+                )));
+            }
+            if TcpStream::connect(("127.0.0.1", local_port)).await.is_ok() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                return Err(BrokerError::ConnectionError(format!(
+                    "SSH tunnel through {} not ready after {}s",
+                    config.host,
+                    TUNNEL_READY_TIMEOUT.as_secs()
+                )));
+            }
+            tokio::time::sleep(TUNNEL_POLL_INTERVAL).await;
+        }
+
+        Ok(Self {
+            child,
+            url: tunneled_url(broker_url, local_port),
+        })
+    }
+
+    /// Broker URL pointing at the local end of the tunnel
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl Drop for SshTunnel {
+    fn drop(&mut self) {
+        let _ = self.child.start_kill();
+    }
+}
+
+/// Arguments for an ssh process forwarding `127.0.0.1:local_port` to `remote`
+///
+/// Batch mode keeps ssh from prompting for passwords or host keys over the
+/// TUI; the bastion has to accept the key without interaction.
+pub fn ssh_args(config: &SshTunnelConfig, remote: &str, local_port: u16) -> Vec<String> {
+    let (host, port) = split_port(&config.host);
+    let mut args = vec![
+        "-N".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        "ExitOnForwardFailure=yes".to_string(),
+        "-L".to_string(),
+        format!("127.0.0.1:{local_port}:{remote}"),
+    ];
+    if let Some(port) = port {
+        args.extend(["-p".to_string(), port.to_string()]);
+    }
+    if let Some(key_path) = &config.key_path {
+        args.extend(["-i".to_string(), key_path.display().to_string()]);
+    }
+    args.push(match &config.user {
+        Some(user) => format!("{user}@{host}"),
+        None => host.to_string(),
+    });
+    args
+}
+
+/// `host:port` the bastion forwards to, the configured `remote` or the host
+/// and port of `broker_url`
+pub fn remote_address(config: &SshTunnelConfig, broker_url: &str) -> Result<String, BrokerError> {
+    if let Some(remote) = &config.remote {
+        return Ok(remote.clone());
+    }
+    let (scheme, rest) = broker_url
+        .split_once("://")
+        .ok_or_else(|| BrokerError::InvalidUrl(format!("No scheme in {broker_url}")))?;
+    let (_, authority, _) = split_authority(rest);
+    if authority.is_empty() {
+        return Err(BrokerError::InvalidUrl(format!("No host in {broker_url}")));
+    }
+    match split_port(authority) {
+        (_, Some(_)) => Ok(authority.to_string()),
+        (host, None) => Ok(format!("{host}:{}", default_port(scheme))),
+    }
+}
+
+/// `url` with its host and port replaced by `127.0.0.1:local_port`, keeping
+/// the scheme, credentials, database and query
+pub fn tunneled_url(url: &str, local_port: u16) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("redis", url));
+    let (userinfo, _, tail) = split_authority(rest);
+    format!("{scheme}://{userinfo}127.0.0.1:{local_port}{tail}")
+}
+
+/// Split what follows `scheme://` into the `user:pass@` prefix, the host and
+/// port, and the path and query
+fn split_authority(rest: &str) -> (&str, &str, &str) {
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    let (authority, tail) = rest.split_at(end);
+    match authority.rfind('@') {
+        Some(at) => (&authority[..=at], &authority[at + 1..], tail),
+        None => ("", authority, tail),
+    }
+}
+
+/// Split `host:port`, leaving bracketed IPv6 addresses whole
+fn split_port(address: &str) -> (&str, Option<&str>) {
+    match address.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') && !port.is_empty() => (host, Some(port)),
+        _ => (address, None),
+    }
+}
+
+fn default_port(scheme: &str) -> u16 {
+    match scheme {
+        "amqp" | "rabbitmq" => 5672,
+        "amqps" => 5671,
+        _ => 6379,
+    }
+}
+
+/// A local port nothing listens on right now
+fn free_local_port() -> Result<u16, BrokerError> {
+    TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|address| address.port())
+        .map_err(|e| {
+            BrokerError::ConnectionError(format!("No free local port for the tunnel: {e}"))
+        })
+}
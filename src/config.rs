@@ -1,11 +1,162 @@
-use anyhow::Result;
+use crate::broker::registry::BrokerRegistry;
+use crate::error::ConfigError;
+use crate::ui::keymap::KeyMap;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Allowed UI refresh interval in milliseconds
+const REFRESH_INTERVAL_RANGE: RangeInclusive<u64> = 100..=60_000;
+
+/// Result backend URL schemes lazycelery can read task metadata from
+const SUPPORTED_RESULT_BACKEND_SCHEMES: &[&str] = &["redis://"];
+
+/// Prefix of the environment variables that override config values
+pub const ENV_PREFIX: &str = "LAZYCELERY_";
+
+/// Theme names understood by the UI
+const KNOWN_THEMES: &[&str] = &["dark", "light"];
+
+/// Tab names accepted by `ui.default_tab` and `--tab`, in tab order
+pub const TAB_NAMES: [&str; 5] = ["workers", "queues", "tasks", "scheduled", "overview"];
+
+/// Version stamped into config files written by this release
+///
+/// Files without a `version` key predate versioning and count as version 0.
+pub const CONFIG_VERSION: u32 = 17;
+
+/// Keys added to sections of the file, as `(section, keys)`
+type SectionKeys = (&'static str, &'static [&'static str]);
+
+/// Keys added to each section by each version, written with their defaults
+/// when an older file is upgraded
+const ADDED_KEYS: &[(u32, &[SectionKeys])] = &[
+    (
+        1,
+        &[
+            (
+                "broker",
+                &[
+                    "max_result_length",
+                    "operation_timeout",
+                    "max_task_metadata_keys",
+                    "max_task_results",
+                    "max_queue_messages",
+                    "max_pending_tasks",
+                ],
+            ),
+            (
+                "ui",
+                &[
+                    "status_message_timeout",
+                    "confirm_quit",
+                    "full_refresh_interval",
+                    "task_columns",
+                    "min_refresh_gap",
+                    "dry_run",
+                    "compact_width",
+                    "timestamp_mode",
+                    "stuck_task_threshold",
+                ],
+            ),
+        ],
+    ),
+    (2, &[("ui", &["mouse"])]),
+    (3, &[("ui", &["header_format", "status_format"])]),
+    (4, &[("ui", &["purge_double_confirm_above"])]),
+    (5, &[("broker", &["pool_size"])]),
+    (6, &[("ui", &["archived_tasks"])]),
+    (7, &[("broker", &["inspect_timeout"])]),
+    (8, &[("ui", &["default_tab"])]),
+    (9, &[("broker", &["queue_history"])]),
+    (10, &[("ui", &["read_only"])]),
+    (11, &[("broker", &["queue_glob"])]),
+    (12, &[("ui", &["wrap_navigation"])]),
+    (13, &[("ui", &["task_rows_multiline"])]),
+    (14, &[("broker", &["max_args_length"])]),
+    (15, &[("broker", &["events"])]),
+    (16, &[("ui", &["humanize_numbers"])]),
+    (17, &[("broker", &["revoke_ttl"])]),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Format version of the file, see `CONFIG_VERSION`
+    #[serde(default)]
+    pub version: u32,
     pub broker: BrokerConfig,
+    /// Separate store for task metadata, the broker is used when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result_backend: Option<ResultBackendConfig>,
     pub ui: UiConfig,
+    /// Key bindings replacing the defaults, keyed by action name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, KeyBinding>,
+}
+
+/// Upgrade config file `contents` written by an older release
+///
+/// Fills in the keys added since the file's version with their defaults and
+/// stamps the current version, leaving every other key as it was. Returns the
+/// upgraded text, or `None` when the file is already current. Missing sections
+/// are left for deserialization to report.
+pub fn migrate(contents: &str) -> Result<Option<String>> {
+    let mut table: toml::Table = contents.parse()?;
+    let version = match table.get("version") {
+        None => 0,
+        Some(value) => value
+            .as_integer()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| anyhow::anyhow!("version: expected a non-negative integer"))?,
+    };
+    if version >= CONFIG_VERSION {
+        return Ok(None);
+    }
+
+    let defaults = toml::Table::try_from(Config::default())?;
+    let added = ADDED_KEYS
+        .iter()
+        .filter(|(added_in, _)| *added_in > version)
+        .flat_map(|(_, sections)| sections.iter());
+    for (section, keys) in added {
+        let (Some(toml::Value::Table(existing)), Some(toml::Value::Table(default))) =
+            (table.get_mut(*section), defaults.get(*section))
+        else {
+            continue;
+        };
+        for key in *keys {
+            if let (false, Some(value)) = (existing.contains_key(*key), default.get(*key)) {
+                existing.insert(key.to_string(), value.clone());
+            }
+        }
+    }
+
+    // Keep the version ahead of the sections
+    let mut upgraded = toml::Table::new();
+    upgraded.insert("version".to_string(), toml::Value::from(CONFIG_VERSION));
+    upgraded.extend(table.into_iter().filter(|(key, _)| key != "version"));
+
+    Ok(Some(toml::to_string_pretty(&upgraded)?))
+}
+
+/// Chords bound to one action in the `[keys]` section, a single chord or a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBinding {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyBinding {
+    pub fn chords(&self) -> Vec<&str> {
+        match self {
+            KeyBinding::One(chord) => vec![chord.as_str()],
+            KeyBinding::Many(chords) => chords.iter().map(String::as_str).collect(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,49 +164,770 @@ pub struct BrokerConfig {
     pub url: String,
     pub timeout: u32,
     pub retry_attempts: u32,
+    #[serde(default = "default_max_result_length")]
+    pub max_result_length: usize, // bytes, 0 disables truncation
+    #[serde(default = "default_max_args_length")]
+    pub max_args_length: usize, // bytes for each of args and kwargs, 0 disables truncation
+    #[serde(default = "default_operation_timeout")]
+    pub operation_timeout: u64, // milliseconds per broker call, 0 waits indefinitely
+    #[serde(default = "default_pool_size")]
+    pub pool_size: usize, // connections per Redis pool, at least 1
+    #[serde(default = "default_inspect_timeout")]
+    pub inspect_timeout: u64, // milliseconds to wait for inspect replies, 0 disables inspect
+    #[serde(default)]
+    pub queue_history: bool, // store queue lengths in lazycelery:qlen:<queue> on each refresh
+    #[serde(default)]
+    pub queue_glob: String, // only discover queues matching this glob, e.g. "tenantA.*", empty for all
+    #[serde(default)]
+    pub events: bool, // follow the celeryev event stream, workers must run with -E
+    #[serde(default)]
+    pub revoke_ttl: u64, // seconds a revoke stays in effect, 0 keeps revokes forever
+    #[serde(flatten)]
+    pub limits: ParserLimits,
+    /// Reach the broker through an SSH bastion instead of connecting directly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_tunnel: Option<SshTunnelConfig>,
+}
+
+/// SSH tunnel to the broker, set up with the system `ssh` before connecting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SshTunnelConfig {
+    /// Bastion host, with an optional port like `bastion.example.com:2222`
+    pub host: String,
+    /// Login on the bastion, ssh's own default when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Private key to log in with, ssh's own default keys when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<PathBuf>,
+    /// `host:port` of the broker as seen from the bastion, the broker URL's
+    /// host and port when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+}
+
+/// Caps on how much Celery data a refresh reads from the broker
+///
+/// Anything past a cap is left out of the UI, which flags when that happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParserLimits {
+    /// Task metadata keys read to build worker statistics
+    pub max_task_metadata_keys: usize,
+    /// Task results listed in the Tasks tab
+    pub max_task_results: usize,
+    /// Queue messages read to look up the names of finished tasks
+    pub max_queue_messages: usize,
+    /// Pending messages listed per queue
+    pub max_pending_tasks: usize,
+}
+
+impl Default for ParserLimits {
+    fn default() -> Self {
+        Self {
+            max_task_metadata_keys: 500,
+            max_task_results: 100,
+            max_queue_messages: 100,
+            max_pending_tasks: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultBackendConfig {
+    pub url: String,
+}
+
+fn default_max_result_length() -> usize {
+    crate::broker::DEFAULT_MAX_RESULT_LENGTH
+}
+
+fn default_max_args_length() -> usize {
+    crate::broker::DEFAULT_MAX_ARGS_LENGTH
+}
+
+fn default_operation_timeout() -> u64 {
+    crate::broker::DEFAULT_OPERATION_TIMEOUT_MS
+}
+
+fn default_pool_size() -> usize {
+    crate::broker::DEFAULT_POOL_SIZE
+}
+
+fn default_inspect_timeout() -> u64 {
+    crate::broker::DEFAULT_INSPECT_TIMEOUT_MS
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UiConfig {
     pub refresh_interval: u64, // milliseconds
     pub theme: String,
+    #[serde(default = "default_status_message_timeout")]
+    pub status_message_timeout: u64, // seconds, 0 disables auto-expiry
+    #[serde(default)]
+    pub confirm_quit: bool,
+    #[serde(default)]
+    pub full_refresh_interval: u64, // milliseconds, 0 runs a full refresh on every tick
+    #[serde(default = "default_task_columns")]
+    pub task_columns: Vec<String>,
+    #[serde(default)]
+    pub min_refresh_gap: u64, // milliseconds, 0 never skips scheduled refreshes
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub read_only: bool, // disable every action that changes the broker, for shared dashboards
+    #[serde(default = "default_compact_width")]
+    pub compact_width: u16, // columns, narrower terminals use the compact layout, 0 disables it
+    #[serde(default)]
+    pub timestamp_mode: TimestampMode,
+    #[serde(default = "default_stuck_task_threshold")]
+    pub stuck_task_threshold: u64, // seconds an active task may run before it is flagged, 0 disables
+    #[serde(default = "default_wrap_navigation")]
+    pub wrap_navigation: bool, // moving past the end of a list continues at the other end
+    #[serde(default)]
+    pub task_rows_multiline: bool, // two-line task rows with an args preview under the name
+    #[serde(default = "default_humanize_numbers")]
+    pub humanize_numbers: bool, // large counts in lists shown as 15.2k, details stay exact
+    #[serde(default = "default_mouse")]
+    pub mouse: bool, // capture mouse events, some terminals and tmux setups misbehave with it
+    #[serde(default)]
+    pub header_format: String, // e.g. "{broker_host} db{db}", empty keeps the built-in title
+    #[serde(default)]
+    pub status_format: String, // replaces the status bar summary, empty keeps the built-in one
+    #[serde(default = "default_purge_double_confirm_above")]
+    pub purge_double_confirm_above: u64, // messages, purging a longer queue asks for its name
+    #[serde(default = "default_archived_tasks")]
+    pub archived_tasks: usize, // finished tasks kept after the broker drops them, 0 disables
+    #[serde(default = "default_tab")]
+    pub default_tab: String, // tab shown on startup: workers, queues, tasks, scheduled or overview
+}
+
+fn default_stuck_task_threshold() -> u64 {
+    3_600
+}
+
+fn default_wrap_navigation() -> bool {
+    true
+}
+
+fn default_humanize_numbers() -> bool {
+    true
+}
+
+fn default_mouse() -> bool {
+    true
+}
+
+fn default_purge_double_confirm_above() -> u64 {
+    100
+}
+
+fn default_archived_tasks() -> usize {
+    50
+}
+
+fn default_tab() -> String {
+    TAB_NAMES[0].to_string()
+}
+
+/// Whether `name` is one of `TAB_NAMES`, ignoring case and surrounding spaces
+fn is_tab_name(name: &str) -> bool {
+    TAB_NAMES.contains(&name.trim().to_lowercase().as_str())
+}
+
+/// How task times are shown, toggled at runtime with `t`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimestampMode {
+    /// Time elapsed or remaining, e.g. "3m ago"
+    #[default]
+    Relative,
+    /// Exact date and time
+    Absolute,
+}
+
+impl TimestampMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            TimestampMode::Relative => TimestampMode::Absolute,
+            TimestampMode::Absolute => TimestampMode::Relative,
+        }
+    }
+
+    /// Name of the mode as written in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            TimestampMode::Relative => "relative",
+            TimestampMode::Absolute => "absolute",
+        }
+    }
+}
+
+impl FromStr for TimestampMode {
+    type Err = ();
+
+    fn from_str(name: &str) -> std::result::Result<Self, ()> {
+        match name.trim().to_lowercase().as_str() {
+            "relative" => Ok(TimestampMode::Relative),
+            "absolute" => Ok(TimestampMode::Absolute),
+            _ => Err(()),
+        }
+    }
+}
+
+fn default_compact_width() -> u16 {
+    80
+}
+
+pub fn default_task_columns() -> Vec<String> {
+    TaskColumn::DEFAULTS
+        .iter()
+        .map(|column| column.name().to_string())
+        .collect()
+}
+
+/// Columns the task table can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskColumn {
+    Id,
+    Name,
+    Status,
+    Worker,
+    Duration,
+    Eta,
+    Timestamp,
+    Retries,
+    Result,
+    Queue,
+    Priority,
+}
+
+impl TaskColumn {
+    pub const DEFAULTS: [TaskColumn; 5] = [
+        TaskColumn::Id,
+        TaskColumn::Name,
+        TaskColumn::Status,
+        TaskColumn::Worker,
+        TaskColumn::Duration,
+    ];
+
+    /// Name of the column as written in the config file
+    pub fn name(self) -> &'static str {
+        match self {
+            TaskColumn::Id => "id",
+            TaskColumn::Name => "name",
+            TaskColumn::Status => "status",
+            TaskColumn::Worker => "worker",
+            TaskColumn::Duration => "duration",
+            TaskColumn::Eta => "eta",
+            TaskColumn::Timestamp => "timestamp",
+            TaskColumn::Retries => "retries",
+            TaskColumn::Result => "result",
+            TaskColumn::Queue => "queue",
+            TaskColumn::Priority => "priority",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "id" => Some(TaskColumn::Id),
+            "name" => Some(TaskColumn::Name),
+            "status" => Some(TaskColumn::Status),
+            "worker" => Some(TaskColumn::Worker),
+            "duration" => Some(TaskColumn::Duration),
+            "eta" => Some(TaskColumn::Eta),
+            "timestamp" => Some(TaskColumn::Timestamp),
+            "retries" => Some(TaskColumn::Retries),
+            "result" => Some(TaskColumn::Result),
+            "queue" => Some(TaskColumn::Queue),
+            "priority" => Some(TaskColumn::Priority),
+            _ => None,
+        }
+    }
+}
+
+impl UiConfig {
+    /// Resolve `task_columns` into known columns, also returning the names that
+    /// were ignored. Falls back to the default columns when none are usable.
+    pub fn resolve_task_columns(&self) -> (Vec<TaskColumn>, Vec<String>) {
+        let mut columns = Vec::new();
+        let mut unknown = Vec::new();
+        for name in &self.task_columns {
+            match TaskColumn::from_name(name) {
+                Some(column) if !columns.contains(&column) => columns.push(column),
+                Some(_) => {}
+                None => unknown.push(name.clone()),
+            }
+        }
+
+        if columns.is_empty() {
+            columns = TaskColumn::DEFAULTS.to_vec();
+        }
+
+        (columns, unknown)
+    }
+}
+
+fn default_status_message_timeout() -> u64 {
+    5
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             broker: BrokerConfig {
                 url: "redis://localhost:6379/0".to_string(),
                 timeout: 30,
                 retry_attempts: 3,
+                max_result_length: default_max_result_length(),
+                max_args_length: default_max_args_length(),
+                operation_timeout: default_operation_timeout(),
+                pool_size: default_pool_size(),
+                inspect_timeout: default_inspect_timeout(),
+                queue_history: false,
+                queue_glob: String::new(),
+                events: false,
+                revoke_ttl: 0,
+                limits: ParserLimits::default(),
+                ssh_tunnel: None,
             },
+            result_backend: None,
             ui: UiConfig {
                 refresh_interval: 1000,
                 theme: "dark".to_string(),
+                status_message_timeout: default_status_message_timeout(),
+                confirm_quit: false,
+                full_refresh_interval: 0,
+                task_columns: default_task_columns(),
+                min_refresh_gap: 0,
+                dry_run: false,
+                read_only: false,
+                compact_width: default_compact_width(),
+                timestamp_mode: TimestampMode::default(),
+                stuck_task_threshold: default_stuck_task_threshold(),
+                wrap_navigation: default_wrap_navigation(),
+                task_rows_multiline: false,
+                humanize_numbers: default_humanize_numbers(),
+                mouse: default_mouse(),
+                header_format: String::new(),
+                status_format: String::new(),
+                purge_double_confirm_above: default_purge_double_confirm_above(),
+                archived_tasks: default_archived_tasks(),
+                default_tab: default_tab(),
             },
+            keys: BTreeMap::new(),
         }
     }
 }
 
+/// Values given on the command line, which take precedence over everything else
+#[derive(Debug, Clone, Default)]
+pub struct CliOverrides {
+    pub broker_url: Option<String>,
+    pub dry_run: bool,
+    pub read_only: bool,
+    pub no_mouse: bool,
+    /// Tab shown on startup, replacing `ui.default_tab`
+    pub tab: Option<String>,
+}
+
+impl CliOverrides {
+    /// Replace a `--tab` that names no tab with the Workers tab, returning
+    /// the warning to show for it
+    pub fn fall_back_from_unknown_tab(&mut self) -> Option<String> {
+        let tab = self.tab.as_mut().filter(|tab| !is_tab_name(tab))?;
+        let warning = format!(
+            "Unknown tab '{tab}' (expected one of: {}), starting on {}",
+            TAB_NAMES.join(", "),
+            TAB_NAMES[0]
+        );
+        *tab = default_tab();
+        Some(warning)
+    }
+}
+
+/// Parse an environment variable value, naming the variable on failure
+fn parse_env<T: FromStr>(
+    name: &str,
+    value: &str,
+    expected: &'static str,
+) -> Result<T, ConfigError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError::InvalidEnvVar {
+            name: name.to_string(),
+            value: value.to_string(),
+            expected,
+        })
+}
+
+fn parse_env_bool(name: &str, value: &str) -> Result<bool, ConfigError> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "0" | "false" | "no" | "off" => Ok(false),
+        _ => Err(ConfigError::InvalidEnvVar {
+            name: name.to_string(),
+            value: value.to_string(),
+            expected: "boolean",
+        }),
+    }
+}
+
 impl Config {
+    /// Apply `LAZYCELERY_*` environment variables over the current values
+    pub fn apply_env_overrides(&mut self) -> Result<(), ConfigError> {
+        self.apply_overrides_from(|name| std::env::var(name).ok())
+    }
+
+    /// Apply overrides looked up by environment variable name
+    ///
+    /// Each config value has a variable named after it, e.g. `LAZYCELERY_BROKER_URL`,
+    /// `LAZYCELERY_REFRESH_INTERVAL` or `LAZYCELERY_THEME`. Unset variables leave
+    /// the value alone; malformed numbers and booleans are rejected.
+    pub fn apply_overrides_from(
+        &mut self,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), ConfigError> {
+        let var = |key: &str| {
+            let name = format!("{ENV_PREFIX}{key}");
+            lookup(&name).map(|value| (name, value))
+        };
+
+        if let Some((_, value)) = var("BROKER_URL") {
+            self.broker.url = value;
+        }
+        if let Some((name, value)) = var("BROKER_TIMEOUT") {
+            self.broker.timeout = parse_env(&name, &value, "number of seconds")?;
+        }
+        if let Some((name, value)) = var("RETRY_ATTEMPTS") {
+            self.broker.retry_attempts = parse_env(&name, &value, "number")?;
+        }
+        if let Some((name, value)) = var("MAX_RESULT_LENGTH") {
+            self.broker.max_result_length = parse_env(&name, &value, "number of bytes")?;
+        }
+        if let Some((name, value)) = var("MAX_ARGS_LENGTH") {
+            self.broker.max_args_length = parse_env(&name, &value, "number of bytes")?;
+        }
+        if let Some((name, value)) = var("OPERATION_TIMEOUT") {
+            self.broker.operation_timeout = parse_env(&name, &value, "number of milliseconds")?;
+        }
+        if let Some((name, value)) = var("POOL_SIZE") {
+            self.broker.pool_size = parse_env(&name, &value, "number of connections")?;
+        }
+        if let Some((name, value)) = var("INSPECT_TIMEOUT") {
+            self.broker.inspect_timeout = parse_env(&name, &value, "number of milliseconds")?;
+        }
+        if let Some((name, value)) = var("QUEUE_HISTORY") {
+            self.broker.queue_history = parse_env_bool(&name, &value)?;
+        }
+        if let Some((_, value)) = var("QUEUE_GLOB") {
+            self.broker.queue_glob = value;
+        }
+        if let Some((name, value)) = var("EVENTS") {
+            self.broker.events = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("REVOKE_TTL") {
+            self.broker.revoke_ttl = parse_env(&name, &value, "number of seconds")?;
+        }
+        if let Some((_, value)) = var("RESULT_BACKEND_URL") {
+            self.result_backend = Some(ResultBackendConfig { url: value });
+        }
+        if let Some((name, value)) = var("REFRESH_INTERVAL") {
+            self.ui.refresh_interval = parse_env(&name, &value, "number of milliseconds")?;
+        }
+        if let Some((_, value)) = var("THEME") {
+            self.ui.theme = value;
+        }
+        if let Some((name, value)) = var("STATUS_MESSAGE_TIMEOUT") {
+            self.ui.status_message_timeout = parse_env(&name, &value, "number of seconds")?;
+        }
+        if let Some((name, value)) = var("CONFIRM_QUIT") {
+            self.ui.confirm_quit = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("FULL_REFRESH_INTERVAL") {
+            self.ui.full_refresh_interval = parse_env(&name, &value, "number of milliseconds")?;
+        }
+        if let Some((_, value)) = var("TASK_COLUMNS") {
+            self.ui.task_columns = value.split(',').map(|c| c.trim().to_string()).collect();
+        }
+        if let Some((name, value)) = var("MIN_REFRESH_GAP") {
+            self.ui.min_refresh_gap = parse_env(&name, &value, "number of milliseconds")?;
+        }
+        if let Some((name, value)) = var("DRY_RUN") {
+            self.ui.dry_run = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("READ_ONLY") {
+            self.ui.read_only = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("WRAP_NAVIGATION") {
+            self.ui.wrap_navigation = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("TASK_ROWS_MULTILINE") {
+            self.ui.task_rows_multiline = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("HUMANIZE_NUMBERS") {
+            self.ui.humanize_numbers = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("COMPACT_WIDTH") {
+            self.ui.compact_width = parse_env(&name, &value, "number of columns")?;
+        }
+        if let Some((name, value)) = var("STUCK_TASK_THRESHOLD") {
+            self.ui.stuck_task_threshold = parse_env(&name, &value, "number of seconds")?;
+        }
+        if let Some((name, value)) = var("MOUSE") {
+            self.ui.mouse = parse_env_bool(&name, &value)?;
+        }
+        if let Some((name, value)) = var("PURGE_DOUBLE_CONFIRM_ABOVE") {
+            self.ui.purge_double_confirm_above = parse_env(&name, &value, "number of messages")?;
+        }
+        if let Some((name, value)) = var("ARCHIVED_TASKS") {
+            self.ui.archived_tasks = parse_env(&name, &value, "number of tasks")?;
+        }
+        if let Some((_, value)) = var("DEFAULT_TAB") {
+            self.ui.default_tab = value;
+        }
+        if let Some((name, value)) = var("TIMESTAMP_MODE") {
+            self.ui.timestamp_mode =
+                parse_env(&name, &value, "timestamp mode (relative/absolute)")?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply command line values, which win over the environment and the file
+    pub fn apply_cli_overrides(&mut self, cli: &CliOverrides) {
+        if let Some(url) = &cli.broker_url {
+            self.broker.url = url.clone();
+        }
+        self.ui.dry_run |= cli.dry_run;
+        self.ui.read_only |= cli.read_only;
+        if cli.no_mouse {
+            self.ui.mouse = false;
+        }
+        if let Some(tab) = &cli.tab {
+            self.ui.default_tab = tab.clone();
+        }
+    }
+
+    /// Load the configuration used by the TUI
+    ///
+    /// Precedence, highest first: CLI flags > `LAZYCELERY_*` environment
+    /// variables > config file (`path`, or the default location) > defaults.
+    /// The result is validated once, after all layers are applied, so a flag
+    /// can fix a value the file or environment got wrong.
+    pub fn load(path: Option<PathBuf>, cli: &CliOverrides) -> Result<Self> {
+        let config = match path {
+            Some(path) => Self::open_file(&path)?,
+            None => Self::load_or_create_default()?,
+        };
+        config.with_overrides(cli)
+    }
+
+    /// Load the configuration again while the TUI is running
+    ///
+    /// Same layers and precedence as `load`, without side effects: an older
+    /// config file is upgraded in memory only, a missing default file is not
+    /// created and nothing is printed, so outcomes can be shown in the TUI.
+    pub fn reload(path: Option<PathBuf>, cli: &CliOverrides) -> Result<Self> {
+        let config = match path.or_else(|| Self::default_path().filter(|path| path.exists())) {
+            Some(path) => Self::read_file(&path)?.0,
+            None => Self::default(),
+        };
+        config.with_overrides(cli)
+    }
+
+    /// Apply environment and CLI overrides, then validate the result
+    fn with_overrides(mut self, cli: &CliOverrides) -> Result<Self> {
+        self.apply_env_overrides()
+            .context("Invalid environment override")?;
+        self.apply_cli_overrides(cli);
+        self.validate().context("Invalid configuration")?;
+        Ok(self)
+    }
+
+    /// Load a config file, upgrading it in place when it has an older version
+    ///
+    /// The upgraded file is only written once it parses. Keys lazycelery
+    /// doesn't know are kept, comments are not.
     pub fn from_file(path: PathBuf) -> Result<Self> {
-        let contents = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&contents)?;
+        let config = Self::open_file(&path)?;
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
         Ok(config)
     }
 
-    pub fn load_or_create_default() -> Result<Self> {
+    /// Parse a config file without validating it, upgrading it in place when
+    /// it has an older version and parses
+    fn open_file(path: &Path) -> Result<Self> {
+        let (config, upgraded) = Self::read_file(path)?;
+
+        if let Some(upgraded) = upgraded {
+            match std::fs::write(path, upgraded) {
+                Ok(()) => eprintln!(
+                    "✅ Upgraded config at {} to version {CONFIG_VERSION}",
+                    path.display()
+                ),
+                Err(e) => eprintln!("⚠️  Could not upgrade config file {}: {e}", path.display()),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Parse a config file, upgrading an older version in memory
+    ///
+    /// Also returns the upgraded file contents when an upgrade was needed.
+    fn read_file(path: &Path) -> Result<(Self, Option<String>)> {
+        let contents = std::fs::read_to_string(path)?;
+        let upgraded = migrate(&contents)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        let config: Config = toml::from_str(upgraded.as_deref().unwrap_or(&contents))
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+        Ok((config, upgraded))
+    }
+
+    /// Whether going from this config to `new` means talking to another
+    /// broker, which takes a reconnect rather than just new settings
+    pub fn needs_reconnect(&self, new: &Config) -> bool {
+        self.broker.url != new.broker.url
+    }
+
+    /// Location of the config file used when none is given
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("lazycelery").join("config.toml"))
+    }
+
+    /// Write the setting that can be changed from the TUI, the refresh
+    /// interval, to the config file at `path`
+    ///
+    /// Only that key is edited, so environment and CLI overrides of the
+    /// running config are never written and keys lazycelery doesn't know are
+    /// kept; comments are not. A missing file is created with defaults for
+    /// everything else.
+    pub fn save_ui_settings(&self, path: &Path) -> Result<()> {
+        let mut table: toml::Table = if path.exists() {
+            std::fs::read_to_string(path)?
+                .parse()
+                .with_context(|| format!("Failed to parse config file {}", path.display()))?
+        } else {
+            toml::Table::try_from(Self::default())?
+        };
+
+        let ui = table
+            .entry("ui")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| anyhow::anyhow!("ui: expected a table in {}", path.display()))?;
+        ui.insert(
+            "refresh_interval".to_string(),
+            toml::Value::from(self.ui.refresh_interval as i64),
+        );
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string_pretty(&table)?)
+            .with_context(|| format!("Failed to write config file {}", path.display()))
+    }
+
+    /// Check that all fields hold values the application can work with
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if BrokerRegistry::builtin().find(&self.broker.url).is_none() {
+            return Err(ConfigError::InvalidBrokerUrl(self.broker.url.clone()));
+        }
+
+        if let Some(backend) = &self.result_backend {
+            if !SUPPORTED_RESULT_BACKEND_SCHEMES
+                .iter()
+                .any(|scheme| backend.url.starts_with(scheme))
+            {
+                return Err(ConfigError::InvalidResultBackendUrl(backend.url.clone()));
+            }
+        }
+
+        if !REFRESH_INTERVAL_RANGE.contains(&self.ui.refresh_interval) {
+            return Err(ConfigError::RefreshIntervalOutOfRange {
+                value: self.ui.refresh_interval,
+                min: *REFRESH_INTERVAL_RANGE.start(),
+                max: *REFRESH_INTERVAL_RANGE.end(),
+            });
+        }
+
+        if self.ui.full_refresh_interval != 0
+            && self.ui.full_refresh_interval < self.ui.refresh_interval
+        {
+            return Err(ConfigError::FullRefreshIntervalTooShort {
+                value: self.ui.full_refresh_interval,
+                refresh_interval: self.ui.refresh_interval,
+            });
+        }
+
+        if self.broker.retry_attempts == 0 {
+            return Err(ConfigError::InvalidRetryAttempts);
+        }
+
+        if self.broker.pool_size == 0 {
+            return Err(ConfigError::InvalidPoolSize);
+        }
+
+        if let Some(tunnel) = &self.broker.ssh_tunnel {
+            if tunnel.host.trim().is_empty() {
+                return Err(ConfigError::InvalidSshTunnel(
+                    "host must name the bastion to connect through".to_string(),
+                ));
+            }
+            if let Some(remote) = &tunnel.remote {
+                let valid = remote
+                    .rsplit_once(':')
+                    .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+                if !valid {
+                    return Err(ConfigError::InvalidSshTunnel(format!(
+                        "remote '{remote}' is not a host:port address"
+                    )));
+                }
+            }
+        }
+
+        if !KNOWN_THEMES.contains(&self.ui.theme.as_str()) {
+            return Err(ConfigError::UnknownTheme {
+                name: self.ui.theme.clone(),
+                known: KNOWN_THEMES.join(", "),
+            });
+        }
+
+        if !is_tab_name(&self.ui.default_tab) {
+            return Err(ConfigError::UnknownTab {
+                name: self.ui.default_tab.clone(),
+                known: TAB_NAMES.join(", "),
+            });
+        }
+
+        KeyMap::from_config(&self.keys)?;
+
+        Ok(())
+    }
+
+    /// Load the default config file without validating it, creating it if
+    /// missing
+    ///
+    /// Overrides are applied by the caller, so they are never written to the
+    /// created file.
+    fn load_or_create_default() -> Result<Self> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine config directory"))?
             .join("lazycelery");
 
         let config_path = config_dir.join("config.toml");
 
-        if config_path.exists() {
-            Self::from_file(config_path)
+        let config = if config_path.exists() {
+            Self::open_file(&config_path)?
         } else {
             // Create default config
             let default_config = Self::default();
+            default_config.validate()?;
 
             // Try to create config directory and file
             if let Err(e) = std::fs::create_dir_all(&config_dir) {
@@ -69,7 +941,9 @@ impl Config {
                 }
             }
 
-            Ok(default_config)
-        }
+            default_config
+        };
+
+        Ok(config)
     }
 }
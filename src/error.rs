@@ -25,6 +25,39 @@ pub enum BrokerError {
     NotImplemented,
 }
 
+/// How the app reacts to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// A request the user can correct, reported with a hint on what to change
+    User,
+    /// A broker or network hiccup that may clear up, retried with backoff
+    Transient,
+    /// Retrying can't help until something outside the app changes, e.g. the
+    /// broker credentials, so automatic retries stop
+    Fatal,
+}
+
+impl BrokerError {
+    /// How the app should react to the error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            BrokerError::ValidationError(_)
+            | BrokerError::InvalidUrl(_)
+            | BrokerError::NotImplemented => ErrorCategory::User,
+            BrokerError::ConnectionError(_)
+            | BrokerError::OperationError(_)
+            | BrokerError::Timeout => ErrorCategory::Transient,
+            BrokerError::AuthError => ErrorCategory::Fatal,
+        }
+    }
+
+    /// Whether the error comes from a request the user can correct, rather
+    /// than from the broker or the connection
+    pub fn is_user_error(&self) -> bool {
+        self.category() == ErrorCategory::User
+    }
+}
+
 #[derive(Debug, Error)]
 #[allow(dead_code)]
 pub enum AppError {
@@ -37,3 +70,77 @@ pub enum AppError {
     #[error("Configuration error: {0}")]
     Config(String),
 }
+
+impl AppError {
+    /// The broker error behind this error, if any
+    #[allow(dead_code)]
+    pub fn broker_error(&self) -> Option<&BrokerError> {
+        match self {
+            AppError::Broker(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// How the app should react to the error. Broker errors keep their own
+    /// category, a broken terminal is fatal and bad configuration is the user's
+    /// to fix.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AppError::Broker(e) => e.category(),
+            AppError::Ui(_) => ErrorCategory::Fatal,
+            AppError::Config(_) => ErrorCategory::User,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigError {
+    #[error(
+        "broker.url: '{0}' is not a supported broker URL (expected redis://, amqp:// or rabbitmq://)"
+    )]
+    InvalidBrokerUrl(String),
+
+    #[error("ui.refresh_interval: {value}ms is out of range (must be between {min} and {max} ms)")]
+    RefreshIntervalOutOfRange { value: u64, min: u64, max: u64 },
+
+    #[error("ui.full_refresh_interval: {value}ms is shorter than ui.refresh_interval ({refresh_interval}ms)")]
+    FullRefreshIntervalTooShort { value: u64, refresh_interval: u64 },
+
+    #[error("result_backend.url: '{0}' is not a supported result backend URL (expected redis://)")]
+    InvalidResultBackendUrl(String),
+
+    #[error("{name}: '{value}' is not a valid {expected}")]
+    InvalidEnvVar {
+        name: String,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error("broker.retry_attempts: must be at least 1")]
+    InvalidRetryAttempts,
+
+    #[error("broker.pool_size: must be at least 1")]
+    InvalidPoolSize,
+
+    #[error("broker.ssh_tunnel: {0}")]
+    InvalidSshTunnel(String),
+
+    #[error("ui.theme: unknown theme '{name}' (expected one of: {known})")]
+    UnknownTheme { name: String, known: String },
+
+    #[error("ui.default_tab: unknown tab '{name}' (expected one of: {known})")]
+    UnknownTab { name: String, known: String },
+
+    #[error("keys.{name}: unknown action (expected one of: {known})")]
+    UnknownKeyAction { name: String, known: String },
+
+    #[error("keys.{action}: '{chord}' is not a valid key chord")]
+    InvalidKeyChord { action: &'static str, chord: String },
+
+    #[error("keys: '{chord}' is bound to both {first} and {second}")]
+    ConflictingKeyBinding {
+        chord: String,
+        first: &'static str,
+        second: &'static str,
+    },
+}
@@ -3,6 +3,8 @@ pub mod broker;
 pub mod config;
 pub mod error;
 pub mod models;
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod ui;
 pub mod update;
 pub mod utils;
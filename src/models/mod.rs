@@ -1,7 +1,11 @@
 pub mod queue;
+pub mod scheduled;
 pub mod task;
 pub mod worker;
 
-pub use queue::Queue;
-pub use task::{Task, TaskStatus};
-pub use worker::{Worker, WorkerStatus};
+pub use queue::{Queue, QueueDiscrepancy};
+pub use scheduled::ScheduledEntry;
+pub use task::{looks_like_exception, ResultKind, Task, TaskStatus};
+pub use worker::{
+    canonical_hostname, canonical_node, find_worker, merge_workers_by_host, Worker, WorkerStatus,
+};
@@ -1,10 +1,58 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Queue {
     pub name: String,
     pub length: u64,
     pub consumers: u32,
+    /// How long the oldest message has been waiting, when it could be determined
+    #[serde(default)]
+    pub oldest_message_age: Option<Duration>,
+    /// Sign of a transport mismatch, for queues kombu has a binding for
+    #[serde(default)]
+    pub discrepancy: Option<QueueDiscrepancy>,
+}
+
+/// Something about a bound queue that doesn't add up, e.g. because it is
+/// stored in a way lazycelery or the workers don't read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QueueDiscrepancy {
+    /// The queue key holds a Redis type other than a list or a stream
+    UnexpectedKeyType(String),
+    /// Nothing is waiting although consumers are attached
+    EmptyWithConsumers,
+}
+
+impl QueueDiscrepancy {
+    /// Check a queue that has a kombu binding
+    ///
+    /// `key_type` is what Redis `TYPE` says about the queue key, `none` when
+    /// it doesn't exist. Queues without a binding are never flagged.
+    pub fn detect(bound: bool, key_type: &str, length: u64, consumers: u32) -> Option<Self> {
+        if !bound {
+            return None;
+        }
+        match key_type {
+            "list" | "stream" | "none" if length == 0 && consumers > 0 => {
+                Some(QueueDiscrepancy::EmptyWithConsumers)
+            }
+            "list" | "stream" | "none" => None,
+            other => Some(QueueDiscrepancy::UnexpectedKeyType(other.to_string())),
+        }
+    }
+
+    /// One line explanation for the queue details
+    pub fn describe(&self) -> String {
+        match self {
+            QueueDiscrepancy::UnexpectedKeyType(key_type) => {
+                format!("Bound, but the queue key is a Redis {key_type}, not a list or stream")
+            }
+            QueueDiscrepancy::EmptyWithConsumers => {
+                "Bound and consumed, but no messages: check the transport".to_string()
+            }
+        }
+    }
 }
 
 impl Queue {
@@ -15,6 +63,8 @@ impl Queue {
             name,
             length: 0,
             consumers: 0,
+            oldest_message_age: None,
+            discrepancy: None,
         }
     }
 
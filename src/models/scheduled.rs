@@ -0,0 +1,16 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A periodic task from the celery beat schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEntry {
+    /// Name of the entry in the beat schedule
+    pub name: String,
+    /// Name of the task the entry sends
+    pub task: String,
+    /// Human readable schedule, e.g. "every 10s" or "crontab */5 * * * *"
+    pub schedule: String,
+    /// When beat will next send the task, when known
+    #[serde(default)]
+    pub next_run: Option<DateTime<Utc>>,
+}
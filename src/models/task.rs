@@ -1,5 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -11,7 +15,57 @@ pub struct Task {
     pub worker: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub result: Option<String>,
+    /// Shape of the stored result, `None` when the broker couldn't tell
+    #[serde(default)]
+    pub result_kind: Option<ResultKind>,
+    /// The result holds a serialized exception, whatever the status says,
+    /// see `looks_like_exception`
+    #[serde(default)]
+    pub looks_like_error: bool,
     pub traceback: Option<String>,
+    /// Original size in bytes of `result` when it was truncated during parsing
+    #[serde(default)]
+    pub result_full_len: Option<usize>,
+    /// Original size in bytes of `traceback` when it was truncated during parsing
+    #[serde(default)]
+    pub traceback_full_len: Option<usize>,
+    /// Original size in bytes of `args` when it was truncated during parsing
+    #[serde(default)]
+    pub args_full_len: Option<usize>,
+    /// Original size in bytes of `kwargs` when it was truncated during parsing
+    #[serde(default)]
+    pub kwargs_full_len: Option<usize>,
+    /// Earliest time the task is scheduled to run, from the message `eta` header
+    #[serde(default)]
+    pub eta: Option<DateTime<Utc>>,
+    /// Id of the first task of the workflow (chain, group or chord) this task belongs to
+    #[serde(default)]
+    pub root_id: Option<String>,
+    /// Id of the task that spawned this one
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// Id of the group this task is a member of
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Queue the task message was routed to, when known
+    #[serde(default)]
+    pub queue: Option<String>,
+    /// Number of times the task has been retried
+    #[serde(default)]
+    pub retries: u32,
+    /// Retry limit of the task, when the message or metadata carries it
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+    /// Message priority from the `priority` property, when the producer set one
+    #[serde(default)]
+    pub priority: Option<u8>,
+    /// Ids of the tasks this one spawned, from the result metadata `children`
+    #[serde(default)]
+    pub children: Vec<String>,
+    /// How long the task ran, from the metadata `runtime` or its start and
+    /// completion times, when the result backend records them
+    #[serde(default)]
+    pub duration: Option<Duration>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,6 +78,130 @@ pub enum TaskStatus {
     Revoked,
 }
 
+/// Shape of a task result, used to summarize results too large or too noisy
+/// to show inline
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ResultKind {
+    Null,
+    Boolean,
+    Number,
+    String,
+    Object { keys: usize },
+    Array { items: usize },
+    Binary { bytes: usize },
+}
+
+impl ResultKind {
+    /// Classify a JSON result value
+    ///
+    /// Bytes encoded by kombu's JSON serializer (`{"__type__": "bytes", ...}`)
+    /// and strings holding control characters or replacement characters left
+    /// by a lossy UTF-8 decode are treated as binary.
+    pub fn classify(value: &Value) -> Self {
+        match value {
+            Value::Null => ResultKind::Null,
+            Value::Bool(_) => ResultKind::Boolean,
+            Value::Number(_) => ResultKind::Number,
+            Value::String(s) if is_binary_text(s) => ResultKind::Binary { bytes: s.len() },
+            Value::String(_) => ResultKind::String,
+            Value::Object(map) if map.get("__type__").and_then(Value::as_str) == Some("bytes") => {
+                ResultKind::Binary {
+                    bytes: map
+                        .get("__value__")
+                        .and_then(Value::as_str)
+                        .map_or(0, str::len),
+                }
+            }
+            Value::Object(map) => ResultKind::Object { keys: map.len() },
+            Value::Array(items) => ResultKind::Array { items: items.len() },
+        }
+    }
+
+    /// Text shown for a result value
+    ///
+    /// Strings are shown without JSON quoting, structures are pretty-printed
+    /// and binary content is escaped so it can't garble the terminal.
+    pub fn render(value: &Value) -> String {
+        match (Self::classify(value), value) {
+            (ResultKind::String, Value::String(s)) => s.clone(),
+            (ResultKind::Binary { .. }, Value::Object(map)) => map
+                .get("__value__")
+                .map(Value::to_string)
+                .unwrap_or_default(),
+            (ResultKind::Object { .. } | ResultKind::Array { .. }, _) => {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Whether the value is a structure or binary blob best summarized in lists
+    pub fn is_opaque(self) -> bool {
+        matches!(
+            self,
+            ResultKind::Object { .. } | ResultKind::Array { .. } | ResultKind::Binary { .. }
+        )
+    }
+}
+
+impl fmt::Display for ResultKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn count(n: usize, unit: &str) -> String {
+            if n == 1 {
+                format!("1 {unit}")
+            } else {
+                format!("{n} {unit}s")
+            }
+        }
+
+        match self {
+            ResultKind::Null => write!(f, "null"),
+            ResultKind::Boolean => write!(f, "<boolean>"),
+            ResultKind::Number => write!(f, "<number>"),
+            ResultKind::String => write!(f, "<string>"),
+            ResultKind::Object { keys } => write!(f, "<object, {}>", count(*keys, "key")),
+            ResultKind::Array { items } => write!(f, "<array, {}>", count(*items, "item")),
+            ResultKind::Binary { bytes } => write!(f, "<binary, {}>", count(*bytes, "byte")),
+        }
+    }
+}
+
+/// Whether a result value holds a serialized exception, which Celery stores as
+/// an object with `exc_type` and `exc_message` keys
+///
+/// Some frameworks store one for tasks that didn't fail, e.g. a retry that
+/// ended up succeeding. Objects stored as JSON text are recognized as well.
+pub fn looks_like_exception(value: &Value) -> bool {
+    fn has_exception_keys(value: &Value) -> bool {
+        value
+            .as_object()
+            .is_some_and(|map| map.contains_key("exc_type") || map.contains_key("exc_message"))
+    }
+
+    match value {
+        Value::String(text) => serde_json::from_str::<Value>(text)
+            .as_ref()
+            .is_ok_and(has_exception_keys),
+        value => has_exception_keys(value),
+    }
+}
+
+/// Whether text looks like undecodable bytes rather than something readable
+fn is_binary_text(s: &str) -> bool {
+    s.chars()
+        .any(|c| c == char::REPLACEMENT_CHARACTER || (c.is_control() && !c.is_whitespace()))
+}
+
+impl TaskStatus {
+    /// Whether the task has stopped for good and its status won't change again
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            TaskStatus::Success | TaskStatus::Failure | TaskStatus::Revoked
+        )
+    }
+}
+
 impl Task {
     /// Basic constructor for creating a new Task - kept for future API use
     #[allow(dead_code)]
@@ -37,11 +215,80 @@ impl Task {
             worker: None,
             timestamp: Utc::now(),
             result: None,
+            result_kind: None,
+            looks_like_error: false,
             traceback: None,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+        }
+    }
+
+    /// One-line view of the result for lists: structures and binary blobs are
+    /// summarized, other results are shown up to their first line
+    pub fn result_summary(&self) -> Option<String> {
+        let result = self.result.as_deref()?;
+        match self.result_kind {
+            Some(kind) if kind.is_opaque() => Some(kind.to_string()),
+            _ => Some(result.lines().next().unwrap_or_default().to_string()),
         }
     }
 
     pub fn duration_since(&self, now: DateTime<Utc>) -> chrono::Duration {
         now - self.timestamp
     }
+
+    /// Whether the task used up its retries without succeeding
+    pub fn retries_exhausted(&self) -> bool {
+        self.status != TaskStatus::Success
+            && self
+                .max_retries
+                .is_some_and(|max_retries| self.retries >= max_retries)
+    }
+
+    /// Whether the task has been running for longer than `threshold`, which
+    /// usually means its worker died. A zero threshold never flags tasks.
+    pub fn is_stuck(&self, threshold: chrono::Duration, now: DateTime<Utc>) -> bool {
+        self.status == TaskStatus::Active
+            && threshold > chrono::Duration::zero()
+            && self.duration_since(now) > threshold
+    }
+}
+
+/// Order tasks by how long they ran, longest first
+///
+/// Tasks without a known duration go last, keeping their relative order.
+pub fn sort_by_duration(tasks: &mut [&Task]) {
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.duration));
+}
+
+/// Order tasks by message priority, highest value first as AMQP orders them
+///
+/// Tasks without a priority go last, keeping their relative order.
+pub fn sort_by_priority(tasks: &mut [&Task]) {
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.priority));
+}
+
+/// Bucket tasks by the workflow they belong to
+///
+/// Tasks without a `root_id` are not part of a workflow and are left out.
+pub fn group_by_root(tasks: &[Task]) -> BTreeMap<&str, Vec<&Task>> {
+    let mut groups: BTreeMap<&str, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        if let Some(root_id) = task.root_id.as_deref() {
+            groups.entry(root_id).or_default().push(task);
+        }
+    }
+    groups
 }
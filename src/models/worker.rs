@@ -1,12 +1,22 @@
 use serde::{Deserialize, Serialize};
 
+use super::task::{Task, TaskStatus};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Worker {
     pub hostname: String,
     pub status: WorkerStatus,
     pub concurrency: u32,
+    /// Pool implementation such as `prefork`, `gevent` or `solo`, known only
+    /// when the worker answered an inspect request
+    #[serde(default)]
+    pub pool_type: Option<String>,
     pub queues: Vec<String>,
     pub active_tasks: Vec<String>,
+    /// Tasks the worker has prefetched but not started, known only when the
+    /// worker answered an inspect request
+    #[serde(default)]
+    pub reserved_tasks: Vec<String>,
     pub processed: u64,
     pub failed: u64,
 }
@@ -17,21 +27,169 @@ pub enum WorkerStatus {
     Offline,
 }
 
+/// Canonical hostname of a Celery node name
+///
+/// The part after the last `@`, trimmed and lowercased: `worker-1` for
+/// `celery@Worker-1`.
+pub fn canonical_hostname(node: &str) -> String {
+    node.rsplit_once('@')
+        .map_or(node, |(_, host)| host)
+        .trim()
+        .to_lowercase()
+}
+
+/// Canonical form of a Celery node name, trimmed and lowercased
+///
+/// Inspect replies, events and extended results name workers by node, like
+/// `celery@worker-1`, and several nodes may run on one host. Names without
+/// an `@`, such as message origins reduced to their host, stay bare.
+pub fn canonical_node(node: &str) -> String {
+    node.trim().to_lowercase()
+}
+
+/// Whether a canonical worker name is a bare host rather than a node name
+fn is_bare_host(name: &str) -> bool {
+    !name.contains('@')
+}
+
+/// Index of the worker among `workers` that `name` refers to
+///
+/// Names are compared in canonical form. A bare host also refers to the
+/// node running on it when `workers` have exactly one node on that host;
+/// with several it can't be told which one is meant.
+pub fn find_worker(workers: &[Worker], name: &str) -> Option<usize> {
+    let name = canonical_node(name);
+    if let Some(idx) = workers
+        .iter()
+        .position(|w| canonical_node(&w.hostname) == name)
+    {
+        return Some(idx);
+    }
+    if !is_bare_host(&name) {
+        return None;
+    }
+    let mut nodes = workers
+        .iter()
+        .enumerate()
+        .filter(|(_, w)| !is_bare_host(&w.hostname) && canonical_hostname(&w.hostname) == name);
+    match (nodes.next(), nodes.next()) {
+        (Some((idx, _)), None) => Some(idx),
+        _ => None,
+    }
+}
+
+/// Combine workers listed under different forms of the same name
+///
+/// Workers are listed under their canonical node name and distinct nodes
+/// stay apart, even on one host. A worker known only by its bare host is
+/// folded into the node on that host when there is exactly one, keeping the
+/// node's status, and listed on its own otherwise.
+pub fn merge_workers_by_host(workers: Vec<Worker>) -> Vec<Worker> {
+    let (bare, nodes): (Vec<Worker>, Vec<Worker>) = workers
+        .into_iter()
+        .map(|mut worker| {
+            worker.hostname = canonical_node(&worker.hostname);
+            worker
+        })
+        .partition(|worker| is_bare_host(&worker.hostname));
+
+    let mut merged: Vec<Worker> = Vec::with_capacity(nodes.len() + bare.len());
+    for worker in nodes.into_iter().chain(bare) {
+        match find_worker(&merged, &worker.hostname) {
+            Some(idx) if merged[idx].hostname != worker.hostname => {
+                let status = merged[idx].status.clone();
+                merged[idx].absorb(worker);
+                merged[idx].status = status;
+            }
+            Some(idx) => merged[idx].absorb(worker),
+            None => merged.push(worker),
+        }
+    }
+    merged
+}
+
 impl Worker {
-    /// Basic constructor for creating a new Worker - kept for future API use
-    #[allow(dead_code)]
+    /// A worker known only by its hostname, offline until found otherwise
     pub fn new(hostname: String) -> Self {
         Self {
             hostname,
             status: WorkerStatus::Offline,
             concurrency: 1,
+            pool_type: None,
             queues: Vec::new(),
             active_tasks: Vec::new(),
+            reserved_tasks: Vec::new(),
             processed: 0,
             failed: 0,
         }
     }
 
+    /// Whether `task` ran on this worker
+    ///
+    /// Tasks are matched by the worker recorded for them, or by being one of
+    /// the worker's active tasks. Node names must match; when either side is
+    /// a bare host, matching hosts are enough.
+    pub fn ran_task(&self, task: &Task) -> bool {
+        let origin_matches = task.worker.as_deref().is_some_and(|worker| {
+            let (worker, own) = (canonical_node(worker), canonical_node(&self.hostname));
+            worker == own
+                || ((is_bare_host(&worker) || is_bare_host(&own))
+                    && canonical_hostname(&worker) == canonical_hostname(&own))
+        });
+        origin_matches || self.active_tasks.contains(&task.id)
+    }
+
+    /// Fold `other`, another report of the same worker, into this worker
+    ///
+    /// The worker is online when either report says so and keeps the higher
+    /// concurrency. Counts add up, queues and task lists are joined.
+    pub fn absorb(&mut self, other: Worker) {
+        if other.status == WorkerStatus::Online {
+            self.status = WorkerStatus::Online;
+        }
+        self.concurrency = self.concurrency.max(other.concurrency);
+        if self.pool_type.is_none() {
+            self.pool_type = other.pool_type;
+        }
+        self.queues.extend(other.queues);
+        self.queues.sort();
+        self.queues.dedup();
+        for task_id in other.active_tasks {
+            if !self.active_tasks.contains(&task_id) {
+                self.active_tasks.push(task_id);
+            }
+        }
+        for task_id in other.reserved_tasks {
+            if !self.reserved_tasks.contains(&task_id) {
+                self.reserved_tasks.push(task_id);
+            }
+        }
+        self.processed += other.processed;
+        self.failed += other.failed;
+    }
+
+    /// The latest `limit` failed tasks that ran on this worker, newest first
+    pub fn recent_failures<'a>(&self, tasks: &'a [Task], limit: usize) -> Vec<&'a Task> {
+        let mut failures: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Failure && self.ran_task(task))
+            .collect();
+        failures.sort_by_key(|task| std::cmp::Reverse(task.timestamp));
+        failures.truncate(limit);
+        failures
+    }
+
+    /// Concurrency with the pool type when known, e.g. "16 prefork"
+    ///
+    /// The number means processes for prefork, greenlets for gevent and
+    /// eventlet, and is 1 for solo.
+    pub fn concurrency_label(&self) -> String {
+        match &self.pool_type {
+            Some(pool_type) => format!("{} {pool_type}", self.concurrency),
+            None => self.concurrency.to_string(),
+        }
+    }
+
     pub fn utilization(&self) -> f32 {
         if self.concurrency == 0 {
             0.0
@@ -1,6 +1,13 @@
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use std::time::Duration;
 
+use super::keymap::KeyAction;
+use super::modals::task_details_line_count;
+use crate::app::Tab;
+
+/// Lines scrolled by PageUp/PageDown in the task details modal
+const TASK_DETAILS_PAGE: i32 = 10;
+
 #[allow(dead_code)]
 pub enum AppEvent {
     Key(KeyEvent),
@@ -19,16 +26,143 @@ pub async fn next_event(tick_rate: Duration) -> Result<AppEvent, std::io::Error>
     }
 }
 
+/// Whether `key` confirms the open confirmation dialog, so its pending action
+/// should run once the key has been handled
+pub fn confirms_pending_action(key: &KeyEvent, app: &crate::app::App) -> bool {
+    if !app.show_confirmation {
+        return false;
+    }
+    if app.is_typing_confirmation() {
+        return key.code == KeyCode::Enter && app.typed_confirmation_matches();
+    }
+    matches!(
+        key.code,
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter
+    )
+}
+
+/// Apply a typed character or backspace to a text input
+fn edit_input(input: &mut String, code: KeyCode) {
+    match code {
+        KeyCode::Char(c) => input.push(c),
+        KeyCode::Backspace => {
+            input.pop();
+        }
+        _ => {}
+    }
+}
+
 pub fn handle_key_event(key: KeyEvent, app: &mut crate::app::App) {
     if app.is_searching {
         match key.code {
             KeyCode::Esc => app.stop_search(),
             KeyCode::Enter => app.stop_search(),
-            KeyCode::Char(c) => app.search_query.push(c),
-            KeyCode::Backspace => {
-                app.search_query.pop();
+            KeyCode::Tab => app.toggle_search_args(),
+            code => edit_input(&mut app.search_query, code),
+        }
+        return;
+    }
+
+    if app.is_editing_replay() {
+        match key.code {
+            KeyCode::Esc => app.cancel_replay_edit(),
+            KeyCode::Enter => app.submit_replay_edit(),
+            code => {
+                app.clear_status_message();
+                edit_input(&mut app.replay_input, code);
+            }
+        }
+        return;
+    }
+
+    if app.is_editing_move() {
+        match key.code {
+            KeyCode::Esc => app.cancel_move_edit(),
+            KeyCode::Enter => app.submit_move_edit(),
+            code => {
+                app.clear_status_message();
+                edit_input(&mut app.move_input, code);
+            }
+        }
+        return;
+    }
+
+    if app.is_editing_rate_limit() {
+        match key.code {
+            KeyCode::Esc => app.cancel_rate_limit_edit(),
+            KeyCode::Enter => app.submit_rate_limit_edit(),
+            code => {
+                app.clear_status_message();
+                edit_input(&mut app.rate_limit_input, code);
+            }
+        }
+        return;
+    }
+
+    if app.is_editing_config() {
+        match key.code {
+            KeyCode::Esc => app.cancel_config_edit(),
+            KeyCode::Enter => app.submit_config_edit(),
+            code => {
+                if let Some(input) = &mut app.config_input {
+                    edit_input(input, code);
+                }
             }
-            _ => {}
+        }
+        return;
+    }
+
+    if app.show_config {
+        let action = app.keymap.action_for(&key);
+        match key.code {
+            KeyCode::Up => app.move_config_selection(-1),
+            KeyCode::Down => app.move_config_selection(1),
+            _ if action == Some(KeyAction::Up) => app.move_config_selection(-1),
+            _ if action == Some(KeyAction::Down) => app.move_config_selection(1),
+            KeyCode::Enter => app.start_config_edit(),
+            KeyCode::Char('w') => app.save_settings(),
+            _ => app.close_config_overlay(),
+        }
+        return;
+    }
+
+    if app.show_audit_log {
+        let action = app.keymap.action_for(&key);
+        match key.code {
+            KeyCode::Up => app.scroll_audit_log(-1),
+            KeyCode::Down => app.scroll_audit_log(1),
+            _ if action == Some(KeyAction::Up) => app.scroll_audit_log(-1),
+            _ if action == Some(KeyAction::Down) => app.scroll_audit_log(1),
+            KeyCode::Char('s') => app.export_audit_log(),
+            _ => app.toggle_audit_log(),
+        }
+        return;
+    }
+
+    if app.show_palette {
+        match key.code {
+            KeyCode::Esc => app.close_palette(),
+            KeyCode::Enter => app.submit_palette(),
+            KeyCode::Up => app.move_palette_selection(-1),
+            KeyCode::Down => app.move_palette_selection(1),
+            code => {
+                edit_input(&mut app.palette_query, code);
+                app.palette_selected = 0;
+            }
+        }
+        return;
+    }
+
+    if app.is_typing_confirmation() {
+        match key.code {
+            KeyCode::Esc => app.hide_confirmation_dialog(),
+            KeyCode::Enter => {
+                // A match is executed by the main loop
+                if !app.typed_confirmation_matches() {
+                    app.reject_typed_confirmation();
+                }
+            }
+            code => edit_input(&mut app.confirmation_input, code),
         }
         return;
     }
@@ -52,36 +186,83 @@ pub fn handle_key_event(key: KeyEvent, app: &mut crate::app::App) {
     }
 
     if app.show_task_details {
-        app.hide_task_details();
+        let line_count = task_details_line_count(app);
+        let action = app.keymap.action_for(&key);
+        match key.code {
+            KeyCode::Up => app.scroll_task_details(-1, line_count),
+            KeyCode::Down => app.scroll_task_details(1, line_count),
+            _ if action == Some(KeyAction::Up) => app.scroll_task_details(-1, line_count),
+            _ if action == Some(KeyAction::Down) => app.scroll_task_details(1, line_count),
+            KeyCode::PageUp => app.scroll_task_details(-TASK_DETAILS_PAGE, line_count),
+            KeyCode::PageDown => app.scroll_task_details(TASK_DETAILS_PAGE, line_count),
+            KeyCode::Char('s') => app.initiate_dump_task_metadata(),
+            KeyCode::Char('r') => app.toggle_task_details_raw(),
+            KeyCode::Char('c') if app.task_details_have_children() => app.select_next_child_task(),
+            KeyCode::Enter if app.task_details_have_children() => app.jump_to_child_task(),
+            _ => app.hide_task_details(),
+        }
+        return;
+    }
+
+    let action = app.keymap.action_for(&key);
+
+    if app.show_queue_peek {
+        app.hide_queue_peek();
         return;
     }
 
     // Clear status message on any key press (except actions that set new status)
-    match key.code {
-        KeyCode::Char('p')
-        | KeyCode::Char('r')
-        | KeyCode::Char('x')
-        | KeyCode::Enter
-        | KeyCode::Char('d') => {
-            // These will set their own status messages or open modals
-        }
-        _ => {
-            app.clear_status_message();
-        }
+    if !action.is_some_and(KeyAction::keeps_status) {
+        app.clear_status_message();
     }
 
-    match key.code {
-        KeyCode::Char('q') => app.should_quit = true,
-        KeyCode::Char('?') => app.toggle_help(),
-        KeyCode::Tab => app.next_tab(),
-        KeyCode::BackTab => app.previous_tab(),
-        KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
-        KeyCode::Down | KeyCode::Char('j') => app.select_next(),
-        KeyCode::Char('/') => app.start_search(),
-        KeyCode::Char('p') => app.initiate_purge_queue(),
-        KeyCode::Char('r') => app.initiate_retry_task(),
-        KeyCode::Char('x') => app.initiate_revoke_task(),
-        KeyCode::Enter | KeyCode::Char('d') => app.show_task_details(),
-        _ => {}
+    if key.code == KeyCode::Esc {
+        app.clear_worker_queue_filter();
+        app.clear_stuck_filter();
+        app.clear_task_status_filter();
+        return;
+    }
+
+    let Some(action) = action else {
+        return;
+    };
+    match action {
+        KeyAction::Quit => app.request_quit(),
+        KeyAction::Help => app.toggle_help(),
+        KeyAction::NextTab => app.next_tab(),
+        KeyAction::PreviousTab => app.previous_tab(),
+        KeyAction::Up => app.select_previous(),
+        KeyAction::Down => app.select_next(),
+        KeyAction::Search => app.start_search(),
+        KeyAction::Palette => app.open_palette(),
+        KeyAction::Reconnect => app.request_reconnect(),
+        KeyAction::Purge => app.initiate_purge_queue(),
+        KeyAction::WorkersFilter => app.filter_workers_by_selected_queue(),
+        KeyAction::Retry => app.initiate_retry_task(),
+        KeyAction::Revoke => match app.selected_tab {
+            Tab::Workers => app.initiate_revoke_worker_tasks(),
+            _ => app.initiate_revoke_task(),
+        },
+        KeyAction::Replay => app.start_replay_edit(),
+        KeyAction::Move => app.start_move_edit(),
+        KeyAction::RateLimit => app.start_rate_limit_edit(),
+        KeyAction::Follow => app.toggle_watch_selected_task(),
+        KeyAction::Timestamps => app.toggle_timestamp_mode(),
+        KeyAction::StuckFilter => app.toggle_stuck_filter(),
+        KeyAction::StatusFilter => app.cycle_task_status_filter(),
+        KeyAction::TimeRange => app.cycle_task_time_range(),
+        KeyAction::Cleanup => app.initiate_clear_completed_results(),
+        KeyAction::Diagnostics => app.request_diagnostics(),
+        KeyAction::Pause => app.toggle_pause(),
+        KeyAction::Settings => app.open_config_overlay(),
+        KeyAction::AuditLog => app.toggle_audit_log(),
+        KeyAction::Sort => match app.selected_tab {
+            Tab::Tasks => app.cycle_task_sort(),
+            _ => app.cycle_queue_sort(),
+        },
+        KeyAction::Details => match app.selected_tab {
+            Tab::Queues => app.show_queue_peek(),
+            _ => app.show_task_details(),
+        },
     }
 }
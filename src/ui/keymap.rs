@@ -0,0 +1,376 @@
+//! Key bindings for the main view, configurable through the `[keys]` config section
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::config::KeyBinding;
+use crate::error::ConfigError;
+
+/// Actions that can be bound to keys in the main view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    Quit,
+    Help,
+    NextTab,
+    PreviousTab,
+    Up,
+    Down,
+    Search,
+    Palette,
+    Reconnect,
+    Purge,
+    WorkersFilter,
+    Retry,
+    Revoke,
+    Replay,
+    Move,
+    RateLimit,
+    Follow,
+    Timestamps,
+    StuckFilter,
+    StatusFilter,
+    TimeRange,
+    Cleanup,
+    Details,
+    /// Cycle the sort order of the queues, or of the tasks in the Tasks tab
+    Sort,
+    Diagnostics,
+    Pause,
+    Settings,
+    AuditLog,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 28] = [
+        KeyAction::Quit,
+        KeyAction::Help,
+        KeyAction::NextTab,
+        KeyAction::PreviousTab,
+        KeyAction::Up,
+        KeyAction::Down,
+        KeyAction::Search,
+        KeyAction::Palette,
+        KeyAction::Reconnect,
+        KeyAction::Purge,
+        KeyAction::WorkersFilter,
+        KeyAction::Retry,
+        KeyAction::Revoke,
+        KeyAction::Replay,
+        KeyAction::Move,
+        KeyAction::RateLimit,
+        KeyAction::Follow,
+        KeyAction::Timestamps,
+        KeyAction::StuckFilter,
+        KeyAction::StatusFilter,
+        KeyAction::TimeRange,
+        KeyAction::Cleanup,
+        KeyAction::Details,
+        KeyAction::Sort,
+        KeyAction::Diagnostics,
+        KeyAction::Pause,
+        KeyAction::Settings,
+        KeyAction::AuditLog,
+    ];
+
+    /// Name used for the action in the `[keys]` config section
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyAction::Quit => "quit",
+            KeyAction::Help => "help",
+            KeyAction::NextTab => "next_tab",
+            KeyAction::PreviousTab => "previous_tab",
+            KeyAction::Up => "up",
+            KeyAction::Down => "down",
+            KeyAction::Search => "search",
+            KeyAction::Palette => "palette",
+            KeyAction::Reconnect => "reconnect",
+            KeyAction::Purge => "purge",
+            KeyAction::WorkersFilter => "workers_filter",
+            KeyAction::Retry => "retry",
+            KeyAction::Revoke => "revoke",
+            KeyAction::Replay => "replay",
+            KeyAction::Move => "move",
+            KeyAction::RateLimit => "rate_limit",
+            KeyAction::Follow => "follow",
+            KeyAction::Timestamps => "timestamps",
+            KeyAction::StuckFilter => "stuck_filter",
+            KeyAction::StatusFilter => "status_filter",
+            KeyAction::TimeRange => "time_range",
+            KeyAction::Cleanup => "cleanup",
+            KeyAction::Details => "details",
+            KeyAction::Sort => "sort",
+            KeyAction::Diagnostics => "diagnostics",
+            KeyAction::Pause => "pause",
+            KeyAction::Settings => "settings",
+            KeyAction::AuditLog => "audit_log",
+        }
+    }
+
+    /// Chords bound to the action when the config doesn't rebind it
+    pub fn default_chords(self) -> &'static [&'static str] {
+        match self {
+            KeyAction::Quit => &["q", "Ctrl-c"],
+            KeyAction::Help => &["?"],
+            KeyAction::NextTab => &["Tab"],
+            KeyAction::PreviousTab => &["BackTab"],
+            KeyAction::Up => &["Up", "k"],
+            KeyAction::Down => &["Down", "j"],
+            KeyAction::Search => &["/"],
+            KeyAction::Palette => &[":", "Ctrl-p"],
+            KeyAction::Reconnect => &["Ctrl-r"],
+            KeyAction::Purge => &["p"],
+            KeyAction::WorkersFilter => &["w"],
+            KeyAction::Retry => &["r"],
+            KeyAction::Revoke => &["x"],
+            KeyAction::Replay => &["e"],
+            KeyAction::Move => &["m"],
+            KeyAction::RateLimit => &["l"],
+            KeyAction::Follow => &["f"],
+            KeyAction::Timestamps => &["t"],
+            KeyAction::StuckFilter => &["z"],
+            KeyAction::StatusFilter => &["F"],
+            KeyAction::TimeRange => &["T"],
+            KeyAction::Cleanup => &["C"],
+            KeyAction::Details => &["Enter", "d"],
+            KeyAction::Sort => &["s"],
+            KeyAction::Diagnostics => &["D"],
+            KeyAction::Pause => &["Space"],
+            KeyAction::Settings => &["c"],
+            KeyAction::AuditLog => &["L"],
+        }
+    }
+
+    /// Whether the action changes the broker, hidden in read-only mode
+    pub fn modifies_broker(self) -> bool {
+        matches!(
+            self,
+            KeyAction::Purge
+                | KeyAction::Retry
+                | KeyAction::Revoke
+                | KeyAction::Replay
+                | KeyAction::Move
+                | KeyAction::RateLimit
+                | KeyAction::Cleanup
+        )
+    }
+
+    /// Whether the action sets its own status message or opens a modal, so
+    /// the current status message must survive the key press
+    pub fn keeps_status(self) -> bool {
+        matches!(
+            self,
+            KeyAction::Purge
+                | KeyAction::Retry
+                | KeyAction::Revoke
+                | KeyAction::Replay
+                | KeyAction::Move
+                | KeyAction::RateLimit
+                | KeyAction::Follow
+                | KeyAction::StatusFilter
+                | KeyAction::TimeRange
+                | KeyAction::Cleanup
+                | KeyAction::Details
+                | KeyAction::Sort
+                | KeyAction::Diagnostics
+                | KeyAction::Settings
+                | KeyAction::AuditLog
+        )
+    }
+}
+
+impl FromStr for KeyAction {
+    type Err = ();
+
+    /// Parse an action name, also accepting `sort_queues`, the name `sort`
+    /// had before it sorted tasks too
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "sort_queues" {
+            return Ok(KeyAction::Sort);
+        }
+        KeyAction::ALL
+            .into_iter()
+            .find(|action| action.name() == s)
+            .ok_or(())
+    }
+}
+
+/// A key together with the Ctrl and Alt modifiers held while pressing it
+///
+/// Shift is not tracked, it is already part of the character (`C`) or the
+/// key code (`BackTab`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    /// The chord a terminal key press corresponds to
+    pub fn from_event(key: &KeyEvent) -> Self {
+        Self {
+            code: key.code,
+            modifiers: key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT),
+        }
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = ();
+
+    /// Parse chords like `q`, `Ctrl-c`, `Alt-x`, `Tab`, `PageDown` or `F5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = s;
+        loop {
+            if let Some(rest) = key.strip_prefix("Ctrl-") {
+                modifiers |= KeyModifiers::CONTROL;
+                key = rest;
+            } else if let Some(rest) = key.strip_prefix("Alt-") {
+                modifiers |= KeyModifiers::ALT;
+                key = rest;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key {
+                "Tab" => KeyCode::Tab,
+                "BackTab" | "Shift-Tab" => KeyCode::BackTab,
+                "Enter" => KeyCode::Enter,
+                "Esc" => KeyCode::Esc,
+                "Space" => KeyCode::Char(' '),
+                "Backspace" => KeyCode::Backspace,
+                "Delete" => KeyCode::Delete,
+                "Up" => KeyCode::Up,
+                "Down" => KeyCode::Down,
+                "Left" => KeyCode::Left,
+                "Right" => KeyCode::Right,
+                "Home" => KeyCode::Home,
+                "End" => KeyCode::End,
+                "PageUp" => KeyCode::PageUp,
+                "PageDown" => KeyCode::PageDown,
+                _ => match key.strip_prefix('F').map(str::parse::<u8>) {
+                    Some(Ok(n @ 1..=12)) => KeyCode::F(n),
+                    _ => return Err(()),
+                },
+            },
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// Lookup from key chords to the main view actions they trigger
+#[derive(Debug, Clone)]
+pub struct KeyMap {
+    bindings: HashMap<KeyChord, KeyAction>,
+    /// Chords of each action in the order they were configured
+    chords: HashMap<KeyAction, Vec<KeyChord>>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::from_config(&BTreeMap::new()).expect("default key bindings don't conflict")
+    }
+}
+
+impl KeyMap {
+    /// Build the key map from the `[keys]` config section
+    ///
+    /// An action listed in `keys` loses its default chords, the others keep
+    /// theirs. Unknown actions, unparsable chords and chords bound to more
+    /// than one action are rejected.
+    pub fn from_config(keys: &BTreeMap<String, KeyBinding>) -> Result<Self, ConfigError> {
+        let mut configured = HashMap::new();
+        for (name, binding) in keys {
+            let action = name
+                .parse::<KeyAction>()
+                .map_err(|_| ConfigError::UnknownKeyAction {
+                    name: name.clone(),
+                    known: KeyAction::ALL.map(KeyAction::name).join(", "),
+                })?;
+            configured.insert(action, binding.chords());
+        }
+
+        let mut bindings = HashMap::new();
+        let mut action_chords: HashMap<KeyAction, Vec<KeyChord>> = HashMap::new();
+        for action in KeyAction::ALL {
+            let chords = match configured.get(&action) {
+                Some(chords) => chords.clone(),
+                None => action.default_chords().to_vec(),
+            };
+            for chord in chords {
+                let parsed =
+                    chord
+                        .parse::<KeyChord>()
+                        .map_err(|_| ConfigError::InvalidKeyChord {
+                            action: action.name(),
+                            chord: chord.to_string(),
+                        })?;
+                if let Some(existing) = bindings.insert(parsed, action) {
+                    if existing != action {
+                        return Err(ConfigError::ConflictingKeyBinding {
+                            chord: parsed.to_string(),
+                            first: existing.name(),
+                            second: action.name(),
+                        });
+                    }
+                }
+                action_chords.entry(action).or_default().push(parsed);
+            }
+        }
+
+        Ok(Self {
+            bindings,
+            chords: action_chords,
+        })
+    }
+
+    /// The first chord bound to `action`, the one shown in key hints
+    pub fn chord_for(&self, action: KeyAction) -> Option<KeyChord> {
+        self.chords.get(&action)?.first().copied()
+    }
+
+    /// Every chord bound to `action`, in the order they were configured
+    pub fn chords_for(&self, action: KeyAction) -> &[KeyChord] {
+        self.chords.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The action bound to a key press, if any
+    ///
+    /// A modified press with no binding of its own falls back to the bare
+    /// key, so `Ctrl-q` still quits unless `Ctrl-q` is bound to something.
+    pub fn action_for(&self, key: &KeyEvent) -> Option<KeyAction> {
+        let chord = KeyChord::from_event(key);
+        self.bindings.get(&chord).copied().or_else(|| {
+            self.bindings
+                .get(&KeyChord {
+                    modifiers: KeyModifiers::NONE,
+                    ..chord
+                })
+                .copied()
+        })
+    }
+}
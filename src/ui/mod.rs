@@ -1,26 +1,44 @@
 pub mod events;
+pub mod keymap;
 pub mod layout;
 pub mod modals;
+pub mod snapshot;
+pub mod terminal;
 pub mod widgets;
 
 use ratatui::Frame;
 
-use crate::app::{App, Tab};
-use crate::ui::layout::{create_main_layout, draw_header, draw_status_bar};
-use crate::ui::modals::{draw_confirmation_dialog, draw_help, draw_task_details_modal};
-use crate::ui::widgets::{QueueWidget, TaskWidget, Widget, WorkerWidget};
+use crate::app::{App, LayoutMode, Tab};
+use crate::ui::layout::{
+    create_main_layout, draw_header, draw_key_footer, draw_setup_guide, draw_status_bar,
+    draw_watch_banner,
+};
+use crate::ui::modals::{
+    draw_audit_log, draw_command_palette, draw_config_overlay, draw_confirmation_dialog, draw_help,
+    draw_queue_peek_modal, draw_task_details_modal,
+};
+use crate::ui::widgets::{
+    OverviewWidget, QueueWidget, ScheduledWidget, TaskWidget, Widget, WorkerWidget,
+};
 
 pub fn draw(f: &mut Frame, app: &mut App) {
+    app.layout_mode = LayoutMode::for_width(f.area().width, app.compact_width);
     let chunks = create_main_layout(f.area());
 
     // Draw header with tabs
     draw_header(f, app, chunks[0]);
 
-    // Draw main content based on selected tab
+    // Draw main content based on selected tab, or setup advice while
+    // disconnected, between the watched task banner and the key footer
+    let content = draw_key_footer(f, app, chunks[1]);
+    let content = draw_watch_banner(f, app, content);
     match app.selected_tab {
-        Tab::Workers => WorkerWidget::draw(f, app, chunks[1]),
-        Tab::Tasks => TaskWidget::draw(f, app, chunks[1]),
-        Tab::Queues => QueueWidget::draw(f, app, chunks[1]),
+        _ if !app.is_connected() => draw_setup_guide(f, app, content),
+        Tab::Overview => OverviewWidget::draw(f, app, content),
+        Tab::Workers => WorkerWidget::draw(f, app, content),
+        Tab::Tasks => TaskWidget::draw(f, app, content),
+        Tab::Queues => QueueWidget::draw(f, app, content),
+        Tab::Scheduled => ScheduledWidget::draw(f, app, content),
     }
 
     // Draw status bar
@@ -28,7 +46,7 @@ pub fn draw(f: &mut Frame, app: &mut App) {
 
     // Draw help overlay if active
     if app.show_help {
-        draw_help(f);
+        draw_help(f, app);
     }
 
     // Draw confirmation dialog if active
@@ -40,4 +58,24 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     if app.show_task_details {
         draw_task_details_modal(f, app);
     }
+
+    // Draw queue peek modal if active
+    if app.show_queue_peek {
+        draw_queue_peek_modal(f, app);
+    }
+
+    // Draw config overlay if active
+    if app.show_config {
+        draw_config_overlay(f, app);
+    }
+
+    // Draw audit log if active
+    if app.show_audit_log {
+        draw_audit_log(f, app);
+    }
+
+    // Draw command palette on top of everything else
+    if app.show_palette {
+        draw_command_palette(f, app);
+    }
 }
@@ -6,36 +6,148 @@ use ratatui::{
     Frame,
 };
 
+use super::keymap::KeyAction;
 use super::layout::centered_rect;
-use crate::app::App;
+use crate::app::{App, SettingField};
+use crate::utils::formatting::broker_location;
 
-/// Draw the help modal overlay
-pub fn draw_help(f: &mut Frame) {
+/// Actions listed under navigation in the help modal
+const HELP_NAVIGATION: &[(KeyAction, &str)] = &[
+    (KeyAction::NextTab, "Switch between tabs"),
+    (KeyAction::Palette, "Open the command palette"),
+    (KeyAction::Up, "Move up"),
+    (KeyAction::Down, "Move down"),
+    (
+        KeyAction::Details,
+        "View task details / peek queue messages",
+    ),
+];
+
+/// Actions listed under actions in the help modal
+const HELP_ACTIONS: &[(KeyAction, &str)] = &[
+    (KeyAction::Search, "Search"),
+    (KeyAction::Pause, "Pause/resume auto-refresh"),
+    (KeyAction::Settings, "View and edit settings"),
+    (KeyAction::AuditLog, "Show actions taken this session"),
+    (KeyAction::Purge, "Purge queue (in Queues tab)"),
+    (
+        KeyAction::WorkersFilter,
+        "Show workers consuming queue (in Queues tab)",
+    ),
+    (
+        KeyAction::Sort,
+        "Sort queues by name/length/consumers/age (in Queues tab)",
+    ),
+    (
+        KeyAction::Sort,
+        "Sort tasks by duration or priority (in Tasks tab)",
+    ),
+    (KeyAction::Retry, "Retry task (in Tasks tab)"),
+    (KeyAction::Revoke, "Revoke task (in Tasks tab)"),
+    (
+        KeyAction::Revoke,
+        "Revoke all active tasks of worker (in Workers tab)",
+    ),
+    (
+        KeyAction::Replay,
+        "Edit args and replay task (in Tasks tab)",
+    ),
+    (
+        KeyAction::Move,
+        "Move pending task to another queue (in Tasks tab)",
+    ),
+    (
+        KeyAction::RateLimit,
+        "Set a rate limit for the task's name on every worker (in Tasks tab)",
+    ),
+    (
+        KeyAction::Follow,
+        "Follow task status until it finishes (in Tasks tab)",
+    ),
+    (KeyAction::Timestamps, "Toggle relative/absolute timestamps"),
+    (
+        KeyAction::StuckFilter,
+        "Show only stuck tasks (in Tasks tab)",
+    ),
+    (
+        KeyAction::StatusFilter,
+        "Cycle the task status filter (in Tasks tab)",
+    ),
+    (
+        KeyAction::TimeRange,
+        "Cycle the task time range: 15m/1h/24h/all (in Tasks tab)",
+    ),
+    (
+        KeyAction::Cleanup,
+        "Delete finished task results older than 7d (in Tasks tab)",
+    ),
+];
+
+/// General actions listed last in the help modal
+const HELP_GENERAL: &[(KeyAction, &str)] = &[
+    (KeyAction::Help, "Toggle this help"),
+    (KeyAction::Reconnect, "Reconnect to broker"),
+    (
+        KeyAction::Diagnostics,
+        "Save diagnostics for a bug report, without credentials",
+    ),
+    (KeyAction::Quit, "Quit application"),
+];
+
+/// Help lines for `actions` with the keys they are bound to, leaving out
+/// actions bound to nothing and, in read-only mode, those that change the
+/// broker
+fn help_lines(app: &App, actions: &[(KeyAction, &str)]) -> Vec<Line<'static>> {
+    actions
+        .iter()
+        .filter(|(action, _)| !(app.read_only && action.modifies_broker()))
+        .filter_map(|&(action, description)| {
+            let chords = app.keymap.chords_for(action);
+            if chords.is_empty() {
+                return None;
+            }
+            let keys: Vec<String> = chords.iter().map(ToString::to_string).collect();
+            Some(Line::from(format!(
+                "  {:<9} - {description}",
+                keys.join("/")
+            )))
+        })
+        .collect()
+}
+
+/// Draw the help modal overlay, leaving out actions read-only mode disables
+pub fn draw_help(f: &mut Frame, app: &App) {
     let area = centered_rect(60, 60, f.area());
     f.render_widget(Clear, area);
 
-    let help_text = vec![
+    let mut help_text = vec![
         Line::from("LazyCelery - Keyboard Shortcuts"),
         Line::from(""),
         Line::from("Navigation:"),
-        Line::from("  Tab       - Switch between tabs"),
-        Line::from("  ↑/k       - Move up"),
-        Line::from("  ↓/j       - Move down"),
-        Line::from("  Enter/d   - View details (in Tasks tab)"),
-        Line::from("  Esc       - Go back"),
+    ];
+    help_text.extend(help_lines(app, HELP_NAVIGATION));
+    help_text.extend([
+        Line::from("  Esc       - Go back / dismiss status message"),
+        Line::from(""),
+        Line::from(if app.read_only {
+            "Actions (read-only mode, changes to the broker are disabled):"
+        } else {
+            "Actions:"
+        }),
+    ]);
+    help_text.extend(help_lines(app, HELP_ACTIONS));
+    help_text.extend([
         Line::from(""),
-        Line::from("Actions:"),
-        Line::from("  /         - Search"),
-        Line::from("  p         - Purge queue (in Queues tab)"),
-        Line::from("  r         - Retry task (in Tasks tab)"),
-        Line::from("  x         - Revoke task (in Tasks tab)"),
+        Line::from("Task status legend (Tasks tab title):"),
+        Line::from("  ✓ success  ✗ failure  ▶ active  ⏳ pending  ↻ retry  ⊘ revoked"),
         Line::from(""),
         Line::from("General:"),
-        Line::from("  ?         - Toggle this help"),
-        Line::from("  q         - Quit application"),
+    ]);
+    help_text.extend(help_lines(app, HELP_GENERAL));
+    help_text.extend([
         Line::from(""),
         Line::from("Press any key to close this help..."),
-    ];
+    ]);
 
     let help = Paragraph::new(help_text)
         .block(
@@ -54,12 +166,23 @@ pub fn draw_confirmation_dialog(f: &mut Frame, app: &App) {
     let area = centered_rect(50, 30, f.area());
     f.render_widget(Clear, area);
 
-    let confirmation_text = vec![
-        Line::from(""),
-        Line::from(app.confirmation_message.clone()),
-        Line::from(""),
-        Line::from("Press [y/Enter] to confirm or [n/Esc] to cancel"),
-    ];
+    let confirmation_text = match &app.confirmation_phrase {
+        Some(phrase) => vec![
+            Line::from(""),
+            Line::from(app.confirmation_message.clone()),
+            Line::from(""),
+            Line::from(format!("Type '{phrase}':")),
+            Line::from(format!("> {}_", app.confirmation_input)),
+            Line::from(""),
+            Line::from("Press [Enter] to confirm or [Esc] to cancel"),
+        ],
+        None => vec![
+            Line::from(""),
+            Line::from(app.confirmation_message.clone()),
+            Line::from(""),
+            Line::from("Press [y/Enter] to confirm or [n/Esc] to cancel"),
+        ],
+    };
 
     let confirmation = Paragraph::new(confirmation_text)
         .block(
@@ -73,6 +196,237 @@ pub fn draw_confirmation_dialog(f: &mut Frame, app: &App) {
     f.render_widget(confirmation, area);
 }
 
+/// Draw the command palette with the query and the matching actions
+pub fn draw_command_palette(f: &mut Frame, app: &App) {
+    let area = centered_rect(50, 50, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(": ", Style::default().fg(Color::Cyan)),
+            Span::raw(format!("{}_", app.palette_query)),
+        ]),
+        Line::from(""),
+    ];
+
+    let matches = app.palette_matches();
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No matching actions",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for (idx, action) in matches.iter().enumerate() {
+        let supported = action
+            .operation()
+            .is_none_or(|op| app.capabilities.supports(op));
+        let label = format!(" {}", action.label());
+        let mut spans = vec![if supported {
+            Span::raw(label)
+        } else {
+            Span::styled(label, Style::default().fg(Color::DarkGray))
+        }];
+        if !action.key_hint().is_empty() {
+            spans.push(Span::styled(
+                format!("  [{}]", action.key_hint()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        let line = Line::from(spans);
+        if idx == app.palette_selected {
+            lines.push(
+                line.style(
+                    Style::default()
+                        .bg(Color::DarkGray)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            );
+        } else {
+            lines.push(line);
+        }
+    }
+
+    // Keep the selected entry in view when the list is taller than the modal
+    let visible = area.height.saturating_sub(4) as usize;
+    let scroll = app
+        .palette_selected
+        .saturating_sub(visible.saturating_sub(1));
+    let palette = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Command Palette ")
+                .style(Style::default().bg(Color::Black)),
+        )
+        .scroll((scroll as u16, 0));
+
+    f.render_widget(palette, area);
+}
+
+/// Draw the running configuration, with inline editing of the settings that
+/// can change while the app runs
+pub fn draw_config_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(60, 60, f.area());
+    f.render_widget(Clear, area);
+
+    let config = &app.config;
+    let row = |key: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("  {key:<28}"), Style::default().fg(Color::Gray)),
+            Span::raw(value),
+        ])
+    };
+
+    let mut lines = vec![Line::from(Span::styled(
+        "Settings",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (idx, field) in SettingField::ALL.iter().enumerate() {
+        let selected = idx == app.config_selected;
+        let value = match &app.config_input {
+            Some(input) if selected => format!("{input}_"),
+            _ => field.value(config),
+        };
+        let line = Line::from(vec![
+            Span::raw(format!("  {:<28}", field.key())),
+            Span::raw(value),
+        ]);
+        lines.push(if selected {
+            line.style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            line
+        });
+    }
+
+    // Credentials in the broker URL stay hidden
+    let scheme = config
+        .broker
+        .url
+        .split_once("://")
+        .map_or("", |(scheme, _)| scheme);
+    let (host, db) = broker_location(&config.broker.url);
+    lines.extend([
+        Line::from(""),
+        Line::from(Span::styled(
+            "Read only here, change them in the config file",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        )),
+        row(
+            "broker.url",
+            format!("{scheme}://{host}/{}", db.as_deref().unwrap_or("0")),
+        ),
+        row(
+            "broker.operation_timeout",
+            format!("{} ms", config.broker.operation_timeout),
+        ),
+        row("broker.pool_size", config.broker.pool_size.to_string()),
+        row(
+            "ui.full_refresh_interval",
+            format!("{} ms", config.ui.full_refresh_interval),
+        ),
+        row(
+            "ui.status_message_timeout",
+            format!("{} s", config.ui.status_message_timeout),
+        ),
+        row("ui.default_tab", config.ui.default_tab.clone()),
+        row("ui.dry_run", config.ui.dry_run.to_string()),
+        row("ui.read_only", config.ui.read_only.to_string()),
+        Line::from(""),
+    ]);
+
+    let footer = if app.is_editing_config() {
+        "[Enter] Apply | [Esc] Cancel".to_string()
+    } else {
+        match &app.config_path {
+            Some(path) => format!(
+                "[↑↓] Select | [Enter] Edit | [w] Save to {} | Any other key to close",
+                path.display()
+            ),
+            None => "[↑↓] Select | [Enter] Edit | Any other key to close".to_string(),
+        }
+    };
+    lines.push(Line::from(Span::styled(
+        footer,
+        Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    let overlay = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(" Configuration ")
+            .style(Style::default().bg(Color::Black)),
+    );
+    f.render_widget(overlay, area);
+}
+
+/// Draw the audit log, newest action first, with the time of day in UTC
+pub fn draw_audit_log(f: &mut Frame, app: &App) {
+    let area = centered_rect(80, 60, f.area());
+    f.render_widget(Clear, area);
+
+    // Leave room for the borders and the footer
+    let visible = area.height.saturating_sub(3) as usize;
+    let mut lines: Vec<Line> = if app.audit_log.is_empty() {
+        vec![Line::from(Span::styled(
+            "No actions taken yet",
+            Style::default().fg(Color::DarkGray),
+        ))]
+    } else {
+        app.audit_log
+            .iter()
+            .rev()
+            .skip(app.audit_log_scroll)
+            .take(visible)
+            .map(|entry| {
+                let result_color = if entry.succeeded {
+                    Color::Green
+                } else {
+                    Color::Red
+                };
+                Line::from(vec![
+                    Span::styled(
+                        entry.timestamp.format("%H:%M:%S ").to_string(),
+                        Style::default().fg(Color::Gray),
+                    ),
+                    Span::styled(
+                        format!("{} {} ", entry.action, entry.target),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(entry.result.clone(), Style::default().fg(result_color)),
+                ])
+            })
+            .collect()
+    };
+    lines.resize(visible, Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "[↑↓] Scroll | [s] Export to a file | Any other key to close",
+        Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    let title = format!(" Audit Log ({}) ", app.audit_log.len());
+    let overlay = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(title)
+            .style(Style::default().bg(Color::Black)),
+    );
+    f.render_widget(overlay, area);
+}
+
 /// Draw the detailed task information modal
 pub fn draw_task_details_modal(f: &mut Frame, app: &App) {
     if let Some(task) = &app.selected_task_details {
@@ -86,7 +440,11 @@ pub fn draw_task_details_modal(f: &mut Frame, app: &App) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::Cyan))
-                .title(" Task Details ")
+                .title(if app.show_task_details_raw {
+                    " Task Details · stored metadata "
+                } else {
+                    " Task Details "
+                })
                 .style(Style::default().bg(Color::Black)),
             popup_area,
         );
@@ -97,18 +455,84 @@ pub fn draw_task_details_modal(f: &mut Frame, app: &App) {
             .split(popup_area)[0];
 
         // Create task details content
-        let details_lines = build_task_details_content(task);
+        let details_lines = if app.show_task_details_raw {
+            build_raw_metadata_content(app)
+        } else {
+            build_task_details_content(app, task)
+        };
 
+        // Keep the indentation of pretty-printed metadata
         let paragraph = Paragraph::new(details_lines)
-            .wrap(Wrap { trim: true })
-            .scroll((0, 0));
+            .wrap(Wrap {
+                trim: !app.show_task_details_raw,
+            })
+            .scroll((app.task_details_scroll, 0));
 
         f.render_widget(paragraph, inner_area);
     }
 }
 
+/// Draw the messages peeked from the selected queue
+pub fn draw_queue_peek_modal(f: &mut Frame, app: &App) {
+    let Some(queue) = &app.peeked_queue else {
+        return;
+    };
+    let popup_area = centered_rect(80, 70, f.area());
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title(format!(" Next messages in '{queue}' "))
+            .style(Style::default().bg(Color::Black)),
+        popup_area,
+    );
+
+    let inner_area = Layout::default()
+        .margin(1)
+        .constraints([Constraint::Percentage(100)])
+        .split(popup_area)[0];
+
+    let mut lines = Vec::new();
+    if app.queue_peek_messages.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No task messages waiting",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    for (index, task) in app.queue_peek_messages.iter().enumerate() {
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("{:>2}. ", index + 1),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                task.name.clone(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  {}", task.id), Style::default().fg(Color::Gray)),
+        ]));
+        lines.push(Line::from(format!(
+            "    args: {}  kwargs: {}",
+            task.args, task.kwargs
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Messages stay in the queue. Press any key to close",
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::ITALIC),
+    )));
+
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), inner_area);
+}
+
 /// Build the content lines for task details modal
-fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
+fn build_task_details_content<'a>(app: &'a App, task: &'a crate::models::Task) -> Vec<Line<'a>> {
     let mut details_lines = vec![
         Line::from(vec![
             Span::styled(
@@ -156,7 +580,7 @@ fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
                     .fg(Color::Cyan)
                     .add_modifier(Modifier::BOLD),
             ),
-            Span::raw("default".to_string()),
+            Span::raw(task.queue.as_deref().unwrap_or("Unknown").to_string()),
         ]),
         Line::from(vec![
             Span::styled(
@@ -175,6 +599,11 @@ fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(task.args.as_str()),
+    ];
+    if let Some(full_len) = task.args_full_len {
+        details_lines.push(truncation_notice_line("Arguments", full_len));
+    }
+    details_lines.extend([
         Line::from(""),
         Line::from(vec![Span::styled(
             "Keyword Arguments: ",
@@ -183,15 +612,38 @@ fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from(task.kwargs.as_str()),
+    ]);
+    if let Some(full_len) = task.kwargs_full_len {
+        details_lines.push(truncation_notice_line("Keyword arguments", full_len));
+    }
+    details_lines.extend([
         Line::from(""),
-        Line::from(vec![Span::styled(
-            "Result: ",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(task.result.as_deref().unwrap_or("None")),
-    ];
+        Line::from(vec![
+            Span::styled(
+                "Result: ",
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                task.result_kind
+                    .filter(|kind| kind.is_opaque())
+                    .map(|kind| kind.to_string())
+                    .unwrap_or_default(),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+    ]);
+
+    // Pretty-printed structures span several lines
+    match task.result.as_deref() {
+        Some(result) => details_lines.extend(result.lines().map(Line::from)),
+        None => details_lines.push(Line::from("None")),
+    }
+
+    if let Some(full_len) = task.result_full_len {
+        details_lines.push(truncation_notice_line("Result", full_len));
+    }
 
     // Add traceback if available and task failed
     if task.status == crate::models::TaskStatus::Failure {
@@ -208,13 +660,24 @@ fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
                     Style::default().fg(Color::Red),
                 )));
             }
+            if let Some(full_len) = task.traceback_full_len {
+                details_lines.push(truncation_notice_line("Traceback", full_len));
+            }
         }
     }
 
+    details_lines.extend(build_workflow_lines(app, task));
+    details_lines.extend(build_children_lines(app, task));
+
     // Add footer
+    let footer = if task.children.is_empty() {
+        "[↑↓/PgUp/PgDn] Scroll | [s] Save full metadata to a file | [r] Raw metadata | Any other key to close"
+    } else {
+        "[↑↓/PgUp/PgDn] Scroll | [c] Next child | [Enter] Open child | [s] Save full metadata to a file | [r] Raw metadata | Any other key to close"
+    };
     details_lines.push(Line::from(""));
     details_lines.push(Line::from(vec![Span::styled(
-        "Press any key to close",
+        footer,
         Style::default()
             .fg(Color::Gray)
             .add_modifier(Modifier::ITALIC),
@@ -223,6 +686,156 @@ fn build_task_details_content(task: &crate::models::Task) -> Vec<Line<'_>> {
     details_lines
 }
 
+/// Build the content lines for the stored metadata view of the task details modal
+fn build_raw_metadata_content(app: &App) -> Vec<Line<'_>> {
+    let mut lines = match &app.task_details_raw {
+        Some(raw) => {
+            // Up front, as a single huge value can wrap past the end of scrolling
+            let mut lines = Vec::new();
+            if app.task_details_raw_len > raw.len() {
+                lines.push(truncation_notice_line("Metadata", app.task_details_raw_len));
+                lines.push(Line::from(""));
+            }
+            lines.extend(raw.lines().map(Line::raw));
+            lines
+        }
+        None => vec![Line::from(Span::styled(
+            "Loading stored metadata...",
+            Style::default().fg(Color::Gray),
+        ))],
+    };
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "[↑↓/PgUp/PgDn] Scroll | [s] Save full metadata to a file | [r] Parsed fields | Any other key to close",
+        Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC),
+    )));
+    lines
+}
+
+/// Number of lines in the task details modal, used to bound scrolling
+pub fn task_details_line_count(app: &App) -> usize {
+    match &app.selected_task_details {
+        Some(_) if app.show_task_details_raw => build_raw_metadata_content(app).len(),
+        Some(task) => build_task_details_content(app, task).len(),
+        None => 0,
+    }
+}
+
+/// Maximum number of sibling tasks listed in the workflow section
+const MAX_LISTED_SIBLINGS: usize = 10;
+
+/// Build the workflow section: root, parent and sibling tasks of a canvas member
+fn build_workflow_lines<'a>(app: &'a App, task: &'a crate::models::Task) -> Vec<Line<'a>> {
+    if task.root_id.is_none() && task.parent_id.is_none() && task.group_id.is_none() {
+        return Vec::new();
+    }
+
+    let label_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let (parent, siblings) = app.task_relations(task);
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("Workflow:", label_style)),
+    ];
+    if let Some(root_id) = &task.root_id {
+        lines.push(Line::from(vec![
+            Span::styled("  Root: ", label_style),
+            Span::raw(root_id.as_str()),
+        ]));
+    }
+    if let Some(parent_id) = &task.parent_id {
+        let parent_name = parent.map(|p| format!(" ({})", p.name)).unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled("  Parent: ", label_style),
+            Span::raw(format!("{parent_id}{parent_name}")),
+        ]));
+    }
+    if let Some(group_id) = &task.group_id {
+        lines.push(Line::from(vec![
+            Span::styled("  Group: ", label_style),
+            Span::raw(group_id.as_str()),
+        ]));
+    }
+    if !siblings.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("  Related tasks ({}):", siblings.len()),
+            label_style,
+        )));
+        for sibling in siblings.iter().take(MAX_LISTED_SIBLINGS) {
+            lines.push(Line::from(vec![
+                Span::raw(format!("    {} {} ", sibling.id, sibling.name)),
+                Span::styled(
+                    format!("{:?}", sibling.status),
+                    Style::default().fg(get_status_color(&sibling.status)),
+                ),
+            ]));
+        }
+        if siblings.len() > MAX_LISTED_SIBLINGS {
+            lines.push(Line::from(format!(
+                "    ... and {} more",
+                siblings.len() - MAX_LISTED_SIBLINGS
+            )));
+        }
+    }
+
+    lines
+}
+
+/// Build the children section, marking the child `Enter` opens
+fn build_children_lines<'a>(app: &'a App, task: &'a crate::models::Task) -> Vec<Line<'a>> {
+    if task.children.is_empty() {
+        return Vec::new();
+    }
+
+    let label_style = Style::default()
+        .fg(Color::Cyan)
+        .add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("Children ({}):", task.children.len()),
+            label_style,
+        )),
+    ];
+    for (index, child_id) in task.children.iter().enumerate() {
+        let selected = index == app.task_details_child;
+        let marker = if selected { "  > " } else { "    " };
+        let id_style = if selected {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        let mut spans = vec![Span::raw(marker), Span::styled(child_id.as_str(), id_style)];
+        if let Some(child) = app.tasks.iter().find(|t| &t.id == child_id) {
+            spans.push(Span::raw(format!(" {} ", child.name)));
+            spans.push(Span::styled(
+                format!("{:?}", child.status),
+                Style::default().fg(get_status_color(&child.status)),
+            ));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines
+}
+
+/// Build the notice shown below a value that was truncated during parsing
+fn truncation_notice_line(label: &str, full_len: usize) -> Line<'static> {
+    Line::from(Span::styled(
+        format!(
+            "{label} truncated for display ({full_len} bytes total) - press [s] to save it in full"
+        ),
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::ITALIC),
+    ))
+}
+
 /// Get the appropriate color for a task status
 fn get_status_color(status: &crate::models::TaskStatus) -> Color {
     match status {
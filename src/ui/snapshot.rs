@@ -0,0 +1,32 @@
+//! Off-screen rendering of full frames for headless UI tests
+//!
+//! Only used through the library API, the binary always draws to a real terminal.
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::Terminal;
+
+use crate::app::App;
+use crate::ui::draw;
+
+/// Render one full frame of `app` off-screen at `width` x `height`
+///
+/// Returns the frame as plain text, one line per row, for snapshot tests.
+#[allow(dead_code)]
+pub fn render_to_string(app: &mut App, width: u16, height: u16) -> String {
+    // The test backend can't fail, its error type is uninhabited
+    let Ok(mut terminal) = Terminal::new(TestBackend::new(width, height));
+    let Ok(_) = terminal.draw(|f| draw(f, app));
+    buffer_to_string(terminal.backend().buffer())
+}
+
+/// Flatten a rendered buffer into text, dropping styles
+#[allow(dead_code)]
+pub fn buffer_to_string(buffer: &Buffer) -> String {
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
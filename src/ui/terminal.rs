@@ -0,0 +1,31 @@
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use std::io::{self, Write};
+
+/// Switch to the alternate screen, capturing the mouse when `mouse` is set
+///
+/// Raw mode is enabled separately by the caller.
+pub fn enter_screen<W: Write>(writer: &mut W, mouse: bool) -> io::Result<()> {
+    execute!(writer, EnterAlternateScreen)?;
+    if mouse {
+        execute!(writer, EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+/// Leave raw mode and the alternate screen, stop mouse capture when it was
+/// enabled and show the cursor
+///
+/// Safe to call more than once, so every shutdown path (normal exit, errors,
+/// SIGINT) can run it without checking whether another path already did.
+pub fn restore_terminal<W: Write>(writer: &mut W, mouse: bool) -> io::Result<()> {
+    disable_raw_mode()?;
+    if mouse {
+        execute!(writer, DisableMouseCapture)?;
+    }
+    execute!(writer, LeaveAlternateScreen, Show)
+}
@@ -15,12 +15,22 @@ pub trait Widget {
 
 /// Common helper functions for widget styling and layout
 pub mod helpers {
+    use crate::app::{App, LayoutMode};
     use ratatui::{
+        layout::Direction,
         style::{Color, Modifier, Style},
         text::{Line, Span},
         widgets::{Block, BorderType, Borders, Paragraph},
     };
 
+    /// Direction to split list and details panels, stacked in the compact layout
+    pub fn list_details_direction(app: &App) -> Direction {
+        match app.layout_mode {
+            LayoutMode::Standard => Direction::Horizontal,
+            LayoutMode::Compact => Direction::Vertical,
+        }
+    }
+
     /// Create a standard selection style for highlighted items
     pub fn selection_style() -> Style {
         Style::default()
@@ -1,9 +1,13 @@
 pub mod base;
+pub mod overview;
 pub mod queues;
+pub mod scheduled;
 pub mod tasks;
 pub mod workers;
 
 pub use base::Widget;
+pub use overview::OverviewWidget;
 pub use queues::QueueWidget;
+pub use scheduled::ScheduledWidget;
 pub use tasks::TaskWidget;
 pub use workers::WorkerWidget;
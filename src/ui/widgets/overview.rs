@@ -0,0 +1,66 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::base::{helpers, Widget};
+use super::{QueueWidget, WorkerWidget};
+use crate::app::App;
+use crate::models::{Task, TaskStatus};
+
+/// Failed tasks listed on the overview, newest first
+const OVERVIEW_FAILURES_SHOWN: usize = 10;
+
+/// Queues, workers and failures at a glance, each in a smaller form of the
+/// pane its own tab shows
+pub struct OverviewWidget;
+
+impl OverviewWidget {
+    pub fn draw(f: &mut Frame, app: &App, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Percentage(55),
+                Constraint::Min(0),
+            ])
+            .split(area);
+        WorkerWidget::draw_cluster_summary(f, app, rows[0]);
+
+        let lists = Layout::default()
+            .direction(helpers::list_details_direction(app))
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[1]);
+        QueueWidget::draw_list(f, app, lists[0]);
+        WorkerWidget::draw_list(f, app, lists[1]);
+
+        Self::draw_failures(f, app, rows[2]);
+    }
+
+    /// The latest failed tasks across all workers
+    fn draw_failures(f: &mut Frame, app: &App, area: Rect) {
+        let failures = Self::recent_failures(&app.tasks, OVERVIEW_FAILURES_SHOWN);
+        let block = helpers::titled_block("Recent Failures");
+
+        if failures.is_empty() {
+            f.render_widget(Paragraph::new("No recent failures").block(block), area);
+            return;
+        }
+
+        f.render_widget(
+            WorkerWidget::failures_table(app, &failures).block(block),
+            area,
+        );
+    }
+
+    fn recent_failures(tasks: &[Task], limit: usize) -> Vec<&Task> {
+        let mut failures: Vec<&Task> = tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Failure)
+            .collect();
+        failures.sort_by_key(|task| std::cmp::Reverse(task.timestamp));
+        failures.truncate(limit);
+        failures
+    }
+}
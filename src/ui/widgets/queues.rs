@@ -8,13 +8,22 @@ use ratatui::{
 
 use super::base::{helpers, Widget};
 use crate::app::App;
+use crate::ui::keymap::KeyAction;
+use crate::utils::formatting::{format_count, format_duration};
+use std::time::Duration;
+
+/// Oldest message ages above this are highlighted as a stalled queue
+const STALE_MESSAGE_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// Shown after queues with a `QueueDiscrepancy`
+const DISCREPANCY_MARKER: &str = "?";
 
 pub struct QueueWidget;
 
 impl Widget for QueueWidget {
     fn draw(f: &mut Frame, app: &App, area: Rect) {
         let chunks = Layout::default()
-            .direction(Direction::Horizontal)
+            .direction(helpers::list_details_direction(app))
             .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
             .split(area);
 
@@ -27,8 +36,8 @@ impl Widget for QueueWidget {
 
     fn draw_list(f: &mut Frame, app: &App, area: Rect) {
         let queues: Vec<ListItem> = app
-            .queues
-            .iter()
+            .get_sorted_queues()
+            .into_iter()
             .enumerate()
             .map(|(idx, queue)| {
                 let status_color = if queue.length > 100 {
@@ -39,11 +48,25 @@ impl Widget for QueueWidget {
                     Color::Green
                 };
 
-                let content = Line::from(vec![
+                let mut spans = vec![
                     Span::raw(&queue.name),
                     Span::raw("   "),
-                    Span::styled(queue.length.to_string(), Style::default().fg(status_color)),
-                ]);
+                    Span::styled(
+                        format_count(queue.length, app.humanize_numbers),
+                        Style::default().fg(status_color),
+                    ),
+                ];
+                if let Some(age) = queue.oldest_message_age {
+                    spans.push(Span::raw("   "));
+                    spans.push(Self::age_span(age));
+                }
+                if queue.discrepancy.is_some() {
+                    spans.push(Span::styled(
+                        format!(" {DISCREPANCY_MARKER}"),
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                let content = Line::from(spans);
 
                 if idx == app.selected_queue {
                     ListItem::new(content).style(helpers::selection_style())
@@ -53,7 +76,14 @@ impl Widget for QueueWidget {
             })
             .collect();
 
-        let title = format!("Queues ({})", app.queues.len());
+        let mut title = format!(
+            "Queues ({}) · by {}",
+            app.queues.len(),
+            app.queue_sort.label()
+        );
+        if let Some(scope) = &app.queue_scope {
+            title.push_str(&format!(" · only {scope}"));
+        }
         let queues_list = List::new(queues)
             .block(helpers::titled_block(&title))
             .highlight_style(helpers::selection_style());
@@ -67,11 +97,11 @@ impl Widget for QueueWidget {
             return;
         }
 
-        if let Some(queue) = app.queues.get(app.selected_queue) {
+        if let Some(queue) = app.get_sorted_queues().get(app.selected_queue) {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(8),
+                    Constraint::Length(9),
                     Constraint::Length(3),
                     Constraint::Min(0),
                 ])
@@ -92,6 +122,13 @@ impl Widget for QueueWidget {
                     },
                 ),
                 helpers::field_line("Consumers", &queue.consumers.to_string()),
+                Line::from(vec![
+                    Span::raw("Oldest message: "),
+                    queue
+                        .oldest_message_age
+                        .map(Self::age_span)
+                        .unwrap_or_else(|| Span::raw("-")),
+                ]),
                 helpers::status_line(
                     "Status",
                     if queue.has_consumers() {
@@ -109,9 +146,16 @@ impl Widget for QueueWidget {
                         Color::Yellow
                     },
                 ),
-                Line::from(""),
+                // Takes the spacer line, so the details keep their height
+                match &queue.discrepancy {
+                    Some(discrepancy) => Line::from(Span::styled(
+                        format!("{DISCREPANCY_MARKER} {}", discrepancy.describe()),
+                        Style::default().fg(Color::Yellow),
+                    )),
+                    None => Line::from(""),
+                },
                 Line::from(vec![Span::styled(
-                    "[p] Purge queue (requires confirmation)",
+                    Self::purge_hint(app),
                     Style::default().fg(Color::DarkGray),
                 )]),
             ];
@@ -148,3 +192,27 @@ impl Widget for QueueWidget {
         }
     }
 }
+
+impl QueueWidget {
+    /// Key hint for purging, empty when purge is bound to nothing or read-only
+    /// mode disables it
+    fn purge_hint(app: &App) -> String {
+        match app.keymap.chord_for(KeyAction::Purge) {
+            Some(chord) if !app.read_only => {
+                format!("[{chord}] Purge queue (requires confirmation)")
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// Render how long the oldest message has waited, red once the queue looks stalled
+    fn age_span(age: Duration) -> Span<'static> {
+        let color = if age > STALE_MESSAGE_AGE {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        let age = chrono::Duration::from_std(age).unwrap_or(chrono::Duration::MAX);
+        Span::styled(format_duration(age), Style::default().fg(color))
+    }
+}
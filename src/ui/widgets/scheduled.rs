@@ -0,0 +1,120 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Cell, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+use super::base::{helpers, Widget};
+use crate::app::App;
+use crate::broker::BrokerOperation;
+use crate::models::ScheduledEntry;
+use crate::utils::formatting::format_time;
+use chrono::{DateTime, Utc};
+
+pub struct ScheduledWidget;
+
+impl Widget for ScheduledWidget {
+    fn draw(f: &mut Frame, app: &App, area: Rect) {
+        let chunks = Layout::default()
+            .direction(helpers::list_details_direction(app))
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+
+        // Draw schedule list on the left
+        Self::draw_list(f, app, chunks[0]);
+
+        // Draw entry details on the right
+        Self::draw_details(f, app, chunks[1]);
+    }
+
+    fn draw_list(f: &mut Frame, app: &App, area: Rect) {
+        let now = app.now();
+        let header = Row::new(["Name", "Schedule", "Next run"])
+            .style(Style::default().fg(Color::Yellow))
+            .bottom_margin(1);
+
+        let rows: Vec<Row> = app
+            .scheduled
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| {
+                let row = Row::new([
+                    Cell::from(entry.name.clone()),
+                    Cell::from(entry.schedule.clone()),
+                    Self::next_run_cell(entry, app, now),
+                ]);
+
+                if idx == app.selected_scheduled {
+                    row.style(helpers::selection_style())
+                } else {
+                    row
+                }
+            })
+            .collect();
+
+        let title = format!("Scheduled ({})", app.scheduled.len());
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(4),
+                Constraint::Fill(3),
+                Constraint::Fill(3),
+            ],
+        )
+        .header(header)
+        .block(helpers::titled_block(&title))
+        .row_highlight_style(helpers::selection_style());
+
+        f.render_widget(table, area);
+    }
+
+    fn draw_details(f: &mut Frame, app: &App, area: Rect) {
+        if !app.capabilities.supports(BrokerOperation::Scheduled) {
+            let message = Paragraph::new("This broker can't read the beat schedule")
+                .block(helpers::titled_block("Scheduled Details"));
+            f.render_widget(message, area);
+            return;
+        }
+
+        let Some(entry) = app.scheduled.get(app.selected_scheduled) else {
+            f.render_widget(helpers::no_data_message("scheduled tasks"), area);
+            return;
+        };
+
+        let next_run = entry
+            .next_run
+            .map(|next_run| format_time(next_run, app.now(), app.timestamp_mode))
+            .unwrap_or_else(|| "-".to_string());
+        let lines = vec![
+            helpers::highlighted_field_line("Name", &entry.name, Color::Cyan),
+            helpers::highlighted_field_line("Task", &entry.task, Color::Yellow),
+            helpers::field_line("Schedule", &entry.schedule),
+            helpers::field_line("Next run", &next_run),
+        ];
+
+        let details = Paragraph::new(lines)
+            .block(helpers::titled_block("Schedule Details"))
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(details, area);
+    }
+}
+
+impl ScheduledWidget {
+    /// Next run time, highlighted when it has already passed, which usually
+    /// means beat isn't running
+    fn next_run_cell(entry: &ScheduledEntry, app: &App, now: DateTime<Utc>) -> Cell<'static> {
+        match entry.next_run {
+            Some(next_run) => {
+                let cell = Cell::from(format_time(next_run, now, app.timestamp_mode));
+                if next_run < now {
+                    cell.style(Style::default().fg(Color::Yellow))
+                } else {
+                    cell
+                }
+            }
+            None => Cell::from("-"),
+        }
+    }
+}
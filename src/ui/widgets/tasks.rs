@@ -1,15 +1,17 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Cell, Paragraph, Row, Table, Wrap},
     Frame,
 };
 
 use super::base::{helpers, Widget};
-use crate::app::App;
-use crate::models::TaskStatus;
-use chrono::Utc;
+use crate::app::{App, LayoutMode};
+use crate::config::{TaskColumn, TimestampMode};
+use crate::models::{Task, TaskStatus};
+use crate::utils::formatting::{ellipsize_middle, format_runtime, format_time};
+use chrono::{DateTime, Utc};
 
 pub struct TaskWidget;
 
@@ -29,36 +31,33 @@ impl Widget for TaskWidget {
 
     fn draw_list(f: &mut Frame, app: &App, area: Rect) {
         let filtered_tasks = app.get_filtered_tasks();
+        let columns = &Self::visible_columns(app);
 
-        let header = Row::new(vec!["ID", "Name", "Status", "Worker", "Duration"])
+        let header = Row::new(Self::header_cells(columns))
             .style(Style::default().fg(Color::Yellow))
             .bottom_margin(1);
+        let widths: Vec<Constraint> = columns.iter().map(|c| Self::column_width(*c)).collect();
 
-        // Calculate viewport
-        let height = area.height.saturating_sub(4) as usize; // Account for borders and header
+        // Calculate viewport, in tasks rather than terminal lines
+        let row_height = if app.task_rows_multiline { 2 } else { 1 };
+        let height = area.height.saturating_sub(4) / row_height; // Account for borders and header
+        let height = height as usize;
 
         if filtered_tasks.is_empty() {
-            let no_tasks = Row::new(vec![
-                Cell::from(""),
-                Cell::from("No tasks found"),
-                Cell::from(""),
-                Cell::from(""),
-                Cell::from(""),
-            ])
+            // Show the message in the second column, like the name would be
+            let message_column = 1.min(columns.len().saturating_sub(1));
+            let no_tasks = Row::new((0..columns.len()).map(|idx| {
+                Cell::from(if idx == message_column {
+                    "No tasks found"
+                } else {
+                    ""
+                })
+            }))
             .style(Style::default().fg(Color::DarkGray));
 
-            let table = Table::new(
-                vec![no_tasks],
-                [
-                    Constraint::Percentage(20),
-                    Constraint::Percentage(30),
-                    Constraint::Percentage(15),
-                    Constraint::Percentage(20),
-                    Constraint::Percentage(15),
-                ],
-            )
-            .header(header)
-            .block(Block::default().borders(Borders::ALL).title(" Tasks (0) "));
+            let table = Table::new(vec![no_tasks], widths)
+                .header(header)
+                .block(Block::default().borders(Borders::ALL).title(" Tasks (0) "));
 
             f.render_widget(table, area);
             return;
@@ -77,40 +76,40 @@ impl Widget for TaskWidget {
 
         let end = (start + height).min(filtered_tasks.len());
         let visible_tasks = &filtered_tasks[start..end];
+        let now = app.now();
+        let name_width = columns
+            .iter()
+            .zip(Self::column_widths(&widths, area))
+            .find(|(column, _)| **column == TaskColumn::Name)
+            .map_or(usize::MAX, |(_, width)| width as usize);
 
         let rows: Vec<Row> = visible_tasks
             .iter()
             .enumerate()
             .map(|(idx, task)| {
                 let actual_idx = start + idx;
-                let status_color = match task.status {
-                    TaskStatus::Success => Color::Green,
-                    TaskStatus::Failure => Color::Red,
-                    TaskStatus::Active => Color::Yellow,
-                    TaskStatus::Pending => Color::Gray,
-                    TaskStatus::Retry => Color::Magenta,
-                    TaskStatus::Revoked => Color::DarkGray,
-                };
-
-                let duration = task.duration_since(Utc::now());
-                let duration_str = format!(
-                    "{:02}:{:02}:{:02}",
-                    duration.num_hours(),
-                    duration.num_minutes() % 60,
-                    duration.num_seconds() % 60
-                );
-
-                let row = Row::new(vec![
-                    Cell::from(task.id.clone()),
-                    Cell::from(task.name.clone()),
-                    Cell::from(format!("{:?}", task.status))
-                        .style(Style::default().fg(status_color)),
-                    Cell::from(task.worker.as_deref().unwrap_or("-")),
-                    Cell::from(duration_str),
-                ]);
+                let stuck = app.is_task_stuck(task, now);
+                let archived = app.is_archived(&task.id);
+                let row = Row::new(columns.iter().map(|column| {
+                    if app.task_rows_multiline && *column == TaskColumn::Name {
+                        return Self::name_with_args_cell(task, name_width);
+                    }
+                    Self::column_cell(
+                        *column,
+                        task,
+                        now,
+                        app.timestamp_mode,
+                        stuck,
+                        archived,
+                        name_width,
+                    )
+                }))
+                .height(row_height);
 
                 if actual_idx == app.selected_task {
                     row.style(helpers::selection_style())
+                } else if archived {
+                    row.style(Self::archived_style())
                 } else {
                     row
                 }
@@ -124,30 +123,42 @@ impl Widget for TaskWidget {
             String::new()
         };
 
-        let title = if app.is_searching {
+        let title = if app.show_only_stuck {
+            format!(
+                " Tasks (stuck: {}/{}){} ",
+                filtered_tasks.len(),
+                app.tasks.len(),
+                scroll_info
+            )
+        } else if app.is_searching {
             format!(
                 " Tasks (filtered: {}/{}){} ",
                 filtered_tasks.len(),
                 app.tasks.len(),
                 scroll_info
             )
+        } else if !app.archived_tasks.is_empty() {
+            format!(
+                " Tasks ({} + {} archived){} ",
+                app.tasks.len(),
+                app.archived_tasks.len(),
+                scroll_info
+            )
         } else {
             format!(" Tasks ({}){} ", app.tasks.len(), scroll_info)
         };
 
-        let table = Table::new(
-            rows,
-            [
-                Constraint::Percentage(20),
-                Constraint::Percentage(30),
-                Constraint::Percentage(15),
-                Constraint::Percentage(20),
-                Constraint::Percentage(15),
-            ],
-        )
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title(title))
-        .row_highlight_style(helpers::selection_style());
+        let mut block = Block::default().borders(Borders::ALL).title(title.clone());
+        let legend = Self::status_legend(&filtered_tasks);
+        // Only show the legend when it fits next to the title
+        if (area.width as usize) >= title.chars().count() + legend.width() + 4 {
+            block = block.title_top(legend.right_aligned());
+        }
+
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(block)
+            .row_highlight_style(helpers::selection_style());
 
         f.render_widget(table, area);
     }
@@ -170,21 +181,42 @@ impl Widget for TaskWidget {
                 helpers::status_line(
                     "Status",
                     &format!("{:?}", task.status),
-                    match task.status {
-                        TaskStatus::Success => Color::Green,
-                        TaskStatus::Failure => Color::Red,
-                        TaskStatus::Active => Color::Yellow,
-                        TaskStatus::Pending => Color::Gray,
-                        TaskStatus::Retry => Color::Magenta,
-                        TaskStatus::Revoked => Color::DarkGray,
-                    },
+                    Self::status_color(&task.status),
                 ),
                 helpers::field_line("Worker", task.worker.as_deref().unwrap_or("None")),
+                helpers::field_line("Queue", task.queue.as_deref().unwrap_or("Unknown")),
+                Line::from(vec![
+                    Span::raw("Retries: "),
+                    Span::styled(
+                        if task.retries_exhausted() {
+                            format!("{} (exhausted)", Self::retries_text(task))
+                        } else {
+                            Self::retries_text(task)
+                        },
+                        Self::retries_style(task),
+                    ),
+                ]),
                 helpers::field_line(
                     "Timestamp",
-                    &task.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    &format_time(task.timestamp, app.now(), app.timestamp_mode),
                 ),
             ];
+            if let Some(duration) = task.duration {
+                lines.push(helpers::field_line("Duration", &format_runtime(duration)));
+            }
+            if let Some(priority) = task.priority {
+                lines.push(helpers::field_line("Priority", &priority.to_string()));
+            }
+
+            if app.is_archived(&task.id) {
+                lines.insert(
+                    0,
+                    Line::from(Span::styled(
+                        "Archived: the broker no longer lists this task",
+                        Self::archived_style(),
+                    )),
+                );
+            }
 
             if !task.args.is_empty() && task.args != "[]" {
                 lines.push(helpers::field_line("Args", &task.args));
@@ -194,11 +226,18 @@ impl Widget for TaskWidget {
                 lines.push(helpers::field_line("Kwargs", &task.kwargs));
             }
 
-            if let Some(result) = &task.result {
+            if task.looks_like_error && task.status != TaskStatus::Failure {
+                lines.push(Line::from(Span::styled(
+                    format!("{ERROR_RESULT_MARKER} The result looks like an exception"),
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+
+            if let Some(result) = task.result_summary() {
                 lines.push(Line::from(""));
                 lines.push(helpers::highlighted_field_line(
                     "Result",
-                    result,
+                    &result,
                     Color::Green,
                 ));
             }
@@ -225,3 +264,251 @@ impl Widget for TaskWidget {
         }
     }
 }
+
+/// Prefix of the status of tasks that have been active for too long
+const STUCK_MARKER: &str = "⚠";
+
+/// Suffix of the status of tasks the broker no longer lists
+const ARCHIVED_MARKER: &str = "(archived)";
+
+/// Suffix of the status of tasks whose result looks like an exception
+/// although they didn't fail
+const ERROR_RESULT_MARKER: &str = "!";
+
+/// Order and symbols of the statuses in the legend
+const STATUS_LEGEND: [(TaskStatus, &str); 6] = [
+    (TaskStatus::Success, "✓"),
+    (TaskStatus::Failure, "✗"),
+    (TaskStatus::Active, "▶"),
+    (TaskStatus::Pending, "⏳"),
+    (TaskStatus::Retry, "↻"),
+    (TaskStatus::Revoked, "⊘"),
+];
+
+impl TaskWidget {
+    pub fn status_color(status: &TaskStatus) -> Color {
+        match status {
+            TaskStatus::Success => Color::Green,
+            TaskStatus::Failure => Color::Red,
+            TaskStatus::Active => Color::Yellow,
+            TaskStatus::Pending => Color::Gray,
+            TaskStatus::Retry => Color::Magenta,
+            TaskStatus::Revoked => Color::DarkGray,
+        }
+    }
+
+    /// Number of `tasks` in each status, in legend order
+    pub fn status_counts(tasks: &[&Task]) -> Vec<(TaskStatus, usize)> {
+        STATUS_LEGEND
+            .iter()
+            .map(|(status, _)| {
+                let count = tasks.iter().filter(|t| &t.status == status).count();
+                (status.clone(), count)
+            })
+            .collect()
+    }
+
+    /// Colored status symbols with their counts, e.g. "✓12 ✗3 ▶2 ⏳5 ↻1 ⊘0"
+    pub fn status_legend(tasks: &[&Task]) -> Line<'static> {
+        let mut spans = Vec::new();
+        for ((status, count), (_, symbol)) in
+            Self::status_counts(tasks).into_iter().zip(STATUS_LEGEND)
+        {
+            spans.push(Span::styled(
+                format!(" {symbol}{count}"),
+                Style::default().fg(Self::status_color(&status)),
+            ));
+        }
+        spans.push(Span::raw(" "));
+        Line::from(spans)
+    }
+
+    /// Archived tasks are dimmed so they don't pass for live ones
+    pub fn archived_style() -> Style {
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(Modifier::ITALIC)
+    }
+
+    /// Retries so far, with the limit when known, e.g. "2" or "3/3"
+    pub fn retries_text(task: &Task) -> String {
+        match task.max_retries {
+            Some(max_retries) => format!("{}/{}", task.retries, max_retries),
+            None => task.retries.to_string(),
+        }
+    }
+
+    /// Tasks that used up their retries stand out in red
+    pub fn retries_style(task: &Task) -> Style {
+        if task.retries_exhausted() {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Self::status_color(&task.status))
+        }
+    }
+
+    /// Status and name only in the compact layout, the configured columns otherwise
+    pub fn visible_columns(app: &App) -> Vec<TaskColumn> {
+        match app.layout_mode {
+            LayoutMode::Standard => app.task_columns.clone(),
+            LayoutMode::Compact => vec![TaskColumn::Status, TaskColumn::Name],
+        }
+    }
+
+    /// Header titles for the configured task table columns
+    pub fn header_cells(columns: &[TaskColumn]) -> Vec<&'static str> {
+        columns
+            .iter()
+            .map(|column| match column {
+                TaskColumn::Id => "ID",
+                TaskColumn::Name => "Name",
+                TaskColumn::Status => "Status",
+                TaskColumn::Worker => "Worker",
+                TaskColumn::Duration => "Duration",
+                TaskColumn::Eta => "ETA",
+                TaskColumn::Timestamp => "Time",
+                TaskColumn::Retries => "Retries",
+                TaskColumn::Result => "Result",
+                TaskColumn::Queue => "Queue",
+                TaskColumn::Priority => "Priority",
+            })
+            .collect()
+    }
+
+    /// Relative width of a column, shares are split among the visible columns
+    fn column_width(column: TaskColumn) -> Constraint {
+        match column {
+            TaskColumn::Id => Constraint::Fill(4),
+            TaskColumn::Name => Constraint::Fill(6),
+            TaskColumn::Status => Constraint::Fill(3),
+            TaskColumn::Worker => Constraint::Fill(4),
+            TaskColumn::Duration => Constraint::Fill(3),
+            TaskColumn::Eta => Constraint::Fill(4),
+            TaskColumn::Timestamp => Constraint::Fill(4),
+            TaskColumn::Retries => Constraint::Fill(2),
+            TaskColumn::Result => Constraint::Fill(5),
+            TaskColumn::Queue => Constraint::Fill(3),
+            TaskColumn::Priority => Constraint::Fill(2),
+        }
+    }
+
+    /// Widths the table gives each column when drawn in `area`
+    fn column_widths(widths: &[Constraint], area: Rect) -> Vec<u16> {
+        let inner = Block::default().borders(Borders::ALL).inner(area);
+        Layout::horizontal(widths.iter().copied())
+            .spacing(1)
+            .split(inner)
+            .iter()
+            .map(|rect| rect.width)
+            .collect()
+    }
+
+    /// Name of the task over a preview of its arguments, for two-line rows
+    fn name_with_args_cell(task: &Task, width: usize) -> Cell<'static> {
+        Cell::from(Text::from(vec![
+            Line::from(ellipsize_middle(&task.name, width)),
+            Line::from(Span::styled(
+                Self::args_preview(task, width),
+                Style::default().fg(Color::Gray),
+            )),
+        ]))
+    }
+
+    /// Arguments of the task on one line, cut to `width` characters
+    fn args_preview(task: &Task, width: usize) -> String {
+        let mut preview = match task.args.as_str() {
+            "" | "[]" => String::new(),
+            args => args.to_string(),
+        };
+        if !task.kwargs.is_empty() && task.kwargs != "{}" {
+            if !preview.is_empty() {
+                preview.push(' ');
+            }
+            preview.push_str(&task.kwargs);
+        }
+        if preview.is_empty() {
+            return "(no arguments)".to_string();
+        }
+
+        let preview = preview.split_whitespace().collect::<Vec<_>>().join(" ");
+        if preview.chars().count() <= width {
+            return preview;
+        }
+        let mut cut: String = preview.chars().take(width.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+
+    fn column_cell(
+        column: TaskColumn,
+        task: &Task,
+        now: DateTime<Utc>,
+        mode: TimestampMode,
+        stuck: bool,
+        archived: bool,
+        name_width: usize,
+    ) -> Cell<'static> {
+        match column {
+            TaskColumn::Id => Cell::from(task.id.clone()),
+            TaskColumn::Name => Cell::from(ellipsize_middle(&task.name, name_width)),
+            TaskColumn::Status if archived => {
+                Cell::from(format!("{:?} {ARCHIVED_MARKER}", task.status))
+                    .style(Self::archived_style())
+            }
+            TaskColumn::Status if stuck => Cell::from(format!("{STUCK_MARKER} {:?}", task.status))
+                .style(
+                    Style::default()
+                        .fg(Color::LightRed)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            TaskColumn::Status if task.retries > 0 => {
+                Cell::from(format!("{:?} ↻{}", task.status, Self::retries_text(task)))
+                    .style(Self::retries_style(task))
+            }
+            TaskColumn::Status if task.looks_like_error && task.status != TaskStatus::Failure => {
+                Cell::from(Line::from(vec![
+                    Span::styled(
+                        format!("{:?}", task.status),
+                        Style::default().fg(Self::status_color(&task.status)),
+                    ),
+                    Span::styled(
+                        format!(" {ERROR_RESULT_MARKER}"),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]))
+            }
+            TaskColumn::Status => Cell::from(format!("{:?}", task.status))
+                .style(Style::default().fg(Self::status_color(&task.status))),
+            TaskColumn::Worker => Cell::from(task.worker.clone().unwrap_or_else(|| "-".into())),
+            TaskColumn::Duration => match task.duration {
+                Some(duration) => Cell::from(format_runtime(duration)),
+                // A running task has been going since it was handed to a worker
+                None if task.status == TaskStatus::Active => {
+                    let duration = task.duration_since(now);
+                    Cell::from(format!(
+                        "{:02}:{:02}:{:02}",
+                        duration.num_hours(),
+                        duration.num_minutes() % 60,
+                        duration.num_seconds() % 60
+                    ))
+                }
+                None => Cell::from(""),
+            },
+            TaskColumn::Eta => Cell::from(
+                task.eta
+                    .map(|eta| format_time(eta, now, mode))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            TaskColumn::Timestamp => Cell::from(format_time(task.timestamp, now, mode)),
+            TaskColumn::Retries => {
+                Cell::from(Self::retries_text(task)).style(Self::retries_style(task))
+            }
+            TaskColumn::Result => Cell::from(task.result_summary().unwrap_or_else(|| "-".into())),
+            TaskColumn::Queue => Cell::from(task.queue.clone().unwrap_or_else(|| "-".into())),
+            TaskColumn::Priority => Cell::from(
+                task.priority
+                    .map_or_else(|| "-".to_string(), |priority| priority.to_string()),
+            ),
+        }
+    }
+}
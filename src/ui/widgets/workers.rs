@@ -8,16 +8,26 @@ use ratatui::{
 
 use super::base::{helpers, Widget};
 use crate::app::App;
-use crate::models::WorkerStatus;
+use crate::models::{Task, Worker, WorkerStatus};
+use crate::utils::formatting::{format_count, format_time};
+
+/// Failed tasks listed for the selected worker
+const RECENT_FAILURES_SHOWN: usize = 5;
 
 pub struct WorkerWidget;
 
 impl Widget for WorkerWidget {
     fn draw(f: &mut Frame, app: &App, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(area);
+        Self::draw_cluster_summary(f, app, rows[0]);
+
         let chunks = Layout::default()
-            .direction(Direction::Horizontal)
+            .direction(helpers::list_details_direction(app))
             .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
-            .split(area);
+            .split(rows[1]);
 
         // Draw worker list on the left
         Self::draw_list(f, app, chunks[0]);
@@ -27,8 +37,8 @@ impl Widget for WorkerWidget {
     }
 
     fn draw_list(f: &mut Frame, app: &App, area: Rect) {
-        let workers: Vec<ListItem> = app
-            .workers
+        let filtered_workers = app.get_filtered_workers();
+        let workers: Vec<ListItem> = filtered_workers
             .iter()
             .enumerate()
             .map(|(idx, worker)| {
@@ -55,7 +65,14 @@ impl Widget for WorkerWidget {
             })
             .collect();
 
-        let title = format!("Workers ({})", app.workers.len());
+        let title = match &app.worker_queue_filter {
+            Some(queue) => format!(
+                "Workers ({}/{}) [queue: {queue}]",
+                filtered_workers.len(),
+                app.workers.len()
+            ),
+            None => format!("Workers ({})", app.workers.len()),
+        };
         let workers_list = List::new(workers)
             .block(helpers::titled_block(&title))
             .highlight_style(helpers::selection_style());
@@ -64,15 +81,20 @@ impl Widget for WorkerWidget {
     }
 
     fn draw_details(f: &mut Frame, app: &App, area: Rect) {
-        if app.workers.is_empty() {
+        let filtered_workers = app.get_filtered_workers();
+        if filtered_workers.is_empty() {
             f.render_widget(helpers::no_data_message("workers"), area);
             return;
         }
 
-        if let Some(worker) = app.workers.get(app.selected_worker) {
+        if let Some(worker) = filtered_workers.get(app.selected_worker) {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Length(10), Constraint::Min(0)])
+                .constraints([
+                    Constraint::Length(11),
+                    Constraint::Fill(1),
+                    Constraint::Fill(1),
+                ])
                 .split(area);
 
             // Worker info section
@@ -89,11 +111,12 @@ impl Widget for WorkerWidget {
                         WorkerStatus::Offline => Color::Red,
                     },
                 ),
-                helpers::field_line("Concurrency", &worker.concurrency.to_string()),
+                helpers::field_line("Concurrency", &worker.concurrency_label()),
                 helpers::field_line(
                     "Active Tasks",
                     &format!("{}/{}", worker.active_tasks.len(), worker.concurrency),
                 ),
+                helpers::field_line("Reserved Tasks", &worker.reserved_tasks.len().to_string()),
                 helpers::field_line("Utilization", &format!("{:.1}%", worker.utilization())),
                 helpers::highlighted_field_line(
                     "Processed",
@@ -129,6 +152,83 @@ impl Widget for WorkerWidget {
                     Paragraph::new("No active tasks").block(helpers::titled_block("Active Tasks"));
                 f.render_widget(no_tasks, chunks[1]);
             }
+
+            Self::draw_recent_failures(f, app, worker, chunks[2]);
+        }
+    }
+}
+
+impl WorkerWidget {
+    /// One line of totals across every worker
+    pub(crate) fn draw_cluster_summary(f: &mut Frame, app: &App, area: Rect) {
+        let stats = app.cluster_stats();
+        let throughput = stats
+            .throughput
+            .map(|rate| format!("{rate:.1} tasks/s"))
+            .unwrap_or_else(|| "-".to_string());
+        let online_color = if stats.online < stats.total {
+            Color::Yellow
+        } else {
+            Color::Green
+        };
+
+        let summary = Line::from(vec![
+            Span::raw(" Cluster: "),
+            Span::styled(
+                format!("{}/{} online", stats.online, stats.total),
+                Style::default().fg(online_color),
+            ),
+            Span::raw(" | Processed: "),
+            Span::styled(
+                format_count(stats.processed, app.humanize_numbers),
+                Style::default().fg(Color::Green),
+            ),
+            Span::raw(" | Failed: "),
+            Span::styled(
+                format_count(stats.failed, app.humanize_numbers),
+                Style::default().fg(Color::Red),
+            ),
+            Span::raw(format!(" | Throughput: {throughput}")),
+        ]);
+        f.render_widget(summary, area);
+    }
+
+    /// List the latest failed tasks that ran on `worker`
+    fn draw_recent_failures(f: &mut Frame, app: &App, worker: &Worker, area: Rect) {
+        let failures = worker.recent_failures(&app.tasks, RECENT_FAILURES_SHOWN);
+        let block = helpers::titled_block("Recent Failures");
+
+        if failures.is_empty() {
+            f.render_widget(Paragraph::new("No recent failures").block(block), area);
+            return;
         }
+
+        f.render_widget(Self::failures_table(app, &failures).block(block), area);
+    }
+
+    /// Failed tasks, one row each with when they failed, name and id
+    pub(crate) fn failures_table(app: &App, failures: &[&Task]) -> Table<'static> {
+        let now = app.now();
+        let rows: Vec<Row> = failures
+            .iter()
+            .map(|task| {
+                Row::new(vec![
+                    format_time(task.timestamp, now, app.timestamp_mode),
+                    task.name.clone(),
+                    task.id.clone(),
+                ])
+                .style(Style::default().fg(Color::Red))
+            })
+            .collect();
+
+        Table::new(
+            rows,
+            [
+                Constraint::Fill(2),
+                Constraint::Fill(4),
+                Constraint::Fill(4),
+            ],
+        )
+        .header(Row::new(vec!["When", "Task", "ID"]).style(Style::default().fg(Color::Yellow)))
     }
 }
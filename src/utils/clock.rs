@@ -0,0 +1,27 @@
+//! Source of the current time for timestamp fallbacks, ages and relative times
+//!
+//! Parsers and the app read the time through a `Clock` instead of calling
+//! `Utc::now()`, so tests can pin it with `test_support::FixedClock`.
+
+use chrono::{DateTime, Utc};
+use std::fmt::Debug;
+use std::sync::Arc;
+
+pub trait Clock: Debug + Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system's wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// The clock used unless another one is injected
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
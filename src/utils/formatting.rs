@@ -1,7 +1,7 @@
+use crate::config::TimestampMode;
 use chrono::{DateTime, Duration, Utc};
 
-/// Format duration as HH:MM:SS or MM:SS - utility function for future UI features
-#[allow(dead_code)]
+/// Format duration as HH:MM:SS or MM:SS
 pub fn format_duration(duration: Duration) -> String {
     let hours = duration.num_hours();
     let minutes = duration.num_minutes() % 60;
@@ -14,12 +14,120 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
-/// Format timestamp for display - utility function for future UI features
-#[allow(dead_code)]
+/// Format how long a task ran, with millisecond precision under a minute
+/// and as HH:MM:SS or MM:SS above
+pub fn format_runtime(duration: std::time::Duration) -> String {
+    if duration.as_secs() < 60 {
+        format!("{:.3}s", duration.as_secs_f64())
+    } else {
+        Duration::from_std(duration)
+            .map(format_duration)
+            .unwrap_or_default()
+    }
+}
+
+/// Format an interval in its largest exact unit, e.g. "30s", "5m", "2h" or "7d"
+pub fn format_interval(interval: std::time::Duration) -> String {
+    match interval.as_secs() {
+        0 => "0s".to_string(),
+        s if s % 86_400 == 0 => format!("{}d", s / 86_400),
+        s if s % 3_600 == 0 => format!("{}h", s / 3_600),
+        s if s % 60 == 0 => format!("{}m", s / 60),
+        s => format!("{s}s"),
+    }
+}
+
+/// Parse an interval like "30s", "30m", "24h" or "7d"
+pub fn parse_interval(input: &str) -> Result<std::time::Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (amount, unit) = input.split_at(split);
+
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration '{input}': expected e.g. 30m, 24h or 7d"))?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => {
+            return Err(format!(
+                "invalid duration '{input}': unit must be one of s, m, h, d"
+            ))
+        }
+    };
+
+    amount
+        .checked_mul(unit_seconds)
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration '{input}': too large"))
+}
+
+/// Format timestamp for display
 pub fn format_timestamp(timestamp: DateTime<Utc>) -> String {
     timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
+/// Format a timestamp relative to `now`, e.g. "3m ago" or "in 2h"
+pub fn format_relative_time(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let delta = now - timestamp;
+    let seconds = delta.num_seconds().abs();
+    if seconds < 5 {
+        return "just now".to_string();
+    }
+
+    let amount = if seconds < 60 {
+        format!("{seconds}s")
+    } else if seconds < 3_600 {
+        format!("{}m", seconds / 60)
+    } else if seconds < 86_400 {
+        format!("{}h", seconds / 3_600)
+    } else {
+        format!("{}d", seconds / 86_400)
+    };
+
+    if delta < Duration::zero() {
+        format!("in {amount}")
+    } else {
+        format!("{amount} ago")
+    }
+}
+
+/// Format a timestamp the way the user chose to see times
+pub fn format_time(timestamp: DateTime<Utc>, now: DateTime<Utc>, mode: TimestampMode) -> String {
+    match mode {
+        TimestampMode::Relative => format_relative_time(timestamp, now),
+        TimestampMode::Absolute => format_timestamp(timestamp),
+    }
+}
+
+/// A count, shortened to e.g. `15.2k` or `1.1M` when `humanize` is set
+///
+/// Counts below a thousand are always exact, and a trailing `.0` is left out.
+pub fn format_count(count: u64, humanize: bool) -> String {
+    const UNITS: [&str; 4] = ["k", "M", "B", "T"];
+    if !humanize || count < 1_000 {
+        return count.to_string();
+    }
+
+    let mut scaled = count as f64 / 1_000.0;
+    let mut unit = 0;
+    // 999_950 would round to 1000.0k, which reads better as 1M
+    while (scaled * 10.0).round() >= 10_000.0 && unit < UNITS.len() - 1 {
+        scaled /= 1_000.0;
+        unit += 1;
+    }
+    let text = format!("{:.1}", (scaled * 10.0).round() / 10.0);
+    format!(
+        "{}{}",
+        text.strip_suffix(".0").unwrap_or(&text),
+        UNITS[unit]
+    )
+}
+
 /// Truncate string with ellipsis - utility function for UI text overflow
 #[allow(dead_code)]
 pub fn truncate_string(s: &str, max_len: usize) -> String {
@@ -31,3 +139,85 @@ pub fn truncate_string(s: &str, max_len: usize) -> String {
         format!("{}...", &s[..max_len - 3])
     }
 }
+
+/// Shorten a dotted task name to `width` characters by cutting out its middle
+///
+/// The last path segment is kept whole when it fits, e.g.
+/// `myapp.module.tasks.do_the_thing` becomes `myapp…do_the_thing`. Otherwise
+/// the start and end of the name are kept around the ellipsis.
+pub fn ellipsize_middle(text: &str, width: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= width {
+        return text.to_string();
+    }
+    if width <= 1 {
+        return "…".chars().take(width).collect();
+    }
+
+    let room = width - 1;
+    let tail_len = match text.rsplit_once('.') {
+        Some((_, segment)) if segment.chars().count() < room => segment.chars().count(),
+        _ => room / 2,
+    };
+    let head: String = chars[..room - tail_len].iter().collect();
+    let tail: String = chars[chars.len() - tail_len..].iter().collect();
+    format!("{head}…{tail}")
+}
+
+/// Expand `{name}` variables in a format string like `{broker_host} db{db}`
+///
+/// `{{` and `}}` stand for literal braces. Variables `lookup` doesn't know and
+/// braces that are never closed are kept as written.
+pub fn expand_format(template: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find(['{', '}']) {
+        output.push_str(&rest[..index]);
+        let tail = &rest[index..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            output.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let name = tail
+            .strip_prefix('{')
+            .and_then(|inner| inner.find(['{', '}']).map(|end| &inner[..end]))
+            .filter(|name| tail[name.len() + 1..].starts_with('}'));
+        match name {
+            Some(name) => {
+                match lookup(name) {
+                    Some(value) => output.push_str(&value),
+                    None => output.push_str(&tail[..name.len() + 2]),
+                }
+                rest = &tail[name.len() + 2..];
+            }
+            None => {
+                output.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Host and database of a broker URL for display, without scheme or credentials
+///
+/// `redis://:secret@cache:6379/2` gives `("cache:6379", Some("2"))`. The
+/// credentials end at the last `@`, so a password holding `/`, `?` or `#`
+/// never shows up as the host.
+pub fn broker_location(url: &str) -> (String, Option<String>) {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_userinfo = without_scheme
+        .rsplit_once('@')
+        .map_or(without_scheme, |(_, rest)| rest);
+    let without_query = without_userinfo
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+    let (host, path) = without_query.split_once('/').unwrap_or((without_query, ""));
+    let db = Some(path.trim_matches('/'))
+        .filter(|db| !db.is_empty())
+        .map(str::to_string);
+    (host.to_string(), db)
+}
@@ -71,7 +71,9 @@ async fn test_full_application_flow() {
     assert_eq!(app.selected_task, 0);
 
     // Go back to Workers tab to test worker selection
-    app.next_tab(); // Tasks -> Workers
+    app.next_tab(); // Tasks -> Scheduled
+    app.next_tab(); // Scheduled -> Overview
+    app.next_tab(); // Overview -> Workers
     app.select_next();
     assert_eq!(app.selected_worker, 1);
 
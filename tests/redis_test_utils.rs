@@ -11,10 +11,14 @@ use redis::{AsyncCommands, Client};
 use serde_json::json;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
 
 // Global counter for database isolation
 static DB_COUNTER: AtomicU8 = AtomicU8::new(2);
 
+// Databases handed back by finished tests, reused before the counter advances
+static RELEASED_DBS: Mutex<Vec<u8>> = Mutex::new(Vec::new());
+
 /// Test database configuration that ensures isolation between tests
 #[derive(Clone)]
 pub struct TestDatabase {
@@ -26,7 +30,8 @@ pub struct TestDatabase {
 impl TestDatabase {
     /// Create a new isolated test database
     pub async fn new() -> Result<Self> {
-        let db_id = DB_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let released = RELEASED_DBS.lock().unwrap().pop();
+        let db_id = released.unwrap_or_else(|| DB_COUNTER.fetch_add(1, Ordering::SeqCst));
         if db_id > 15 {
             // Redis supports databases 0-15 by default
             return Err(anyhow::anyhow!("Too many concurrent tests"));
@@ -78,6 +83,11 @@ impl TestDatabase {
         }
         Ok(())
     }
+
+    /// Hand the database back for reuse by a later test
+    pub fn release(self) {
+        RELEASED_DBS.lock().unwrap().push(self.database_id);
+    }
 }
 
 /// Builder for creating various types of test data
@@ -535,13 +545,14 @@ where
     Fut: std::future::Future<Output = Result<T>>,
 {
     let mut db = TestDatabase::new().await?;
-    let _client = db
-        .setup()
-        .await
-        .map_err(|_| anyhow::anyhow!("Redis not available"))?;
+    if db.setup().await.is_err() {
+        db.release();
+        return Err(anyhow::anyhow!("Redis not available"));
+    }
 
     let result = test_fn(db.clone()).await;
     let _ = db.cleanup().await; // Best effort cleanup
+    db.release();
 
     result
 }
@@ -1,5 +1,9 @@
-use lazycelery::app::{App, Tab};
-use lazycelery::models::{Queue, Task, TaskStatus, Worker, WorkerStatus};
+use lazycelery::app::{App, QueueSortKey, Tab, TaskSortKey, TaskTimeRange};
+use lazycelery::broker::BrokerCapabilities;
+use lazycelery::models::{Queue, ScheduledEntry, Task, TaskStatus, Worker, WorkerStatus};
+use lazycelery::test_support::FixedClock;
+use std::sync::Arc;
+use std::time::Duration;
 
 mod test_broker_utils;
 use test_broker_utils::MockBrokerBuilder;
@@ -19,6 +23,24 @@ fn test_app_creation() {
     assert_eq!(app.search_query, "");
 }
 
+#[test]
+fn test_app_reads_broker_capabilities() {
+    let capabilities = BrokerCapabilities {
+        peek: false,
+        scheduled: false,
+        ..BrokerCapabilities::ALL
+    };
+    let broker = MockBrokerBuilder::empty()
+        .with_capabilities(capabilities)
+        .build();
+    let app = App::new(broker);
+    assert_eq!(app.capabilities, capabilities);
+
+    // Brokers support everything unless they say otherwise
+    let app = App::new(MockBrokerBuilder::empty().build());
+    assert_eq!(app.capabilities, BrokerCapabilities::ALL);
+}
+
 #[test]
 fn test_tab_navigation() {
     let broker = MockBrokerBuilder::empty().build();
@@ -32,9 +54,21 @@ fn test_tab_navigation() {
     app.next_tab();
     assert_eq!(app.selected_tab, Tab::Tasks);
 
+    app.next_tab();
+    assert_eq!(app.selected_tab, Tab::Scheduled);
+
+    app.next_tab();
+    assert_eq!(app.selected_tab, Tab::Overview);
+
     app.next_tab();
     assert_eq!(app.selected_tab, Tab::Workers);
 
+    app.previous_tab();
+    assert_eq!(app.selected_tab, Tab::Overview);
+
+    app.previous_tab();
+    assert_eq!(app.selected_tab, Tab::Scheduled);
+
     app.previous_tab();
     assert_eq!(app.selected_tab, Tab::Tasks);
 
@@ -51,8 +85,10 @@ async fn test_app_refresh_data() {
         hostname: "worker-1".to_string(),
         status: WorkerStatus::Online,
         concurrency: 4,
+        pool_type: None,
         queues: vec!["default".to_string()],
         active_tasks: vec![],
+        reserved_tasks: vec![],
         processed: 100,
         failed: 5,
     }];
@@ -67,12 +103,30 @@ async fn test_app_refresh_data() {
         timestamp: chrono::Utc::now(),
         result: None,
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     }];
 
     let test_queues = vec![Queue {
         name: "default".to_string(),
         length: 10,
         consumers: 2,
+        oldest_message_age: None,
+        discrepancy: None,
     }];
 
     let broker = MockBrokerBuilder::new()
@@ -101,8 +155,10 @@ fn test_item_selection() {
                 hostname: "worker-1".to_string(),
                 status: WorkerStatus::Online,
                 concurrency: 4,
+                pool_type: None,
                 queues: vec![],
                 active_tasks: vec![],
+                reserved_tasks: vec![],
                 processed: 0,
                 failed: 0,
             },
@@ -110,8 +166,10 @@ fn test_item_selection() {
                 hostname: "worker-2".to_string(),
                 status: WorkerStatus::Online,
                 concurrency: 4,
+                pool_type: None,
                 queues: vec![],
                 active_tasks: vec![],
+                reserved_tasks: vec![],
                 processed: 0,
                 failed: 0,
             },
@@ -124,8 +182,10 @@ fn test_item_selection() {
             hostname: "worker-1".to_string(),
             status: WorkerStatus::Online,
             concurrency: 4,
+            pool_type: None,
             queues: vec![],
             active_tasks: vec![],
+            reserved_tasks: vec![],
             processed: 0,
             failed: 0,
         },
@@ -133,8 +193,10 @@ fn test_item_selection() {
             hostname: "worker-2".to_string(),
             status: WorkerStatus::Online,
             concurrency: 4,
+            pool_type: None,
             queues: vec![],
             active_tasks: vec![],
+            reserved_tasks: vec![],
             processed: 0,
             failed: 0,
         },
@@ -184,6 +246,22 @@ fn test_search_functionality() {
             timestamp: chrono::Utc::now(),
             result: None,
             traceback: None,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+            result_kind: None,
+            looks_like_error: false,
         },
         Task {
             id: "def456".to_string(),
@@ -195,6 +273,22 @@ fn test_search_functionality() {
             timestamp: chrono::Utc::now(),
             result: None,
             traceback: None,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+            result_kind: None,
+            looks_like_error: false,
         },
     ];
 
@@ -234,3 +328,933 @@ fn test_empty_state_selection() {
     assert_eq!(app.selected_task, 0);
     assert_eq!(app.selected_queue, 0);
 }
+
+#[test]
+fn test_status_message_expires_after_timeout() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.status_message_timeout = Duration::from_secs(5);
+
+    app.set_status_message("Purged 3 messages".to_string());
+    let set_at = app.status_message_set_at.unwrap();
+
+    // Not yet expired
+    app.expire_status_message(set_at + Duration::from_secs(4));
+    assert_eq!(app.status_message, "Purged 3 messages");
+
+    // Advance past the expiry
+    app.expire_status_message(set_at + Duration::from_secs(6));
+    assert!(app.status_message.is_empty());
+    assert!(app.status_message_set_at.is_none());
+}
+
+#[test]
+fn test_status_message_zero_timeout_never_expires() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.status_message_timeout = Duration::ZERO;
+
+    app.set_status_message("Sticky".to_string());
+    let set_at = app.status_message_set_at.unwrap();
+
+    app.expire_status_message(set_at + Duration::from_secs(3600));
+    assert_eq!(app.status_message, "Sticky");
+}
+
+fn worker_with_queues(hostname: &str, queues: &[&str]) -> Worker {
+    Worker {
+        hostname: hostname.to_string(),
+        status: WorkerStatus::Online,
+        concurrency: 4,
+        pool_type: None,
+        queues: queues.iter().map(|q| q.to_string()).collect(),
+        active_tasks: vec![],
+        reserved_tasks: vec![],
+        processed: 0,
+        failed: 0,
+    }
+}
+
+#[test]
+fn test_filter_workers_by_selected_queue() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.workers = vec![
+        worker_with_queues("worker-1", &["default"]),
+        worker_with_queues("worker-2", &["emails", "default"]),
+        worker_with_queues("worker-3", &["emails"]),
+    ];
+    app.queues = vec![
+        Queue {
+            name: "default".to_string(),
+            length: 0,
+            consumers: 2,
+            oldest_message_age: None,
+            discrepancy: None,
+        },
+        Queue {
+            name: "emails".to_string(),
+            length: 0,
+            consumers: 2,
+            oldest_message_age: None,
+            discrepancy: None,
+        },
+    ];
+    app.selected_worker = 2;
+    app.selected_tab = Tab::Queues;
+    app.selected_queue = 1;
+
+    app.filter_workers_by_selected_queue();
+
+    assert_eq!(app.selected_tab, Tab::Workers);
+    assert_eq!(app.worker_queue_filter.as_deref(), Some("emails"));
+    assert_eq!(app.selected_worker, 0);
+    let hostnames: Vec<&str> = app
+        .get_filtered_workers()
+        .iter()
+        .map(|w| w.hostname.as_str())
+        .collect();
+    assert_eq!(hostnames, vec!["worker-2", "worker-3"]);
+
+    // Navigation wraps within the filtered list
+    app.select_next();
+    assert_eq!(app.selected_worker, 1);
+    app.select_next();
+    assert_eq!(app.selected_worker, 0);
+
+    app.clear_worker_queue_filter();
+    assert!(app.worker_queue_filter.is_none());
+    assert_eq!(app.get_filtered_workers().len(), 3);
+}
+
+#[test]
+fn test_cluster_stats_aggregate_all_workers() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut offline = worker_with_queues("worker-3", &["emails"]);
+    offline.status = WorkerStatus::Offline;
+    offline.processed = 50;
+    offline.failed = 5;
+    app.workers = vec![
+        Worker {
+            processed: 100,
+            failed: 2,
+            ..worker_with_queues("worker-1", &["default"])
+        },
+        Worker {
+            processed: 250,
+            failed: 0,
+            ..worker_with_queues("worker-2", &["default"])
+        },
+        offline,
+    ];
+    // Filters only narrow the list, the summary covers the whole cluster
+    app.worker_queue_filter = Some("emails".to_string());
+
+    let stats = app.cluster_stats();
+    assert_eq!(stats.processed, 400);
+    assert_eq!(stats.failed, 7);
+    assert_eq!(stats.online, 2);
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.throughput, None);
+}
+
+#[test]
+fn test_format_variables_follow_selection() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.refresh_interval = Duration::from_millis(1500);
+    app.workers = vec![
+        worker_with_queues("worker-1", &["default"]),
+        worker_with_queues("worker-2", &["default"]),
+    ];
+    app.selected_worker = 1;
+
+    assert_eq!(app.format_variable("tab"), Some("Workers".to_string()));
+    assert_eq!(
+        app.format_variable("selected"),
+        Some("worker-2".to_string())
+    );
+    assert_eq!(app.format_variable("refresh_ms"), Some("1500".to_string()));
+    assert_eq!(app.format_variable("workers"), Some("2".to_string()));
+    // No broker URL is known without a reconnect target
+    assert_eq!(app.format_variable("broker_host"), Some(String::new()));
+    assert_eq!(app.format_variable("nope"), None);
+}
+
+#[test]
+fn test_cluster_throughput_from_processed_delta() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let start = std::time::Instant::now();
+    app.workers = vec![Worker {
+        processed: 100,
+        ..worker_with_queues("worker-1", &["default"])
+    }];
+
+    // The first sample has nothing to compare against
+    app.record_worker_totals(start);
+    assert_eq!(app.cluster_stats().throughput, None);
+
+    app.workers[0].processed = 130;
+    app.record_worker_totals(start + Duration::from_secs(2));
+    assert_eq!(app.cluster_stats().throughput, Some(15.0));
+
+    // A worker restarting resets its count, which gives no estimate
+    app.workers[0].processed = 10;
+    app.record_worker_totals(start + Duration::from_secs(4));
+    assert_eq!(app.cluster_stats().throughput, None);
+
+    app.workers[0].processed = 20;
+    app.record_worker_totals(start + Duration::from_secs(9));
+    assert_eq!(app.cluster_stats().throughput, Some(2.0));
+}
+
+#[test]
+fn test_search_matches_args_only_when_enabled() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut by_arg = Task::new("task-1".to_string(), "tasks.sync_user".to_string());
+    by_arg.args = "[12345]".to_string();
+    let mut by_kwarg = Task::new("task-2".to_string(), "tasks.notify".to_string());
+    by_kwarg.kwargs = r#"{"email": "Ada@Example.com"}"#.to_string();
+    let other = Task::new("task-3".to_string(), "tasks.cleanup".to_string());
+    app.tasks = vec![by_arg, by_kwarg, other];
+    app.selected_tab = Tab::Tasks;
+
+    app.search_query = "12345".to_string();
+    assert!(app.get_filtered_tasks().is_empty());
+    assert_eq!(app.search_fields(), "name, id");
+
+    app.toggle_search_args();
+    assert_eq!(app.search_fields(), "name, id, args");
+    assert_eq!(filtered_ids(&app), vec!["task-1"]);
+
+    // Kwargs match too, ignoring case
+    app.search_query = "ada@example".to_string();
+    assert_eq!(filtered_ids(&app), vec!["task-2"]);
+
+    app.toggle_search_args();
+    assert!(app.get_filtered_tasks().is_empty());
+}
+
+#[test]
+fn test_search_clears_worker_queue_filter() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.workers = vec![worker_with_queues("worker-1", &["default"])];
+    app.worker_queue_filter = Some("emails".to_string());
+    assert!(app.get_filtered_workers().is_empty());
+
+    app.start_search();
+    assert!(app.worker_queue_filter.is_none());
+    assert_eq!(app.get_filtered_workers().len(), 1);
+}
+
+fn task_with_status(id: &str, status: TaskStatus) -> Task {
+    let mut task = Task::new(id.to_string(), "process_data".to_string());
+    task.status = status;
+    task
+}
+
+#[test]
+fn test_merge_tasks_updates_changed_and_preserves_selection() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.tasks = vec![
+        task_with_status("task-1", TaskStatus::Success),
+        task_with_status("task-2", TaskStatus::Pending),
+        task_with_status("task-3", TaskStatus::Failure),
+    ];
+    app.selected_tab = Tab::Tasks;
+    app.selected_task = 2;
+
+    app.merge_tasks(vec![
+        task_with_status("task-4", TaskStatus::Pending),
+        task_with_status("task-2", TaskStatus::Active),
+    ]);
+
+    let ids: Vec<&str> = app.tasks.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["task-1", "task-2", "task-3", "task-4"]);
+    // Unchanged items are kept as they were
+    assert_eq!(app.tasks[0].status, TaskStatus::Success);
+    assert_eq!(app.tasks[2].status, TaskStatus::Failure);
+    // Changed items are updated in place
+    assert_eq!(app.tasks[1].status, TaskStatus::Active);
+    assert_eq!(app.selected_task, 2);
+}
+
+#[test]
+fn test_merge_tasks_selection_follows_item_in_filtered_view() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.tasks = vec![
+        Task::new("a-1".to_string(), "send_email".to_string()),
+        Task::new("b-1".to_string(), "process_data".to_string()),
+        Task::new("a-2".to_string(), "send_email".to_string()),
+    ];
+    app.search_query = "email".to_string();
+    app.selected_task = 1; // a-2 in the filtered view
+
+    app.merge_tasks(vec![Task::new("a-0".to_string(), "send_email".to_string())]);
+    assert_eq!(app.get_filtered_tasks()[app.selected_task].id, "a-2");
+}
+
+fn filtered_ids(app: &App) -> Vec<&str> {
+    app.get_filtered_tasks()
+        .iter()
+        .map(|t| t.id.as_str())
+        .collect()
+}
+
+/// Tasks of alternating status, `minutes_ago` old each, with a clock at `now`
+fn app_with_task_ages(minutes_ago: &[i64]) -> App {
+    let now = chrono::Utc::now();
+    let mut app =
+        App::new(MockBrokerBuilder::empty().build()).with_clock(Arc::new(FixedClock::new(now)));
+    app.tasks = minutes_ago
+        .iter()
+        .enumerate()
+        .map(|(i, &minutes)| {
+            let status = if i % 2 == 0 {
+                TaskStatus::Failure
+            } else {
+                TaskStatus::Success
+            };
+            let mut task = task_with_status(&format!("task-{minutes}m"), status);
+            task.timestamp = now - chrono::Duration::minutes(minutes);
+            task
+        })
+        .collect();
+    app.selected_tab = Tab::Tasks;
+    app
+}
+
+#[test]
+fn test_time_range_combines_with_status_filter() {
+    // Failures at 5m, 59m and 180m, successes at 30m and 2 days
+    let mut app = app_with_task_ages(&[5, 30, 59, 2_880, 180]);
+
+    app.cycle_task_status_filter();
+    assert_eq!(app.task_status_filter, Some(TaskStatus::Failure));
+    assert_eq!(filtered_ids(&app), vec!["task-5m", "task-59m", "task-180m"]);
+
+    app.cycle_task_time_range();
+    app.cycle_task_time_range();
+    assert_eq!(app.task_time_range, TaskTimeRange::Last1h);
+    assert_eq!(filtered_ids(&app), vec!["task-5m", "task-59m"]);
+    assert_eq!(app.status_message, "Showing tasks from last 1h");
+
+    // Search narrows the range further
+    app.search_query = "59m".to_string();
+    assert_eq!(filtered_ids(&app), vec!["task-59m"]);
+    app.search_query.clear();
+
+    // Clearing the status filter keeps the range
+    app.clear_task_status_filter();
+    assert_eq!(filtered_ids(&app), vec!["task-5m", "task-30m", "task-59m"]);
+}
+
+#[test]
+fn test_time_range_cycles_back_to_all() {
+    let mut app = app_with_task_ages(&[5, 30, 59, 2_880, 180]);
+    let mut visible = Vec::new();
+    for _ in 0..4 {
+        app.cycle_task_time_range();
+        visible.push((app.task_time_range, app.get_filtered_tasks().len()));
+    }
+    assert_eq!(
+        visible,
+        vec![
+            (TaskTimeRange::Last15m, 1),
+            (TaskTimeRange::Last1h, 3),
+            (TaskTimeRange::Last24h, 4),
+            (TaskTimeRange::All, 5),
+        ]
+    );
+
+    // Only the Tasks tab reacts
+    app.selected_tab = Tab::Queues;
+    app.cycle_task_time_range();
+    app.cycle_task_status_filter();
+    assert_eq!(app.task_time_range, TaskTimeRange::All);
+    assert_eq!(app.task_status_filter, None);
+}
+
+#[test]
+fn test_status_filter_steps_through_statuses_and_keeps_selection() {
+    let mut app = app_with_task_ages(&[5, 30, 59]);
+    app.selected_task = 2; // task-59m, a failure
+
+    app.cycle_task_status_filter();
+    assert_eq!(app.get_filtered_tasks()[app.selected_task].id, "task-59m");
+
+    let mut statuses = vec![app.task_status_filter.clone()];
+    for _ in 0..6 {
+        app.cycle_task_status_filter();
+        statuses.push(app.task_status_filter.clone());
+    }
+    assert_eq!(
+        statuses,
+        vec![
+            Some(TaskStatus::Failure),
+            Some(TaskStatus::Active),
+            Some(TaskStatus::Pending),
+            Some(TaskStatus::Retry),
+            Some(TaskStatus::Success),
+            Some(TaskStatus::Revoked),
+            None,
+        ]
+    );
+    assert_eq!(app.get_filtered_tasks().len(), 3);
+}
+
+#[test]
+fn test_finished_tasks_dropped_by_broker_stay_visible_as_archived() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.replace_tasks(vec![
+        task_with_status("task-1", TaskStatus::Success),
+        task_with_status("task-2", TaskStatus::Active),
+        task_with_status("task-3", TaskStatus::Failure),
+    ]);
+    assert!(app.archived_tasks.is_empty());
+
+    // The broker forgets every task; only the finished ones are archived
+    app.replace_tasks(vec![task_with_status("task-4", TaskStatus::Pending)]);
+    assert_eq!(app.tasks.len(), 1);
+    assert_eq!(filtered_ids(&app), vec!["task-4", "task-3", "task-1"]);
+    assert!(app.is_archived("task-1"));
+    assert!(app.is_archived("task-3"));
+    assert!(!app.is_archived("task-2"));
+    assert!(!app.is_archived("task-4"));
+
+    // A task listed again is live, not archived
+    app.replace_tasks(vec![
+        task_with_status("task-4", TaskStatus::Pending),
+        task_with_status("task-1", TaskStatus::Success),
+    ]);
+    assert!(!app.is_archived("task-1"));
+    assert_eq!(filtered_ids(&app), vec!["task-4", "task-1", "task-3"]);
+}
+
+#[test]
+fn test_archived_tasks_are_evicted_beyond_capacity() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.archive_capacity = 2;
+
+    for id in ["task-1", "task-2", "task-3"] {
+        app.replace_tasks(vec![task_with_status(id, TaskStatus::Success)]);
+    }
+    app.replace_tasks(Vec::new());
+
+    // The longest archived task goes first
+    assert_eq!(filtered_ids(&app), vec!["task-3", "task-2"]);
+    assert!(!app.is_archived("task-1"));
+
+    // A capacity of zero disables archiving
+    app.archive_capacity = 0;
+    app.replace_tasks(vec![task_with_status("task-4", TaskStatus::Success)]);
+    app.replace_tasks(Vec::new());
+    assert!(app.get_filtered_tasks().is_empty());
+}
+
+#[test]
+fn test_archived_tasks_refuse_live_operations() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.replace_tasks(vec![
+        task_with_status("task-1", TaskStatus::Pending),
+        task_with_status("task-2", TaskStatus::Failure),
+    ]);
+    app.replace_tasks(vec![task_with_status("task-1", TaskStatus::Pending)]);
+    app.selected_tab = Tab::Tasks;
+    app.selected_task = 1;
+
+    app.initiate_retry_task();
+    assert!(!app.show_confirmation);
+    assert!(app.status_message.contains("archived"));
+
+    app.initiate_revoke_task();
+    assert!(!app.show_confirmation);
+
+    app.toggle_watch_selected_task();
+    assert!(app.watched_task.is_none());
+
+    // Details still open from the archive
+    app.show_task_details();
+    assert!(app.show_task_details);
+    app.initiate_dump_task_metadata();
+    assert!(!app.show_confirmation);
+}
+
+#[test]
+fn test_merge_queues_replaces_listing_and_preserves_selection() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let queue = |name: &str, length: u64| Queue {
+        name: name.to_string(),
+        length,
+        consumers: 1,
+        oldest_message_age: None,
+        discrepancy: None,
+    };
+    app.queues = vec![queue("celery", 5), queue("default", 0), queue("emails", 2)];
+    app.selected_queue = 2;
+
+    app.merge_queues(vec![queue("emails", 7), queue("celery", 3)]);
+
+    let names: Vec<&str> = app.queues.iter().map(|q| q.name.as_str()).collect();
+    assert_eq!(names, vec!["celery", "emails"]);
+    assert_eq!(app.queues[0].length, 3);
+    assert_eq!(app.queues[1].length, 7);
+    assert_eq!(app.selected_queue, 1);
+}
+
+fn sort_fixture() -> Vec<Queue> {
+    let queue = |name: &str, length: u64, consumers: u32, age: Option<u64>| Queue {
+        name: name.to_string(),
+        length,
+        consumers,
+        oldest_message_age: age.map(Duration::from_secs),
+        discrepancy: None,
+    };
+    vec![
+        queue("celery", 5, 1, Some(30)),
+        queue("default", 40, 3, None),
+        queue("emails", 5, 4, Some(600)),
+        queue("reports", 0, 0, Some(90)),
+    ]
+}
+
+fn sorted_names(app: &App) -> Vec<&str> {
+    app.get_sorted_queues()
+        .iter()
+        .map(|q| q.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_queue_sort_defaults_to_length() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.queues = sort_fixture();
+
+    assert_eq!(app.queue_sort, QueueSortKey::Length);
+    // Ties on length fall back to name
+    assert_eq!(
+        sorted_names(&app),
+        vec!["default", "celery", "emails", "reports"]
+    );
+}
+
+#[test]
+fn test_queue_sort_by_name() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.queues = sort_fixture();
+    app.queue_sort = QueueSortKey::Name;
+
+    assert_eq!(
+        sorted_names(&app),
+        vec!["celery", "default", "emails", "reports"]
+    );
+}
+
+#[test]
+fn test_queue_sort_by_consumers() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.queues = sort_fixture();
+    app.queue_sort = QueueSortKey::Consumers;
+
+    assert_eq!(
+        sorted_names(&app),
+        vec!["emails", "default", "celery", "reports"]
+    );
+}
+
+#[test]
+fn test_queue_sort_by_oldest_age() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.queues = sort_fixture();
+    app.queue_sort = QueueSortKey::OldestAge;
+
+    // Queues without a known age go last
+    assert_eq!(
+        sorted_names(&app),
+        vec!["emails", "reports", "celery", "default"]
+    );
+}
+
+#[test]
+fn test_cycle_queue_sort_keeps_selected_queue() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    app.queues = sort_fixture();
+    app.selected_tab = Tab::Queues;
+    app.selected_queue = 2; // "emails" when sorted by length
+
+    app.cycle_queue_sort();
+    assert_eq!(app.queue_sort, QueueSortKey::Consumers);
+    assert_eq!(app.selected_queue, 0);
+    assert_eq!(app.get_sorted_queues()[app.selected_queue].name, "emails");
+
+    app.cycle_queue_sort();
+    app.cycle_queue_sort();
+    assert_eq!(app.queue_sort, QueueSortKey::Name);
+    assert_eq!(app.get_sorted_queues()[app.selected_queue].name, "emails");
+
+    // Only cycles from the Queues tab
+    app.selected_tab = Tab::Tasks;
+    app.cycle_queue_sort();
+    assert_eq!(app.queue_sort, QueueSortKey::Name);
+}
+
+#[test]
+fn test_cycle_task_sort_orders_by_duration_then_priority() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let task = |id: &str, secs: Option<u64>, priority: Option<u8>| {
+        let mut task = Task::new(id.to_string(), "tasks.step".to_string());
+        task.duration = secs.map(Duration::from_secs);
+        task.priority = priority;
+        task
+    };
+    app.tasks = vec![
+        task("short", Some(2), Some(9)),
+        task("unknown", None, None),
+        task("long", Some(90), Some(1)),
+    ];
+    app.selected_tab = Tab::Tasks;
+    app.selected_task = 0;
+    let ids = |app: &App| -> Vec<String> {
+        app.get_filtered_tasks()
+            .iter()
+            .map(|t| t.id.clone())
+            .collect()
+    };
+
+    app.cycle_task_sort();
+    assert_eq!(app.task_sort, TaskSortKey::Duration);
+    assert_eq!(ids(&app), vec!["long", "short", "unknown"]);
+    // The selected task stays selected
+    assert_eq!(app.get_filtered_tasks()[app.selected_task].id, "short");
+
+    app.cycle_task_sort();
+    assert_eq!(app.task_sort, TaskSortKey::Priority);
+    assert_eq!(app.status_message, "Sorting tasks by priority");
+    assert_eq!(ids(&app), vec!["short", "long", "unknown"]);
+    assert_eq!(app.get_filtered_tasks()[app.selected_task].id, "short");
+
+    app.cycle_task_sort();
+    assert_eq!(app.task_sort, TaskSortKey::Broker);
+    assert_eq!(ids(&app), vec!["short", "unknown", "long"]);
+}
+
+#[test]
+fn test_full_refresh_schedule() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let now = std::time::Instant::now();
+
+    // Incremental refreshes are disabled by default
+    app.last_full_refresh = Some(now);
+    assert!(app.is_full_refresh_due(now));
+
+    app.full_refresh_interval = Duration::from_secs(10);
+    assert!(!app.is_full_refresh_due(now + Duration::from_secs(9)));
+    assert!(app.is_full_refresh_due(now + Duration::from_secs(10)));
+
+    app.last_full_refresh = None;
+    assert!(app.is_full_refresh_due(now));
+}
+
+#[tokio::test]
+async fn test_incremental_refresh_merges_recent_tasks() {
+    let broker = MockBrokerBuilder::new()
+        .with_tasks(vec![task_with_status("task-2", TaskStatus::Success)])
+        .build();
+    let mut app = App::new(broker);
+    app.tasks = vec![
+        task_with_status("task-1", TaskStatus::Failure),
+        task_with_status("task-2", TaskStatus::Active),
+    ];
+
+    app.refresh_incremental().await.unwrap();
+
+    assert_eq!(app.tasks.len(), 2);
+    assert_eq!(app.tasks[0].status, TaskStatus::Failure);
+    assert_eq!(app.tasks[1].status, TaskStatus::Success);
+}
+
+#[tokio::test]
+async fn test_refresh_records_broker_latency() {
+    let broker = MockBrokerBuilder::new().build();
+    // The mock relies on the default ping, which times a queue listing
+    let latency = broker.ping().await.unwrap();
+    assert!(latency < Duration::from_secs(1));
+
+    let mut app = App::new(broker);
+    assert!(app.broker_latency.is_none());
+
+    app.refresh_data().await.unwrap();
+    assert!(app.broker_latency.is_some());
+}
+
+#[tokio::test]
+async fn test_slow_workers_time_out_without_blocking_other_collections() {
+    let broker = MockBrokerBuilder::with_basic_data()
+        .with_slow_workers(Duration::from_secs(5))
+        .build();
+    let mut app = App::new(broker);
+    app.operation_timeout = Duration::from_millis(50);
+    let previous_workers = vec![Worker {
+        hostname: "previous-worker".to_string(),
+        status: WorkerStatus::Online,
+        concurrency: 1,
+        pool_type: None,
+        queues: vec![],
+        active_tasks: vec![],
+        reserved_tasks: vec![],
+        processed: 0,
+        failed: 0,
+    }];
+    app.workers = previous_workers.clone();
+
+    let start = std::time::Instant::now();
+    app.refresh_data().await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Workers keep their previous data, tasks and queues are refreshed
+    assert_eq!(app.workers.len(), 1);
+    assert_eq!(app.workers[0].hostname, "previous-worker");
+    assert_eq!(app.tasks.len(), 2);
+    assert_eq!(app.queues.len(), 2);
+    assert_eq!(
+        app.collection_errors.get(&Tab::Workers).map(String::as_str),
+        Some("timed out after 50ms")
+    );
+    assert!(!app.collection_errors.contains_key(&Tab::Tasks));
+}
+
+#[tokio::test]
+async fn test_collection_error_clears_after_successful_fetch() {
+    let broker = MockBrokerBuilder::with_basic_data().build();
+    let mut app = App::new(broker);
+    app.collection_errors
+        .insert(Tab::Workers, "timed out after 50ms".to_string());
+
+    app.refresh_data().await.unwrap();
+
+    assert!(app.collection_errors.is_empty());
+    assert_eq!(app.workers.len(), 2);
+}
+
+#[test]
+fn test_apply_config_sets_operation_timeout() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut config = lazycelery::config::Config::default();
+    config.broker.operation_timeout = 750;
+
+    app.apply_config(&config);
+
+    assert_eq!(app.operation_timeout, Duration::from_millis(750));
+}
+
+#[test]
+fn test_apply_config_warns_about_unknown_task_columns() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut config = lazycelery::config::Config::default();
+    config.ui.task_columns = vec!["id".to_string(), "colour".to_string()];
+
+    app.apply_config(&config);
+
+    assert_eq!(app.task_columns, vec![lazycelery::config::TaskColumn::Id]);
+    assert_eq!(app.status_message, "Ignoring unknown task columns: colour");
+}
+
+#[test]
+fn test_apply_config_sets_timestamp_mode() {
+    use lazycelery::config::TimestampMode;
+
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut config = lazycelery::config::Config::default();
+    config.ui.timestamp_mode = TimestampMode::Absolute;
+
+    app.apply_config(&config);
+    assert_eq!(app.timestamp_mode, TimestampMode::Absolute);
+
+    app.toggle_timestamp_mode();
+    assert_eq!(app.timestamp_mode, TimestampMode::Relative);
+}
+
+#[test]
+fn test_tab_names_match_the_config() {
+    use lazycelery::config::TAB_NAMES;
+
+    assert_eq!(Tab::ALL.map(Tab::name), TAB_NAMES);
+    for name in TAB_NAMES {
+        assert!(Tab::from_name(name).is_some());
+    }
+}
+
+#[test]
+fn test_apply_config_selects_default_tab() {
+    use lazycelery::config::{CliOverrides, Config};
+
+    let mut config = Config::default();
+    config.ui.default_tab = "queues".to_string();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.apply_config(&config);
+    assert_eq!(app.selected_tab, Tab::Queues);
+
+    // --tab wins over the config file
+    config.apply_cli_overrides(&CliOverrides {
+        tab: Some("Scheduled".to_string()),
+        ..Default::default()
+    });
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.apply_config(&config);
+    assert_eq!(app.selected_tab, Tab::Scheduled);
+
+    // A name that slipped past validation starts on Workers
+    config.ui.default_tab = "flower".to_string();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.selected_tab = Tab::Tasks;
+    app.apply_config(&config);
+    assert_eq!(app.selected_tab, Tab::Workers);
+}
+
+#[test]
+fn test_task_relations() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut root = Task::new("root".to_string(), "tasks.start".to_string());
+    root.root_id = Some("root".to_string());
+    let mut child = Task::new("child".to_string(), "tasks.step".to_string());
+    child.root_id = Some("root".to_string());
+    child.parent_id = Some("root".to_string());
+    let mut sibling = Task::new("sibling".to_string(), "tasks.step".to_string());
+    sibling.root_id = Some("root".to_string());
+    sibling.parent_id = Some("root".to_string());
+    let unrelated = Task::new("other".to_string(), "tasks.other".to_string());
+    app.tasks = vec![root, child.clone(), sibling, unrelated.clone()];
+
+    let (parent, related) = app.task_relations(&child);
+    assert_eq!(parent.map(|p| p.id.as_str()), Some("root"));
+    let related_ids: Vec<&str> = related.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(related_ids, vec!["root", "sibling"]);
+
+    let (parent, related) = app.task_relations(&unrelated);
+    assert!(parent.is_none());
+    assert!(related.is_empty());
+}
+
+#[test]
+fn test_refresh_coalescing() {
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let now = std::time::Instant::now();
+
+    // Nothing to coalesce with before the first refresh
+    app.min_refresh_gap = Duration::from_millis(500);
+    assert!(!app.should_skip_refresh(now));
+
+    app.last_refresh = Some(now);
+    assert!(app.should_skip_refresh(now + Duration::from_millis(100)));
+    assert!(!app.should_skip_refresh(now + Duration::from_millis(500)));
+    assert!(!app.should_skip_refresh(now + Duration::from_secs(2)));
+
+    // A zero gap never skips
+    app.min_refresh_gap = Duration::ZERO;
+    assert!(!app.should_skip_refresh(now));
+}
+
+#[test]
+fn test_pause_skips_scheduled_refreshes() {
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use lazycelery::ui::events::handle_key_event;
+
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let now = std::time::Instant::now();
+    assert!(!app.should_skip_refresh(now));
+
+    let space = KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE);
+    handle_key_event(space, &mut app);
+    assert!(app.paused);
+    assert!(app.should_skip_refresh(now));
+    assert!(app.should_skip_refresh(now + Duration::from_secs(60)));
+
+    handle_key_event(space, &mut app);
+    assert!(!app.paused);
+    assert!(!app.should_skip_refresh(now));
+}
+
+#[tokio::test]
+async fn test_manual_refresh_runs_once_while_paused() {
+    use lazycelery::app::PaletteAction;
+
+    let broker = MockBrokerBuilder::with_basic_data().build();
+    let mut app = App::new(broker);
+    app.toggle_pause();
+    assert!(app.workers.is_empty());
+
+    app.run_palette_action(PaletteAction::Refresh);
+    assert!(app.refresh_requested);
+
+    // The main loop runs requested refreshes whether or not scheduled ones are paused
+    app.refresh_requested = false;
+    app.refresh_data().await.unwrap();
+    assert!(!app.workers.is_empty());
+
+    // The pause holds for the refreshes after it
+    assert!(app.paused);
+    assert!(app.should_skip_refresh(std::time::Instant::now() + Duration::from_secs(60)));
+}
+
+#[tokio::test]
+async fn test_refresh_records_completion_time() {
+    let broker = MockBrokerBuilder::new().build();
+    let mut app = App::new(broker);
+
+    app.refresh_data().await.unwrap();
+    let after_full = app.last_refresh.expect("full refresh should be recorded");
+
+    app.refresh_incremental().await.unwrap();
+    assert!(app.last_refresh.unwrap() >= after_full);
+}
+
+fn scheduled_entry(name: &str) -> ScheduledEntry {
+    ScheduledEntry {
+        name: name.to_string(),
+        task: format!("tasks.{name}"),
+        schedule: "every 5m".to_string(),
+        next_run: None,
+    }
+}
+
+#[tokio::test]
+async fn test_refresh_loads_scheduled_entries() {
+    let broker = MockBrokerBuilder::new()
+        .with_scheduled(vec![scheduled_entry("cleanup"), scheduled_entry("report")])
+        .build();
+    let mut app = App::new(broker);
+
+    app.refresh_data().await.unwrap();
+    assert_eq!(app.scheduled.len(), 2);
+
+    app.selected_tab = Tab::Scheduled;
+    app.select_next();
+    assert_eq!(app.selected_scheduled, 1);
+    app.select_next();
+    assert_eq!(app.selected_scheduled, 0);
+    app.select_previous();
+    assert_eq!(app.selected_scheduled, 1);
+}
@@ -0,0 +1,124 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use lazycelery::app::{filter_actions, fuzzy_score, App, PaletteAction, PendingAction, Tab};
+use lazycelery::ui::events::handle_key_event;
+
+mod test_broker_utils;
+use test_broker_utils::MockBrokerBuilder;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::empty())
+}
+
+fn type_query(app: &mut App, query: &str) {
+    for c in query.chars() {
+        handle_key_event(key(KeyCode::Char(c)), app);
+    }
+}
+
+#[test]
+fn test_fuzzy_score_requires_ordered_subsequence() {
+    assert!(fuzzy_score("rty", "Retry selected task").is_some());
+    assert!(fuzzy_score("RETRY", "Retry selected task").is_some());
+    assert!(fuzzy_score("ytr", "Retry selected task").is_none());
+    assert!(fuzzy_score("xyz", "Retry selected task").is_none());
+    assert_eq!(fuzzy_score("", "Quit"), Some(0));
+}
+
+#[test]
+fn test_fuzzy_score_prefers_contiguous_and_word_start_matches() {
+    let contiguous = fuzzy_score("que", "Go to queues").unwrap();
+    let scattered = fuzzy_score("que", "Toggle stuck tasks filter").unwrap_or(i64::MIN);
+    assert!(contiguous > scattered);
+
+    let word_start = fuzzy_score("rev", "Revoke selected task").unwrap();
+    let mid_word = fuzzy_score("rev", "Retry relative events").unwrap();
+    assert!(word_start > mid_word);
+}
+
+#[test]
+fn test_filter_actions_ranks_best_match_first() {
+    assert_eq!(filter_actions("").len(), PaletteAction::ALL.len());
+    assert_eq!(filter_actions("")[0], PaletteAction::ALL[0]);
+
+    assert_eq!(filter_actions("revoke")[0], PaletteAction::RevokeTask);
+    assert_eq!(filter_actions("purge")[0], PaletteAction::PurgeQueue);
+    assert_eq!(filter_actions("sched")[0], PaletteAction::GoToScheduled);
+    assert!(filter_actions("zzzz").is_empty());
+}
+
+#[test]
+fn test_colon_and_ctrl_p_open_the_palette() {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+
+    handle_key_event(key(KeyCode::Char(':')), &mut app);
+    assert!(app.show_palette);
+    handle_key_event(key(KeyCode::Esc), &mut app);
+    assert!(!app.show_palette);
+
+    handle_key_event(
+        KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+        &mut app,
+    );
+    assert!(app.show_palette);
+    assert!(!app.show_confirmation, "Ctrl-P must not start a purge");
+}
+
+#[test]
+fn test_palette_keys_edit_the_query_not_the_app() {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    handle_key_event(key(KeyCode::Char(':')), &mut app);
+
+    type_query(&mut app, "qx");
+    assert_eq!(app.palette_query, "qx");
+    assert!(!app.should_quit);
+
+    handle_key_event(key(KeyCode::Backspace), &mut app);
+    assert_eq!(app.palette_query, "q");
+}
+
+#[test]
+fn test_selecting_an_entry_dispatches_its_action() {
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.tasks = vec![lazycelery::models::Task::new(
+        "task-1".to_string(),
+        "tasks.add".to_string(),
+    )];
+
+    handle_key_event(key(KeyCode::Char(':')), &mut app);
+    type_query(&mut app, "go to tasks");
+    handle_key_event(key(KeyCode::Enter), &mut app);
+    assert!(!app.show_palette);
+    assert_eq!(app.selected_tab, Tab::Tasks);
+
+    handle_key_event(key(KeyCode::Char(':')), &mut app);
+    type_query(&mut app, "revoke");
+    handle_key_event(key(KeyCode::Enter), &mut app);
+    assert!(app.show_confirmation);
+    assert!(matches!(
+        app.pending_action,
+        Some(PendingAction::RevokeTask(ref id)) if id == "task-1"
+    ));
+}
+
+#[test]
+fn test_arrow_keys_move_the_palette_selection() {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    handle_key_event(key(KeyCode::Char(':')), &mut app);
+
+    handle_key_event(key(KeyCode::Down), &mut app);
+    assert_eq!(app.palette_selected, 1);
+    handle_key_event(key(KeyCode::Up), &mut app);
+    handle_key_event(key(KeyCode::Up), &mut app);
+    assert_eq!(app.palette_selected, PaletteAction::ALL.len() - 1);
+
+    // The last entry is quit
+    handle_key_event(key(KeyCode::Enter), &mut app);
+    assert!(app.should_quit);
+}
+
+#[test]
+fn test_refresh_action_requests_a_refresh() {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.run_palette_action(PaletteAction::Refresh);
+    assert!(app.refresh_requested);
+}
@@ -0,0 +1,145 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use lazycelery::app::{App, SettingField, StatusKind};
+use lazycelery::config::Config;
+use lazycelery::ui::events::handle_key_event;
+use lazycelery::ui::modals::draw_config_overlay;
+use lazycelery::ui::snapshot::buffer_to_string;
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+use std::time::Duration;
+use tempfile::tempdir;
+
+mod test_broker_utils;
+use test_broker_utils::MockBrokerBuilder;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::empty())
+}
+
+fn configured_app() -> App {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.apply_config(&Config::default());
+    app
+}
+
+/// Open the overlay on `field` and replace its value with `value`
+fn edit_setting(app: &mut App, field: SettingField, value: &str) {
+    if !app.show_config {
+        handle_key_event(key(KeyCode::Char('c')), app);
+    }
+    while app.selected_setting() != field {
+        handle_key_event(key(KeyCode::Down), app);
+    }
+    handle_key_event(key(KeyCode::Enter), app);
+    let current = app.config_input.clone().unwrap_or_default();
+    for _ in current.chars() {
+        handle_key_event(key(KeyCode::Backspace), app);
+    }
+    for c in value.chars() {
+        handle_key_event(key(KeyCode::Char(c)), app);
+    }
+    handle_key_event(key(KeyCode::Enter), app);
+}
+
+#[test]
+fn test_new_refresh_interval_applies_live() {
+    let mut app = configured_app();
+    assert_eq!(app.refresh_interval, Duration::from_millis(1000));
+
+    edit_setting(&mut app, SettingField::RefreshInterval, "2500");
+    assert!(!app.is_editing_config());
+    assert!(app.show_config);
+    assert_eq!(app.refresh_interval, Duration::from_millis(2500));
+    assert_eq!(app.config.ui.refresh_interval, 2500);
+    assert!(app.refresh_interval_changed);
+    assert_eq!(app.status_message, "Set ui.refresh_interval to 2500");
+
+    // Backing off after failures starts from the new interval
+    assert_eq!(app.next_refresh_interval(), Duration::from_millis(2500));
+}
+
+#[test]
+fn test_out_of_range_refresh_interval_is_rejected() {
+    let mut app = configured_app();
+
+    edit_setting(&mut app, SettingField::RefreshInterval, "50");
+    assert_eq!(app.status_kind, StatusKind::UserError);
+    assert!(app.status_message.contains(
+        "Invalid ui.refresh_interval: ui.refresh_interval: 50ms is out of range (must be between 100 and 60000 ms)"
+    ));
+    // The value stays in the input to be corrected
+    assert_eq!(app.config_input.as_deref(), Some("50"));
+    assert_eq!(app.refresh_interval, Duration::from_millis(1000));
+    assert_eq!(app.config.ui.refresh_interval, 1000);
+    assert!(!app.refresh_interval_changed);
+
+    handle_key_event(key(KeyCode::Esc), &mut app);
+    assert!(!app.is_editing_config());
+    edit_setting(&mut app, SettingField::RefreshInterval, "soon");
+    assert!(app
+        .status_message
+        .contains("'soon' is not a number of milliseconds"));
+    assert_eq!(app.refresh_interval, Duration::from_millis(1000));
+}
+
+#[test]
+fn test_other_keys_close_the_overlay() {
+    let mut app = configured_app();
+    handle_key_event(key(KeyCode::Char('c')), &mut app);
+    assert!(app.show_config);
+
+    // Any other key closes the overlay
+    handle_key_event(key(KeyCode::Char('x')), &mut app);
+    assert!(!app.show_config);
+}
+
+#[test]
+fn test_saving_settings_keeps_the_rest_of_the_file() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    let mut file_config = Config::default();
+    file_config.broker.url = "redis://cache:6379/3".to_string();
+    let mut contents = toml::to_string_pretty(&file_config).unwrap();
+    contents.push_str("\n[plugins]\nenabled = [\"audit\"]\n");
+    std::fs::write(&path, contents).unwrap();
+
+    // The running config carries a CLI override that must not be saved
+    let mut running = file_config.clone();
+    running.broker.url = "redis://override:6379/0".to_string();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.apply_config(&running);
+    app.config_path = Some(path.clone());
+
+    edit_setting(&mut app, SettingField::RefreshInterval, "750");
+    handle_key_event(key(KeyCode::Char('w')), &mut app);
+    assert!(app.status_message.starts_with("Saved settings to"));
+
+    // Keys lazycelery doesn't know are kept
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("[plugins]"));
+    assert!(contents.contains(r#"enabled = ["audit"]"#));
+
+    let saved = Config::from_file(path).unwrap();
+    assert_eq!(saved.ui.refresh_interval, 750);
+    assert_eq!(saved.ui.theme, "dark");
+    assert_eq!(saved.broker.url, "redis://cache:6379/3");
+}
+
+#[test]
+fn test_config_overlay_shows_values_without_credentials() {
+    let mut config = Config::default();
+    config.broker.url = "redis://:hunter2@cache:6379/2".to_string();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.apply_config(&config);
+    app.open_config_overlay();
+    app.start_config_edit();
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| draw_config_overlay(f, &app)).unwrap();
+    let text = buffer_to_string(terminal.backend().buffer());
+    assert!(text.contains("ui.refresh_interval"));
+    assert!(text.contains("1000_"));
+    assert!(text.contains("redis://cache:6379/2"));
+    assert!(!text.contains("hunter2"));
+    assert!(text.contains("[Enter] Apply"));
+}
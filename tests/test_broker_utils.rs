@@ -6,9 +6,17 @@
 
 use async_trait::async_trait;
 use chrono::Utc;
-use lazycelery::broker::Broker;
+use lazycelery::broker::{Broker, BrokerCapabilities, ScanCaps};
 use lazycelery::error::BrokerError;
-use lazycelery::models::{Queue, Task, TaskStatus, Worker, WorkerStatus};
+use lazycelery::models::{Queue, ScheduledEntry, Task, TaskStatus, Worker, WorkerStatus};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Answers a broker call that takes a task id in place of the canned behavior
+type TaskIdHook<T> = Arc<dyn Fn(&str) -> Result<T, BrokerError> + Send + Sync>;
+
+/// Answers the task listing in place of the configured tasks
+type TasksHook = Arc<dyn Fn() -> Result<Vec<Task>, BrokerError> + Send + Sync>;
 
 /// Builder for configurable mock broker instances
 #[derive(Default)]
@@ -16,8 +24,17 @@ pub struct MockBrokerBuilder {
     workers: Vec<Worker>,
     tasks: Vec<Task>,
     queues: Vec<Queue>,
+    scheduled: Vec<ScheduledEntry>,
+    scan_caps: ScanCaps,
+    capabilities: BrokerCapabilities,
     should_fail_operations: bool,
     should_return_not_implemented: bool,
+    workers_delay: Option<Duration>,
+    tasks_hook: Option<TasksHook>,
+    get_task_hook: Option<TaskIdHook<Option<Task>>>,
+    raw_metadata_hook: Option<TaskIdHook<Option<String>>>,
+    retry_hook: Option<TaskIdHook<()>>,
+    revoke_hook: Option<TaskIdHook<()>>,
 }
 
 impl MockBrokerBuilder {
@@ -39,8 +56,10 @@ impl MockBrokerBuilder {
                     hostname: "test-worker-1".to_string(),
                     status: WorkerStatus::Online,
                     concurrency: 4,
+                    pool_type: None,
                     queues: vec!["default".to_string()],
                     active_tasks: vec!["task-1".to_string()],
+                    reserved_tasks: vec![],
                     processed: 100,
                     failed: 5,
                 },
@@ -48,8 +67,10 @@ impl MockBrokerBuilder {
                     hostname: "test-worker-2".to_string(),
                     status: WorkerStatus::Offline,
                     concurrency: 2,
+                    pool_type: None,
                     queues: vec!["priority".to_string()],
                     active_tasks: vec![],
+                    reserved_tasks: vec![],
                     processed: 50,
                     failed: 2,
                 },
@@ -65,6 +86,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now(),
                     result: None,
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
                 Task {
                     id: "task-2".to_string(),
@@ -76,6 +113,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now() - chrono::Duration::minutes(5),
                     result: Some(r#"{"result": "success"}"#.to_string()),
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
             ])
             .with_queues(vec![
@@ -83,11 +136,15 @@ impl MockBrokerBuilder {
                     name: "default".to_string(),
                     length: 10,
                     consumers: 2,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
                 Queue {
                     name: "priority".to_string(),
                     length: 5,
                     consumers: 1,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
             ])
     }
@@ -100,12 +157,14 @@ impl MockBrokerBuilder {
                     hostname: "celery@worker-prod-1".to_string(),
                     status: WorkerStatus::Online,
                     concurrency: 8,
+                    pool_type: None,
                     queues: vec![
                         "default".to_string(),
                         "priority".to_string(),
                         "emails".to_string(),
                     ],
                     active_tasks: vec!["task-001".to_string(), "task-002".to_string()],
+                    reserved_tasks: vec![],
                     processed: 15234,
                     failed: 23,
                 },
@@ -113,8 +172,10 @@ impl MockBrokerBuilder {
                     hostname: "celery@worker-prod-2".to_string(),
                     status: WorkerStatus::Online,
                     concurrency: 8,
+                    pool_type: None,
                     queues: vec!["default".to_string(), "priority".to_string()],
                     active_tasks: vec![],
+                    reserved_tasks: vec![],
                     processed: 14892,
                     failed: 19,
                 },
@@ -122,8 +183,10 @@ impl MockBrokerBuilder {
                     hostname: "celery@worker-prod-3".to_string(),
                     status: WorkerStatus::Offline,
                     concurrency: 4,
+                    pool_type: None,
                     queues: vec!["background".to_string()],
                     active_tasks: vec![],
+                    reserved_tasks: vec![],
                     processed: 8923,
                     failed: 5,
                 },
@@ -139,6 +202,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now() - chrono::Duration::minutes(2),
                     result: None,
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
                 Task {
                     id: "task-002".to_string(),
@@ -150,6 +229,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now() - chrono::Duration::seconds(30),
                     result: None,
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
                 Task {
                     id: "task-003".to_string(),
@@ -161,6 +256,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now() - chrono::Duration::hours(1),
                     result: Some(r#"{"status": "completed", "rows": 1523}"#.to_string()),
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
                 Task {
                     id: "task-004".to_string(),
@@ -172,6 +283,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now() - chrono::Duration::minutes(15),
                     result: None,
                     traceback: Some("Traceback (most recent call last):\n  File \"tasks.py\", line 45\n    ConnectionError: Database timeout".to_string()),
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
                 Task {
                     id: "task-005".to_string(),
@@ -183,6 +310,22 @@ impl MockBrokerBuilder {
                     timestamp: Utc::now(),
                     result: None,
                     traceback: None,
+                    result_full_len: None,
+                    traceback_full_len: None,
+                    args_full_len: None,
+                    kwargs_full_len: None,
+                    eta: None,
+                    root_id: None,
+                    parent_id: None,
+                    group_id: None,
+                    queue: None,
+                    retries: 0,
+                    max_retries: None,
+                    priority: None,
+                    children: Vec::new(),
+                    duration: None,
+                    result_kind: None,
+                    looks_like_error: false,
                 },
             ])
             .with_queues(vec![
@@ -190,21 +333,29 @@ impl MockBrokerBuilder {
                     name: "default".to_string(),
                     length: 42,
                     consumers: 3,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
                 Queue {
                     name: "priority".to_string(),
                     length: 8,
                     consumers: 2,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
                 Queue {
                     name: "emails".to_string(),
                     length: 15,
                     consumers: 1,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
                 Queue {
                     name: "background".to_string(),
                     length: 0,
                     consumers: 0,
+                    oldest_message_age: None,
+                    discrepancy: None,
                 },
             ])
     }
@@ -227,6 +378,24 @@ impl MockBrokerBuilder {
         self
     }
 
+    /// Add beat schedule entries to the broker
+    pub fn with_scheduled(mut self, scheduled: Vec<ScheduledEntry>) -> Self {
+        self.scheduled = scheduled;
+        self
+    }
+
+    /// Report `scan_caps` as hit by the latest fetches
+    pub fn with_scan_caps(mut self, scan_caps: ScanCaps) -> Self {
+        self.scan_caps = scan_caps;
+        self
+    }
+
+    /// Report only `capabilities` as supported
+    pub fn with_capabilities(mut self, capabilities: BrokerCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
     /// Configure broker to fail all operations (for error testing)
     pub fn with_failing_operations(mut self) -> Self {
         self.should_fail_operations = true;
@@ -239,25 +408,75 @@ impl MockBrokerBuilder {
         self
     }
 
+    /// Configure `get_workers` to take `delay` before answering (for timeout testing)
+    pub fn with_slow_workers(mut self, delay: Duration) -> Self {
+        self.workers_delay = Some(delay);
+        self
+    }
+
+    /// Answer `get_tasks` with `hook`, e.g. to list different tasks on
+    /// every refresh
+    pub fn with_tasks_hook(
+        mut self,
+        hook: impl Fn() -> Result<Vec<Task>, BrokerError> + Send + Sync + 'static,
+    ) -> Self {
+        self.tasks_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Answer `get_task` with `hook` instead of searching the listed tasks
+    pub fn with_get_task_hook(
+        mut self,
+        hook: impl Fn(&str) -> Result<Option<Task>, BrokerError> + Send + Sync + 'static,
+    ) -> Self {
+        self.get_task_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Answer `get_raw_task_metadata` with `hook`
+    pub fn with_raw_metadata_hook(
+        mut self,
+        hook: impl Fn(&str) -> Result<Option<String>, BrokerError> + Send + Sync + 'static,
+    ) -> Self {
+        self.raw_metadata_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Answer `retry_task` with `hook`
+    pub fn with_retry_hook(
+        mut self,
+        hook: impl Fn(&str) -> Result<(), BrokerError> + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Answer `revoke_task` with `hook`, e.g. to record the revoked ids
+    pub fn with_revoke_hook(
+        mut self,
+        hook: impl Fn(&str) -> Result<(), BrokerError> + Send + Sync + 'static,
+    ) -> Self {
+        self.revoke_hook = Some(Arc::new(hook));
+        self
+    }
+
     /// Build the configured mock broker
     pub fn build(self) -> Box<dyn Broker> {
-        Box::new(MockBroker {
-            workers: self.workers,
-            tasks: self.tasks,
-            queues: self.queues,
-            should_fail_operations: self.should_fail_operations,
-            should_return_not_implemented: self.should_return_not_implemented,
-        })
+        Box::new(MockBroker { config: self })
     }
 }
 
 /// Mock broker implementation with configurable behavior
 struct MockBroker {
-    workers: Vec<Worker>,
-    tasks: Vec<Task>,
-    queues: Vec<Queue>,
-    should_fail_operations: bool,
-    should_return_not_implemented: bool,
+    config: MockBrokerBuilder,
+}
+
+impl std::ops::Deref for MockBroker {
+    type Target = MockBrokerBuilder;
+
+    fn deref(&self) -> &Self::Target {
+        &self.config
+    }
 }
 
 #[async_trait]
@@ -265,15 +484,14 @@ impl Broker for MockBroker {
     async fn connect(_url: &str) -> Result<Self, BrokerError> {
         // This should not be called directly - use MockBrokerBuilder instead
         Ok(MockBroker {
-            workers: vec![],
-            tasks: vec![],
-            queues: vec![],
-            should_fail_operations: false,
-            should_return_not_implemented: false,
+            config: MockBrokerBuilder::new(),
         })
     }
 
     async fn get_workers(&self) -> Result<Vec<Worker>, BrokerError> {
+        if let Some(delay) = self.workers_delay {
+            tokio::time::sleep(delay).await;
+        }
         if self.should_fail_operations {
             return Err(BrokerError::ConnectionError(
                 "Simulated failure".to_string(),
@@ -283,6 +501,9 @@ impl Broker for MockBroker {
     }
 
     async fn get_tasks(&self) -> Result<Vec<Task>, BrokerError> {
+        if let Some(hook) = &self.tasks_hook {
+            return hook();
+        }
         if self.should_fail_operations {
             return Err(BrokerError::ConnectionError(
                 "Simulated failure".to_string(),
@@ -300,7 +521,42 @@ impl Broker for MockBroker {
         Ok(self.queues.clone())
     }
 
-    async fn retry_task(&self, _task_id: &str) -> Result<(), BrokerError> {
+    async fn get_scheduled(&self) -> Result<Vec<ScheduledEntry>, BrokerError> {
+        if self.should_fail_operations {
+            return Err(BrokerError::ConnectionError(
+                "Simulated failure".to_string(),
+            ));
+        }
+        Ok(self.scheduled.clone())
+    }
+
+    fn scan_caps(&self) -> ScanCaps {
+        self.scan_caps
+    }
+
+    fn capabilities(&self) -> BrokerCapabilities {
+        self.capabilities
+    }
+
+    async fn get_task(&self, task_id: &str) -> Result<Option<Task>, BrokerError> {
+        if let Some(hook) = &self.get_task_hook {
+            return hook(task_id);
+        }
+        let tasks = self.get_tasks().await?;
+        Ok(tasks.into_iter().find(|task| task.id == task_id))
+    }
+
+    async fn get_raw_task_metadata(&self, task_id: &str) -> Result<Option<String>, BrokerError> {
+        match &self.raw_metadata_hook {
+            Some(hook) => hook(task_id),
+            None => Err(BrokerError::NotImplemented),
+        }
+    }
+
+    async fn retry_task(&self, task_id: &str) -> Result<(), BrokerError> {
+        if let Some(hook) = &self.retry_hook {
+            return hook(task_id);
+        }
         if self.should_fail_operations {
             return Err(BrokerError::OperationError("Retry failed".to_string()));
         }
@@ -310,7 +566,10 @@ impl Broker for MockBroker {
         Ok(())
     }
 
-    async fn revoke_task(&self, _task_id: &str) -> Result<(), BrokerError> {
+    async fn revoke_task(&self, task_id: &str) -> Result<(), BrokerError> {
+        if let Some(hook) = &self.revoke_hook {
+            return hook(task_id);
+        }
         if self.should_fail_operations {
             return Err(BrokerError::OperationError("Revoke failed".to_string()));
         }
@@ -330,6 +589,62 @@ impl Broker for MockBroker {
         // Return simulated purge count
         Ok(42)
     }
+
+    async fn replay_task(
+        &self,
+        task_name: &str,
+        _queue: &str,
+        _args: &str,
+        _kwargs: &str,
+    ) -> Result<String, BrokerError> {
+        if self.should_fail_operations {
+            return Err(BrokerError::OperationError("Replay failed".to_string()));
+        }
+        if self.should_return_not_implemented {
+            return Err(BrokerError::NotImplemented);
+        }
+        Ok(format!("replay-of-{task_name}"))
+    }
+
+    async fn move_task(
+        &self,
+        _task_id: &str,
+        _from_queue: &str,
+        _to_queue: &str,
+    ) -> Result<(), BrokerError> {
+        if self.should_fail_operations {
+            return Err(BrokerError::OperationError("Move failed".to_string()));
+        }
+        if self.should_return_not_implemented {
+            return Err(BrokerError::NotImplemented);
+        }
+        Ok(())
+    }
+
+    async fn set_rate_limit(&self, _task_name: &str, rate: &str) -> Result<(), BrokerError> {
+        if self.should_fail_operations {
+            return Err(BrokerError::OperationError("Rate limit failed".to_string()));
+        }
+        if self.should_return_not_implemented {
+            return Err(BrokerError::NotImplemented);
+        }
+        lazycelery::broker::validate_rate_limit(rate)
+    }
+
+    async fn peek_queue(&self, queue: &str, n: usize) -> Result<Vec<Task>, BrokerError> {
+        if self.should_fail_operations {
+            return Err(BrokerError::OperationError("Peek failed".to_string()));
+        }
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|task| {
+                task.status == TaskStatus::Pending && task.queue.as_deref() == Some(queue)
+            })
+            .take(n)
+            .cloned()
+            .collect())
+    }
 }
 
 /// Helper functions for common test scenarios
@@ -358,6 +673,7 @@ impl MockBrokerBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lazycelery::broker::{BrokerOperation, ScanCap};
 
     #[tokio::test]
     async fn test_empty_broker() {
@@ -424,6 +740,65 @@ mod tests {
         assert!(matches!(result, Err(BrokerError::OperationError(_))));
     }
 
+    #[tokio::test]
+    async fn test_slow_workers() {
+        let broker = MockBrokerBuilder::with_basic_data()
+            .with_slow_workers(Duration::from_millis(20))
+            .build();
+
+        let start = std::time::Instant::now();
+        let workers = broker.get_workers().await.unwrap();
+        assert_eq!(workers.len(), 2);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled() {
+        let broker = MockBrokerBuilder::empty()
+            .with_scheduled(vec![ScheduledEntry {
+                name: "cleanup".to_string(),
+                task: "tasks.cleanup".to_string(),
+                schedule: "every 1h".to_string(),
+                next_run: None,
+            }])
+            .build();
+
+        let scheduled = broker.get_scheduled().await.unwrap();
+        assert_eq!(scheduled.len(), 1);
+        assert_eq!(scheduled[0].name, "cleanup");
+    }
+
+    #[test]
+    fn test_scan_caps() {
+        let caps = ScanCaps {
+            tasks: ScanCap::check(250, 100),
+            workers: None,
+            unreadable_tasks: 3,
+        };
+        let broker = MockBrokerBuilder::empty().with_scan_caps(caps).build();
+        assert_eq!(broker.scan_caps(), caps);
+    }
+
+    #[test]
+    fn test_capabilities() {
+        assert_eq!(
+            MockBrokerBuilder::empty().build().capabilities(),
+            BrokerCapabilities::ALL
+        );
+
+        let capabilities = BrokerCapabilities {
+            peek: false,
+            ..BrokerCapabilities::ALL
+        };
+        let broker = MockBrokerBuilder::empty()
+            .with_capabilities(capabilities)
+            .build();
+        assert_eq!(broker.capabilities(), capabilities);
+        assert!(!broker.capabilities().supports(BrokerOperation::Peek));
+        assert!(broker.capabilities().supports(BrokerOperation::Retry));
+        assert!(broker.capabilities().supports(BrokerOperation::Replay));
+    }
+
     #[tokio::test]
     async fn test_convenience_constructors() {
         let ui_broker = MockBrokerBuilder::for_ui_tests();
@@ -447,4 +822,45 @@ mod tests {
         let result = error_broker.retry_task("test").await;
         assert!(matches!(result, Err(BrokerError::OperationError(_))));
     }
+
+    #[tokio::test]
+    async fn test_hooks_replace_canned_answers() {
+        let broker = MockBrokerBuilder::with_basic_data()
+            .with_failing_operations()
+            .with_tasks_hook(|| Ok(vec![Task::new("hooked".to_string(), "t".to_string())]))
+            .with_raw_metadata_hook(|task_id| Ok(Some(format!("{{\"task_id\": \"{task_id}\"}}"))))
+            .with_retry_hook(|task_id| Err(BrokerError::ValidationError(task_id.to_string())))
+            .with_revoke_hook(|_| Ok(()))
+            .build();
+
+        // Hooks win over failing operations, the rest still fail
+        assert_eq!(broker.get_tasks().await.unwrap()[0].id, "hooked");
+        assert_eq!(
+            broker.get_task("hooked").await.unwrap().unwrap().id,
+            "hooked"
+        );
+        assert_eq!(
+            broker
+                .get_raw_task_metadata("t-1")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"task_id": "t-1"}"#)
+        );
+        assert!(matches!(
+            broker.retry_task("t-1").await,
+            Err(BrokerError::ValidationError(id)) if id == "t-1"
+        ));
+        assert!(broker.revoke_task("t-1").await.is_ok());
+        assert!(broker.get_workers().await.is_err());
+
+        let broker = MockBrokerBuilder::empty()
+            .with_get_task_hook(|task_id| Ok(Some(Task::new(task_id.to_string(), "t".to_string()))))
+            .build();
+        assert_eq!(
+            broker.get_task("only-hooked").await.unwrap().unwrap().id,
+            "only-hooked"
+        );
+        assert!(broker.get_tasks().await.unwrap().is_empty());
+    }
 }
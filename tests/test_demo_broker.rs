@@ -0,0 +1,55 @@
+use lazycelery::app::App;
+use lazycelery::broker::demo::DemoBroker;
+use lazycelery::broker::Broker;
+use lazycelery::models::TaskStatus;
+
+#[tokio::test]
+async fn test_demo_broker_data_changes_between_refreshes() {
+    let mut app = App::new(Box::new(DemoBroker::new()));
+
+    app.refresh_data().await.unwrap();
+    assert!(!app.workers.is_empty());
+    assert!(!app.tasks.is_empty());
+    assert!(!app.queues.is_empty());
+    assert!(!app.scheduled.is_empty());
+    let first_tasks: Vec<(String, TaskStatus)> = app
+        .tasks
+        .iter()
+        .map(|t| (t.id.clone(), t.status.clone()))
+        .collect();
+    let first_queues: Vec<u64> = app.queues.iter().map(|q| q.length).collect();
+
+    app.refresh_data().await.unwrap();
+    let second_tasks: Vec<(String, TaskStatus)> = app
+        .tasks
+        .iter()
+        .map(|t| (t.id.clone(), t.status.clone()))
+        .collect();
+    let second_queues: Vec<u64> = app.queues.iter().map(|q| q.length).collect();
+
+    assert_ne!(first_tasks, second_tasks);
+    assert_ne!(first_queues, second_queues);
+}
+
+#[tokio::test]
+async fn test_demo_broker_actions_update_its_data() {
+    let broker = DemoBroker::new();
+    let pending = broker
+        .get_tasks()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|t| t.status == TaskStatus::Pending)
+        .expect("the demo starts with waiting tasks");
+    let queue = pending.queue.clone().unwrap();
+
+    let peeked = broker.peek_queue(&queue, 100).await.unwrap();
+    assert!(peeked.iter().any(|t| t.id == pending.id));
+
+    broker.revoke_task(&pending.id).await.unwrap();
+    let revoked = broker.get_task(&pending.id).await.unwrap().unwrap();
+    assert_eq!(revoked.status, TaskStatus::Revoked);
+
+    assert!(broker.purge_queue(&queue).await.unwrap() > 0);
+    assert!(broker.peek_queue(&queue, 100).await.unwrap().is_empty());
+}
@@ -1,4 +1,4 @@
-use lazycelery::error::{AppError, BrokerError};
+use lazycelery::error::{AppError, BrokerError, ErrorCategory};
 
 #[test]
 fn test_broker_error_display() {
@@ -44,3 +44,49 @@ fn test_app_error_display() {
     let app_err = AppError::Config("Invalid config".to_string());
     assert_eq!(app_err.to_string(), "Configuration error: Invalid config");
 }
+
+#[test]
+fn test_broker_error_categories() {
+    let cases = [
+        (
+            BrokerError::ConnectionError("refused".to_string()),
+            ErrorCategory::Transient,
+        ),
+        (BrokerError::AuthError, ErrorCategory::Fatal),
+        (
+            BrokerError::OperationError("busy".to_string()),
+            ErrorCategory::Transient,
+        ),
+        (
+            BrokerError::InvalidUrl("bad url".to_string()),
+            ErrorCategory::User,
+        ),
+        (
+            BrokerError::ValidationError("empty task id".to_string()),
+            ErrorCategory::User,
+        ),
+        (BrokerError::Timeout, ErrorCategory::Transient),
+        (BrokerError::NotImplemented, ErrorCategory::User),
+    ];
+
+    for (error, category) in cases {
+        assert_eq!(error.category(), category, "{error:?}");
+        assert_eq!(error.is_user_error(), category == ErrorCategory::User);
+
+        // Converting keeps the category for the refresh loop to act on
+        let app_err: AppError = error.into();
+        assert_eq!(app_err.category(), category);
+    }
+}
+
+#[test]
+fn test_app_error_categories() {
+    assert_eq!(
+        AppError::Ui("terminal gone".to_string()).category(),
+        ErrorCategory::Fatal
+    );
+    assert_eq!(
+        AppError::Config("bad refresh interval".to_string()).category(),
+        ErrorCategory::User
+    );
+}
@@ -1,8 +1,10 @@
 use chrono::Utc;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use lazycelery::app::{App, Tab};
+use lazycelery::broker::BrokerCapabilities;
+use lazycelery::config::{Config, TimestampMode};
 use lazycelery::models::{Task, TaskStatus, Worker, WorkerStatus};
-use lazycelery::ui::events::{handle_key_event, AppEvent};
+use lazycelery::ui::events::{confirms_pending_action, handle_key_event, AppEvent};
 
 mod test_broker_utils;
 use test_broker_utils::MockBrokerBuilder;
@@ -21,8 +23,10 @@ fn create_test_app() -> App {
             hostname: "worker-1".to_string(),
             status: WorkerStatus::Online,
             concurrency: 4,
+            pool_type: None,
             queues: vec!["default".to_string()],
             active_tasks: vec![],
+            reserved_tasks: vec![],
             processed: 100,
             failed: 5,
         },
@@ -30,8 +34,10 @@ fn create_test_app() -> App {
             hostname: "worker-2".to_string(),
             status: WorkerStatus::Offline,
             concurrency: 8,
+            pool_type: None,
             queues: vec!["celery".to_string()],
             active_tasks: vec![],
+            reserved_tasks: vec![],
             processed: 250,
             failed: 12,
         },
@@ -48,6 +54,22 @@ fn create_test_app() -> App {
             timestamp: Utc::now(),
             result: None,
             traceback: None,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+            result_kind: None,
+            looks_like_error: false,
         },
         Task {
             id: "task-2".to_string(),
@@ -59,6 +81,22 @@ fn create_test_app() -> App {
             timestamp: Utc::now(),
             result: None,
             traceback: None,
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+            result_kind: None,
+            looks_like_error: false,
         },
     ];
 
@@ -67,11 +105,15 @@ fn create_test_app() -> App {
             name: "default".to_string(),
             length: 10,
             consumers: 2,
+            oldest_message_age: None,
+            discrepancy: None,
         },
         lazycelery::models::Queue {
             name: "priority".to_string(),
             length: 5,
             consumers: 1,
+            oldest_message_age: None,
+            discrepancy: None,
         },
     ];
 
@@ -91,6 +133,144 @@ fn test_quit_key_handling() {
     assert!(app.should_quit);
 }
 
+#[test]
+fn test_t_toggles_timestamp_mode() {
+    let mut app = create_test_app();
+    assert_eq!(app.timestamp_mode, TimestampMode::Relative);
+
+    handle_key_event(create_key_event(KeyCode::Char('t')), &mut app);
+    assert_eq!(app.timestamp_mode, TimestampMode::Absolute);
+
+    handle_key_event(create_key_event(KeyCode::Char('t')), &mut app);
+    assert_eq!(app.timestamp_mode, TimestampMode::Relative);
+}
+
+#[test]
+fn test_escape_dismisses_status_message() {
+    let mut app = create_test_app();
+    app.set_status_message("Purged 42 messages from queue 'default'".to_string());
+
+    handle_key_event(create_key_event(KeyCode::Esc), &mut app);
+
+    assert!(app.status_message.is_empty());
+    assert!(!app.should_quit);
+}
+
+#[test]
+fn test_ctrl_c_quits_without_confirmation() {
+    let mut app = create_test_app();
+    assert!(!app.confirm_quit);
+
+    handle_key_event(
+        KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        &mut app,
+    );
+    assert!(app.should_quit);
+}
+
+#[test]
+fn test_rebound_quit_key() {
+    let config: Config = toml::from_str(
+        r#"
+[broker]
+url = "redis://localhost:6379/0"
+timeout = 30
+retry_attempts = 3
+
+[ui]
+refresh_interval = 1000
+theme = "dark"
+
+[keys]
+quit = "Ctrl-c"
+"#,
+    )
+    .unwrap();
+    let mut app = create_test_app();
+    app.apply_config(&config);
+
+    handle_key_event(create_key_event(KeyCode::Char('q')), &mut app);
+    assert!(!app.should_quit);
+
+    handle_key_event(
+        KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        &mut app,
+    );
+    assert!(app.should_quit);
+}
+
+#[test]
+fn test_unsupported_action_is_suppressed() {
+    let broker = MockBrokerBuilder::new()
+        .with_capabilities(BrokerCapabilities {
+            retry: false,
+            ..BrokerCapabilities::ALL
+        })
+        .build();
+    let mut app = App::new(broker);
+    app.tasks = create_test_app().tasks;
+    app.selected_tab = Tab::Tasks;
+
+    handle_key_event(create_key_event(KeyCode::Char('r')), &mut app);
+    assert!(!app.show_confirmation);
+    assert!(app.pending_action.is_none());
+    assert_eq!(
+        app.status_message,
+        "Retrying tasks is not supported by this broker"
+    );
+
+    // Supported actions still ask for confirmation
+    handle_key_event(create_key_event(KeyCode::Char('x')), &mut app);
+    assert!(app.show_confirmation);
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        handle_key_event(create_key_event(KeyCode::Char(c)), app);
+    }
+}
+
+#[test]
+fn test_mismatched_queue_name_aborts_purge() {
+    let mut app = create_test_app();
+    app.purge_double_confirm_above = 5;
+    app.selected_tab = Tab::Queues;
+
+    handle_key_event(create_key_event(KeyCode::Char('p')), &mut app);
+    assert!(app.is_typing_confirmation());
+
+    // `y` is just another typed character
+    let y = create_key_event(KeyCode::Char('y'));
+    assert!(!confirms_pending_action(&y, &app));
+    type_text(&mut app, "defauly");
+    assert_eq!(app.confirmation_input, "defauly");
+    assert!(app.show_confirmation);
+
+    let enter = create_key_event(KeyCode::Enter);
+    assert!(!confirms_pending_action(&enter, &app));
+    handle_key_event(enter, &mut app);
+    assert!(!app.show_confirmation);
+    assert!(app.pending_action.is_none());
+    assert!(app.status_message.contains("doesn't match 'default'"));
+}
+
+#[test]
+fn test_typed_queue_name_confirms_purge() {
+    let mut app = create_test_app();
+    app.purge_double_confirm_above = 5;
+    app.selected_tab = Tab::Queues;
+
+    handle_key_event(create_key_event(KeyCode::Char('p')), &mut app);
+    type_text(&mut app, "defaulx");
+    handle_key_event(create_key_event(KeyCode::Backspace), &mut app);
+    type_text(&mut app, "t");
+
+    let enter = create_key_event(KeyCode::Enter);
+    assert!(confirms_pending_action(&enter, &app));
+    handle_key_event(enter, &mut app);
+    assert!(app.pending_action.is_some());
+}
+
 #[test]
 fn test_help_toggle() {
     let mut app = create_test_app();
@@ -117,10 +297,22 @@ fn test_tab_navigation() {
     handle_key_event(create_key_event(KeyCode::Tab), &mut app);
     assert_eq!(app.selected_tab, Tab::Tasks);
 
+    handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+    assert_eq!(app.selected_tab, Tab::Scheduled);
+
+    handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+    assert_eq!(app.selected_tab, Tab::Overview);
+
     handle_key_event(create_key_event(KeyCode::Tab), &mut app);
     assert_eq!(app.selected_tab, Tab::Workers); // Wrap around
 
     // Backward tab navigation
+    handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
+    assert_eq!(app.selected_tab, Tab::Overview);
+
+    handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
+    assert_eq!(app.selected_tab, Tab::Scheduled);
+
     handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
     assert_eq!(app.selected_tab, Tab::Tasks);
 
@@ -178,6 +370,88 @@ fn test_item_navigation_queues_tab() {
     assert_eq!(app.selected_queue, 0);
 }
 
+/// Presses Down twice and Up three times from the top of the current tab,
+/// returning the selection after each press
+fn walk_list_boundaries(app: &mut App, selected: fn(&App) -> usize) -> Vec<usize> {
+    let mut seen = Vec::new();
+    for code in [
+        KeyCode::Down,
+        KeyCode::Down,
+        KeyCode::Up,
+        KeyCode::Up,
+        KeyCode::Up,
+    ] {
+        handle_key_event(create_key_event(code), app);
+        seen.push(selected(app));
+    }
+    seen
+}
+
+#[test]
+fn test_navigation_wraps_at_list_boundaries_by_default() {
+    let mut app = create_test_app();
+    app.apply_config(&Config::default());
+    assert!(app.wrap_navigation);
+
+    app.selected_tab = Tab::Workers;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_worker),
+        vec![1, 0, 1, 0, 1]
+    );
+
+    app.selected_tab = Tab::Tasks;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_task),
+        vec![1, 0, 1, 0, 1]
+    );
+
+    app.selected_tab = Tab::Queues;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_queue),
+        vec![1, 0, 1, 0, 1]
+    );
+}
+
+#[test]
+fn test_navigation_clamps_at_list_boundaries_without_wrap() {
+    let mut config = Config::default();
+    config.ui.wrap_navigation = false;
+    let mut app = create_test_app();
+    app.apply_config(&config);
+
+    app.selected_tab = Tab::Workers;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_worker),
+        vec![1, 1, 0, 0, 0]
+    );
+
+    app.selected_tab = Tab::Tasks;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_task),
+        vec![1, 1, 0, 0, 0]
+    );
+
+    app.selected_tab = Tab::Queues;
+    assert_eq!(
+        walk_list_boundaries(&mut app, |app| app.selected_queue),
+        vec![1, 1, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_navigation_on_empty_list_stays_put_in_both_modes() {
+    for wrap in [true, false] {
+        let mut app = create_test_app();
+        app.wrap_navigation = wrap;
+        app.queues.clear();
+        app.selected_tab = Tab::Queues;
+
+        handle_key_event(create_key_event(KeyCode::Down), &mut app);
+        handle_key_event(create_key_event(KeyCode::Up), &mut app);
+        assert_eq!(app.selected_queue, 0);
+    }
+}
+
 #[test]
 fn test_search_mode_activation() {
     let mut app = create_test_app();
@@ -245,6 +519,21 @@ fn test_search_mode_enter() {
     assert!(!app.is_searching);
 }
 
+#[test]
+fn test_tab_in_search_mode_toggles_arg_search() {
+    let mut app = create_test_app();
+    handle_key_event(create_key_event(KeyCode::Char('/')), &mut app);
+    assert!(!app.search_args);
+
+    handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+    assert!(app.search_args);
+    assert!(app.is_searching);
+    assert!(app.search_query.is_empty());
+
+    handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+    assert!(!app.search_args);
+}
+
 #[test]
 fn test_search_mode_blocks_other_keys() {
     let mut app = create_test_app();
@@ -384,15 +673,15 @@ mod navigation_edge_cases {
         let starting_tab = app.selected_tab;
 
         // Full forward cycle should return to start
-        handle_key_event(create_key_event(KeyCode::Tab), &mut app);
-        handle_key_event(create_key_event(KeyCode::Tab), &mut app);
-        handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+        for _ in 0..Tab::ALL.len() {
+            handle_key_event(create_key_event(KeyCode::Tab), &mut app);
+        }
         assert_eq!(app.selected_tab, starting_tab);
 
         // Full backward cycle should return to start
-        handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
-        handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
-        handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
+        for _ in 0..Tab::ALL.len() {
+            handle_key_event(create_key_event(KeyCode::BackTab), &mut app);
+        }
         assert_eq!(app.selected_tab, starting_tab);
     }
 
@@ -1,5 +1,10 @@
 use chrono::Utc;
-use lazycelery::models::{Queue, Task, TaskStatus, Worker, WorkerStatus};
+use lazycelery::models::task::{group_by_root, sort_by_duration, sort_by_priority};
+use lazycelery::models::{
+    canonical_hostname, find_worker, looks_like_exception, merge_workers_by_host, Queue,
+    QueueDiscrepancy, ResultKind, Task, TaskStatus, Worker, WorkerStatus,
+};
+use serde_json::json;
 
 #[test]
 fn test_worker_creation() {
@@ -7,8 +12,10 @@ fn test_worker_creation() {
         hostname: "test-worker".to_string(),
         status: WorkerStatus::Online,
         concurrency: 4,
+        pool_type: None,
         queues: vec!["default".to_string()],
         active_tasks: vec![],
+        reserved_tasks: vec![],
         processed: 100,
         failed: 5,
     };
@@ -25,8 +32,10 @@ fn test_worker_utilization() {
         hostname: "test-worker".to_string(),
         status: WorkerStatus::Online,
         concurrency: 4,
+        pool_type: None,
         queues: vec![],
         active_tasks: vec!["task1".to_string(), "task2".to_string()],
+        reserved_tasks: vec![],
         processed: 0,
         failed: 0,
     };
@@ -48,8 +57,10 @@ fn test_worker_serialization() {
         hostname: "worker-1".to_string(),
         status: WorkerStatus::Online,
         concurrency: 2,
+        pool_type: None,
         queues: vec!["queue1".to_string()],
         active_tasks: vec![],
+        reserved_tasks: vec![],
         processed: 50,
         failed: 2,
     };
@@ -73,6 +84,22 @@ fn test_task_creation() {
         timestamp: Utc::now(),
         result: None,
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     assert_eq!(task.id, "abc123");
@@ -93,6 +120,22 @@ fn test_task_duration() {
         timestamp: past_time,
         result: None,
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     let duration = task.duration_since(Utc::now());
@@ -112,6 +155,22 @@ fn test_task_serialization() {
         timestamp: Utc::now(),
         result: Some("error result".to_string()),
         traceback: Some("traceback here".to_string()),
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     let json = serde_json::to_string(&task).unwrap();
@@ -128,6 +187,8 @@ fn test_queue_creation() {
         name: "default".to_string(),
         length: 42,
         consumers: 3,
+        oldest_message_age: None,
+        discrepancy: None,
     };
 
     assert_eq!(queue.name, "default");
@@ -142,6 +203,8 @@ fn test_queue_empty_state() {
         name: "empty".to_string(),
         length: 0,
         consumers: 0,
+        oldest_message_age: None,
+        discrepancy: None,
     };
 
     assert!(queue.is_empty());
@@ -154,6 +217,8 @@ fn test_queue_serialization() {
         name: "priority".to_string(),
         length: 100,
         consumers: 5,
+        oldest_message_age: None,
+        discrepancy: None,
     };
 
     let json = serde_json::to_string(&queue).unwrap();
@@ -181,3 +246,408 @@ fn test_task_status_variants() {
         assert_eq!(status, deserialized);
     }
 }
+
+fn workflow_task(id: &str, root_id: Option<&str>, parent_id: Option<&str>) -> Task {
+    let mut task = Task::new(id.to_string(), "tasks.step".to_string());
+    task.root_id = root_id.map(|r| r.to_string());
+    task.parent_id = parent_id.map(|p| p.to_string());
+    task
+}
+
+#[test]
+fn test_group_by_root() {
+    let tasks = vec![
+        workflow_task("root-a", Some("root-a"), None),
+        workflow_task("a-1", Some("root-a"), Some("root-a")),
+        workflow_task("solo", None, None),
+        workflow_task("b-1", Some("root-b"), None),
+        workflow_task("a-2", Some("root-a"), Some("a-1")),
+    ];
+
+    let groups = group_by_root(&tasks);
+
+    assert_eq!(groups.len(), 2);
+    let ids = |root: &str| -> Vec<&str> { groups[root].iter().map(|t| t.id.as_str()).collect() };
+    assert_eq!(ids("root-a"), vec!["root-a", "a-1", "a-2"]);
+    assert_eq!(ids("root-b"), vec!["b-1"]);
+}
+
+#[test]
+fn test_sort_by_duration_puts_unknown_last() {
+    let timed = |id: &str, millis: Option<u64>| {
+        let mut task = Task::new(id.to_string(), "tasks.step".to_string());
+        task.duration = millis.map(std::time::Duration::from_millis);
+        task
+    };
+    let tasks = [
+        timed("a", None),
+        timed("b", Some(250)),
+        timed("c", None),
+        timed("d", Some(4_000)),
+    ];
+    let mut sorted: Vec<&Task> = tasks.iter().collect();
+
+    sort_by_duration(&mut sorted);
+
+    let ids: Vec<&str> = sorted.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["d", "b", "a", "c"]);
+}
+
+#[test]
+fn test_sort_by_priority_puts_unknown_last() {
+    let prioritized = |id: &str, priority: Option<u8>| {
+        let mut task = Task::new(id.to_string(), "tasks.step".to_string());
+        task.priority = priority;
+        task
+    };
+    let tasks = [
+        prioritized("a", None),
+        prioritized("b", Some(3)),
+        prioritized("c", Some(9)),
+        prioritized("d", None),
+        prioritized("e", Some(3)),
+        prioritized("f", Some(0)),
+    ];
+    let mut sorted: Vec<&Task> = tasks.iter().collect();
+
+    sort_by_priority(&mut sorted);
+
+    let ids: Vec<&str> = sorted.iter().map(|t| t.id.as_str()).collect();
+    assert_eq!(ids, vec!["c", "b", "e", "f", "a", "d"]);
+}
+
+#[test]
+fn test_task_status_is_finished() {
+    assert!(TaskStatus::Success.is_finished());
+    assert!(TaskStatus::Failure.is_finished());
+    assert!(TaskStatus::Revoked.is_finished());
+    assert!(!TaskStatus::Pending.is_finished());
+    assert!(!TaskStatus::Active.is_finished());
+    assert!(!TaskStatus::Retry.is_finished());
+}
+
+#[test]
+fn test_task_is_stuck() {
+    let now = Utc::now();
+    let threshold = chrono::Duration::hours(1);
+    let task_with = |status: TaskStatus, age: chrono::Duration| {
+        let mut task = Task::new("t".to_string(), "tasks.long".to_string());
+        task.status = status;
+        task.timestamp = now - age;
+        task
+    };
+
+    assert!(task_with(TaskStatus::Active, chrono::Duration::hours(3)).is_stuck(threshold, now));
+    assert!(!task_with(TaskStatus::Active, chrono::Duration::minutes(10)).is_stuck(threshold, now));
+    assert!(!task_with(TaskStatus::Active, threshold).is_stuck(threshold, now));
+
+    for status in [
+        TaskStatus::Pending,
+        TaskStatus::Success,
+        TaskStatus::Failure,
+        TaskStatus::Retry,
+        TaskStatus::Revoked,
+    ] {
+        assert!(!task_with(status, chrono::Duration::hours(3)).is_stuck(threshold, now));
+    }
+
+    // A zero threshold disables detection
+    assert!(!task_with(TaskStatus::Active, chrono::Duration::hours(3))
+        .is_stuck(chrono::Duration::zero(), now));
+}
+
+#[test]
+fn test_task_retries_exhausted() {
+    let mut task = Task::new("t".to_string(), "tasks.flaky".to_string());
+    task.status = TaskStatus::Failure;
+    task.retries = 3;
+    assert!(
+        !task.retries_exhausted(),
+        "unknown limit is never exhausted"
+    );
+
+    task.max_retries = Some(5);
+    assert!(!task.retries_exhausted());
+
+    task.max_retries = Some(3);
+    assert!(task.retries_exhausted());
+
+    task.status = TaskStatus::Retry;
+    assert!(task.retries_exhausted());
+
+    // Succeeding on the last attempt is fine
+    task.status = TaskStatus::Success;
+    assert!(!task.retries_exhausted());
+}
+
+#[test]
+fn test_result_kind_classification() {
+    assert_eq!(ResultKind::classify(&json!(null)), ResultKind::Null);
+    assert_eq!(ResultKind::classify(&json!(true)), ResultKind::Boolean);
+    assert_eq!(ResultKind::classify(&json!(42)), ResultKind::Number);
+    assert_eq!(ResultKind::classify(&json!(-1.5)), ResultKind::Number);
+    assert_eq!(ResultKind::classify(&json!("done")), ResultKind::String);
+    assert_eq!(
+        ResultKind::classify(&json!("line one\nline two\tindented")),
+        ResultKind::String
+    );
+    assert_eq!(
+        ResultKind::classify(&json!({"a": 1, "b": {"c": [1, 2]}})),
+        ResultKind::Object { keys: 2 }
+    );
+    assert_eq!(
+        ResultKind::classify(&json!([1, "two", null])),
+        ResultKind::Array { items: 3 }
+    );
+    assert_eq!(
+        ResultKind::classify(&json!([])),
+        ResultKind::Array { items: 0 }
+    );
+}
+
+#[test]
+fn test_result_kind_detects_binary() {
+    // Bytes as encoded by kombu's JSON serializer
+    assert_eq!(
+        ResultKind::classify(&json!({"__type__": "bytes", "__value__": "\u{0089}PNG"})),
+        ResultKind::Binary { bytes: 5 }
+    );
+    // Control characters and lossy UTF-8 decoding leftovers
+    assert_eq!(
+        ResultKind::classify(&json!("\u{0000}\u{0001}\u{0002}")),
+        ResultKind::Binary { bytes: 3 }
+    );
+    assert_eq!(
+        ResultKind::classify(&json!("ab\u{fffd}")),
+        ResultKind::Binary { bytes: 5 }
+    );
+}
+
+#[test]
+fn test_exception_results_are_recognized() {
+    assert!(looks_like_exception(
+        &json!({"exc_type": "ValueError", "exc_message": ["bad input"], "exc_module": "builtins"})
+    ));
+    assert!(looks_like_exception(&json!({"exc_type": "KeyError"})));
+    assert!(looks_like_exception(&json!({"exc_message": "boom"})));
+    // Results stored as JSON text
+    assert!(looks_like_exception(&json!(
+        "{\"exc_type\": \"RuntimeError\", \"exc_message\": []}"
+    )));
+}
+
+#[test]
+fn test_plain_results_are_not_exceptions() {
+    assert!(!looks_like_exception(&json!(null)));
+    assert!(!looks_like_exception(&json!(42)));
+    assert!(!looks_like_exception(&json!("exc_type")));
+    assert!(!looks_like_exception(&json!("{\"status\": \"ok\"}")));
+    assert!(!looks_like_exception(&json!({"status": "ok", "rows": 3})));
+    // Only top level keys count
+    assert!(!looks_like_exception(
+        &json!({"errors": [{"exc_type": "ValueError"}]})
+    ));
+    assert!(!looks_like_exception(&json!([{"exc_type": "ValueError"}])));
+}
+
+#[test]
+fn test_result_rendering_and_summary() {
+    assert_eq!(ResultKind::render(&json!("plain text")), "plain text");
+    assert_eq!(ResultKind::render(&json!(3.5)), "3.5");
+    assert_eq!(
+        ResultKind::render(&json!({"ok": true})),
+        "{\n  \"ok\": true\n}"
+    );
+    assert_eq!(
+        ResultKind::render(&json!("\u{0007}bell")),
+        r#""\u0007bell""#
+    );
+
+    assert_eq!(
+        ResultKind::Object { keys: 12 }.to_string(),
+        "<object, 12 keys>"
+    );
+    assert_eq!(
+        ResultKind::Array { items: 1 }.to_string(),
+        "<array, 1 item>"
+    );
+    assert_eq!(
+        ResultKind::Binary { bytes: 2048 }.to_string(),
+        "<binary, 2048 bytes>"
+    );
+
+    let mut task = Task::new("task-1".to_string(), "tasks.report".to_string());
+    assert_eq!(task.result_summary(), None);
+
+    let value = json!({"rows": 10, "errors": []});
+    task.result = Some(ResultKind::render(&value));
+    task.result_kind = Some(ResultKind::classify(&value));
+    assert_eq!(task.result_summary().as_deref(), Some("<object, 2 keys>"));
+
+    task.result = Some("first line\nsecond line".to_string());
+    task.result_kind = Some(ResultKind::String);
+    assert_eq!(task.result_summary().as_deref(), Some("first line"));
+}
+
+#[test]
+fn test_worker_recent_failures() {
+    let now = Utc::now();
+    let task = |id: &str, status: TaskStatus, worker: Option<&str>, minutes_ago: i64| {
+        let mut task = Task::new(id.to_string(), format!("tasks.{id}"));
+        task.status = status;
+        task.worker = worker.map(str::to_string);
+        task.timestamp = now - chrono::Duration::minutes(minutes_ago);
+        task
+    };
+    let tasks = vec![
+        task("old-failure", TaskStatus::Failure, Some("worker-1"), 30),
+        task("success", TaskStatus::Success, Some("worker-1"), 1),
+        task("other-worker", TaskStatus::Failure, Some("worker-2"), 2),
+        // Unacked messages record the worker as a `name@hostname` origin
+        task(
+            "origin-failure",
+            TaskStatus::Failure,
+            Some("gen42@worker-1"),
+            5,
+        ),
+        task("newest-failure", TaskStatus::Failure, Some("worker-1"), 3),
+        // Metadata has no hostname, the worker's active task list links it
+        task("active-failure", TaskStatus::Failure, None, 10),
+        task("unattributed", TaskStatus::Failure, None, 0),
+    ];
+
+    let mut worker = Worker::new("worker-1".to_string());
+    worker.active_tasks = vec!["active-failure".to_string()];
+
+    let ids =
+        |failures: Vec<&Task>| -> Vec<String> { failures.iter().map(|t| t.id.clone()).collect() };
+    assert_eq!(
+        ids(worker.recent_failures(&tasks, 10)),
+        vec![
+            "newest-failure",
+            "origin-failure",
+            "active-failure",
+            "old-failure"
+        ]
+    );
+    assert_eq!(
+        ids(worker.recent_failures(&tasks, 2)),
+        vec!["newest-failure", "origin-failure"]
+    );
+
+    let other = Worker::new("worker-2".to_string());
+    assert_eq!(ids(other.recent_failures(&tasks, 5)), vec!["other-worker"]);
+    assert!(Worker::new("worker-3".to_string())
+        .recent_failures(&tasks, 5)
+        .is_empty());
+}
+
+#[test]
+fn test_canonical_hostname_drops_node_name() {
+    assert_eq!(canonical_hostname("celery@worker-prod-1"), "worker-prod-1");
+    assert_eq!(canonical_hostname("gen123@worker-prod-1"), "worker-prod-1");
+    assert_eq!(canonical_hostname("worker-prod-1"), "worker-prod-1");
+    assert_eq!(
+        canonical_hostname(" celery@Worker-Prod-1 "),
+        "worker-prod-1"
+    );
+}
+
+#[test]
+fn test_node_name_and_bare_host_merge_into_one_worker() {
+    let mut inspected = Worker::new("celery@worker-prod-1".to_string());
+    inspected.status = WorkerStatus::Online;
+    inspected.concurrency = 8;
+    inspected.pool_type = Some("prefork".to_string());
+    inspected.queues = vec!["default".to_string()];
+    inspected.active_tasks = vec!["task-1".to_string()];
+    inspected.processed = 10;
+
+    let mut from_origin = Worker::new("worker-prod-1".to_string());
+    from_origin.concurrency = 16;
+    from_origin.queues = vec!["celery".to_string(), "default".to_string()];
+    from_origin.active_tasks = vec!["task-1".to_string(), "task-2".to_string()];
+    from_origin.processed = 2;
+    from_origin.failed = 1;
+
+    let other = Worker::new("celery@worker-prod-2".to_string());
+
+    let workers = merge_workers_by_host(vec![inspected, other, from_origin]);
+    let hostnames: Vec<&str> = workers.iter().map(|w| w.hostname.as_str()).collect();
+    assert_eq!(
+        hostnames,
+        vec!["celery@worker-prod-1", "celery@worker-prod-2"]
+    );
+
+    let merged = &workers[0];
+    assert_eq!(merged.status, WorkerStatus::Online);
+    assert_eq!(merged.concurrency, 16);
+    assert_eq!(merged.pool_type.as_deref(), Some("prefork"));
+    assert_eq!(merged.queues, vec!["celery", "default"]);
+    assert_eq!(merged.active_tasks, vec!["task-1", "task-2"]);
+    assert_eq!((merged.processed, merged.failed), (12, 1));
+    assert_eq!(workers[1].status, WorkerStatus::Offline);
+}
+
+#[test]
+fn test_nodes_sharing_a_host_stay_separate() {
+    let mut first = Worker::new("w1@Host".to_string());
+    first.processed = 5;
+    let mut second = Worker::new("w2@host".to_string());
+    second.processed = 7;
+    let mut from_origin = Worker::new("host".to_string());
+    from_origin.processed = 1;
+
+    let workers = merge_workers_by_host(vec![first, from_origin, second]);
+    let listed: Vec<(&str, u64)> = workers
+        .iter()
+        .map(|w| (w.hostname.as_str(), w.processed))
+        .collect();
+    // The bare host can't be told apart between the two nodes
+    assert_eq!(listed, vec![("w1@host", 5), ("w2@host", 7), ("host", 1)]);
+
+    assert_eq!(find_worker(&workers, "W2@host"), Some(1));
+    assert_eq!(find_worker(&workers[..1], "host"), Some(0));
+    assert_eq!(find_worker(&workers[..2], "host"), None);
+    assert_eq!(find_worker(&workers, "w3@host"), None);
+}
+
+#[test]
+fn test_tasks_match_workers_by_canonical_hostname() {
+    let mut task = Task::new("task-1".to_string(), "tasks.add".to_string());
+    task.worker = Some("celery@Worker-Prod-1".to_string());
+    assert!(Worker::new("worker-prod-1".to_string()).ran_task(&task));
+    assert!(Worker::new("celery@worker-prod-1".to_string()).ran_task(&task));
+    assert!(!Worker::new("other@worker-prod-1".to_string()).ran_task(&task));
+    assert!(!Worker::new("worker-prod-2".to_string()).ran_task(&task));
+}
+
+#[test]
+fn test_bound_empty_queue_is_flagged_only_with_consumers() {
+    assert_eq!(
+        QueueDiscrepancy::detect(true, "none", 0, 2),
+        Some(QueueDiscrepancy::EmptyWithConsumers)
+    );
+    assert_eq!(
+        QueueDiscrepancy::detect(true, "stream", 0, 1),
+        Some(QueueDiscrepancy::EmptyWithConsumers)
+    );
+    assert_eq!(QueueDiscrepancy::detect(true, "none", 0, 0), None);
+    assert_eq!(QueueDiscrepancy::detect(true, "list", 3, 1), None);
+    // Without a binding there is nothing to compare with
+    assert_eq!(QueueDiscrepancy::detect(false, "none", 0, 2), None);
+}
+
+#[test]
+fn test_bound_queue_with_unexpected_key_type_is_flagged() {
+    for consumers in [0, 1] {
+        assert_eq!(
+            QueueDiscrepancy::detect(true, "hash", 0, consumers),
+            Some(QueueDiscrepancy::UnexpectedKeyType("hash".to_string()))
+        );
+    }
+    assert_eq!(QueueDiscrepancy::detect(false, "string", 0, 0), None);
+    assert!(QueueDiscrepancy::UnexpectedKeyType("zset".to_string())
+        .describe()
+        .contains("Redis zset"));
+}
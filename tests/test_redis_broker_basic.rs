@@ -8,10 +8,16 @@
 mod redis_test_utils;
 
 use anyhow::Result;
-use lazycelery::broker::{redis::RedisBroker, Broker};
+use chrono::TimeZone;
+use lazycelery::broker::redis::{BrokerFacade, RedisBroker};
+use lazycelery::broker::{Broker, BrokerOptions};
 use lazycelery::error::BrokerError;
 use lazycelery::models::TaskStatus;
+use lazycelery::test_support::FixedClock;
+use redis::AsyncCommands;
 use redis_test_utils::*;
+use serde_json::json;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 
@@ -215,13 +221,751 @@ mod integration_tests {
             .await,
         )
     }
+
+    #[tokio::test]
+    async fn test_queue_oldest_message_age() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    let sent_at = chrono::Utc::now().timestamp() as f64 - 120.0;
+                    let message = json!({
+                        "body": "",
+                        "headers": {"id": "aged-task", "task": "tasks.slow"},
+                        "properties": {"timestamp": sent_at},
+                    });
+                    conn.lpush::<_, _, ()>("celery", message.to_string())
+                        .await?;
+
+                    let broker = db.broker().await?;
+                    let queues = broker.get_queues().await?;
+                    let celery = queues.iter().find(|q| q.name == "celery").unwrap();
+
+                    let age = celery
+                        .oldest_message_age
+                        .expect("age of the queued message should be computed");
+                    assert!(age >= Duration::from_secs(119));
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_unacked_messages_are_active_tasks() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    let message = json!({
+                        "body": "",
+                        "headers": {
+                            "id": "reserved-task",
+                            "task": "tasks.long_running",
+                            "origin": "gen7@worker-1",
+                        },
+                    });
+                    let delivery_tag = "6f1c0a2e-delivery";
+                    conn.hset::<_, _, _, ()>(
+                        "unacked",
+                        delivery_tag,
+                        json!([message, "", "celery"]).to_string(),
+                    )
+                    .await?;
+                    conn.zadd::<_, _, _, ()>(
+                        "unacked_index",
+                        delivery_tag,
+                        chrono::Utc::now().timestamp() as f64,
+                    )
+                    .await?;
+
+                    let broker = db.broker().await?;
+                    let tasks = broker.get_tasks().await?;
+                    let task = tasks
+                        .iter()
+                        .find(|t| t.id == "reserved-task")
+                        .expect("unacked message should be listed as a task");
+                    assert_eq!(task.status, TaskStatus::Active);
+                    assert_eq!(task.worker.as_deref(), Some("gen7@worker-1"));
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_scheduled_reads_redisbeat_schedule() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    let broker = db.broker().await?;
+                    assert!(broker.get_scheduled().await?.is_empty());
+
+                    let entry = json!({
+                        "py/object": "celery.beat.ScheduleEntry",
+                        "name": "cleanup-every-hour",
+                        "task": "tasks.cleanup",
+                        "schedule": {
+                            "py/object": "celery.schedules.schedule",
+                            "run_every": {
+                                "py/reduce": [
+                                    {"py/type": "datetime.timedelta"},
+                                    {"py/tuple": [0, 3600, 0]}
+                                ]
+                            }
+                        }
+                    });
+                    conn.zadd::<_, _, _, ()>(
+                        "celery:beat:order_tasks",
+                        entry.to_string(),
+                        1_700_000_000.0,
+                    )
+                    .await?;
+
+                    let scheduled = broker.get_scheduled().await?;
+                    assert_eq!(scheduled.len(), 1);
+                    assert_eq!(scheduled[0].name, "cleanup-every-hour");
+                    assert_eq!(scheduled[0].task, "tasks.cleanup");
+                    assert_eq!(scheduled[0].schedule, "every 1h");
+                    assert_eq!(
+                        scheduled[0].next_run.map(|t| t.timestamp()),
+                        Some(1_700_000_000)
+                    );
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_parsers_honor_custom_limits() -> Result<()> {
+        use lazycelery::broker::redis::operations::TaskOperations;
+        use lazycelery::broker::redis::pool::ConnectionPool;
+        use lazycelery::broker::redis::protocol::{TaskParser, WorkerParser};
+        use lazycelery::broker::ScanCap;
+        use lazycelery::config::ParserLimits;
+
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let builder = TestDataBuilder::new(client.clone());
+                    builder.add_performance_data(6).await?;
+
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+                    for i in 0..3 {
+                        let message = TaskOperations::build_task_message(
+                            &format!("queued-{i}"),
+                            "tasks.add",
+                            "celery",
+                            &json!([i]),
+                            &json!({}),
+                        );
+                        conn.lpush::<_, _, ()>("celery", message.to_string())
+                            .await?;
+                    }
+
+                    let pool = ConnectionPool::new(&db.url, Some(4)).await?;
+                    let options = BrokerOptions {
+                        limits: ParserLimits {
+                            max_task_metadata_keys: 3,
+                            max_task_results: 2,
+                            max_pending_tasks: 1,
+                            ..ParserLimits::default()
+                        },
+                        ..BrokerOptions::default()
+                    };
+
+                    let parsed = TaskParser::parse_tasks(&pool, &pool, &options).await?;
+                    let count = |prefix: &str| {
+                        parsed
+                            .items
+                            .iter()
+                            .filter(|t| t.id.starts_with(prefix))
+                            .count()
+                    };
+                    assert_eq!(count("perf-task-"), 2);
+                    assert_eq!(count("queued-"), 1);
+                    assert_eq!(parsed.capped, Some(ScanCap { shown: 3, found: 9 }));
+
+                    let parsed = WorkerParser::parse_workers(&pool, &pool, &options.limits).await?;
+                    assert_eq!(parsed.capped, Some(ScanCap { shown: 3, found: 6 }));
+                    let worker = &parsed.items[0];
+                    assert!(worker.processed + worker.failed <= 3);
+
+                    // Nothing is cut off with the default limits
+                    let defaults = BrokerOptions::default();
+                    let parsed = TaskParser::parse_tasks(&pool, &pool, &defaults).await?;
+                    assert_eq!(parsed.items.len(), 9);
+                    assert_eq!(parsed.capped, None);
+                    let parsed =
+                        WorkerParser::parse_workers(&pool, &pool, &defaults.limits).await?;
+                    assert_eq!(parsed.capped, None);
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_clear_completed_results_removes_only_old_finished_tasks() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    // Ages are measured from the broker's clock, not the system's
+                    let now = chrono::Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+                    let old = (now - chrono::Duration::days(10)).to_rfc3339();
+                    let recent = (now - chrono::Duration::hours(1)).to_rfc3339();
+                    // Older Celery versions store date_done without an offset
+                    let old_naive = (now - chrono::Duration::days(10))
+                        .naive_utc()
+                        .format("%Y-%m-%dT%H:%M:%S%.6f")
+                        .to_string();
+                    let results = [
+                        ("old-success", "SUCCESS", Some(old.as_str())),
+                        ("old-failure", "FAILURE", Some(old_naive.as_str())),
+                        ("old-revoked", "REVOKED", Some(old.as_str())),
+                        ("recent-success", "SUCCESS", Some(recent.as_str())),
+                        ("old-started", "STARTED", Some(old.as_str())),
+                        ("old-pending", "PENDING", Some(old.as_str())),
+                        ("undated-success", "SUCCESS", None),
+                    ];
+                    for (task_id, status, date_done) in results {
+                        let metadata = json!({
+                            "task_id": task_id,
+                            "status": status,
+                            "result": null,
+                            "date_done": date_done,
+                        });
+                        conn.set::<_, _, ()>(
+                            format!("celery-task-meta-{task_id}"),
+                            metadata.to_string(),
+                        )
+                        .await?;
+                    }
+
+                    let options = BrokerOptions {
+                        clock: Arc::new(FixedClock::new(now)),
+                        ..BrokerOptions::default()
+                    };
+                    let broker = RedisBroker::connect_with_options(&db.url, options).await?;
+                    let cleared = broker
+                        .clear_completed_results(Duration::from_secs(7 * 86_400))
+                        .await?;
+                    assert_eq!(cleared, 3);
+
+                    let mut remaining: Vec<String> = conn.keys("celery-task-meta-*").await?;
+                    remaining.sort();
+                    assert_eq!(
+                        remaining,
+                        vec![
+                            "celery-task-meta-old-pending",
+                            "celery-task-meta-old-started",
+                            "celery-task-meta-recent-success",
+                            "celery-task-meta-undated-success",
+                        ]
+                    );
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_replay_task_publishes_new_message() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    let broker = db.broker().await?;
+                    let new_id = broker
+                        .replay_task("tasks.add", "celery", "[40, 2]", "{}")
+                        .await?;
+                    assert_ne!(new_id, "original-task");
+
+                    let messages: Vec<String> = conn.lrange("celery", 0, -1).await?;
+                    assert_eq!(messages.len(), 1);
+                    let message: serde_json::Value = serde_json::from_str(&messages[0])?;
+                    assert_eq!(message["headers"]["id"], new_id.as_str());
+                    assert_eq!(message["headers"]["task"], "tasks.add");
+
+                    let tasks = broker.get_tasks().await?;
+                    let replayed = tasks.iter().find(|t| t.id == new_id).unwrap();
+                    assert_eq!(replayed.args, "[40,2]");
+                    assert_eq!(replayed.queue.as_deref(), Some("celery"));
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_task_by_id() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                    let task_id = "0c8e2b7a-4f1d-4c3e-9b2a-6d5e4f3a2b1c";
+                    let meta = json!({
+                        "status": "FAILURE",
+                        "result": {"exc_type": "ValueError"},
+                        "traceback": "Traceback (most recent call last): ...",
+                        "task_id": task_id,
+                        "date_done": "2024-01-15T14:30:45Z",
+                    });
+                    conn.set::<_, _, ()>(format!("celery-task-meta-{task_id}"), meta.to_string())
+                        .await?;
+
+                    let broker = db.broker().await?;
+                    let task = broker
+                        .get_task(task_id)
+                        .await?
+                        .expect("stored task should be found");
+                    assert_eq!(task.id, task_id);
+                    assert_eq!(task.status, TaskStatus::Failure);
+                    assert!(task.traceback.is_some());
+
+                    let missing = broker
+                        .get_task("9f8e7d6c-5b4a-4392-8a1b-0c9d8e7f6a5b")
+                        .await?;
+                    assert!(missing.is_none());
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_parse_queues_counts_consumers_and_flags_empty_consumed_queues() -> Result<()> {
+        use lazycelery::broker::redis::protocol::QueueParser;
+        use lazycelery::models::{QueueDiscrepancy, Worker};
+
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+                    conn.sadd::<_, _, ()>(
+                        "_kombu.binding.reports",
+                        "reports\x06\x16\x06\x16reports",
+                    )
+                    .await?;
+                    conn.lpush::<_, _, ()>("celery", "{}").await?;
+
+                    let worker = |hostname: &str, queues: &[&str]| Worker {
+                        queues: queues.iter().map(|q| q.to_string()).collect(),
+                        ..Worker::new(hostname.to_string())
+                    };
+                    let consumers = QueueParser::consumer_counts(&[
+                        worker("w1@host", &["celery", "reports"]),
+                        worker("w2@host", &["reports"]),
+                    ]);
+
+                    let queues =
+                        QueueParser::parse_queues(&conn, chrono::Utc::now(), None, &consumers)
+                            .await?;
+                    let queue = |name: &str| queues.iter().find(|q| q.name == name).unwrap();
+
+                    assert_eq!(queue("reports").consumers, 2);
+                    assert_eq!(queue("reports").length, 0);
+                    assert_eq!(
+                        queue("reports").discrepancy,
+                        Some(QueueDiscrepancy::EmptyWithConsumers)
+                    );
+                    assert_eq!(queue("celery").consumers, 1);
+                    assert_eq!(queue("celery").length, 1);
+                    assert_eq!(queue("celery").discrepancy, None);
+
+                    // Nobody inspected, nobody counted
+                    let queues = QueueParser::parse_queues(
+                        &conn,
+                        chrono::Utc::now(),
+                        None,
+                        &Default::default(),
+                    )
+                    .await?;
+                    assert!(queues
+                        .iter()
+                        .all(|q| q.consumers == 0 && q.discrepancy.is_none()));
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pool_reuses_connections_and_caps_leases() -> Result<()> {
+        use lazycelery::broker::redis::pool::ConnectionPool;
+        use std::sync::Arc;
+
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|db| async move {
+                    // Leases taken one after the other share one connection
+                    let pool = ConnectionPool::new(&db.url, Some(4)).await?;
+                    for _ in 0..20 {
+                        let mut conn = pool.get_connection().await?;
+                        redis::cmd("PING")
+                            .query_async::<_, String>(&mut *conn)
+                            .await?;
+                    }
+                    let stats = pool.snapshot().await;
+                    assert_eq!(stats.opened_connections, 1);
+                    assert_eq!(stats.total_connections, 1);
+
+                    // Under load, no more than the pool size are lent out or opened
+                    let pool = Arc::new(pool);
+                    let handles: Vec<_> = (0..50)
+                        .map(|_| {
+                            let pool = Arc::clone(&pool);
+                            tokio::spawn(async move {
+                                let mut conn = pool.get_connection().await?;
+                                redis::cmd("PING")
+                                    .query_async::<_, String>(&mut *conn)
+                                    .await?;
+                                tokio::time::sleep(Duration::from_millis(5)).await;
+                                anyhow::Ok(())
+                            })
+                        })
+                        .collect();
+                    for handle in handles {
+                        handle.await??;
+                    }
+                    let stats = pool.snapshot().await;
+                    assert_eq!(stats.peak_connections, 4);
+                    assert!(stats.opened_connections <= 4, "{stats:?}");
+                    assert_eq!(stats.active_connections, 0);
+                    assert_eq!(stats.total_connections, stats.opened_connections);
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_pool_size_and_saturation() -> Result<()> {
+        use lazycelery::broker::redis::pool::ConnectionPool;
+
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    TestDataBuilder::new(client).add_basic_tasks().await?;
+
+                    // The facade sizes its pool from the options
+                    let options = BrokerOptions {
+                        pool_size: 3,
+                        ..BrokerOptions::default()
+                    };
+                    let facade = BrokerFacade::new(&db.url, options).await?;
+                    assert_eq!(facade.get_pool_stats().await.max_size, 3);
+
+                    // Every connection checked out at once saturates the pool
+                    let pool = ConnectionPool::new(&db.url, Some(2)).await?;
+                    let first = pool.get_connection().await?;
+                    let second = pool.get_connection().await?;
+                    let stats = pool.stats().await;
+                    assert_eq!(stats.active_connections, 2);
+                    assert!(stats.is_saturated());
+                    drop((first, second));
+                    let stats = pool.stats().await;
+                    assert_eq!(stats.active_connections, 0);
+                    assert!(!stats.is_saturated());
+
+                    // Task parsing holds a broker and a backend connection at once
+                    let options = BrokerOptions {
+                        pool_size: 1,
+                        ..BrokerOptions::default()
+                    };
+                    let facade = BrokerFacade::new(&db.url, options).await?;
+                    facade.get_tasks().await?;
+                    assert_eq!(facade.take_pool_saturation(), Some(1));
+                    assert_eq!(facade.take_pool_saturation(), None);
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_revoke_completes_with_single_connection_pool() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+                    let task_id = "single-lease-task";
+                    let meta = json!({"status": "STARTED", "task_id": task_id});
+                    conn.set::<_, _, ()>(format!("celery-task-meta-{task_id}"), meta.to_string())
+                        .await?;
+
+                    // The backend shares the broker's only connection
+                    let options = BrokerOptions {
+                        pool_size: 1,
+                        ..BrokerOptions::default()
+                    };
+                    let facade = BrokerFacade::new(&db.url, options).await?;
+                    timeout(Duration::from_secs(5), facade.revoke_task(task_id))
+                        .await
+                        .map_err(|_| anyhow::anyhow!("revoke waited for a second lease"))??;
+
+                    TestAssertions::assert_task_revoked(&client, task_id, true).await?;
+                    let stored: String = conn.get(format!("celery-task-meta-{task_id}")).await?;
+                    let stored: serde_json::Value = serde_json::from_str(&stored)?;
+                    assert_eq!(stored["status"], "REVOKED");
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_metadata_is_read_from_result_backend() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut broker_db| async move {
+                    with_test_db(|mut backend_db| async move {
+                        let task_id = "5b0d1f8e-3c2a-4e71-9a44-0f6d2c8b7e13";
+                        let meta = json!({
+                            "status": "SUCCESS",
+                            "result": "from-backend",
+                            "task_id": task_id,
+                            "date_done": "2024-01-15T14:30:45Z",
+                        });
+
+                        let backend_client = backend_db.client().await?;
+                        let mut backend_conn =
+                            backend_client.get_multiplexed_tokio_connection().await?;
+                        backend_conn
+                            .set::<_, _, ()>(
+                                format!("celery-task-meta-{task_id}"),
+                                meta.to_string(),
+                            )
+                            .await?;
+
+                        // Only the broker database holds a queue message
+                        let broker_client = broker_db.client().await?;
+                        let mut broker_conn =
+                            broker_client.get_multiplexed_tokio_connection().await?;
+                        let message = json!({
+                            "body": "",
+                            "headers": {"id": "queued-task", "task": "tasks.queued"},
+                        });
+                        broker_conn
+                            .lpush::<_, _, ()>("celery", message.to_string())
+                            .await?;
+
+                        let options = BrokerOptions {
+                            result_backend_url: Some(backend_db.url.clone()),
+                            ..BrokerOptions::default()
+                        };
+                        let facade = BrokerFacade::new(&broker_db.url, options).await?;
+
+                        let raw = facade.get_raw_task_metadata(task_id).await?;
+                        assert!(raw.unwrap().contains("from-backend"));
+
+                        let tasks = facade.get_tasks().await?;
+                        let completed = tasks.iter().find(|t| t.id == task_id).unwrap();
+                        assert_eq!(completed.status, TaskStatus::Success);
+                        assert!(tasks.iter().any(|t| t.id == "queued-task"));
+
+                        // Worker statistics are counted from the backend too
+                        let workers = facade.get_workers().await?;
+                        assert_eq!(workers.iter().map(|w| w.processed).sum::<u64>(), 1);
+
+                        // Without the result backend the metadata is not found
+                        let broker_only =
+                            BrokerFacade::new(&broker_db.url, BrokerOptions::default()).await?;
+                        assert_eq!(broker_only.get_raw_task_metadata(task_id).await?, None);
+
+                        Ok(())
+                    })
+                    .await
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_redis_server_version_read_on_connect() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let broker = db.broker().await?;
+                    let version = broker.server_version().expect("INFO server is allowed");
+                    assert!(!version.is_empty());
+                    assert!(version.chars().next().unwrap().is_ascii_digit());
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_redis_connect_survives_refused_info() -> Result<()> {
+        skip_if_redis_unavailable(
+            async {
+                with_test_db(|mut db| async move {
+                    let client = db.client().await?;
+                    let mut conn = client.get_multiplexed_tokio_connection().await?;
+                    let user = "lazycelery-test-noinfo";
+                    let created: redis::RedisResult<()> = redis::cmd("ACL")
+                        .arg(&[
+                            "SETUSER", user, "on", ">secret", "~*", "&*", "+@all", "-info",
+                        ])
+                        .query_async(&mut conn)
+                        .await;
+                    if created.is_err() {
+                        // Servers older than Redis 6 have no ACLs to refuse INFO with
+                        return Ok(());
+                    }
+
+                    let url = db
+                        .url
+                        .replace("redis://", &format!("redis://{user}:secret@"));
+                    let connected = RedisBroker::connect(&url).await;
+                    let _: () = redis::cmd("ACL")
+                        .arg(&["DELUSER", user])
+                        .query_async(&mut conn)
+                        .await?;
+
+                    let broker = connected?;
+                    assert_eq!(broker.server_version(), None);
+                    broker.get_queues().await?;
+
+                    Ok(())
+                })
+                .await
+            }
+            .await,
+        )
+    }
 }
 
 // Unit tests for parsing logic (without Redis dependency)
 mod parsing_tests {
     use super::*;
+    use lazycelery::broker::redis::parse_server_version;
+    use lazycelery::broker::ScanCap;
     use serde_json::json;
 
+    #[test]
+    fn test_parse_server_version() {
+        let info = "# Server\r\nredis_version:7.2.4\r\nredis_git_sha1:00000000\r\n";
+        assert_eq!(parse_server_version(info), Some("7.2.4".to_string()));
+        assert_eq!(
+            parse_server_version("# Server\r\nredis_mode:standalone\r\n"),
+            None
+        );
+        assert_eq!(parse_server_version("redis_version:\r\n"), None);
+    }
+
+    #[test]
+    fn test_scan_cap_check_and_merge() {
+        assert_eq!(ScanCap::check(100, 100), None);
+        assert_eq!(
+            ScanCap::check(250, 100),
+            Some(ScanCap {
+                shown: 100,
+                found: 250
+            })
+        );
+
+        let metadata = ScanCap::check(250, 100);
+        let pending = ScanCap::check(30, 20);
+        assert_eq!(
+            ScanCap::merge(metadata, pending),
+            Some(ScanCap {
+                shown: 120,
+                found: 280
+            })
+        );
+        assert_eq!(ScanCap::merge(None, pending), pending);
+        assert_eq!(ScanCap::merge(None, None), None);
+    }
+
+    #[test]
+    fn test_replay_message_construction() {
+        use base64::Engine;
+        use lazycelery::broker::redis::operations::TaskOperations;
+
+        let message = TaskOperations::build_task_message(
+            "new-task-id",
+            "tasks.add",
+            "priority",
+            &json!([40, 2]),
+            &json!({"retry": true}),
+        );
+
+        assert_eq!(message["headers"]["id"], "new-task-id");
+        assert_eq!(message["headers"]["task"], "tasks.add");
+        assert_eq!(message["headers"]["root_id"], "new-task-id");
+        assert_eq!(message["properties"]["correlation_id"], "new-task-id");
+        assert_eq!(
+            message["properties"]["delivery_info"]["routing_key"],
+            "priority"
+        );
+
+        let body = base64::engine::general_purpose::STANDARD
+            .decode(message["body"].as_str().unwrap())
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body[0], json!([40, 2]));
+        assert_eq!(body[1], json!({"retry": true}));
+    }
+
     #[test]
     fn test_task_status_mapping() {
         // Test all possible status mappings
@@ -9,11 +9,16 @@ mod redis_test_utils;
 
 use anyhow::Result;
 use base64::Engine;
-use lazycelery::broker::Broker;
+use lazycelery::broker::redis::pool::ConnectionPool;
+use lazycelery::broker::redis::protocol::TaskParser;
+use lazycelery::broker::{Broker, BrokerOptions};
 use lazycelery::models::TaskStatus;
+use lazycelery::test_support::FixedClock;
+use lazycelery::utils::clock::Clock;
 use redis::AsyncCommands;
 use redis_test_utils::*;
 use serde_json::json;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[tokio::test]
@@ -204,6 +209,72 @@ async fn test_task_revoke_functionality() -> Result<()> {
     )
 }
 
+#[tokio::test]
+async fn test_expiring_revokes() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let builder = TestDataBuilder::new(client.clone());
+                builder.add_real_celery_data().await?;
+
+                let clock = Arc::new(FixedClock::new(chrono::Utc::now()));
+                let options = BrokerOptions {
+                    revoke_ttl: Duration::from_secs(60),
+                    clock: clock.clone(),
+                    ..BrokerOptions::default()
+                };
+                let broker =
+                    lazycelery::broker::redis::RedisBroker::connect_with_options(&db.url, options)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Redis not available for testing"))?;
+                let status_of = |tasks: Vec<lazycelery::models::Task>, id: &str| {
+                    tasks.into_iter().find(|t| t.id == id).map(|t| t.status)
+                };
+
+                broker.revoke_task("real-queue-task").await?;
+
+                // The revoke lands in the revoked set, scored by when it expires
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+                let expires_at: Option<f64> =
+                    conn.zscore("lazycelery:revokes", "real-queue-task").await?;
+                let left = expires_at.unwrap() - clock.now().timestamp() as f64;
+                assert_eq!(left, 60.0);
+                TestAssertions::assert_task_revoked(&client, "real-queue-task", true).await?;
+                assert_eq!(
+                    status_of(broker.get_tasks().await?, "real-queue-task"),
+                    Some(TaskStatus::Revoked)
+                );
+
+                // Once the clock passes the expiry the task is no longer revoked
+                clock.advance(chrono::Duration::seconds(61));
+                assert_eq!(
+                    status_of(broker.get_tasks().await?, "real-queue-task"),
+                    Some(TaskStatus::Pending)
+                );
+
+                // The next revoke takes the expired one out of both sets
+                broker.revoke_task("another-task").await?;
+                let expired: Option<f64> =
+                    conn.zscore("lazycelery:revokes", "real-queue-task").await?;
+                assert_eq!(expired, None);
+                TestAssertions::assert_task_revoked(&client, "real-queue-task", false).await?;
+
+                // Revokes that never expire are listed too
+                let _: () = conn.sadd("revoked", "real-queue-task").await?;
+                assert_eq!(
+                    status_of(broker.get_tasks().await?, "real-queue-task"),
+                    Some(TaskStatus::Revoked)
+                );
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
 #[tokio::test]
 async fn test_task_timestamp_parsing() -> Result<()> {
     skip_if_redis_unavailable(
@@ -280,6 +351,47 @@ async fn test_base64_task_body_decoding() -> Result<()> {
     )
 }
 
+#[tokio::test]
+async fn test_parallel_task_parsing_matches_serial() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let builder = TestDataBuilder::new(client.clone());
+                builder.add_performance_data(80).await?;
+
+                let pool = ConnectionPool::new(&db.url, Some(8)).await?;
+                let options = BrokerOptions::default();
+
+                let start = std::time::Instant::now();
+                let serial = TaskParser::parse_tasks_with_concurrency(&pool, &pool, &options, 1)
+                    .await?
+                    .items;
+                let serial_duration = start.elapsed();
+
+                let start = std::time::Instant::now();
+                let parallel = TaskParser::parse_tasks(&pool, &pool, &options).await?.items;
+                let parallel_duration = start.elapsed();
+
+                eprintln!(
+                    "serial: {serial_duration:?}, parallel: {parallel_duration:?} ({} tasks)",
+                    parallel.len()
+                );
+
+                // Same tasks, in the same order
+                let serial_ids: Vec<&str> = serial.iter().map(|t| t.id.as_str()).collect();
+                let parallel_ids: Vec<&str> = parallel.iter().map(|t| t.id.as_str()).collect();
+                assert_eq!(serial_ids, parallel_ids);
+                assert_eq!(parallel.len(), 80);
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
 #[tokio::test]
 async fn test_performance_with_large_dataset() -> Result<()> {
     skip_if_redis_unavailable(
@@ -344,6 +456,8 @@ async fn test_edge_cases_and_malformed_data() -> Result<()> {
                 // Should handle malformed data gracefully
                 let result = broker.get_tasks().await;
                 assert!(result.is_ok(), "Should handle malformed data gracefully");
+                // The invalid and empty values are skipped and counted
+                assert_eq!(broker.scan_caps().unreadable_tasks, 2);
 
                 let tasks = result.unwrap();
                 // Should find at least the incomplete task
@@ -361,3 +475,448 @@ async fn test_edge_cases_and_malformed_data() -> Result<()> {
         .await,
     )
 }
+
+#[tokio::test]
+async fn test_ping_measures_round_trip() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let broker = db.broker().await?;
+
+                let latency = broker.ping().await?;
+                // A local Redis answers well within a second
+                assert!(latency < Duration::from_secs(1));
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_peek_queue_leaves_messages_in_place() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                for (task_id, task_name, args) in [
+                    ("peek-1", "orders.create", json!([[1], {}])),
+                    ("peek-2", "orders.ship", json!([[2], {"express": true}])),
+                    ("peek-3", "orders.bill", json!([[3], {}])),
+                ] {
+                    let message = json!({
+                        "body": base64::engine::general_purpose::STANDARD.encode(args.to_string()),
+                        "headers": {"task": task_name, "id": task_id},
+                        "properties": {"delivery_info": {"routing_key": "orders"}}
+                    });
+                    let _: () = conn.lpush("orders", message.to_string()).await?;
+                }
+
+                // The messages workers take next come first
+                let broker = db.broker().await?;
+                let peeked = broker.peek_queue("orders", 2).await?;
+
+                let ids: Vec<&str> = peeked.iter().map(|t| t.id.as_str()).collect();
+                assert_eq!(ids, vec!["peek-1", "peek-2"]);
+                assert!(peeked.iter().all(|t| t.status == TaskStatus::Pending));
+                assert!(peeked.iter().all(|t| t.queue.as_deref() == Some("orders")));
+                assert_eq!(peeked[1].name, "orders.ship");
+                assert!(peeked[1].kwargs.contains("express"));
+
+                // Nothing was consumed
+                let length: u64 = conn.llen("orders").await?;
+                assert_eq!(length, 3);
+                let all = broker.peek_queue("orders", 10).await?;
+                assert_eq!(all.len(), 3);
+
+                assert!(broker.peek_queue("orders; FLUSHALL", 1).await.is_err());
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_move_task_between_queues() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                for task_id in ["stay-1", "move-1", "stay-2"] {
+                    let message = json!({
+                        "body": base64::engine::general_purpose::STANDARD.encode("[[], {}, {}]"),
+                        "headers": {"task": "reports.build", "id": task_id},
+                        "properties": {"delivery_info": {"exchange": "", "routing_key": "busy"}}
+                    });
+                    let _: () = conn.lpush("busy", message.to_string()).await?;
+                }
+
+                let broker = db.broker().await?;
+                broker.move_task("move-1", "busy", "idle").await?;
+
+                let busy_length: u64 = conn.llen("busy").await?;
+                let idle_length: u64 = conn.llen("idle").await?;
+                assert_eq!(busy_length, 2);
+                assert_eq!(idle_length, 1);
+
+                let remaining = broker.peek_queue("busy", 10).await?;
+                let ids: Vec<&str> = remaining.iter().map(|t| t.id.as_str()).collect();
+                assert_eq!(ids, vec!["stay-1", "stay-2"]);
+
+                let moved: String = conn.lindex("idle", 0).await?;
+                let moved: serde_json::Value = serde_json::from_str(&moved)?;
+                assert_eq!(moved["headers"]["id"], "move-1");
+                assert_eq!(moved["properties"]["delivery_info"]["routing_key"], "idle");
+
+                // Missing messages and bad queue names are rejected
+                assert!(broker.move_task("move-1", "busy", "idle").await.is_err());
+                assert!(broker
+                    .move_task("stay-1", "busy", "idle; FLUSHALL")
+                    .await
+                    .is_err());
+                assert!(broker.move_task("stay-1", "busy", "busy").await.is_err());
+                let busy_length: u64 = conn.llen("busy").await?;
+                assert_eq!(busy_length, 2);
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_stream_queue_is_read_like_a_list() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                for task_id in ["stream-1", "stream-2"] {
+                    let message = json!({
+                        "body": base64::engine::general_purpose::STANDARD.encode("[[], {}, {}]"),
+                        "headers": {"task": "reports.build", "id": task_id},
+                        "properties": {"delivery_info": {"exchange": "", "routing_key": "celery"}}
+                    });
+                    let _: String = redis::cmd("XADD")
+                        .arg("celery")
+                        .arg("*")
+                        .arg("payload")
+                        .arg(message.to_string())
+                        .query_async(&mut conn)
+                        .await?;
+                }
+
+                let broker = db.broker().await?;
+                let queues = broker.get_queues().await?;
+                let celery = queues
+                    .iter()
+                    .find(|q| q.name == "celery")
+                    .expect("Should list the stream queue");
+                assert_eq!(celery.length, 2);
+                assert!(celery.oldest_message_age.is_some());
+
+                let peeked = broker.peek_queue("celery", 10).await?;
+                let ids: Vec<&str> = peeked.iter().map(|t| t.id.as_str()).collect();
+                assert_eq!(ids, vec!["stream-1", "stream-2"]);
+                assert!(peeked.iter().all(
+                    |t| t.status == TaskStatus::Pending && t.queue.as_deref() == Some("celery")
+                ));
+
+                let tasks = broker.get_tasks().await?;
+                assert!(tasks.iter().any(|t| t.id == "stream-2"));
+
+                // Moving only works between lists
+                assert!(broker
+                    .move_task("stream-1", "celery", "idle")
+                    .await
+                    .is_err());
+
+                assert_eq!(broker.purge_queue("celery").await?, 2);
+                let exists: bool = conn.exists("celery").await?;
+                assert!(!exists);
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+/// Answer the next `count` inspect commands on database `db` like a Celery
+/// worker named `celery@inspect-host` would, replying through the queue bound
+/// to the command's reply routing key
+async fn spawn_fake_worker(
+    client: redis::Client,
+    db: u8,
+    count: usize,
+) -> Result<tokio::task::JoinHandle<Result<()>>> {
+    use futures::StreamExt;
+
+    let mut pubsub = client.get_async_pubsub().await?;
+    pubsub.subscribe(format!("/{db}.celery.pidbox")).await?;
+    let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+    Ok(tokio::spawn(async move {
+        let mut messages = pubsub.on_message();
+        for _ in 0..count {
+            let message = messages.next().await.expect("subscription stays open");
+            let envelope: serde_json::Value =
+                serde_json::from_str(&message.get_payload::<String>()?)?;
+            let body = base64::engine::general_purpose::STANDARD
+                .decode(envelope["body"].as_str().unwrap_or_default())?;
+            let command: serde_json::Value = serde_json::from_slice(&body)?;
+
+            let routing_key = command["reply_to"]["routing_key"]
+                .as_str()
+                .unwrap_or_default();
+            let bindings: Vec<String> = conn.smembers("_kombu.binding.reply.celery.pidbox").await?;
+            let queue = bindings
+                .iter()
+                .filter_map(|binding| {
+                    let fields: Vec<&str> = binding.split("\x06\x16").collect();
+                    (fields.first() == Some(&routing_key)).then(|| fields[2].to_string())
+                })
+                .next()
+                .expect("reply queue is bound");
+
+            let reply = match command["method"].as_str() {
+                Some("active") => json!([{"id": "inspect-active-1", "name": "tasks.add"}]),
+                Some("reserved") => {
+                    json!([{"id": "inspect-reserved-1"}, {"id": "inspect-reserved-2"}])
+                }
+                Some("stats") => json!({"pool": {"max-concurrency": 8}, "total": {"tasks.add": 7}}),
+                _ => json!({"error": "unknown command"}),
+            };
+            let reply_body = json!({"celery@inspect-host": reply}).to_string();
+            let reply_message = json!({
+                "body": base64::engine::general_purpose::STANDARD.encode(reply_body),
+                "content-encoding": "utf-8",
+                "content-type": "application/json",
+                "headers": {"ticket": command["ticket"], "clock": 1},
+                "properties": {"body_encoding": "base64", "delivery_tag": "fake"},
+            });
+            let _: () = conn.lpush(&queue, reply_message.to_string()).await?;
+        }
+        Ok(())
+    }))
+}
+
+#[tokio::test]
+async fn test_inspect_workers_reads_worker_replies() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let worker = spawn_fake_worker(client.clone(), db.database_id, 3).await?;
+
+                // Inspect is off unless a timeout is configured
+                let options = BrokerOptions {
+                    inspect_timeout: Duration::from_millis(1_000),
+                    ..BrokerOptions::default()
+                };
+                let broker =
+                    lazycelery::broker::redis::RedisBroker::connect_with_options(&db.url, options)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Redis not available for testing"))?;
+                let workers = broker.inspect_workers().await?;
+                worker.await??;
+
+                assert_eq!(workers.len(), 1);
+                let inspected = &workers[0];
+                assert_eq!(inspected.hostname, "inspect-host");
+                assert_eq!(inspected.active_tasks, vec!["inspect-active-1"]);
+                assert_eq!(inspected.reserved_tasks.len(), 2);
+                assert_eq!(inspected.concurrency, 8);
+                assert_eq!(inspected.processed, 7);
+
+                // The temporary reply queue and its binding are removed
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+                let bindings: Vec<String> =
+                    conn.smembers("_kombu.binding.reply.celery.pidbox").await?;
+                assert!(bindings.is_empty());
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_inspect_workers_times_out_without_workers() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|db| async move {
+                let options = BrokerOptions {
+                    inspect_timeout: Duration::from_millis(200),
+                    ..BrokerOptions::default()
+                };
+                let broker =
+                    lazycelery::broker::redis::RedisBroker::connect_with_options(&db.url, options)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Redis not available for testing"))?;
+
+                let started = std::time::Instant::now();
+                let result = broker.inspect_workers().await;
+                assert!(matches!(
+                    result,
+                    Err(lazycelery::error::BrokerError::Timeout)
+                ));
+                assert!(started.elapsed() >= Duration::from_millis(200));
+                assert!(started.elapsed() < Duration::from_secs(2));
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_queue_history_reads_back_samples_in_window() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let start = chrono::DateTime::parse_from_rfc3339("2024-05-01T12:00:00Z")?
+                    .with_timezone(&chrono::Utc);
+                let clock = std::sync::Arc::new(FixedClock::new(start));
+                let options = BrokerOptions {
+                    queue_history: true,
+                    clock: clock.clone(),
+                    ..BrokerOptions::default()
+                };
+                let broker =
+                    lazycelery::broker::redis::RedisBroker::connect_with_options(&db.url, options)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Redis not available for testing"))?;
+
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+
+                // One sample per hour, with the queue growing by one message each time
+                for _ in 0..3 {
+                    let _: () = conn.rpush("celery", "message").await?;
+                    broker.get_queues().await?;
+                    clock.advance(chrono::Duration::hours(1));
+                }
+
+                let all = broker.get_queue_history("celery", start).await?;
+                let lengths: Vec<u64> = all.iter().map(|(_, length)| *length).collect();
+                assert_eq!(lengths, vec![1, 2, 3]);
+                assert_eq!(all[0].0, start);
+                assert_eq!(all[2].0, start + chrono::Duration::hours(2));
+
+                // Only samples at or after `since` are returned
+                let recent = broker
+                    .get_queue_history("celery", start + chrono::Duration::minutes(30))
+                    .await?;
+                let lengths: Vec<u64> = recent.iter().map(|(_, length)| *length).collect();
+                assert_eq!(lengths, vec![2, 3]);
+
+                assert!(broker
+                    .get_queue_history("celery", start + chrono::Duration::hours(3))
+                    .await?
+                    .is_empty());
+                assert!(broker
+                    .get_queue_history("celery; FLUSHALL", start)
+                    .await
+                    .is_err());
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_queue_history_is_off_by_default() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+                let _: () = conn.rpush("celery", "message").await?;
+
+                let broker = db.broker().await?;
+                broker.get_queues().await?;
+
+                let exists: bool = conn.exists("lazycelery:qlen:celery").await?;
+                assert!(!exists);
+                let since = chrono::Utc::now() - chrono::Duration::days(1);
+                assert!(broker.get_queue_history("celery", since).await?.is_empty());
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_queue_glob_limits_discovered_queues() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            with_test_db(|mut db| async move {
+                let client = db.client().await?;
+                let mut conn = client.get_multiplexed_tokio_connection().await?;
+                for queue in ["tenantA.celery", "tenantA.high", "tenantB.celery", "celery"] {
+                    let binding = format!("_kombu.binding.{queue}");
+                    let _: () = conn
+                        .sadd(&binding, format!("{queue}\x06\x16\x06\x16{queue}"))
+                        .await?;
+                    let _: () = conn.rpush(queue, "message").await?;
+                }
+
+                let options = BrokerOptions {
+                    queue_glob: Some("tenantA.*".to_string()),
+                    ..BrokerOptions::default()
+                };
+                let broker =
+                    lazycelery::broker::redis::RedisBroker::connect_with_options(&db.url, options)
+                        .await
+                        .map_err(|_| anyhow::anyhow!("Redis not available for testing"))?;
+                let names: Vec<String> = broker
+                    .get_queues()
+                    .await?
+                    .into_iter()
+                    .map(|queue| queue.name)
+                    .collect();
+                assert_eq!(names, vec!["tenantA.celery", "tenantA.high"]);
+
+                // Without a glob every tenant and the standard queues are listed
+                let broker = db.broker().await?;
+                let names: Vec<String> = broker
+                    .get_queues()
+                    .await?
+                    .into_iter()
+                    .map(|queue| queue.name)
+                    .collect();
+                for queue in ["celery", "tenantA.celery", "tenantB.celery"] {
+                    assert!(names.iter().any(|name| name == queue), "{queue} missing");
+                }
+
+                Ok(())
+            })
+            .await
+        }
+        .await,
+    )
+}
@@ -0,0 +1,80 @@
+//! Redis Database Selection Tests
+//!
+//! Separate Celery apps often share a Redis server on different databases.
+//! These tests connect to fixed database indexes, so they live in their own
+//! binary rather than alongside tests drawing databases from the shared counter.
+
+mod redis_test_utils;
+
+use anyhow::Result;
+use lazycelery::broker::redis::{database_index, RedisBroker};
+use lazycelery::broker::Broker;
+use redis::AsyncCommands;
+use redis_test_utils::*;
+
+fn fixed_db(database_id: u8) -> TestDatabase {
+    TestDatabase {
+        database_id,
+        url: format!("redis://127.0.0.1:6379/{database_id}"),
+        client: None,
+    }
+}
+
+#[test]
+fn test_database_index_from_url() {
+    assert_eq!(database_index("redis://127.0.0.1:6379/5").unwrap(), 5);
+    assert_eq!(database_index("redis://:secret@cache:6379/12").unwrap(), 12);
+    assert_eq!(database_index("redis://127.0.0.1:6379").unwrap(), 0);
+    assert_eq!(database_index("redis://127.0.0.1:6379/").unwrap(), 0);
+    assert!(database_index("redis://127.0.0.1:6379/not-a-db").is_err());
+}
+
+#[tokio::test]
+async fn test_broker_only_sees_the_database_in_its_url() -> Result<()> {
+    skip_if_redis_unavailable(
+        async {
+            let mut selected = fixed_db(5);
+            let mut other = fixed_db(6);
+            let (Ok(selected_client), Ok(other_client)) =
+                (selected.setup().await, other.setup().await)
+            else {
+                return Err(anyhow::anyhow!("Redis not available"));
+            };
+
+            let result = async {
+                TestDataBuilder::new(selected_client.clone())
+                    .add_basic_tasks()
+                    .await?;
+                TestDataBuilder::new(other_client.clone())
+                    .add_real_celery_data()
+                    .await?;
+                TestDataBuilder::new(other_client).add_queue_data().await?;
+
+                let broker = RedisBroker::connect("redis://127.0.0.1:6379/5").await?;
+
+                let tasks = broker.get_tasks().await?;
+                assert!(tasks.iter().any(|t| t.id.starts_with("basic-")));
+                assert!(!tasks.iter().any(|t| t.id.starts_with("real-")));
+
+                // Queues and workers of the other database stay invisible too
+                assert!(broker.get_queues().await?.iter().all(|q| q.length == 0));
+                let workers = broker.get_workers().await?;
+                assert!(!workers.iter().any(|w| w.hostname.contains("worker-host-1")));
+
+                // Writes land in the selected database only
+                let mut conn = selected_client.get_multiplexed_tokio_connection().await?;
+                broker.revoke_task("basic-success-1").await?;
+                let revoked: bool = conn.exists("revoked").await?;
+                assert!(revoked);
+
+                Ok(())
+            }
+            .await;
+
+            let _ = selected.cleanup().await;
+            let _ = other.cleanup().await;
+            result
+        }
+        .await,
+    )
+}
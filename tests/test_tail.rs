@@ -0,0 +1,40 @@
+use lazycelery::app::TaskTail;
+use lazycelery::models::Task;
+
+fn tasks(ids: &[&str]) -> Vec<Task> {
+    ids.iter()
+        .map(|id| Task::new(id.to_string(), "reports.build".to_string()))
+        .collect()
+}
+
+fn ids(tasks: Vec<&Task>) -> Vec<&str> {
+    tasks.into_iter().map(|t| t.id.as_str()).collect()
+}
+
+#[test]
+fn test_tail_emits_only_newly_seen_tasks() {
+    let mut tail = TaskTail::new();
+
+    let first = tasks(&["a", "b"]);
+    assert_eq!(ids(tail.new_tasks(&first)), vec!["a", "b"]);
+
+    // Tasks already printed are skipped, new ones keep listing order
+    let second = tasks(&["c", "a", "b", "d"]);
+    assert_eq!(ids(tail.new_tasks(&second)), vec!["c", "d"]);
+
+    let unchanged = tasks(&["c", "a", "b", "d"]);
+    assert!(tail.new_tasks(&unchanged).is_empty());
+
+    // A task dropping out of the listing and coming back isn't printed twice
+    let shrunk = tasks(&["d"]);
+    assert!(tail.new_tasks(&shrunk).is_empty());
+    let back = tasks(&["a", "e"]);
+    assert_eq!(ids(tail.new_tasks(&back)), vec!["e"]);
+}
+
+#[test]
+fn test_tail_ignores_duplicates_within_a_listing() {
+    let mut tail = TaskTail::new();
+    let listing = tasks(&["a", "a", "b"]);
+    assert_eq!(ids(tail.new_tasks(&listing)), vec!["a", "b"]);
+}
@@ -1,7 +1,37 @@
-use lazycelery::ui::layout::{centered_rect, create_main_layout};
+use async_trait::async_trait;
+use lazycelery::app::{App, LayoutMode, Tab};
+use lazycelery::broker::{Broker, BrokerConnector, ScanCap, ScanCaps};
+use lazycelery::config::KeyBinding;
+use lazycelery::error::BrokerError;
+use lazycelery::ui::draw;
+use lazycelery::ui::keymap::KeyMap;
+use lazycelery::ui::layout::{centered_rect, create_main_layout, MIN_HEIGHT_FOR_FOOTER};
+use lazycelery::ui::snapshot::buffer_to_string;
+use ratatui::backend::TestBackend;
 use ratatui::layout::Rect;
+use ratatui::Terminal;
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 mod test_broker_utils;
+use test_broker_utils::MockBrokerBuilder;
+
+fn buffer_text(terminal: &Terminal<TestBackend>) -> String {
+    buffer_to_string(terminal.backend().buffer())
+}
+
+/// Draw every tab of an app with data at the given terminal size
+async fn draw_all_tabs(width: u16, height: u16) -> (App, Terminal<TestBackend>) {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+
+    for tab in [Tab::Workers, Tab::Queues, Tab::Tasks] {
+        app.selected_tab = tab;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+    }
+    (app, terminal)
+}
 
 #[test]
 fn test_create_main_layout() {
@@ -94,3 +124,264 @@ fn test_centered_rect_with_offset() {
 // Note: get_key_hints is a private function in layout.rs
 // Testing it indirectly through integration tests would be more appropriate
 // Since it's mainly used in draw_status_bar function
+
+#[test]
+fn test_layout_mode_for_width() {
+    assert_eq!(LayoutMode::for_width(79, 80), LayoutMode::Compact);
+    assert_eq!(LayoutMode::for_width(80, 80), LayoutMode::Standard);
+    // A zero threshold disables the compact layout
+    assert_eq!(LayoutMode::for_width(20, 0), LayoutMode::Standard);
+}
+
+#[tokio::test]
+async fn test_narrow_terminal_uses_compact_layout() {
+    let (app, terminal) = draw_all_tabs(40, 20).await;
+    assert_eq!(app.layout_mode, LayoutMode::Compact);
+
+    // The task table keeps status and name, dropping the other columns
+    let text = buffer_text(&terminal);
+    assert!(text.contains("Status"));
+    assert!(text.contains("Name"));
+    assert!(!text.contains("Duration"));
+    assert!(!text.contains("[Tab] Switch"));
+}
+
+#[tokio::test]
+async fn test_wide_terminal_uses_standard_layout() {
+    let (app, terminal) = draw_all_tabs(120, 40).await;
+    assert_eq!(app.layout_mode, LayoutMode::Standard);
+
+    let text = buffer_text(&terminal);
+    assert!(text.contains("Worker"));
+    assert!(text.contains("Duration"));
+    assert!(text.contains("[Tab] Switch"));
+}
+
+#[tokio::test]
+async fn test_status_bar_flags_capped_scans() {
+    let broker = MockBrokerBuilder::with_basic_data()
+        .with_scan_caps(ScanCaps {
+            tasks: ScanCap::check(250, 100),
+            workers: None,
+            unreadable_tasks: 0,
+        })
+        .build();
+    let mut app = App::new(broker);
+    app.refresh_data().await.unwrap();
+    assert_eq!(app.scan_caps.tasks, ScanCap::check(250, 100));
+
+    let mut terminal = Terminal::new(TestBackend::new(200, 30)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("showing first 100 of 250+ tasks"));
+    assert!(!buffer_text(&terminal).contains("unreadable"));
+}
+
+#[tokio::test]
+async fn test_status_bar_counts_unreadable_tasks() {
+    let broker = MockBrokerBuilder::with_basic_data()
+        .with_scan_caps(ScanCaps {
+            unreadable_tasks: 12,
+            ..ScanCaps::default()
+        })
+        .build();
+    let mut app = App::new(broker);
+    app.refresh_data().await.unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(200, 30)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("12 tasks unreadable"));
+}
+
+/// Text of the row just above the status bar, where the key footer sits
+fn footer_row(terminal: &Terminal<TestBackend>) -> String {
+    let text = buffer_text(terminal);
+    let rows: Vec<&str> = text.lines().collect();
+    rows[rows.len() - 4].trim_end().to_string()
+}
+
+#[tokio::test]
+async fn test_key_footer_follows_tab_and_mode() {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+
+    app.selected_tab = Tab::Workers;
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let workers_footer = footer_row(&terminal);
+    assert!(workers_footer.starts_with("/ search  x revoke all  Tab switch tab"));
+    assert!(!workers_footer.contains("retry"));
+
+    app.selected_tab = Tab::Tasks;
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let tasks_footer = footer_row(&terminal);
+    assert!(tasks_footer.starts_with("/ search  r retry  x revoke  Enter details"));
+    assert_ne!(workers_footer, tasks_footer);
+
+    app.start_search();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert_eq!(
+        footer_row(&terminal),
+        "Enter confirm  Tab search args  Esc cancel"
+    );
+}
+
+#[tokio::test]
+async fn test_search_box_names_searched_fields() {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    app.selected_tab = Tab::Tasks;
+    app.start_search();
+    app.search_query = "12345".to_string();
+
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("Search (name, id): 12345_"));
+
+    app.toggle_search_args();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("Search (name, id, args): 12345_"));
+}
+
+#[test]
+fn test_header_shows_spinner_while_refreshing() {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(!buffer_text(&terminal).contains("refreshing"));
+
+    app.is_refreshing = true;
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("⠋ refreshing"));
+
+    app.advance_spinner();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("⠙ refreshing"));
+}
+
+#[tokio::test]
+async fn test_read_only_mode_hides_mutating_keys() {
+    let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    app.read_only = true;
+    app.selected_tab = Tab::Tasks;
+
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let text = buffer_text(&terminal);
+    assert!(text.contains("[READ ONLY]"));
+    for hidden in ["retry", "revoke", "Retry", "Revoke", "Replay", "Cleanup"] {
+        assert!(!text.contains(hidden), "{hidden:?} shown in read-only mode");
+    }
+    assert!(footer_row(&terminal).starts_with("/ search  Enter details  f follow"));
+}
+
+#[tokio::test]
+async fn test_header_shows_task_time_range() {
+    let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    app.selected_tab = Tab::Tasks;
+
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(!buffer_text(&terminal).contains("[last"));
+
+    app.cycle_task_time_range();
+    app.cycle_task_time_range();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let header = buffer_text(&terminal).lines().next().unwrap().to_string();
+    assert!(header.contains("[last 1h]"), "{header}");
+}
+
+#[tokio::test]
+async fn test_paused_indicator_in_status_bar() {
+    let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(!buffer_text(&terminal).contains("PAUSED"));
+
+    app.toggle_pause();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("PAUSED  Workers: 2"));
+
+    // Status messages don't hide it
+    app.set_status_message("Task 'task-1' marked for retry".to_string());
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(buffer_text(&terminal).contains("PAUSED  Task 'task-1'"));
+}
+
+#[tokio::test]
+async fn test_key_footer_shows_rebound_keys_and_hides_on_short_terminals() {
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    app.selected_tab = Tab::Tasks;
+    app.keymap = KeyMap::from_config(&BTreeMap::from([(
+        "retry".to_string(),
+        KeyBinding::One("R".to_string()),
+    )]))
+    .unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(footer_row(&terminal).contains("R retry"));
+
+    let mut terminal = Terminal::new(TestBackend::new(120, MIN_HEIGHT_FOR_FOOTER - 1)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    assert!(!buffer_text(&terminal).contains("R retry"));
+}
+
+#[tokio::test]
+async fn test_status_bar_hints_follow_key_bindings() {
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    app.selected_tab = Tab::Tasks;
+    app.keymap = KeyMap::from_config(&BTreeMap::from([
+        ("revoke".to_string(), KeyBinding::One("X".to_string())),
+        ("rate_limit".to_string(), KeyBinding::Many(Vec::new())),
+    ]))
+    .unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(250, 30)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let text = buffer_text(&terminal);
+    assert!(text.contains("[X] Revoke"));
+    assert!(!text.contains("[x] Revoke"));
+    assert!(text.contains("[r] Retry"));
+    // Actions bound to nothing get no hint
+    assert!(!text.contains("Rate limit"));
+}
+
+/// Connector whose broker can never be reached
+struct RefusingConnector;
+
+#[async_trait]
+impl BrokerConnector for RefusingConnector {
+    async fn connect(&self, _url: &str) -> Result<Box<dyn Broker>, BrokerError> {
+        Err(BrokerError::ConnectionError(
+            "Connection refused".to_string(),
+        ))
+    }
+}
+
+#[test]
+fn test_disconnected_app_shows_setup_guide() {
+    let error = BrokerError::ConnectionError("Connection refused".to_string());
+    let mut app = App::disconnected(
+        "redis://localhost:6379/0".to_string(),
+        Arc::new(RefusingConnector),
+        &error,
+    );
+    assert!(!app.is_connected());
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| draw(f, &mut app)).unwrap();
+    let text = buffer_text(&terminal);
+    assert!(text.contains("Not connected"));
+    assert!(text.contains("Failed to connect to Redis broker at redis://localhost:6379/0"));
+    assert!(text.contains("Connection failed: Connection refused"));
+    assert!(text.contains("Quick Setup Guide"));
+    assert!(text.contains("Press Ctrl-r to try connecting again"));
+    assert!(footer_row(&terminal).starts_with("Ctrl-r reconnect"));
+}
@@ -1,8 +1,18 @@
-use lazycelery::app::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use lazycelery::app::{App, PendingAction};
+use lazycelery::broker::Broker;
+use lazycelery::config::KeyBinding;
 use lazycelery::models::{Task, TaskStatus};
-use lazycelery::ui::modals::{draw_confirmation_dialog, draw_help, draw_task_details_modal};
+use lazycelery::ui::events::handle_key_event;
+use lazycelery::ui::keymap::KeyMap;
+use lazycelery::ui::modals::{
+    draw_audit_log, draw_confirmation_dialog, draw_help, draw_task_details_modal,
+    task_details_line_count,
+};
+use lazycelery::ui::snapshot::buffer_to_string;
 use ratatui::backend::TestBackend;
 use ratatui::Terminal;
+use std::collections::BTreeMap;
 
 mod test_broker_utils;
 use test_broker_utils::MockBrokerBuilder;
@@ -29,6 +39,22 @@ fn test_modal_content_generation() {
         kwargs: "{\"key\": \"value\"}".to_string(),
         result: Some("Task completed successfully".to_string()),
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     app.selected_task_details = Some(test_task.clone());
@@ -81,6 +107,22 @@ fn test_modal_state_transitions() {
             "Traceback (most recent call last):\n  File \"test.py\", line 1\nError: Test error"
                 .to_string(),
         ),
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
     app.selected_task_details = Some(task);
 
@@ -104,6 +146,22 @@ fn test_task_details_with_failure_traceback() {
         kwargs: "{\"debug\": true}".to_string(),
         result: None,
         traceback: Some("Traceback (most recent call last):\n  File \"worker.py\", line 42, in execute\n    raise ValueError(\"Test failure\")\nValueError: Test failure".to_string()),
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     app.selected_task_details = Some(failed_task.clone());
@@ -154,6 +212,22 @@ fn test_task_details_various_statuses() {
             } else {
                 None
             },
+            result_full_len: None,
+            traceback_full_len: None,
+            args_full_len: None,
+            kwargs_full_len: None,
+            eta: None,
+            root_id: None,
+            parent_id: None,
+            group_id: None,
+            queue: None,
+            retries: 0,
+            max_retries: None,
+            priority: None,
+            children: Vec::new(),
+            duration: None,
+            result_kind: None,
+            looks_like_error: false,
         };
 
         app.selected_task_details = Some(task.clone());
@@ -220,6 +294,22 @@ fn test_task_details_edge_cases() {
         kwargs: "".to_string(), // Empty kwargs
         result: None,
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     app.selected_task_details = Some(minimal_task.clone());
@@ -245,6 +335,22 @@ fn test_task_details_edge_cases() {
             "Very long result text that might wrap across multiple lines in the UI".to_string(),
         ),
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     };
 
     app.selected_task_details = Some(long_task.clone());
@@ -270,7 +376,7 @@ fn test_modal_rendering_integration() {
     terminal
         .draw(|f| {
             app.show_help = true;
-            draw_help(f);
+            draw_help(f, &app);
         })
         .unwrap();
 
@@ -296,6 +402,22 @@ fn test_modal_rendering_integration() {
         kwargs: "{}".to_string(),
         result: Some("OK".to_string()),
         traceback: None,
+        result_full_len: None,
+        traceback_full_len: None,
+        args_full_len: None,
+        kwargs_full_len: None,
+        eta: None,
+        root_id: None,
+        parent_id: None,
+        group_id: None,
+        queue: None,
+        retries: 0,
+        max_retries: None,
+        priority: None,
+        children: Vec::new(),
+        duration: None,
+        result_kind: None,
+        looks_like_error: false,
     });
 
     terminal
@@ -304,3 +426,196 @@ fn test_modal_rendering_integration() {
         })
         .unwrap();
 }
+
+fn buffer_text(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn test_help_lists_the_keys_actions_are_bound_to() {
+    let mut terminal = Terminal::new(TestBackend::new(200, 80)).unwrap();
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.keymap = KeyMap::from_config(&BTreeMap::from([
+        ("revoke".to_string(), KeyBinding::One("X".to_string())),
+        ("purge".to_string(), KeyBinding::Many(Vec::new())),
+    ]))
+    .unwrap();
+
+    terminal.draw(|f| draw_help(f, &app)).unwrap();
+    let text = buffer_text(&terminal);
+    assert!(text.contains("X         - Revoke task (in Tasks tab)"));
+    assert!(!text.contains("x         - Revoke task"));
+    assert!(text.contains("q/Ctrl-c  - Quit application"));
+    // Actions bound to nothing are left out
+    assert!(!text.contains("Purge queue"));
+}
+
+#[test]
+fn test_task_details_modal_scrolls_long_traceback() {
+    let backend = TestBackend::new(80, 24);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    let broker = MockBrokerBuilder::empty().build();
+    let mut app = App::new(broker);
+    let mut task = Task::new("scroll-task".to_string(), "tasks.crash".to_string());
+    task.status = TaskStatus::Failure;
+    task.traceback = Some(
+        (1..=60)
+            .map(|n| format!("frame {n:02}"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    app.tasks = vec![task];
+    app.selected_tab = lazycelery::app::Tab::Tasks;
+    app.show_task_details();
+    assert_eq!(app.task_details_scroll, 0);
+
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(buffer_text(&terminal).contains("ID: scroll-task"));
+    assert!(!buffer_text(&terminal).contains("frame 60"));
+
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    handle_key_event(key(KeyCode::Down), &mut app);
+    assert_eq!(app.task_details_scroll, 1);
+    handle_key_event(key(KeyCode::PageDown), &mut app);
+    assert_eq!(app.task_details_scroll, 11);
+    handle_key_event(key(KeyCode::Up), &mut app);
+    assert_eq!(app.task_details_scroll, 10);
+    assert!(app.show_task_details);
+
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(!buffer_text(&terminal).contains("ID: scroll-task"));
+
+    // Scrolling clamps at the last line of content
+    let line_count = task_details_line_count(&app);
+    for _ in 0..20 {
+        handle_key_event(key(KeyCode::PageDown), &mut app);
+    }
+    assert_eq!(app.task_details_scroll as usize, line_count - 1);
+
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(buffer_text(&terminal).contains("Save full metadata"));
+
+    for _ in 0..20 {
+        handle_key_event(key(KeyCode::PageUp), &mut app);
+    }
+    assert_eq!(app.task_details_scroll, 0);
+
+    // Closing resets the scroll position
+    handle_key_event(key(KeyCode::PageDown), &mut app);
+    handle_key_event(key(KeyCode::Char('q')), &mut app);
+    assert!(!app.show_task_details);
+    assert_eq!(app.task_details_scroll, 0);
+}
+
+/// Broker storing `metadata` as the raw result of every task
+fn raw_metadata_broker(metadata: String) -> Box<dyn Broker> {
+    MockBrokerBuilder::new()
+        .with_raw_metadata_hook(move |_| Ok(Some(metadata.clone())))
+        .build()
+}
+
+fn app_showing_details(broker: Box<dyn Broker>) -> App {
+    let mut app = App::new(broker);
+    app.tasks = vec![Task::new("raw-task".to_string(), "tasks.add".to_string())];
+    app.selected_tab = lazycelery::app::Tab::Tasks;
+    app.show_task_details();
+    app
+}
+
+#[tokio::test]
+async fn test_raw_view_toggle_fetches_and_renders_stored_metadata() {
+    let backend = TestBackend::new(100, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    let mut app = app_showing_details(raw_metadata_broker(
+        r#"{"status": "SUCCESS", "result": 3, "task_id": "raw-task"}"#.to_string(),
+    ));
+
+    let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+    handle_key_event(key(KeyCode::Char('r')), &mut app);
+    assert!(app.show_task_details);
+    assert!(app.show_task_details_raw);
+
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(buffer_text(&terminal).contains("Loading stored metadata"));
+
+    app.load_task_details_raw().await;
+    let raw = app.task_details_raw.as_deref().unwrap();
+    assert!(raw.contains("\n  \"status\": \"SUCCESS\""));
+
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    let text = buffer_text(&terminal);
+    assert!(text.contains("stored metadata"));
+    assert!(text.contains("  \"result\": 3,"));
+    assert!(text.contains("[r] Parsed fields"));
+
+    // Switching back shows the parsed fields and drops the fetched text
+    handle_key_event(key(KeyCode::Char('r')), &mut app);
+    assert!(!app.show_task_details_raw);
+    assert!(app.task_details_raw.is_none());
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(buffer_text(&terminal).contains("ID: raw-task"));
+}
+
+#[tokio::test]
+async fn test_raw_view_truncates_large_metadata() {
+    let blob = format!(r#"{{"result": "{}"}}"#, "x".repeat(200_000));
+    let mut app = app_showing_details(raw_metadata_broker(blob.clone()));
+    app.toggle_task_details_raw();
+    app.load_task_details_raw().await;
+
+    let raw = app.task_details_raw.as_deref().unwrap();
+    assert!(raw.len() < blob.len());
+    assert!(app.task_details_raw_len > raw.len());
+
+    let backend = TestBackend::new(100, 30);
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.draw(|f| draw_task_details_modal(f, &app)).unwrap();
+    assert!(buffer_text(&terminal).contains("Metadata truncated for display"));
+}
+
+#[tokio::test]
+async fn test_raw_view_falls_back_when_metadata_is_unavailable() {
+    let mut app = app_showing_details(MockBrokerBuilder::for_error_tests());
+    app.toggle_task_details_raw();
+    app.load_task_details_raw().await;
+
+    assert!(app.show_task_details);
+    assert!(!app.show_task_details_raw);
+    assert!(app
+        .status_message
+        .contains("Failed to fetch metadata of task 'raw-task'"));
+}
+
+#[tokio::test]
+async fn test_audit_log_overlay_lists_newest_first() {
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.pending_action = Some(PendingAction::PurgeQueue("celery".to_string()));
+    app.execute_pending_action().await.unwrap();
+    app.pending_action = Some(PendingAction::RevokeTask("task-003".to_string()));
+    app.execute_pending_action().await.unwrap();
+
+    handle_key_event(
+        KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT),
+        &mut app,
+    );
+    assert!(app.show_audit_log);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| draw_audit_log(f, &app)).unwrap();
+    let text = buffer_to_string(terminal.backend().buffer());
+    assert!(text.contains("Audit Log (2)"));
+    let revoke = text.find("revoke task-003").unwrap();
+    let purge = text.find("purge celery Purged 42 messages").unwrap();
+    assert!(revoke < purge);
+
+    // Any other key closes it
+    handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::empty()), &mut app);
+    assert!(!app.show_audit_log);
+}
@@ -0,0 +1,135 @@
+use lazycelery::app::Tab;
+use lazycelery::models::ScheduledEntry;
+
+mod test_broker_utils;
+mod ui_test_utils;
+use test_broker_utils::MockBrokerBuilder;
+use ui_test_utils::{assert_text_at, position_of, render_tab};
+
+const WIDTH: u16 = 100;
+const HEIGHT: u16 = 24;
+
+/// Header and status bar rows shared by every tab
+fn assert_chrome(frame: &str) {
+    assert_text_at(
+        frame,
+        &[
+            (1, 2, "Workers"),
+            (1, 12, "Queues"),
+            (1, 21, "Tasks"),
+            (1, 29, "Scheduled"),
+            (21, 1, "Workers: 2 | Tasks: 2 | Queues: 2"),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn test_workers_tab_snapshot() {
+    let frame = render_tab(
+        MockBrokerBuilder::with_basic_data().build(),
+        Tab::Workers,
+        WIDTH,
+        HEIGHT,
+    )
+    .await;
+
+    assert_chrome(&frame);
+    assert_text_at(
+        &frame,
+        &[
+            (3, 1, "Cluster: 1/2 online | Processed: 150 | Failed: 7"),
+            (4, 2, "Workers (2)"),
+            (5, 3, "test-worker-1"),
+            (6, 3, "test-worker-2"),
+            (5, 41, "Hostname: test-worker-1"),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn test_queues_tab_snapshot() {
+    let frame = render_tab(
+        MockBrokerBuilder::with_basic_data().build(),
+        Tab::Queues,
+        WIDTH,
+        HEIGHT,
+    )
+    .await;
+
+    assert_chrome(&frame);
+    assert_text_at(
+        &frame,
+        &[
+            (3, 2, "Queues (2)"),
+            (4, 1, "default"),
+            (5, 1, "priority"),
+            (4, 41, "Queue Name: default"),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn test_tasks_tab_snapshot() {
+    let frame = render_tab(
+        MockBrokerBuilder::with_basic_data().build(),
+        Tab::Tasks,
+        WIDTH,
+        HEIGHT,
+    )
+    .await;
+
+    assert_chrome(&frame);
+    assert_text_at(
+        &frame,
+        &[
+            (3, 2, "Tasks (2)"),
+            (4, 1, "ID"),
+            (4, 21, "Name"),
+            (4, 50, "Status"),
+            (6, 1, "task-1"),
+            (6, 21, "test.task.example"),
+            (7, 1, "task-2"),
+            (7, 21, "test.task.completed"),
+        ],
+    );
+}
+
+#[tokio::test]
+async fn test_scheduled_tab_snapshot() {
+    let broker = MockBrokerBuilder::with_basic_data()
+        .with_scheduled(vec![ScheduledEntry {
+            name: "cleanup-every-hour".to_string(),
+            task: "tasks.cleanup".to_string(),
+            schedule: "every 1h".to_string(),
+            next_run: None,
+        }])
+        .build();
+    let frame = render_tab(broker, Tab::Scheduled, WIDTH, HEIGHT).await;
+
+    assert_chrome(&frame);
+    assert_text_at(
+        &frame,
+        &[
+            (3, 2, "Scheduled (1)"),
+            (4, 1, "Name"),
+            (6, 1, "cleanup-every-hour"),
+        ],
+    );
+    let (row, _) = position_of(&frame, "every 1h").unwrap();
+    assert_eq!(row, 6);
+}
+
+#[tokio::test]
+async fn test_snapshot_follows_terminal_size() {
+    let broker = MockBrokerBuilder::with_basic_data().build();
+    let frame = render_tab(broker, Tab::Workers, 120, 30).await;
+
+    let lines: Vec<&str> = frame.lines().collect();
+    assert_eq!(lines.len(), 30);
+    assert!(lines.iter().all(|line| line.chars().count() == 120));
+    // The status bar stays on the second to last row
+    assert_eq!(
+        position_of(&frame, "Workers: 2").map(|(row, _)| row),
+        Some(27)
+    );
+}
@@ -0,0 +1,62 @@
+use lazycelery::config::{CliOverrides, Config};
+use lazycelery::ui::terminal::{enter_screen, restore_terminal};
+
+const ENABLE_MOUSE: &str = "\x1b[?1000h";
+const DISABLE_MOUSE: &str = "\x1b[?1000l";
+
+#[test]
+fn test_restore_terminal_is_idempotent() {
+    let mut output = Vec::new();
+
+    // Works without the terminal having been set up, and more than once
+    restore_terminal(&mut output, true).unwrap();
+    let first_len = output.len();
+    restore_terminal(&mut output, true).unwrap();
+
+    assert!(first_len > 0);
+    assert_eq!(output.len(), first_len * 2);
+}
+
+#[test]
+fn test_restore_terminal_leaves_alternate_screen_and_shows_cursor() {
+    let mut output = Vec::new();
+    restore_terminal(&mut output, true).unwrap();
+
+    let written = String::from_utf8(output).unwrap();
+    assert!(
+        written.contains("\x1b[?1049l"),
+        "leaves the alternate screen"
+    );
+    assert!(written.contains("\x1b[?25h"), "shows the cursor");
+}
+
+#[test]
+fn test_mouse_capture_follows_config() {
+    let mut config = Config::default();
+
+    let mut output = Vec::new();
+    enter_screen(&mut output, config.ui.mouse).unwrap();
+    restore_terminal(&mut output, config.ui.mouse).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(
+        written.contains("\x1b[?1049h"),
+        "enters the alternate screen"
+    );
+    assert!(written.contains(ENABLE_MOUSE));
+    assert!(written.contains(DISABLE_MOUSE));
+
+    config.apply_cli_overrides(&CliOverrides {
+        no_mouse: true,
+        ..Default::default()
+    });
+    let mut output = Vec::new();
+    enter_screen(&mut output, config.ui.mouse).unwrap();
+    restore_terminal(&mut output, config.ui.mouse).unwrap();
+    let written = String::from_utf8(output).unwrap();
+    assert!(
+        written.contains("\x1b[?1049h"),
+        "enters the alternate screen"
+    );
+    assert!(!written.contains(ENABLE_MOUSE));
+    assert!(!written.contains(DISABLE_MOUSE));
+}
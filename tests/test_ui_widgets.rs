@@ -1,5 +1,14 @@
-use lazycelery::models::{TaskStatus, Worker, WorkerStatus};
+use chrono::Utc;
+use lazycelery::app::{App, Tab};
+use lazycelery::config::TaskColumn;
+use lazycelery::models::{Task, TaskStatus, Worker, WorkerStatus};
+use lazycelery::ui::widgets::{TaskWidget, Widget};
+use ratatui::backend::TestBackend;
 use ratatui::style::Color;
+use ratatui::Terminal;
+
+mod test_broker_utils;
+use test_broker_utils::MockBrokerBuilder;
 
 // Test for business logic without UI rendering
 mod widget_logic_tests {
@@ -53,8 +62,10 @@ mod widget_logic_tests {
             hostname: "test-worker".to_string(),
             status: WorkerStatus::Online,
             concurrency: 4,
+            pool_type: None,
             queues: vec![],
             active_tasks: vec!["task1".to_string(), "task2".to_string()],
+            reserved_tasks: vec![],
             processed: 100,
             failed: 5,
         };
@@ -114,3 +125,446 @@ mod widget_logic_tests {
         assert_eq!(formatted, "02:30:45");
     }
 }
+
+#[test]
+fn test_task_table_header_follows_configured_columns() {
+    assert_eq!(
+        TaskWidget::header_cells(&TaskColumn::DEFAULTS),
+        vec!["ID", "Name", "Status", "Worker", "Duration"]
+    );
+    assert_eq!(
+        TaskWidget::header_cells(&[
+            TaskColumn::Id,
+            TaskColumn::Name,
+            TaskColumn::Status,
+            TaskColumn::Duration,
+            TaskColumn::Eta,
+        ]),
+        vec!["ID", "Name", "Status", "Duration", "ETA"]
+    );
+}
+
+fn app_with_statuses(statuses: &[TaskStatus]) -> App {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.tasks = statuses
+        .iter()
+        .enumerate()
+        .map(|(idx, status)| {
+            let mut task = Task::new(format!("task-{idx}"), format!("tasks.kind_{idx}"));
+            task.status = status.clone();
+            task
+        })
+        .collect();
+    app.selected_tab = Tab::Tasks;
+    app
+}
+
+fn render_task_list(app: &App, width: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, 12)).unwrap();
+    terminal
+        .draw(|f| TaskWidget::draw_list(f, app, f.area()))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect::<String>()
+}
+
+#[test]
+fn test_task_status_counts() {
+    let app = app_with_statuses(&[
+        TaskStatus::Success,
+        TaskStatus::Success,
+        TaskStatus::Failure,
+        TaskStatus::Pending,
+    ]);
+    let tasks: Vec<&Task> = app.tasks.iter().collect();
+
+    let counts = TaskWidget::status_counts(&tasks);
+    assert_eq!(counts[0], (TaskStatus::Success, 2));
+    assert_eq!(counts[1], (TaskStatus::Failure, 1));
+    assert_eq!(counts[3], (TaskStatus::Pending, 1));
+    assert_eq!(counts[4], (TaskStatus::Retry, 0));
+}
+
+#[test]
+fn test_task_status_legend_is_rendered() {
+    let mut app = app_with_statuses(&[
+        TaskStatus::Success,
+        TaskStatus::Success,
+        TaskStatus::Success,
+        TaskStatus::Failure,
+        TaskStatus::Retry,
+    ]);
+
+    let text = render_task_list(&app, 100);
+    assert!(text.contains("✓3"));
+    assert!(text.contains("✗1"));
+    assert!(text.contains("↻1"));
+    assert!(text.contains("⊘0"));
+
+    // Counts follow the search filter
+    app.is_searching = true;
+    app.search_query = "task-3".to_string();
+    let text = render_task_list(&app, 100);
+    assert!(text.contains("✓0"));
+    assert!(text.contains("✗1"));
+}
+
+#[test]
+fn test_task_status_legend_hidden_when_narrow() {
+    let app = app_with_statuses(&[TaskStatus::Success]);
+    let text = render_task_list(&app, 30);
+    assert!(!text.contains("✓1"));
+}
+
+fn app_with_stuck_task() -> App {
+    let mut app = app_with_statuses(&[TaskStatus::Active, TaskStatus::Active, TaskStatus::Success]);
+    app.tasks[0].timestamp = Utc::now() - chrono::Duration::hours(5);
+    app.tasks[2].timestamp = Utc::now() - chrono::Duration::hours(5);
+    app
+}
+
+#[test]
+fn test_duration_column_shows_known_runtimes_only() {
+    let mut app = app_with_statuses(&[TaskStatus::Success, TaskStatus::Success]);
+    app.tasks[0].duration = Some(std::time::Duration::from_millis(1500));
+    app.tasks[1].timestamp = Utc::now() - chrono::Duration::hours(5);
+
+    let text = render_task_list(&app, 120);
+    assert!(text.contains("1.500s"));
+    // Time since completion isn't passed off as a duration
+    assert!(!text.contains("05:00:0"));
+}
+
+#[test]
+fn test_stuck_tasks_are_marked() {
+    let app = app_with_stuck_task();
+    let text = render_task_list(&app, 100);
+    assert_eq!(text.matches("⚠ Active").count(), 1);
+}
+
+#[test]
+fn test_stuck_filter_shows_only_stuck_tasks() {
+    let mut app = app_with_stuck_task();
+
+    app.toggle_stuck_filter();
+    let ids: Vec<&str> = app
+        .get_filtered_tasks()
+        .iter()
+        .map(|t| t.id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["task-0"]);
+    assert!(render_task_list(&app, 100).contains("Tasks (stuck: 1/3)"));
+
+    app.clear_stuck_filter();
+    assert_eq!(app.get_filtered_tasks().len(), 3);
+}
+
+#[test]
+fn test_stuck_threshold_comes_from_config() {
+    let mut app = app_with_stuck_task();
+    let mut config = lazycelery::config::Config::default();
+    config.ui.stuck_task_threshold = 0;
+    app.apply_config(&config);
+
+    app.toggle_stuck_filter();
+    assert!(app.get_filtered_tasks().is_empty());
+}
+
+#[test]
+fn test_scheduled_entries_are_rendered() {
+    use lazycelery::models::ScheduledEntry;
+    use lazycelery::ui::widgets::ScheduledWidget;
+
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    app.selected_tab = Tab::Scheduled;
+    app.scheduled = vec![ScheduledEntry {
+        name: "nightly-report".to_string(),
+        task: "reports.nightly".to_string(),
+        schedule: "crontab 30 2 * * *".to_string(),
+        next_run: Some(Utc::now() + chrono::Duration::hours(3)),
+    }];
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+    terminal
+        .draw(|f| ScheduledWidget::draw(f, &app, f.area()))
+        .unwrap();
+    let text: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+
+    assert!(text.contains("Scheduled (1)"));
+    assert!(text.contains("nightly-report"));
+    assert!(text.contains("crontab 30 2 * * *"));
+    assert!(text.contains("reports.nightly"));
+    assert!(text.contains("in 2h") || text.contains("in 3h"));
+}
+
+#[test]
+fn test_retries_indicator_and_exhausted_highlight() {
+    let mut app = app_with_statuses(&[TaskStatus::Retry, TaskStatus::Failure]);
+    app.tasks[0].retries = 1;
+    app.tasks[0].max_retries = Some(3);
+    app.tasks[1].retries = 3;
+    app.tasks[1].max_retries = Some(3);
+
+    let text = render_task_list(&app, 120);
+    assert!(text.contains("Retry ↻1/3"));
+    assert!(text.contains("Failure ↻3/3"));
+
+    assert_eq!(
+        TaskWidget::retries_style(&app.tasks[0]).fg,
+        Some(Color::Magenta)
+    );
+    assert_eq!(
+        TaskWidget::retries_style(&app.tasks[1]).fg,
+        Some(Color::Red)
+    );
+}
+
+#[test]
+fn test_multiline_rows_show_args_preview() {
+    let mut app = app_with_statuses(&[TaskStatus::Success, TaskStatus::Pending]);
+    app.tasks[0].args = "[\"invoice-2024\", 42]".to_string();
+    app.tasks[1].kwargs = "{\"retry\": true}".to_string();
+
+    let text = render_task_list(&app, 120);
+    assert!(!text.contains("[\"invoice-2024\", 42]"));
+
+    app.task_rows_multiline = true;
+    let mut terminal = Terminal::new(TestBackend::new(120, 12)).unwrap();
+    terminal
+        .draw(|f| TaskWidget::draw_list(f, &app, f.area()))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+    assert!(text.contains("[\"invoice-2024\", 42]"));
+    assert!(text.contains("{\"retry\": true}"));
+
+    // The first task takes the two lines under the header, and the selection
+    // highlight covers both of them
+    let row_text = |y: u16| -> String {
+        (1..119)
+            .map(|x| buffer[(x, y)].symbol().to_string())
+            .collect()
+    };
+    assert!(row_text(3).contains("tasks.kind_0"));
+    assert!(row_text(4).contains("invoice-2024"));
+    assert!(row_text(5).contains("tasks.kind_1"));
+    for y in [3, 4] {
+        assert!((1..119).all(|x| buffer[(x, y)].bg == Color::DarkGray));
+    }
+    assert!((1..119).all(|x| buffer[(x, 5)].bg != Color::DarkGray));
+}
+
+fn render_worker_details(app: &App) -> String {
+    use lazycelery::ui::widgets::WorkerWidget;
+
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    terminal
+        .draw(|f| WorkerWidget::draw_details(f, app, f.area()))
+        .unwrap();
+    terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect()
+}
+
+#[test]
+fn test_worker_concurrency_shows_pool_type_when_known() {
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    let mut worker = Worker::new("worker-1".to_string());
+    worker.concurrency = 16;
+    app.workers = vec![worker];
+
+    // Workers found only in task metadata have no pool type
+    let text = render_worker_details(&app);
+    assert!(text.contains("Concurrency: 16 "));
+    assert!(!text.contains("prefork"));
+
+    app.workers[0].pool_type = Some("prefork".to_string());
+    let text = render_worker_details(&app);
+    assert!(text.contains("Concurrency: 16 prefork"));
+}
+
+#[test]
+fn test_relative_times_follow_injected_clock() {
+    use lazycelery::test_support::FixedClock;
+    use std::sync::Arc;
+
+    let start = "2024-05-01T12:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    let clock = Arc::new(FixedClock::new(start));
+    let mut app =
+        app_with_statuses(&[TaskStatus::Success, TaskStatus::Active]).with_clock(clock.clone());
+    app.task_columns = vec![TaskColumn::Id, TaskColumn::Status, TaskColumn::Timestamp];
+    app.tasks[0].timestamp = start - chrono::Duration::minutes(3);
+    app.tasks[1].timestamp = start - chrono::Duration::minutes(59);
+
+    let text = render_task_list(&app, 120);
+    assert!(text.contains("3m ago"));
+    assert!(text.contains("59m ago"));
+    assert!(!text.contains("⚠ Active"));
+
+    // Moving the clock ages the tasks, past the 1h stuck threshold
+    clock.advance(chrono::Duration::minutes(2));
+    let text = render_task_list(&app, 120);
+    assert!(text.contains("5m ago"));
+    assert!(text.contains("1h ago"));
+    assert!(text.contains("⚠ Active"));
+}
+
+#[test]
+fn test_queue_list_title_shows_queue_scope() {
+    use lazycelery::config::Config;
+    use lazycelery::ui::widgets::QueueWidget;
+
+    let render = |app: &App| -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 10)).unwrap();
+        terminal
+            .draw(|f| QueueWidget::draw_list(f, app, f.area()))
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    };
+
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    let mut config = Config::default();
+    app.apply_config(&config);
+    assert!(!render(&app).contains("only"));
+
+    config.broker.queue_glob = "tenantA.*".to_string();
+    app.apply_config(&config);
+    assert!(render(&app).contains("Queues (0) · by length · only tenantA.*"));
+}
+
+#[test]
+fn test_queue_discrepancy_is_flagged() {
+    use lazycelery::models::{Queue, QueueDiscrepancy};
+    use lazycelery::ui::widgets::QueueWidget;
+
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    let mut odd = Queue::new("tenantA.jobs".to_string());
+    odd.discrepancy = Some(QueueDiscrepancy::UnexpectedKeyType("hash".to_string()));
+    app.queues = vec![odd, Queue::new("celery".to_string())];
+    app.selected_tab = Tab::Queues;
+    app.selected_queue = app
+        .get_sorted_queues()
+        .iter()
+        .position(|q| q.name == "tenantA.jobs")
+        .unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(140, 20)).unwrap();
+    terminal
+        .draw(|f| QueueWidget::draw(f, &app, f.area()))
+        .unwrap();
+    let text: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(text.contains("tenantA.jobs   0 ?"));
+    assert!(!text.contains("celery   0 ?"));
+    assert!(text.contains("? Bound, but the queue key is a Redis hash, not a list or stream"));
+}
+
+#[tokio::test]
+async fn test_overview_summarizes_queues_workers_and_failures() {
+    use lazycelery::ui::widgets::OverviewWidget;
+
+    let mut app = App::new(MockBrokerBuilder::with_basic_data().build());
+    app.refresh_data().await.unwrap();
+    let mut failed = app.tasks[0].clone();
+    failed.id = "task-failed".to_string();
+    failed.name = "billing.charge_card".to_string();
+    failed.status = TaskStatus::Failure;
+    app.tasks.push(failed);
+    app.selected_tab = Tab::Overview;
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal
+        .draw(|f| OverviewWidget::draw(f, &app, f.area()))
+        .unwrap();
+    let text: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(text.contains("default   10"));
+    assert!(text.contains("Cluster: 1/2 online"));
+    assert!(text.contains("○ test-worker-2"));
+    assert!(text.contains("billing.charge_card"));
+    assert!(text.contains("task-failed"));
+    // Only failures are listed
+    assert!(!text.contains("test.task.completed"));
+}
+
+#[test]
+fn test_large_counts_are_humanized_in_lists_only() {
+    use lazycelery::models::Queue;
+    use lazycelery::ui::widgets::{QueueWidget, WorkerWidget};
+
+    let mut app = App::new(MockBrokerBuilder::empty().build());
+    let mut queue = Queue::new("celery".to_string());
+    queue.length = 15_234;
+    app.queues = vec![queue];
+    let mut worker = Worker::new("worker-1".to_string());
+    worker.status = WorkerStatus::Online;
+    worker.processed = 1_100_000;
+    worker.failed = 2_500;
+    app.workers = vec![worker];
+
+    let render = |app: &App| -> String {
+        let mut terminal = Terminal::new(TestBackend::new(140, 20)).unwrap();
+        terminal
+            .draw(|f| {
+                let [queues, workers] = ratatui::layout::Layout::vertical([
+                    ratatui::layout::Constraint::Length(8),
+                    ratatui::layout::Constraint::Min(0),
+                ])
+                .areas(f.area());
+                QueueWidget::draw(f, app, queues);
+                WorkerWidget::draw(f, app, workers);
+            })
+            .unwrap();
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect()
+    };
+
+    let text = render(&app);
+    assert!(text.contains("celery   15.2k"));
+    assert!(text.contains("Processed: 1.1M | Failed: 2.5k"));
+    // The details keep the exact values
+    assert!(text.contains("Messages: 15234"));
+    assert!(text.contains("Processed: 1100000"));
+
+    app.humanize_numbers = false;
+    let text = render(&app);
+    assert!(text.contains("celery   15234"));
+    assert!(text.contains("Processed: 1100000 | Failed: 2500"));
+}
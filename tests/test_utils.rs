@@ -1,5 +1,9 @@
 use chrono::{Duration, TimeZone, Utc};
-use lazycelery::utils::formatting::{format_duration, format_timestamp, truncate_string};
+use lazycelery::config::TimestampMode;
+use lazycelery::utils::formatting::{
+    format_duration, format_interval, format_relative_time, format_time, format_timestamp,
+    parse_interval, truncate_string,
+};
 
 #[test]
 fn test_format_duration() {
@@ -25,6 +29,44 @@ fn test_format_timestamp() {
     assert_eq!(format_timestamp(timestamp), "2023-12-31 23:59:59");
 }
 
+#[test]
+fn test_format_relative_time() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 45).unwrap();
+    assert_eq!(format_relative_time(now, now), "just now");
+    assert_eq!(
+        format_relative_time(now - Duration::seconds(42), now),
+        "42s ago"
+    );
+    assert_eq!(
+        format_relative_time(now - Duration::minutes(3), now),
+        "3m ago"
+    );
+    assert_eq!(
+        format_relative_time(now - Duration::hours(5), now),
+        "5h ago"
+    );
+    assert_eq!(format_relative_time(now - Duration::days(2), now), "2d ago");
+    assert_eq!(
+        format_relative_time(now + Duration::minutes(10), now),
+        "in 10m"
+    );
+}
+
+#[test]
+fn test_format_time_honors_mode() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 15, 14, 30, 45).unwrap();
+    let timestamp = now - Duration::minutes(3);
+
+    assert_eq!(
+        format_time(timestamp, now, TimestampMode::Relative),
+        "3m ago"
+    );
+    assert_eq!(
+        format_time(timestamp, now, TimestampMode::Absolute),
+        "2024-01-15 14:27:45"
+    );
+}
+
 #[test]
 fn test_truncate_string() {
     assert_eq!(truncate_string("hello", 10), "hello");
@@ -39,3 +81,55 @@ fn test_truncate_string() {
     // Edge case: max_len < 3
     assert_eq!(truncate_string("hello", 2), "...");
 }
+
+#[test]
+fn test_parse_interval() {
+    assert_eq!(
+        parse_interval("45s"),
+        Ok(std::time::Duration::from_secs(45))
+    );
+    assert_eq!(
+        parse_interval("30m"),
+        Ok(std::time::Duration::from_secs(1_800))
+    );
+    assert_eq!(
+        parse_interval("24h"),
+        Ok(std::time::Duration::from_secs(86_400))
+    );
+    assert_eq!(
+        parse_interval("7d"),
+        Ok(std::time::Duration::from_secs(604_800))
+    );
+    assert_eq!(
+        parse_interval(" 2h "),
+        Ok(std::time::Duration::from_secs(7_200))
+    );
+}
+
+#[test]
+fn test_parse_interval_rejects_malformed_input() {
+    for input in [
+        "",
+        "7",
+        "d",
+        "7w",
+        "7 d",
+        "-1d",
+        "1.5h",
+        "99999999999999999999d",
+    ] {
+        assert!(parse_interval(input).is_err(), "accepted {input:?}");
+    }
+}
+
+#[test]
+fn test_format_interval() {
+    assert_eq!(format_interval(std::time::Duration::from_secs(0)), "0s");
+    assert_eq!(format_interval(std::time::Duration::from_secs(90)), "90s");
+    assert_eq!(format_interval(std::time::Duration::from_secs(300)), "5m");
+    assert_eq!(format_interval(std::time::Duration::from_secs(7_200)), "2h");
+    assert_eq!(
+        format_interval(std::time::Duration::from_secs(604_800)),
+        "7d"
+    );
+}
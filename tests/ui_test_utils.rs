@@ -0,0 +1,45 @@
+//! Headless UI test utilities
+//!
+//! Renders full frames of an `App` to an off-screen buffer so tests can check
+//! the overall layout, complementing the per-widget tests.
+
+use lazycelery::app::{App, Tab};
+use lazycelery::broker::Broker;
+use lazycelery::ui::snapshot::render_to_string;
+
+/// Build an app on `broker`, load its data and render `tab` at `width` x `height`
+///
+/// The returned text holds one line per terminal row, suitable for comparing
+/// against a golden copy or for `position_of` lookups.
+pub async fn render_tab(broker: Box<dyn Broker>, tab: Tab, width: u16, height: u16) -> String {
+    let mut app = App::new(broker);
+    app.refresh_data()
+        .await
+        .expect("mock broker data should load");
+    app.selected_tab = tab;
+    render_to_string(&mut app, width, height)
+}
+
+/// Row and column, in characters, of the first occurrence of `needle`
+pub fn position_of(frame: &str, needle: &str) -> Option<(usize, usize)> {
+    frame.lines().enumerate().find_map(|(row, line)| {
+        line.find(needle)
+            .map(|byte| (row, line[..byte].chars().count()))
+    })
+}
+
+/// Assert that each `(row, column, text)` appears exactly where expected
+pub fn assert_text_at(frame: &str, expected: &[(usize, usize, &str)]) {
+    for &(row, column, text) in expected {
+        let line = frame.lines().nth(row).unwrap_or_default();
+        let found: String = line
+            .chars()
+            .skip(column)
+            .take(text.chars().count())
+            .collect();
+        assert_eq!(
+            found, text,
+            "expected {text:?} at row {row}, column {column}\n{frame}"
+        );
+    }
+}